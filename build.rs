@@ -0,0 +1,43 @@
+// With the `embedded_assets` feature, lists every file of the assets folder in
+// $OUT_DIR/embedded_assets.rs so they can be built into the executable
+use std::fs;
+use std::io;
+use std::path::Path;
+
+fn collect_files(root: &Path, dir: &Path, files: &mut Vec<String>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_files(root, &path, files)?;
+        } else if let Ok(relative) = path.strip_prefix(root) {
+            let parts: Vec<_> = relative.iter().map(|part| part.to_string_lossy()).collect();
+            files.push(parts.join("/"));
+        }
+    }
+    Ok(())
+}
+
+fn main() -> io::Result<()> {
+    println!("cargo:rerun-if-changed=build.rs");
+    if std::env::var_os("CARGO_FEATURE_EMBEDDED_ASSETS").is_none() {
+        return Ok(());
+    }
+    println!("cargo:rerun-if-changed=assets");
+    let root = Path::new(&std::env::var("CARGO_MANIFEST_DIR").unwrap()).join("assets");
+    let mut files = Vec::new();
+    collect_files(&root, &root, &mut files)?;
+    files.sort();
+
+    let mut source = String::from("pub static EMBEDDED_ASSETS: &[(&str, &[u8])] = &[\n");
+    for file in &files {
+        let path = root.join(file);
+        source.push_str(&format!(
+            "    ({:?}, include_bytes!({:?})),\n",
+            file,
+            path.to_string_lossy()
+        ));
+    }
+    source.push_str("];\n");
+    let out = Path::new(&std::env::var("OUT_DIR").unwrap()).join("embedded_assets.rs");
+    fs::write(out, source)
+}
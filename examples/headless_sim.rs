@@ -0,0 +1,93 @@
+//! Plays an Endless run with the autopilot and no window or GPU, as fast as the machine
+//! allows, and prints the milestones it reaches and how it ends: the whole game ticking
+//! along headless, the way a balance or regression check would drive it.
+//!
+//! `cargo run --example headless_sim [seconds]`
+//!
+//! The run is saved like any other, to `save.ron` in the working directory.
+
+use bevy::app::{AppExit, ScheduleRunnerPlugin};
+use bevy::prelude::*;
+use bevy::render::settings::{RenderCreation, WgpuSettings};
+use bevy::render::RenderPlugin;
+use bevy::winit::WinitPlugin;
+use dinorun_core::{ActionSource, DinorunGamePlugin, GameState, GameTime, Milestone, Settings};
+use std::time::Duration;
+
+// simulated frames per second, the rate the game is tuned for
+const FRAME_RATE: f64 = 60.0;
+// seconds of play before the run is cut short, unless given on the command line
+const DEFAULT_LENGTH: f32 = 60.0;
+
+// Seconds of play the run is cut short at
+#[derive(Resource)]
+struct Length(f32);
+
+fn main() {
+    let length = std::env::args()
+        .nth(1)
+        .and_then(|arg| arg.parse().ok())
+        .unwrap_or(DEFAULT_LENGTH);
+    App::new()
+        .add_plugins(
+            DefaultPlugins
+                .set(ImagePlugin::default_nearest())
+                // nothing is drawn, so no GPU is needed
+                .set(RenderPlugin {
+                    render_creation: RenderCreation::Automatic(WgpuSettings {
+                        backends: None,
+                        ..default()
+                    }),
+                    ..default()
+                })
+                // the parallax layers are sized to the primary window, which is never
+                // opened without winit
+                .set(WindowPlugin {
+                    exit_condition: bevy::window::ExitCondition::DontExit,
+                    ..default()
+                })
+                .disable::<WinitPlugin>(),
+        )
+        // every frame steps the clock by the same amount, however long it took
+        .add_plugins(ScheduleRunnerPlugin::run_loop(Duration::ZERO))
+        .insert_resource(Time::<Virtual>::from_max_delta(Duration::from_secs(1)))
+        .insert_resource(bevy::time::TimeUpdateStrategy::ManualDuration(
+            Duration::from_secs_f64(1.0 / FRAME_RATE),
+        ))
+        // the defaults, rather than whatever settings.ron has
+        .insert_resource(Settings::default())
+        .add_plugins(DinorunGamePlugin)
+        .insert_resource(ActionSource::Autopilot)
+        .insert_resource(Length(length))
+        .add_systems(Startup, start_run)
+        .add_systems(
+            Update,
+            (report_milestones, stop).run_if(in_state(GameState::Playing)),
+        )
+        .add_systems(OnExit(GameState::Playing), report_end)
+        .run();
+}
+
+fn start_run(mut game_state: ResMut<NextState<GameState>>) {
+    game_state.set(GameState::Playing);
+}
+
+fn report_milestones(time: Res<GameTime>, mut milestones: EventReader<Milestone>) {
+    for Milestone(count) in milestones.read() {
+        println!("{:6.1}s  milestone {}", time.elapsed_seconds(), count);
+    }
+}
+
+// system to end the run once it has been played for long enough
+fn stop(time: Res<GameTime>, length: Res<Length>, mut game_state: ResMut<NextState<GameState>>) {
+    if time.elapsed_seconds() >= length.0 {
+        println!("{:6.1}s  time's up", time.elapsed_seconds());
+        game_state.set(GameState::Menu);
+    }
+}
+
+// system to print how the run went and quit
+fn report_end(time: Res<GameTime>, mut exit: EventWriter<AppExit>) {
+    println!("{:6.1}s  run over", time.elapsed_seconds());
+    exit.send(AppExit);
+}
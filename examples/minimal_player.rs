@@ -0,0 +1,135 @@
+//! The player on its own, on a plain background: its spritesheet exported from Aseprite,
+//! loaded into the `AnimationLibrary` by `SpriteAnimationPlugin`, and an animator switching
+//! between its clips as it walks with the arrow keys and jumps with space. The game's own
+//! player does the same with more states; see `player_clip` in `src/lib.rs`.
+//!
+//! `cargo run --example minimal_player`
+
+use bevy::prelude::*;
+use dinorun_core::{
+    AnimationLibrary, AsepriteSheet, GameState, GameTimePlugin, SpriteAnimationPlugin,
+    SpriteAnimator, SpriteClip,
+};
+
+const SHEET: &str = "player.aseprite.json";
+const SPRITE: &str = "player.png";
+const SCALE: f32 = 4.0;
+const GROUND_Y: f32 = -64.0;
+// pixels per second
+const WALK_SPEED: f32 = 120.0;
+const JUMP_SPEED: f32 = 420.0;
+const GRAVITY: f32 = 1200.0;
+
+// Keeps the sheet loaded, its clips stay in the library once registered
+#[derive(Resource)]
+struct Sheet(#[allow(dead_code)] Handle<AsepriteSheet>);
+
+#[derive(Component, Default)]
+struct Hero {
+    // pixels per second upwards, while in the air
+    rise: f32,
+    on_ground: bool,
+}
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins.set(ImagePlugin::default_nearest()))
+        // the animations run on the game's clock, which starts and stops with runs
+        .init_state::<GameState>()
+        .add_plugins((GameTimePlugin, SpriteAnimationPlugin))
+        .add_systems(Startup, setup)
+        .add_systems(Update, (move_hero, pick_clip).chain())
+        .run();
+}
+
+fn setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut library: ResMut<AnimationLibrary>,
+) {
+    commands.spawn(Camera2dBundle::default());
+    commands.insert_resource(Sheet(asset_server.load(SHEET)));
+    // touching down plays the landing clip before moving on
+    for to in ["walk", "idle"] {
+        library.set_transition("fall", to, "land");
+    }
+    commands.spawn((
+        SpriteSheetBundle {
+            texture: asset_server.load(SPRITE),
+            atlas: TextureAtlas {
+                layout: asset_server.load(format!("{}#layout", SHEET)),
+                index: 0,
+            },
+            transform: Transform::from_xyz(0.0, GROUND_Y, 0.0).with_scale(Vec3::splat(SCALE)),
+            ..default()
+        },
+        // the clips arrive once the sheet has loaded, pick_clip starts one then
+        SpriteAnimator::new(SpriteClip::default()),
+        Hero {
+            on_ground: true,
+            ..default()
+        },
+    ));
+}
+
+fn move_hero(
+    time: Res<Time>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut hero_query: Query<(&mut Hero, &mut Transform, &mut Sprite)>,
+) {
+    let Ok((mut hero, mut transform, mut sprite)) = hero_query.get_single_mut() else {
+        return;
+    };
+    let mut direction = 0.0;
+    if keyboard_input.pressed(KeyCode::ArrowLeft) {
+        direction -= 1.0;
+    }
+    if keyboard_input.pressed(KeyCode::ArrowRight) {
+        direction += 1.0;
+    }
+    if direction != 0.0 {
+        sprite.flip_x = direction < 0.0;
+    }
+    transform.translation.x += direction * WALK_SPEED * time.delta_seconds();
+    if hero.on_ground && keyboard_input.just_pressed(KeyCode::Space) {
+        hero.on_ground = false;
+        hero.rise = JUMP_SPEED;
+    }
+    if !hero.on_ground {
+        hero.rise -= GRAVITY * time.delta_seconds();
+        transform.translation.y += hero.rise * time.delta_seconds();
+        if transform.translation.y <= GROUND_Y {
+            transform.translation.y = GROUND_Y;
+            hero.on_ground = true;
+        }
+    }
+}
+
+// system to play the clip for what the hero is doing, through the transition the
+// library has between the two, if any
+fn pick_clip(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    library: Res<AnimationLibrary>,
+    mut hero_query: Query<(&Hero, &mut SpriteAnimator)>,
+) {
+    let Ok((hero, mut animator)) = hero_query.get_single_mut() else {
+        return;
+    };
+    let moving = keyboard_input.any_pressed([KeyCode::ArrowLeft, KeyCode::ArrowRight]);
+    let name = match (hero.on_ground, hero.rise > 0.0, moving) {
+        (false, true, _) => "jump",
+        (false, false, _) => "fall",
+        (true, _, true) => "walk",
+        (true, _, false) => "idle",
+    };
+    if animator.target().name == name {
+        return;
+    }
+    let Some(clip) = library.clip(name).cloned() else {
+        return;
+    };
+    match library.transition(&animator.clip().name, name) {
+        Some(transition) => animator.play_through(transition.clone(), clip),
+        None => animator.play(clip),
+    }
+}
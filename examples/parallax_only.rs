@@ -0,0 +1,63 @@
+//! The game's parallax background with nothing in front of it: the same layers a run
+//! scrolls through, laid out by `parallax_layers`, moved around with the arrow keys
+//! through bevy-parallax. The ground and foreground keep still vertically while the
+//! layers further back follow the camera up and down more and more.
+//!
+//! `cargo run --example parallax_only`
+
+use bevy::prelude::*;
+use bevy_parallax::{
+    CreateParallaxEvent, ParallaxCameraComponent, ParallaxMoveEvent, ParallaxPlugin,
+};
+use dinorun_core::parallax_layers;
+
+// pixels per frame the camera moves while an arrow key is held
+const CAMERA_SPEED: f32 = 4.0;
+// how far the camera can look up or down
+const MAX_LOOK: f32 = 120.0;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins.set(ImagePlugin::default_nearest()))
+        .add_plugins(ParallaxPlugin)
+        .add_systems(Startup, setup)
+        .add_systems(Update, move_camera)
+        .run();
+}
+
+fn setup(mut commands: Commands, mut create_parallax: EventWriter<CreateParallaxEvent>) {
+    let camera = commands
+        .spawn(Camera2dBundle::default())
+        .insert(ParallaxCameraComponent::default())
+        .id();
+    create_parallax.send(CreateParallaxEvent {
+        // every layer; the game leaves the mountains out in low-spec mode
+        layers_data: parallax_layers(0.0, false),
+        camera,
+    });
+}
+
+fn move_camera(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    camera_query: Query<(Entity, &Transform), With<ParallaxCameraComponent>>,
+    mut move_events: EventWriter<ParallaxMoveEvent>,
+) {
+    let Ok((camera, transform)) = camera_query.get_single() else {
+        return;
+    };
+    let axis = |negative, positive| {
+        keyboard_input.pressed(positive) as i32 as f32
+            - keyboard_input.pressed(negative) as i32 as f32
+    };
+    let mut speed = Vec2::new(
+        axis(KeyCode::ArrowLeft, KeyCode::ArrowRight),
+        axis(KeyCode::ArrowDown, KeyCode::ArrowUp),
+    ) * CAMERA_SPEED;
+    // looking too far up or down shows past the top and bottom of the layers
+    let y = transform.translation.y + speed.y;
+    speed.y = y.clamp(-MAX_LOOK, MAX_LOOK) - transform.translation.y;
+    move_events.send(ParallaxMoveEvent {
+        camera_move_speed: speed,
+        camera,
+    });
+}
@@ -0,0 +1,412 @@
+use crate::collision::Collider;
+use crate::game_time::{GameClockSet, GameTime};
+use crate::timing::timed;
+use crate::GameplaySet;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use rand::Rng;
+use serde::Deserialize;
+
+mod aseprite;
+mod packed;
+
+use aseprite::AsepriteLoader;
+pub use aseprite::AsepriteSheet;
+use packed::PackedSheetLoader;
+
+// shortest time a frame can be shown for
+const MIN_FRAME_TIME: f32 = 0.001;
+
+// What a clip does once it reaches its last frame
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub enum PlaybackMode {
+    // start over from the first frame
+    #[default]
+    Loop,
+    // hold the last frame and report the clip as finished
+    Once,
+    // play backwards to the first frame, then forwards again
+    PingPong,
+}
+
+// A named sequence of spritesheet frames, each shown for its own duration
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SpriteClip {
+    pub name: String,
+    pub frames: Vec<usize>,
+    // seconds each frame stays up, one entry per frame
+    pub durations: Vec<f32>,
+    // hitbox of each frame in sprite pixels around its center with y up, if it has one
+    pub hitboxes: Vec<Option<Rect>>,
+    pub mode: PlaybackMode,
+}
+
+impl SpriteClip {
+    fn duration(&self, index: usize) -> f32 {
+        let duration = self.durations.get(index).copied().unwrap_or(0.1);
+        // zero length frames would never let playback catch up
+        duration.max(MIN_FRAME_TIME)
+    }
+}
+
+// All known clips by name, and the clips played in between two of them
+#[derive(Resource, Default)]
+pub struct AnimationLibrary {
+    clips: HashMap<String, SpriteClip>,
+    // (from, to) clip names to the name of the clip bridging them
+    transitions: HashMap<(String, String), String>,
+}
+
+impl AnimationLibrary {
+    pub fn insert(&mut self, clip: SpriteClip) {
+        self.clips.insert(clip.name.clone(), clip);
+    }
+
+    // play the clip named `via` whenever an animator switches from `from` to `to`
+    pub fn set_transition(&mut self, from: &str, to: &str, via: &str) {
+        self.transitions
+            .insert((from.to_string(), to.to_string()), via.to_string());
+    }
+
+    pub fn clip(&self, name: &str) -> Option<&SpriteClip> {
+        self.clips.get(name)
+    }
+
+    pub fn transition(&self, from: &str, to: &str) -> Option<&SpriteClip> {
+        let name = self.transitions.get(&(from.to_string(), to.to_string()))?;
+        self.clip(name)
+    }
+}
+
+// Plays a clip on the entity's TextureAtlas
+#[derive(Component)]
+pub struct SpriteAnimator {
+    clip: SpriteClip,
+    // position in clip.frames
+    frame: usize,
+    // time the current frame has been shown for
+    elapsed: f32,
+    // playing backwards, for the second half of a ping-pong
+    reversed: bool,
+    finished: bool,
+    // clip to continue with once the current one ends
+    next: Option<SpriteClip>,
+}
+
+impl SpriteAnimator {
+    pub fn new(clip: SpriteClip) -> Self {
+        SpriteAnimator {
+            clip,
+            frame: 0,
+            elapsed: 0.0,
+            reversed: false,
+            finished: false,
+            next: None,
+        }
+    }
+
+    pub fn clip(&self) -> &SpriteClip {
+        &self.clip
+    }
+
+    // the clip playback is headed for: the queued one during a transition, else the current one
+    pub fn target(&self) -> &SpriteClip {
+        self.next.as_ref().unwrap_or(&self.clip)
+    }
+
+    // switch to `clip` from the start, unless it's already playing
+    pub fn play(&mut self, clip: SpriteClip) {
+        if self.clip.name != clip.name {
+            *self = SpriteAnimator::new(clip);
+        }
+    }
+
+    // play `transition` once through, then carry on with `clip`
+    pub fn play_through(&mut self, transition: SpriteClip, clip: SpriteClip) {
+        *self = SpriteAnimator::new(transition);
+        self.next = Some(clip);
+    }
+
+    // switch to `clip` at the same point through it the current clip is at,
+    // so swapping between walk and run cycles keeps the stride
+    pub fn play_in_step(&mut self, clip: SpriteClip) {
+        if self.clip.name == clip.name {
+            return;
+        }
+        let progress = self.progress();
+        let frame = (progress * clip.frames.len().saturating_sub(1) as f32).round() as usize;
+        *self = SpriteAnimator::new(clip);
+        self.frame = frame;
+    }
+
+    // how far through the clip playback is, from 0 to 1
+    pub fn progress(&self) -> f32 {
+        let last = self.clip.frames.len().saturating_sub(1);
+        if last == 0 {
+            0.0
+        } else {
+            self.frame as f32 / last as f32
+        }
+    }
+
+    // hitbox of the frame on screen
+    pub fn current_hitbox(&self) -> Option<Rect> {
+        self.clip.hitboxes.get(self.frame).copied().flatten()
+    }
+
+    fn current_frame(&self) -> Option<usize> {
+        self.clip.frames.get(self.frame).copied()
+    }
+
+    // move on by `delta` seconds; true when the end of the clip was reached
+    fn advance(&mut self, delta: f32) -> bool {
+        let count = self.clip.frames.len();
+        if count == 0 || self.finished {
+            return false;
+        }
+        let mut reached_end = false;
+        self.elapsed += delta;
+        while self.elapsed >= self.clip.duration(self.frame) && !self.finished {
+            self.elapsed -= self.clip.duration(self.frame);
+            let last = count - 1;
+            match self.clip.mode {
+                PlaybackMode::Loop => {
+                    if self.frame == last {
+                        self.frame = 0;
+                        reached_end = true;
+                    } else {
+                        self.frame += 1;
+                    }
+                }
+                PlaybackMode::Once => {
+                    if self.frame == last {
+                        self.finished = true;
+                        reached_end = true;
+                    } else {
+                        self.frame += 1;
+                    }
+                }
+                PlaybackMode::PingPong => {
+                    if last == 0 {
+                        reached_end = true;
+                    } else if self.reversed {
+                        self.frame -= 1;
+                        if self.frame == 0 {
+                            self.reversed = false;
+                            reached_end = true;
+                        }
+                    } else {
+                        self.frame += 1;
+                        if self.frame == last {
+                            self.reversed = true;
+                        }
+                    }
+                }
+            }
+        }
+        reached_end
+    }
+}
+
+// Event sent when an animator plays to the end of its clip: a one-shot clip
+// finishing, or a looping one completing a cycle
+#[derive(Event, Debug, Clone)]
+pub struct AnimationFinished {
+    pub entity: Entity,
+    pub clip: String,
+}
+
+// Animates with real time instead of the run's clock, for sprites shown outside of runs
+#[derive(Component)]
+pub struct MenuAnimation;
+
+// move `animator` on by `delta` seconds and show its frame. Most frames only add to the
+// time the frame has been up, which is kept out of change detection: the animator is only
+// marked changed, and the atlas only touched, when a new frame comes up.
+fn step_animator(
+    entity: Entity,
+    mut animator: Mut<SpriteAnimator>,
+    mut atlas: Mut<TextureAtlas>,
+    delta: f32,
+    finished: &mut EventWriter<AnimationFinished>,
+) {
+    let frame = animator.frame;
+    let reached_end = animator.bypass_change_detection().advance(delta);
+    if reached_end {
+        finished.send(AnimationFinished {
+            entity,
+            clip: animator.clip.name.clone(),
+        });
+    }
+    if animator.frame != frame || reached_end {
+        animator.set_changed();
+    }
+    if let Some(index) = animator.current_frame() {
+        if atlas.index != index {
+            atlas.index = index;
+        }
+    }
+}
+
+// system to step every animated sprite through its clip in one pass: those of the run by
+// its clock, which stands still while it isn't ticking, and menu ones by real time
+fn animate_sprites(
+    game_time: Res<GameTime>,
+    real_time: Res<Time<Real>>,
+    mut query: Query<(
+        Entity,
+        &mut SpriteAnimator,
+        &mut TextureAtlas,
+        Has<MenuAnimation>,
+    )>,
+    mut finished: EventWriter<AnimationFinished>,
+) {
+    let game_delta = game_time.delta_seconds();
+    let real_delta = real_time.delta_seconds();
+    for (entity, animator, atlas, menu) in &mut query {
+        let delta = if menu { real_delta } else { game_delta };
+        // a clip switched to elsewhere still has to show its first frame
+        if delta == 0.0 && !animator.is_changed() {
+            continue;
+        }
+        step_animator(entity, animator, atlas, delta, &mut finished);
+    }
+}
+
+// system to carry on with the clip queued behind a one-shot, as a landing or a skid, once
+// it has played through
+fn play_queued_clips(
+    mut finished: EventReader<AnimationFinished>,
+    mut query: Query<(&mut SpriteAnimator, &mut TextureAtlas)>,
+) {
+    for event in finished.read() {
+        let Ok((mut animator, mut atlas)) = query.get_mut(event.entity) else {
+            continue;
+        };
+        // switched to another clip since
+        if animator.clip.name != event.clip {
+            continue;
+        }
+        let Some(next) = animator.next.take() else {
+            continue;
+        };
+        *animator = SpriteAnimator::new(next);
+        if let Some(index) = animator.current_frame() {
+            atlas.index = index;
+        }
+    }
+}
+
+// Plays one of `clips` now and then, at random, on an animator that has been sitting in
+// its `base` clip for a while, going back to `base` afterwards. Clips missing from the
+// library are skipped.
+#[derive(Component)]
+pub struct AmbientClips {
+    base: &'static str,
+    clips: &'static [&'static str],
+    // shortest and longest seconds in `base` before the next one plays
+    wait: (f32, f32),
+    // seconds left in `base` before it does
+    left: f32,
+}
+
+impl AmbientClips {
+    pub fn new(base: &'static str, clips: &'static [&'static str], wait: (f32, f32)) -> Self {
+        AmbientClips {
+            base,
+            clips,
+            wait,
+            left: wait.1,
+        }
+    }
+}
+
+// system to break up a long stretch in a base clip with an ambient one
+fn play_ambient_clips(
+    game_time: Res<GameTime>,
+    real_time: Res<Time<Real>>,
+    library: Res<AnimationLibrary>,
+    mut query: Query<(&mut AmbientClips, &mut SpriteAnimator, Has<MenuAnimation>)>,
+) {
+    let mut rng = rand::thread_rng();
+    for (mut ambient, mut animator, menu) in &mut query {
+        if animator.clip().name != ambient.base || animator.target().name != ambient.base {
+            ambient.left = rng.gen_range(ambient.wait.0..=ambient.wait.1);
+            continue;
+        }
+        ambient.left -= if menu {
+            real_time.delta_seconds()
+        } else {
+            game_time.delta_seconds()
+        };
+        if ambient.left > 0.0 {
+            continue;
+        }
+        ambient.left = rng.gen_range(ambient.wait.0..=ambient.wait.1);
+        let available: Vec<&SpriteClip> = ambient
+            .clips
+            .iter()
+            .filter_map(|name| library.clip(name))
+            .collect();
+        let (Some(base), Some(clip)) = (
+            library.clip(ambient.base),
+            available.get(rng.gen_range(0..available.len().max(1))),
+        ) else {
+            continue;
+        };
+        animator.play_through((*clip).clone(), base.clone());
+    }
+}
+
+// system to fit each collider to the pose its sprite is in, when the pose changed
+fn update_hitboxes(mut query: Query<(&SpriteAnimator, &mut Collider), Changed<SpriteAnimator>>) {
+    for (animator, mut collider) in &mut query {
+        if let Some(hitbox) = animator.current_hitbox() {
+            if collider.0 != hitbox {
+                collider.0 = hitbox;
+            }
+        }
+    }
+}
+
+// system to add the clips of every sheet to the library as it (re)loads
+fn register_sheets(
+    mut events: EventReader<AssetEvent<AsepriteSheet>>,
+    sheets: Res<Assets<AsepriteSheet>>,
+    mut library: ResMut<AnimationLibrary>,
+) {
+    for event in events.read() {
+        let (AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id }) = event
+        else {
+            continue;
+        };
+        if let Some(sheet) = sheets.get(*id) {
+            for clip in &sheet.clips {
+                library.insert(clip.clone());
+            }
+        }
+    }
+}
+
+pub struct SpriteAnimationPlugin;
+
+impl Plugin for SpriteAnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<AsepriteSheet>()
+            .init_asset_loader::<AsepriteLoader>()
+            .init_asset_loader::<PackedSheetLoader>()
+            .init_resource::<AnimationLibrary>()
+            .add_event::<AnimationFinished>()
+            .add_systems(Update, register_sheets)
+            .add_systems(
+                Update,
+                (
+                    play_ambient_clips.after(GameClockSet),
+                    timed(animate_sprites),
+                    play_queued_clips,
+                    timed(update_hitboxes).in_set(GameplaySet),
+                )
+                    .chain(),
+            );
+    }
+}
@@ -0,0 +1,269 @@
+use super::{PlaybackMode, SpriteClip};
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, AsyncReadExt, LoadContext};
+use bevy::prelude::*;
+use bevy::utils::BoxedFuture;
+use serde::Deserialize;
+use std::fmt;
+
+// A spritesheet exported from Aseprite as JSON: the atlas layout of its frames,
+// one clip per frame tag, and the hitbox slice for every frame. Sheets packed from
+// loose frames (see packed.rs) load into the same asset.
+#[derive(Asset, TypePath, Debug)]
+pub struct AsepriteSheet {
+    #[dependency]
+    pub image: Handle<Image>,
+    #[dependency]
+    pub layout: Handle<TextureAtlasLayout>,
+    pub clips: Vec<SpriteClip>,
+}
+
+// Subset of Aseprite's JSON export that the game uses
+#[derive(Deserialize)]
+struct AsepriteJson {
+    frames: AsepriteFrames,
+    meta: AsepriteMeta,
+}
+
+// Aseprite exports the frames either as an array or as a map keyed by file name,
+// which is kept in file order to line the frames up with the tags
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AsepriteFrames {
+    Array(Vec<AsepriteFrame>),
+    Hash(serde_json::Map<String, serde_json::Value>),
+}
+
+#[derive(Deserialize)]
+struct AsepriteFrame {
+    frame: AsepriteRect,
+    // milliseconds
+    duration: u32,
+}
+
+#[derive(Deserialize, Clone, Copy)]
+struct AsepriteRect {
+    x: f32,
+    y: f32,
+    w: f32,
+    h: f32,
+}
+
+impl AsepriteRect {
+    // this rect, given from the top left corner of `frame`, relative to the frame's center with y up
+    fn centered_in(&self, frame: AsepriteRect) -> Rect {
+        let (half_w, half_h) = (frame.w / 2.0, frame.h / 2.0);
+        Rect::new(
+            self.x - half_w,
+            half_h - self.y - self.h,
+            self.x + self.w - half_w,
+            half_h - self.y,
+        )
+    }
+}
+
+#[derive(Deserialize)]
+struct AsepriteSize {
+    w: f32,
+    h: f32,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AsepriteMeta {
+    image: String,
+    size: AsepriteSize,
+    #[serde(default)]
+    frame_tags: Vec<AsepriteTag>,
+    #[serde(default)]
+    slices: Vec<AsepriteSlice>,
+}
+
+#[derive(Deserialize)]
+struct AsepriteTag {
+    name: String,
+    from: usize,
+    to: usize,
+    #[serde(default)]
+    direction: String,
+    // how many times the tag plays, missing means forever
+    repeat: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct AsepriteSlice {
+    name: String,
+    keys: Vec<AsepriteSliceKey>,
+}
+
+#[derive(Deserialize)]
+struct AsepriteSliceKey {
+    // first frame the key applies to, until the next key
+    frame: usize,
+    bounds: AsepriteRect,
+}
+
+// name of the slice holding a sprite's hitbox
+const HITBOX_SLICE: &str = "hitbox";
+
+impl AsepriteJson {
+    fn frames(self) -> Result<(Vec<AsepriteFrame>, AsepriteMeta), serde_json::Error> {
+        let frames = match self.frames {
+            AsepriteFrames::Array(frames) => frames,
+            AsepriteFrames::Hash(frames) => frames
+                .into_iter()
+                .map(|(_, frame)| serde_json::from_value(frame))
+                .collect::<Result<_, _>>()?,
+        };
+        Ok((frames, self.meta))
+    }
+}
+
+impl AsepriteMeta {
+    // hitbox of every frame, in pixels from the top left corner of the frame
+    fn hitboxes(&self, frame_count: usize) -> Vec<Option<AsepriteRect>> {
+        let mut hitboxes = vec![None; frame_count];
+        let Some(slice) = self.slices.iter().find(|slice| slice.name == HITBOX_SLICE) else {
+            return hitboxes;
+        };
+        for (index, key) in slice.keys.iter().enumerate() {
+            let end = slice
+                .keys
+                .get(index + 1)
+                .map_or(frame_count, |next| next.frame.min(frame_count));
+            for hitbox in hitboxes.iter_mut().take(end).skip(key.frame) {
+                *hitbox = Some(key.bounds);
+            }
+        }
+        hitboxes
+    }
+}
+
+impl AsepriteTag {
+    fn clip(
+        &self,
+        frames: &[AsepriteFrame],
+        hitboxes: &[Option<AsepriteRect>],
+    ) -> Result<SpriteClip, AsepriteError> {
+        // a sheet without frames leaves the tag nothing to point at
+        let Some(last) = frames.len().checked_sub(1) else {
+            return Err(AsepriteError::EmptyTag(self.name.clone()));
+        };
+        let last = self.to.min(last);
+        let mut indices: Vec<usize> = (self.from.min(last)..=last).collect();
+        if self.direction == "reverse" || self.direction == "pingpong_reverse" {
+            indices.reverse();
+        }
+        let mode = if self.direction.starts_with("pingpong") {
+            PlaybackMode::PingPong
+        } else if self.repeat.as_deref() == Some("1") {
+            PlaybackMode::Once
+        } else {
+            PlaybackMode::Loop
+        };
+        Ok(SpriteClip {
+            name: self.name.clone(),
+            durations: indices
+                .iter()
+                .map(|index| frames[*index].duration as f32 / 1000.0)
+                .collect(),
+            hitboxes: indices
+                .iter()
+                .map(|index| {
+                    let bounds = hitboxes.get(*index).copied().flatten()?;
+                    Some(bounds.centered_in(frames[*index].frame))
+                })
+                .collect(),
+            frames: indices,
+            mode,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub enum AsepriteError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    // a tag of the sheet has no frames to play
+    EmptyTag(String),
+}
+
+impl fmt::Display for AsepriteError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AsepriteError::Io(error) => write!(f, "could not read the sheet: {}", error),
+            AsepriteError::Json(error) => write!(f, "invalid Aseprite JSON: {}", error),
+            AsepriteError::EmptyTag(name) => write!(f, "tag {} has no frames", name),
+        }
+    }
+}
+
+impl std::error::Error for AsepriteError {}
+
+impl From<std::io::Error> for AsepriteError {
+    fn from(error: std::io::Error) -> Self {
+        AsepriteError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for AsepriteError {
+    fn from(error: serde_json::Error) -> Self {
+        AsepriteError::Json(error)
+    }
+}
+
+#[derive(Default)]
+pub struct AsepriteLoader;
+
+impl AssetLoader for AsepriteLoader {
+    type Asset = AsepriteSheet;
+    type Settings = ();
+    type Error = AsepriteError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<AsepriteSheet, AsepriteError>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            let (frames, meta) = serde_json::from_slice::<AsepriteJson>(&bytes)?.frames()?;
+
+            let mut layout = TextureAtlasLayout::new_empty(Vec2::new(meta.size.w, meta.size.h));
+            for AsepriteFrame { frame, .. } in &frames {
+                layout.add_texture(Rect::new(
+                    frame.x,
+                    frame.y,
+                    frame.x + frame.w,
+                    frame.y + frame.h,
+                ));
+            }
+            let layout = load_context.add_labeled_asset("layout".to_string(), layout);
+
+            // the image path is relative to the JSON file
+            let image_path = load_context
+                .path()
+                .parent()
+                .map_or_else(|| meta.image.clone().into(), |dir| dir.join(&meta.image));
+            let image = load_context.load(image_path);
+
+            let hitboxes = meta.hitboxes(frames.len());
+            let clips = meta
+                .frame_tags
+                .iter()
+                .map(|tag| tag.clip(&frames, &hitboxes))
+                .collect::<Result<_, _>>()?;
+            Ok(AsepriteSheet {
+                image,
+                layout,
+                clips,
+            })
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["aseprite.json"]
+    }
+}
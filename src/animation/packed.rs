@@ -0,0 +1,158 @@
+use super::{AsepriteSheet, PlaybackMode, SpriteClip};
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, AsyncReadExt, LoadContext, LoadDirectError};
+use bevy::prelude::*;
+use bevy::sprite::TextureAtlasBuilderError;
+use bevy::utils::BoxedFuture;
+use serde::Deserialize;
+use std::fmt;
+
+// A sheet described as a list of loose frame images, packed into one atlas when it
+// loads so nobody has to keep a grid of frames and its constants in sync by hand:
+//
+// (
+//     frames: ["spitter/idle_0.png", "spitter/idle_1.png"],
+//     clips: [(name: "spitter_idle", from: 0, to: 1, frame_time: 0.2)],
+// )
+#[derive(Deserialize)]
+struct PackedSheetRon {
+    // image paths relative to the .sheet.ron file, in frame index order
+    frames: Vec<String>,
+    // transparent pixels kept between packed frames
+    #[serde(default)]
+    padding: u32,
+    #[serde(default)]
+    clips: Vec<PackedClip>,
+}
+
+#[derive(Deserialize)]
+struct PackedClip {
+    name: String,
+    from: usize,
+    to: usize,
+    // seconds each frame stays up
+    frame_time: f32,
+    #[serde(default)]
+    mode: PlaybackMode,
+}
+
+impl PackedClip {
+    fn clip(&self, frame_count: usize) -> SpriteClip {
+        let last = self.to.min(frame_count.saturating_sub(1));
+        let frames: Vec<usize> = (self.from.min(last)..=last).collect();
+        SpriteClip {
+            name: self.name.clone(),
+            durations: vec![self.frame_time; frames.len()],
+            hitboxes: vec![None; frames.len()],
+            frames,
+            mode: self.mode,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum PackedSheetError {
+    Io(std::io::Error),
+    Ron(ron::error::SpannedError),
+    Frame(LoadDirectError),
+    NotAnImage(String),
+    Pack(TextureAtlasBuilderError),
+}
+
+impl fmt::Display for PackedSheetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PackedSheetError::Io(error) => write!(f, "could not read the sheet: {}", error),
+            PackedSheetError::Ron(error) => write!(f, "invalid sheet: {}", error),
+            PackedSheetError::Frame(error) => write!(f, "could not load a frame: {}", error),
+            PackedSheetError::NotAnImage(path) => write!(f, "frame {} is not an image", path),
+            PackedSheetError::Pack(error) => write!(f, "could not pack the frames: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for PackedSheetError {}
+
+impl From<std::io::Error> for PackedSheetError {
+    fn from(error: std::io::Error) -> Self {
+        PackedSheetError::Io(error)
+    }
+}
+
+impl From<ron::error::SpannedError> for PackedSheetError {
+    fn from(error: ron::error::SpannedError) -> Self {
+        PackedSheetError::Ron(error)
+    }
+}
+
+impl From<LoadDirectError> for PackedSheetError {
+    fn from(error: LoadDirectError) -> Self {
+        PackedSheetError::Frame(error)
+    }
+}
+
+impl From<TextureAtlasBuilderError> for PackedSheetError {
+    fn from(error: TextureAtlasBuilderError) -> Self {
+        PackedSheetError::Pack(error)
+    }
+}
+
+#[derive(Default)]
+pub struct PackedSheetLoader;
+
+impl AssetLoader for PackedSheetLoader {
+    type Asset = AsepriteSheet;
+    type Settings = ();
+    type Error = PackedSheetError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<AsepriteSheet, PackedSheetError>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            let sheet = ron::de::from_bytes::<PackedSheetRon>(&bytes)?;
+
+            let dir = load_context.path().parent().map(|dir| dir.to_path_buf());
+            let mut images = Vec::with_capacity(sheet.frames.len());
+            for frame in &sheet.frames {
+                let path = dir
+                    .as_ref()
+                    .map_or_else(|| frame.into(), |dir| dir.join(frame));
+                let image = load_context
+                    .load_direct(path)
+                    .await?
+                    .take::<Image>()
+                    .ok_or_else(|| PackedSheetError::NotAnImage(frame.clone()))?;
+                images.push(image);
+            }
+
+            // frames keep the index of their place in the list
+            let mut builder = TextureAtlasBuilder::default().padding(UVec2::splat(sheet.padding));
+            for image in &images {
+                builder.add_texture(None, image);
+            }
+            let (layout, image) = builder.finish()?;
+            let layout = load_context.add_labeled_asset("layout".to_string(), layout);
+            let image = load_context.add_labeled_asset("image".to_string(), image);
+
+            let clips = sheet
+                .clips
+                .iter()
+                .map(|clip| clip.clip(images.len()))
+                .collect();
+            Ok(AsepriteSheet {
+                image,
+                layout,
+                clips,
+            })
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["sheet.ron"]
+    }
+}
@@ -0,0 +1,90 @@
+use crate::climb::COLUMN_WIDTH;
+use crate::health::Boss;
+use crate::mode::ActiveMode;
+use crate::toast::Toast;
+use crate::{GameState, GameplaySet};
+use bevy::prelude::*;
+use bevy_parallax::ParallaxCameraComponent;
+
+// how far inside the right edge of the view a boss has to come for the arena to close
+const ARENA_EDGE: f32 = 48.0;
+// room kept between the player and the boss, enough for the boss to keep shooting
+const BOSS_GAP: f32 = 140.0;
+// room kept between the player and the left edge of the view
+const LEFT_GAP: f32 = 32.0;
+
+// How the world camera moves: scrolling along with the run, held over a boss arena, or
+// following the player up a climb
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq)]
+pub enum CameraMode {
+    #[default]
+    Scroll,
+    // the camera drifts around `center` and the player is kept between `left` and `right`
+    Arena {
+        center: f32,
+        left: f32,
+        right: f32,
+    },
+    // the camera follows the player up, keeping them between `left` and `right`
+    Climb {
+        left: f32,
+        right: f32,
+    },
+}
+
+fn reset_camera_mode(active: Res<ActiveMode>, mut mode: ResMut<CameraMode>) {
+    *mode = if active.vertical() {
+        CameraMode::Climb {
+            left: -COLUMN_WIDTH / 2.0,
+            right: COLUMN_WIDTH / 2.0,
+        }
+    } else {
+        CameraMode::Scroll
+    };
+}
+
+// system to close the arena around a boss once it's well into view, and open it again
+// once no boss is left
+fn update_arena(
+    mut mode: ResMut<CameraMode>,
+    camera_query: Query<(&Transform, &OrthographicProjection), With<ParallaxCameraComponent>>,
+    boss_query: Query<&Transform, With<Boss>>,
+    mut toasts: EventWriter<Toast>,
+) {
+    let Ok((camera, projection)) = camera_query.get_single() else {
+        return;
+    };
+    match *mode {
+        CameraMode::Scroll => {
+            let view_right = camera.translation.x + projection.area.max.x;
+            let Some(boss) = boss_query
+                .iter()
+                .find(|boss| boss.translation.x <= view_right - ARENA_EDGE)
+            else {
+                return;
+            };
+            *mode = CameraMode::Arena {
+                center: camera.translation.x,
+                left: camera.translation.x + projection.area.min.x + LEFT_GAP,
+                right: boss.translation.x - BOSS_GAP,
+            };
+        }
+        CameraMode::Arena { .. } => {
+            if boss_query.is_empty() {
+                *mode = CameraMode::Scroll;
+                toasts.send(Toast("Boss defeated!".to_string()));
+            }
+        }
+        CameraMode::Climb { .. } => {}
+    }
+}
+
+pub struct ArenaPlugin;
+
+impl Plugin for ArenaPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CameraMode>()
+            .add_systems(OnEnter(GameState::Playing), reset_camera_mode)
+            .add_systems(Update, update_arena.in_set(GameplaySet));
+    }
+}
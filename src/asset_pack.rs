@@ -0,0 +1,246 @@
+use bevy::asset::io::{
+    AssetReader, AssetReaderError, AssetSource, AssetSourceId, PathStream, Reader, VecReader,
+};
+use bevy::prelude::*;
+use bevy::utils::{BoxedFuture, HashMap};
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+// file holding the packed assets, looked for next to the executable
+pub const PACK_FILE: &str = "dinorun.pack";
+const PACK_MAGIC: &[u8; 4] = b"DRPK";
+
+// every file of the assets folder, built into the executable by build.rs
+#[cfg(feature = "embedded_assets")]
+include!(concat!(env!("OUT_DIR"), "/embedded_assets.rs"));
+
+// the built in copy of the file at `path` in the assets folder
+#[cfg(feature = "embedded_assets")]
+fn embedded(path: &Path) -> Option<&'static [u8]> {
+    EMBEDDED_ASSETS
+        .iter()
+        .find(|(file, _)| Path::new(file) == path)
+        .map(|(_, bytes)| *bytes)
+}
+
+#[cfg(not(feature = "embedded_assets"))]
+fn embedded(_path: &Path) -> Option<&'static [u8]> {
+    None
+}
+
+// Every file of the assets folder in one file, each compressed on its own so any of them
+// can be read without unpacking the rest. The layout is the magic, the number of files,
+// then for every file its path length, path and compressed length, and at the end the
+// compressed files in the same order (all numbers little endian, lengths u16 and u32).
+pub struct AssetPack {
+    data: Vec<u8>,
+    // path inside the assets folder to the range of its compressed bytes in data
+    entries: HashMap<PathBuf, (usize, usize)>,
+}
+
+fn invalid(what: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("broken asset pack: {}", what),
+    )
+}
+
+fn take<'a>(data: &'a [u8], cursor: &mut usize, len: usize) -> io::Result<&'a [u8]> {
+    let bytes = data
+        .get(*cursor..*cursor + len)
+        .ok_or_else(|| invalid("ends early"))?;
+    *cursor += len;
+    Ok(bytes)
+}
+
+impl AssetPack {
+    pub fn parse(data: Vec<u8>) -> io::Result<Self> {
+        let mut cursor = 0;
+        if take(&data, &mut cursor, 4)? != PACK_MAGIC {
+            return Err(invalid("not a pack file"));
+        }
+        let count = u32::from_le_bytes(take(&data, &mut cursor, 4)?.try_into().unwrap());
+        let mut index = Vec::new();
+        for _ in 0..count {
+            let path_len = u16::from_le_bytes(take(&data, &mut cursor, 2)?.try_into().unwrap());
+            let path = std::str::from_utf8(take(&data, &mut cursor, path_len as usize)?)
+                .map_err(|_| invalid("path is not utf-8"))?;
+            let len = u32::from_le_bytes(take(&data, &mut cursor, 4)?.try_into().unwrap());
+            index.push((PathBuf::from(path), len as usize));
+        }
+        let mut entries = HashMap::new();
+        for (path, len) in index {
+            take(&data, &mut cursor, len)?;
+            entries.insert(path, (cursor - len, cursor));
+        }
+        Ok(AssetPack { data, entries })
+    }
+
+    // the pack next to the executable, if there is one
+    #[cfg(not(target_arch = "wasm32"))]
+    fn find() -> Option<Self> {
+        let path = std::env::current_exe().ok()?.parent()?.join(PACK_FILE);
+        let data = fs::read(&path).ok()?;
+        match AssetPack::parse(data) {
+            Ok(pack) => {
+                info!(
+                    "Loading {} assets from {}",
+                    pack.entries.len(),
+                    path.display()
+                );
+                Some(pack)
+            }
+            Err(err) => {
+                warn!("Ignoring {}: {}", path.display(), err);
+                None
+            }
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn find() -> Option<Self> {
+        None
+    }
+
+    pub fn contains(&self, path: &Path) -> bool {
+        self.entries.contains_key(path)
+    }
+
+    // the uncompressed contents of the file at `path`, None if it isn't packed
+    pub fn read(&self, path: &Path) -> Option<io::Result<Vec<u8>>> {
+        let (start, end) = *self.entries.get(path)?;
+        let mut bytes = Vec::new();
+        Some(
+            ZlibDecoder::new(&self.data[start..end])
+                .read_to_end(&mut bytes)
+                .map(|_| bytes),
+        )
+    }
+}
+
+// every file under `dir`, as paths relative to `root` with forward slashes
+fn collect_files(root: &Path, dir: &Path, files: &mut Vec<String>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_files(root, &path, files)?;
+        } else if let Ok(relative) = path.strip_prefix(root) {
+            let parts: Vec<_> = relative.iter().map(|part| part.to_string_lossy()).collect();
+            files.push(parts.join("/"));
+        }
+    }
+    Ok(())
+}
+
+// pack every file under `assets` into `out`, returning how many were packed
+pub fn write_pack(assets: &Path, out: &Path) -> io::Result<usize> {
+    let mut files = Vec::new();
+    collect_files(assets, assets, &mut files)?;
+    files.sort();
+    let mut compressed = Vec::with_capacity(files.len());
+    for file in &files {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::best());
+        encoder.write_all(&fs::read(assets.join(file))?)?;
+        compressed.push(encoder.finish()?);
+    }
+
+    let mut pack = PACK_MAGIC.to_vec();
+    pack.extend((files.len() as u32).to_le_bytes());
+    for (file, bytes) in files.iter().zip(&compressed) {
+        pack.extend((file.len() as u16).to_le_bytes());
+        pack.extend(file.as_bytes());
+        pack.extend((bytes.len() as u32).to_le_bytes());
+    }
+    for bytes in &compressed {
+        pack.extend(bytes);
+    }
+    fs::write(out, pack)?;
+    Ok(files.len())
+}
+
+// Reads assets from the ones built into the executable, then the pack, and whatever
+// isn't in either from the loose assets folder
+struct PackReader {
+    pack: Option<Arc<AssetPack>>,
+    fallback: Box<dyn AssetReader>,
+}
+
+impl AssetReader for PackReader {
+    fn read<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> BoxedFuture<'a, Result<Box<Reader<'a>>, AssetReaderError>> {
+        let packed = match embedded(path) {
+            Some(bytes) => Some(Ok(bytes.to_vec())),
+            None => self.pack.as_ref().and_then(|pack| pack.read(path)),
+        };
+        Box::pin(async move {
+            match packed {
+                Some(bytes) => Ok(Box::new(VecReader::new(bytes?)) as Box<Reader>),
+                None => self.fallback.read(path).await,
+            }
+        })
+    }
+
+    fn read_meta<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> BoxedFuture<'a, Result<Box<Reader<'a>>, AssetReaderError>> {
+        self.fallback.read_meta(path)
+    }
+
+    fn read_directory<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> BoxedFuture<'a, Result<Box<PathStream>, AssetReaderError>> {
+        self.fallback.read_directory(path)
+    }
+
+    fn is_directory<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> BoxedFuture<'a, Result<bool, AssetReaderError>> {
+        self.fallback.is_directory(path)
+    }
+}
+
+// Loads assets from PACK_FILE when one sits next to the executable, and from the ones
+// built into it with the `embedded_assets` feature. Has to be added before bevy's
+// AssetPlugin, i.e. before DefaultPlugins.
+pub struct AssetPackPlugin;
+
+impl Plugin for AssetPackPlugin {
+    fn build(&self, app: &mut App) {
+        let pack = AssetPack::find().map(Arc::new);
+        if pack.is_none() && !cfg!(feature = "embedded_assets") {
+            return;
+        }
+        if let Some(pack) = &pack {
+            app.insert_resource(LoadedPack(pack.clone()));
+        }
+        let reader_pack = pack.clone();
+        app.register_asset_source(
+            AssetSourceId::Default,
+            AssetSource::build().with_reader(move || {
+                Box::new(PackReader {
+                    pack: reader_pack.clone(),
+                    fallback: AssetSource::get_default_reader("assets".to_string())(),
+                })
+            }),
+        );
+    }
+}
+
+// The pack assets are read from, if one was found
+#[derive(Resource)]
+pub struct LoadedPack(pub Arc<AssetPack>);
+
+// whether the asset at `path` comes with the executable, built in or in the pack
+pub fn is_bundled(pack: Option<&LoadedPack>, path: &Path) -> bool {
+    embedded(path).is_some() || pack.is_some_and(|pack| pack.0.contains(path))
+}
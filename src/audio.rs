@@ -0,0 +1,98 @@
+use crate::{GameState, GameplaySet};
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+
+#[cfg(feature = "audio")]
+mod playback;
+mod zone;
+
+pub use zone::AudioZone;
+
+// Sound effects that gameplay systems can request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(not(feature = "audio"), allow(dead_code))]
+pub enum Sfx {
+    Jump,
+    Footstep,
+    BirdScreech,
+    BoulderRumble,
+    MilestoneFanfare,
+    Skid,
+    Explosion,
+    // sting of the mega-dash setting off
+    Boost,
+}
+
+impl Sfx {
+    // text shown for the cue when captions are enabled, if it is important enough to caption
+    pub fn caption(&self) -> Option<&'static str> {
+        match self {
+            Sfx::BirdScreech => Some("Spitter screeching"),
+            Sfx::BoulderRumble => Some("Boulder rumbling"),
+            Sfx::MilestoneFanfare => Some("Milestone fanfare"),
+            Sfx::Explosion => Some("Explosion"),
+            Sfx::Boost => Some("Boost"),
+            Sfx::Jump | Sfx::Footstep | Sfx::Skid => None,
+        }
+    }
+}
+
+// Event sent by gameplay systems to play a sound effect
+#[derive(Event, Debug, Clone, Copy)]
+pub struct PlaySfx(pub Sfx);
+
+// Whether sounds can be heard yet. Browsers only allow audio once the page got some
+// input, until then sound effects are dropped instead of piling up for later.
+#[derive(Resource, Debug, PartialEq, Eq)]
+pub struct AudioUnlocked(pub bool);
+
+impl Default for AudioUnlocked {
+    fn default() -> Self {
+        AudioUnlocked(!cfg!(target_arch = "wasm32"))
+    }
+}
+
+// system to unlock audio on the first key, click, touch or gamepad button
+fn unlock_audio(
+    mut unlocked: ResMut<AudioUnlocked>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    touches: Res<Touches>,
+    gamepad_buttons: Res<ButtonInput<GamepadButton>>,
+) {
+    if unlocked.0 {
+        return;
+    }
+    if keys.get_just_pressed().next().is_some()
+        || mouse.get_just_pressed().next().is_some()
+        || touches.any_just_pressed()
+        || gamepad_buttons.get_just_pressed().next().is_some()
+    {
+        crate::web::resume_audio();
+        unlocked.0 = true;
+    }
+}
+
+pub struct SfxPlugin;
+
+impl Plugin for SfxPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<PlaySfx>()
+            .init_resource::<AudioUnlocked>()
+            .init_resource::<zone::Muffle>()
+            .add_systems(PreUpdate, unlock_audio.after(InputSystem))
+            .add_systems(OnExit(GameState::Playing), zone::clear_muffle)
+            .add_systems(Update, zone::update_muffle.in_set(GameplaySet));
+        // without the audio feature the events are still sent, for captions and the like
+        #[cfg(feature = "audio")]
+        app.init_resource::<playback::SfxLibrary>()
+            .add_systems(
+                Update,
+                playback::load_sfx.in_set(crate::startup::DeferredLoad),
+            )
+            .add_systems(
+                Update,
+                playback::play_sfx.run_if(resource_equals(AudioUnlocked(true))),
+            );
+    }
+}
@@ -0,0 +1,150 @@
+use super::zone::Muffle;
+use super::{PlaySfx, Sfx};
+use crate::startup::GameplayAssets;
+use bevy::audio::Volume;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use rand::Rng;
+
+// share of the volume and of the pitch taken off at full muffle. Bevy's audio has no
+// filters, so quieter and a touch lower stands in for a low-pass.
+const MUFFLE_VOLUME: f32 = 0.45;
+const MUFFLE_PITCH: f32 = 0.08;
+
+// A set of interchangeable samples for one sound effect, with the ranges
+// that pitch and volume are randomized within on every playback
+struct SfxBank {
+    variants: Vec<Handle<AudioSource>>,
+    pitch: (f32, f32),
+    volume: (f32, f32),
+    next: usize,
+}
+
+// All loaded sound effects, keyed by the effect they play
+#[derive(Resource, Default)]
+pub struct SfxLibrary {
+    banks: HashMap<Sfx, SfxBank>,
+}
+
+impl SfxLibrary {
+    pub fn insert(
+        &mut self,
+        sfx: Sfx,
+        variants: Vec<Handle<AudioSource>>,
+        pitch: (f32, f32),
+        volume: (f32, f32),
+    ) {
+        self.banks.insert(
+            sfx,
+            SfxBank {
+                variants,
+                pitch,
+                volume,
+                next: 0,
+            },
+        );
+    }
+
+    // Pick the next variant round-robin and roll a pitch and volume for it
+    pub fn next_playback(
+        &mut self,
+        sfx: Sfx,
+        rng: &mut impl Rng,
+    ) -> Option<(Handle<AudioSource>, PlaybackSettings)> {
+        let bank = self.banks.get_mut(&sfx)?;
+        if bank.variants.is_empty() {
+            return None;
+        }
+        let source = bank.variants[bank.next].clone();
+        bank.next = (bank.next + 1) % bank.variants.len();
+        let pitch = random_in(rng, bank.pitch);
+        let volume = random_in(rng, bank.volume);
+        let settings = PlaybackSettings::DESPAWN
+            .with_speed(pitch)
+            .with_volume(Volume::new(volume));
+        Some((source, settings))
+    }
+}
+
+fn random_in(rng: &mut impl Rng, (min, max): (f32, f32)) -> f32 {
+    if max > min {
+        rng.gen_range(min..=max)
+    } else {
+        min
+    }
+}
+
+pub fn load_sfx(
+    asset_server: Res<AssetServer>,
+    mut library: ResMut<SfxLibrary>,
+    mut assets: ResMut<GameplayAssets>,
+) {
+    let mut load = |paths: &[&'static str]| -> Vec<Handle<AudioSource>> {
+        let handles: Vec<Handle<AudioSource>> =
+            paths.iter().map(|path| asset_server.load(*path)).collect();
+        assets
+            .0
+            .extend(handles.iter().map(|handle| handle.clone().untyped()));
+        handles
+    };
+    library.insert(
+        Sfx::Jump,
+        load(&["sfx/jump_1.wav", "sfx/jump_2.wav", "sfx/jump_3.wav"]),
+        (0.9, 1.1),
+        (0.7, 0.9),
+    );
+    library.insert(
+        Sfx::Footstep,
+        load(&[
+            "sfx/footstep_1.wav",
+            "sfx/footstep_2.wav",
+            "sfx/footstep_3.wav",
+        ]),
+        (0.85, 1.15),
+        (0.3, 0.45),
+    );
+    library.insert(
+        Sfx::BirdScreech,
+        load(&["sfx/bird_screech.wav"]),
+        (0.95, 1.05),
+        (0.8, 0.9),
+    );
+    library.insert(
+        Sfx::BoulderRumble,
+        load(&["sfx/boulder_rumble.wav"]),
+        (0.9, 1.0),
+        (0.9, 1.0),
+    );
+    library.insert(
+        Sfx::MilestoneFanfare,
+        load(&["sfx/milestone_fanfare.wav"]),
+        (1.0, 1.0),
+        (0.8, 0.8),
+    );
+    library.insert(Sfx::Skid, load(&["sfx/skid.wav"]), (0.9, 1.1), (0.5, 0.65));
+    library.insert(
+        Sfx::Explosion,
+        load(&["sfx/explosion.wav"]),
+        (0.85, 1.05),
+        (0.8, 0.9),
+    );
+    library.insert(Sfx::Boost, load(&["sfx/boost.wav"]), (1.0, 1.0), (0.8, 0.8));
+}
+
+// system to spawn a one-shot audio entity for every requested sound effect, muffled
+// while the player is in an audio zone
+pub fn play_sfx(
+    mut commands: Commands,
+    mut library: ResMut<SfxLibrary>,
+    muffle: Res<Muffle>,
+    mut events: EventReader<PlaySfx>,
+) {
+    let mut rng = rand::thread_rng();
+    for PlaySfx(sfx) in events.read() {
+        if let Some((source, mut settings)) = library.next_playback(*sfx, &mut rng) {
+            settings.volume = Volume::new(settings.volume.get() * (1.0 - MUFFLE_VOLUME * muffle.0));
+            settings.speed *= 1.0 - MUFFLE_PITCH * muffle.0;
+            commands.spawn(AudioBundle { source, settings });
+        }
+    }
+}
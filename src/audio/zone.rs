@@ -0,0 +1,54 @@
+use crate::collision::Collider;
+use crate::game_time::GameTime;
+use crate::PlayerOne;
+use bevy::prelude::*;
+
+// how fast sounds muffle and clear again, in muffle per second
+const MUFFLE_FADE: f32 = 3.0;
+
+// Trigger volume muffling sounds while the player is in it, over the area of its collider:
+// foreground cover, caves and the like
+#[derive(Component, Debug, Clone, Copy)]
+pub struct AudioZone {
+    // from 0 for not at all to 1 for as muffled as sounds get
+    pub muffle: f32,
+}
+
+// How muffled sounds are now, easing toward the strongest zone the player is in
+#[derive(Resource, Debug, Default)]
+#[cfg_attr(not(feature = "audio"), allow(dead_code))]
+pub struct Muffle(pub f32);
+
+// system to ease the muffle toward that of the zones the first player is in
+pub fn update_muffle(
+    time: Res<GameTime>,
+    mut muffle: ResMut<Muffle>,
+    player_query: Query<(&GlobalTransform, &Collider), PlayerOne>,
+    zone_query: Query<(&GlobalTransform, &Collider, &AudioZone)>,
+) {
+    let Ok((player_transform, player_collider)) = player_query.get_single() else {
+        return;
+    };
+    let player_rect = player_collider.world_rect(player_transform);
+    let target = zone_query
+        .iter()
+        .filter(|(transform, collider, _)| {
+            !collider
+                .world_rect(transform)
+                .intersect(player_rect)
+                .is_empty()
+        })
+        .map(|(_, _, zone)| zone.muffle)
+        .fold(0.0, f32::max);
+    let step = MUFFLE_FADE * time.delta_seconds();
+    if muffle.0 < target {
+        muffle.0 = (muffle.0 + step).min(target);
+    } else if muffle.0 > target {
+        muffle.0 = (muffle.0 - step).max(target);
+    }
+}
+
+// sounds clear up as soon as the run is left
+pub fn clear_muffle(mut muffle: ResMut<Muffle>) {
+    muffle.0 = 0.0;
+}
@@ -0,0 +1,73 @@
+use crate::barrel::Barrel;
+use crate::enemy::Enemy;
+use crate::input::{Action, ActionSet, ActionSource, ActionState};
+use crate::projectile::Projectile;
+#[cfg(feature = "dev_tools")]
+use crate::toast::Toast;
+use crate::PlayerOne;
+use bevy::prelude::*;
+
+// how far ahead of the player the bot looks for something to jump over
+const LOOKAHEAD: f32 = 90.0;
+// hazards higher than this above the player's feet are left to pass overhead
+const JUMP_CEILING: f32 = 40.0;
+
+type Hazards = Or<(With<Enemy>, With<Projectile>, With<Barrel>)>;
+
+// system to hand the player over to the bot with F2, and back. A dev tool: the bot's runs
+// would bank coins, grades and author rewards like anyone's.
+#[cfg(feature = "dev_tools")]
+fn toggle_autopilot(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut source: ResMut<ActionSource>,
+    mut toasts: EventWriter<Toast>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::F2) {
+        return;
+    }
+    *source = match *source {
+        ActionSource::Autopilot => ActionSource::Devices,
+        _ => ActionSource::Autopilot,
+    };
+    let state = if *source == ActionSource::Autopilot {
+        "on"
+    } else {
+        "off"
+    };
+    toasts.send(Toast(format!("Autopilot {}", state)));
+}
+
+// action provider: a simple bot that runs along and jumps over whatever comes its way
+fn autopilot_actions(
+    mut actions: ResMut<ActionState>,
+    player_query: Query<&Transform, PlayerOne>,
+    hazard_query: Query<&GlobalTransform, Hazards>,
+) {
+    actions.release_movement();
+    let Ok(player) = player_query.get_single() else {
+        return;
+    };
+    let feet = player.translation.truncate();
+    let incoming = hazard_query.iter().any(|hazard| {
+        let offset = hazard.translation().truncate() - feet;
+        offset.x > 0.0 && offset.x < LOOKAHEAD && offset.y < JUMP_CEILING
+    });
+    if incoming {
+        actions.press(Action::Jump);
+    }
+}
+
+pub struct AutopilotPlugin;
+
+impl Plugin for AutopilotPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PreUpdate,
+            autopilot_actions
+                .in_set(ActionSet::Provide)
+                .run_if(resource_equals(ActionSource::Autopilot)),
+        );
+        #[cfg(feature = "dev_tools")]
+        app.add_systems(Update, toggle_autopilot);
+    }
+}
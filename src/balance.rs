@@ -0,0 +1,182 @@
+use crate::error::GameError;
+use crate::score::PointSource;
+use crate::storage;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+// downloading the remote balance, over plain sockets the web build doesn't have
+#[cfg(not(target_arch = "wasm32"))]
+mod remote;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use remote::fetch;
+
+// balance shipped with the game, used until a fetched one is known to parse
+const BUNDLED_BALANCE: &str = include_str!("../assets/balance.ron");
+// the last balance fetched from the remote, so it still applies when offline
+const BALANCE_CACHE_PATH: &str = "balance_cache.ron";
+
+// From this distance on, enemies are packed closer together by `spacing`
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct DifficultyStep {
+    pub distance: f32,
+    pub spacing: f32,
+}
+
+// Points each obstacle and enemy is worth when cleared or destroyed, before the combo
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[serde(default)]
+pub struct PointValues {
+    pub coin: u32,
+    pub spitter: u32,
+    pub boss: u32,
+    // a projectile sent back with a dash
+    pub deflect: u32,
+    // an obstacle smashed through on a boost
+    pub smash: u32,
+    // a swing got past without being hit
+    pub swing: u32,
+}
+
+impl Default for PointValues {
+    fn default() -> Self {
+        PointValues {
+            coin: 10,
+            spitter: 100,
+            boss: 1000,
+            deflect: 50,
+            smash: 150,
+            swing: 25,
+        }
+    }
+}
+
+impl PointValues {
+    pub fn of(&self, source: PointSource) -> u32 {
+        match source {
+            PointSource::Coin => self.coin,
+            PointSource::Spitter => self.spitter,
+            PointSource::Boss => self.boss,
+            PointSource::Deflect => self.deflect,
+            PointSource::Smash => self.smash,
+            PointSource::Swing => self.swing,
+        }
+    }
+}
+
+// Spawn tables and difficulty curve, tweakable without shipping a new build
+#[derive(Resource, Serialize, Deserialize, Debug, Clone)]
+pub struct Balance {
+    // distance between two enemies, picked at random in this range
+    pub enemy_spacing: (f32, f32),
+    // every this many enemies the next one is a boss
+    pub boss_every: u32,
+    // distance between two explosive barrels, picked at random in this range
+    #[serde(default = "default_barrel_spacing")]
+    pub barrel_spacing: (f32, f32),
+    // distance between two swinging vines or spike bars, picked at random in this range
+    #[serde(default = "default_swing_spacing")]
+    pub swing_spacing: (f32, f32),
+    pub spitter_health: f32,
+    pub boss_health: f32,
+    // seconds between two shots of regular enemies and of bosses
+    pub spitter_shot_interval: f32,
+    pub boss_shot_interval: f32,
+    // steps ordered by distance
+    pub difficulty: Vec<DifficultyStep>,
+    #[serde(default)]
+    pub points: PointValues,
+}
+
+// for balances written before barrels were in the spawn tables
+fn default_barrel_spacing() -> (f32, f32) {
+    (1200.0, 2600.0)
+}
+
+// for balances written before swings were in the spawn tables
+fn default_swing_spacing() -> (f32, f32) {
+    (1800.0, 3400.0)
+}
+
+impl Balance {
+    fn bundled() -> Result<Self, GameError> {
+        let balance: Balance = ron::from_str(BUNDLED_BALANCE).map_err(GameError::Balance)?;
+        balance.check().map_err(GameError::InvalidBalance)?;
+        Ok(balance)
+    }
+
+    // parsed from `contents`, if that's a balance the game can be played with
+    fn parse(contents: &str) -> Result<Self, String> {
+        let balance: Balance = ron::from_str(contents).map_err(|err| err.to_string())?;
+        balance.check()?;
+        Ok(balance)
+    }
+
+    // the cached remote balance if a remote one is configured and the cache is valid, the
+    // bundled one otherwise
+    pub fn load(remote: bool) -> Result<Self, GameError> {
+        if !remote {
+            return Balance::bundled();
+        }
+        let Ok(contents) = storage::read_to_string(BALANCE_CACHE_PATH) else {
+            return Balance::bundled();
+        };
+        Balance::parse(&contents).or_else(|err| {
+            warn!("Ignoring {}: {}", BALANCE_CACHE_PATH, err);
+            Balance::bundled()
+        })
+    }
+
+    // what would break runs played with the balance: a spacing range running backwards
+    // panics when drawn from, and a spacing that isn't positive never moves the next
+    // spawn along
+    pub fn check(&self) -> Result<(), String> {
+        let ranges = [
+            ("enemy_spacing", self.enemy_spacing),
+            ("barrel_spacing", self.barrel_spacing),
+            ("swing_spacing", self.swing_spacing),
+        ];
+        for (name, (min, max)) in ranges {
+            if !(min > 0.0 && min <= max && max.is_finite()) {
+                return Err(format!(
+                    "{} has to go from a positive minimum up to its maximum, not ({}, {})",
+                    name, min, max
+                ));
+            }
+        }
+        let stalled = self
+            .difficulty
+            .iter()
+            .find(|step| !(step.spacing > 0.0 && step.spacing.is_finite()));
+        if let Some(step) = stalled {
+            return Err(format!(
+                "the difficulty spacing from {} on has to be positive, not {}",
+                step.distance, step.spacing
+            ));
+        }
+        Ok(())
+    }
+
+    // factor applied to the enemy and barrel spacing `distance` into the run
+    pub fn spacing_at(&self, distance: f32) -> f32 {
+        self.difficulty
+            .iter()
+            .take_while(|step| step.distance <= distance)
+            .last()
+            .map_or(1.0, |step| step.spacing)
+    }
+}
+
+pub struct BalancePlugin;
+
+impl Plugin for BalancePlugin {
+    // the web build sticks to the bundled balance
+    #[cfg_attr(target_arch = "wasm32", allow(unused_variables))]
+    fn build(&self, app: &mut App) {
+        #[cfg(not(target_arch = "wasm32"))]
+        app.add_systems(Startup, remote::start_fetch).add_systems(
+            Update,
+            remote::finish_fetch.run_if(resource_exists::<remote::BalanceFetch>),
+        );
+    }
+}
@@ -0,0 +1,88 @@
+use super::{Balance, BALANCE_CACHE_PATH};
+use crate::settings::Settings;
+use crate::storage;
+use crate::toast::Toast;
+use bevy::prelude::*;
+use bevy::tasks::{block_on, futures_lite::future, IoTaskPool, Task};
+use std::io::{self, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+const FETCH_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Remote balance being downloaded in the background
+#[derive(Resource)]
+pub(super) struct BalanceFetch(Task<io::Result<String>>);
+
+// plain HTTP GET of `url`, the body of a 200 response
+pub fn fetch(url: &str) -> io::Result<String> {
+    let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidInput, message.to_string());
+    let rest = url
+        .strip_prefix("http://")
+        .ok_or_else(|| invalid("only http:// URLs are supported"))?;
+    let (host, path) = rest.split_once('/').unwrap_or((rest, ""));
+    let address = if host.contains(':') {
+        host.to_string()
+    } else {
+        format!("{}:80", host)
+    };
+    let address = address
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| invalid("host not found"))?;
+    let mut stream = TcpStream::connect_timeout(&address, FETCH_TIMEOUT)?;
+    stream.set_read_timeout(Some(FETCH_TIMEOUT))?;
+    write!(
+        stream,
+        "GET /{} HTTP/1.0\r\nHost: {}\r\nConnection: close\r\n\r\n",
+        path, host
+    )?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response)?;
+    let (head, body) = response
+        .split_once("\r\n\r\n")
+        .ok_or_else(|| invalid("malformed response"))?;
+    let status = head.lines().next().unwrap_or_default();
+    if status.split_whitespace().nth(1) != Some("200") {
+        return Err(io::Error::other(status.to_string()));
+    }
+    Ok(body.to_string())
+}
+
+// system to start downloading the remote balance, if one is configured
+pub(super) fn start_fetch(mut commands: Commands, settings: Res<Settings>) {
+    let Some(url) = settings.balance_url.clone() else {
+        return;
+    };
+    let task = IoTaskPool::get().spawn(async move { fetch(&url) });
+    commands.insert_resource(BalanceFetch(task));
+}
+
+// system to switch to the downloaded balance once it arrives, and cache it for later
+pub(super) fn finish_fetch(
+    mut commands: Commands,
+    mut fetch: ResMut<BalanceFetch>,
+    mut toasts: EventWriter<Toast>,
+) {
+    let Some(result) = block_on(future::poll_once(&mut fetch.0)) else {
+        return;
+    };
+    commands.remove_resource::<BalanceFetch>();
+    let contents = match result {
+        Ok(contents) => contents,
+        Err(err) => {
+            warn!("Could not fetch the remote balance: {}", err);
+            return;
+        }
+    };
+    match Balance::parse(&contents) {
+        Ok(remote) => {
+            commands.insert_resource(remote);
+            if let Err(err) = storage::write(BALANCE_CACHE_PATH, contents) {
+                warn!("Could not write {}: {}", BALANCE_CACHE_PATH, err);
+            }
+            toasts.send(Toast("Balance updated".to_string()));
+        }
+        Err(err) => warn!("Ignoring the remote balance: {}", err),
+    }
+}
@@ -0,0 +1,311 @@
+use crate::audio::{PlaySfx, Sfx};
+use crate::balance::Balance;
+use crate::coin::coin_bundle;
+use crate::collision::Collider;
+use crate::drop_in::SecondPlayer;
+use crate::dust::DustPuff;
+use crate::enemy::Enemy;
+use crate::feedback::Feedback;
+use crate::game_time::GameTime;
+use crate::gate::Gate;
+use crate::health::{Boss, Health, HitSet, Invincible};
+use crate::heatmap::{Hazard, LastHit};
+use crate::mode::{hazards_enabled, RunRng};
+use crate::mutators::RunConfig;
+use crate::projectile::Projectile;
+use crate::score::{AddPoints, PointSource};
+use crate::timing::timed;
+use crate::{
+    jump_heights, GameState, GameplaySet, Player, PlayerState, RunEntity, GROUND_Y, MOVE_SPEED,
+    PLAYER_SCALE, RUN_SPEED,
+};
+use bevy::prelude::*;
+use bevy_parallax::ParallaxCameraComponent;
+use rand::Rng;
+
+pub const BARREL_SIZE: Vec2 = Vec2::new(28.0, 36.0);
+const BARREL_COLOR: Color = Color::rgb(0.75, 0.22, 0.12);
+// how far past the right edge of the view barrels are placed, enough to hide the
+// start of their coin trail
+const SPAWN_MARGIN: f32 = 128.0;
+// distance between two coins of the trail over a barrel
+const TRAIL_GAP: f32 = 24.0;
+// everything within this distance of a blast is caught in it, less so towards the edge
+const BLAST_RADIUS: f32 = 110.0;
+// sideways speed a blast at point blank gives the player, in pixels per frame
+const KNOCKBACK: f32 = 7.0;
+// how high a blast at point blank throws the player
+const KNOCK_UP: f32 = 60.0;
+// how far a blast at point blank pushes enemies
+const ENEMY_PUSH: f32 = 40.0;
+// damage at point blank, to the player and to enemies
+const BLAST_DAMAGE: f32 = 1.0;
+const ENEMY_BLAST_DAMAGE: f32 = 3.0;
+// seconds between a barrel being caught in a blast and it going off too
+const CHAIN_FUSE: f32 = 0.15;
+// how far a blast right next to the player throws the view, fading out over SHAKE_RANGE
+const SHAKE: f32 = 10.0;
+const SHAKE_RANGE: f32 = 480.0;
+
+// Barrels that haven't been lit yet
+type Unlit = (With<Barrel>, Without<Fuse>);
+
+// Keeps the player apart from the enemies and barrels moved alongside it
+type PlayerOnly = (With<Player>, Without<Enemy>, Without<Barrel>);
+// A player in reach of a blast, who may be thrown back by it
+type Blasted<'a> = (
+    &'a mut Player,
+    &'a mut Transform,
+    &'a mut Health,
+    Has<Invincible>,
+    Has<SecondPlayer>,
+);
+// a player who can set barrels off by running into them, not smashing through on a boost
+type Vulnerable = (With<Player>, Without<Invincible>);
+
+// Explosive barrel standing on the course
+#[derive(Component)]
+pub struct Barrel;
+
+// Barrel about to go off once its fuse burns down
+#[derive(Component)]
+struct Fuse(Timer);
+
+// A barrel went off at this point
+#[derive(Event, Debug, Clone, Copy)]
+struct Explosion(Vec2);
+
+// Where the next barrel of the run goes
+#[derive(Resource, Default)]
+pub struct BarrelSpawner {
+    pub next_x: f32,
+}
+
+fn reset_barrels(mut commands: Commands, balance: Res<Balance>) {
+    commands.insert_resource(BarrelSpawner {
+        next_x: balance.barrel_spacing.1,
+    });
+}
+
+// lay coins along the jump that clears a barrel at `x`, peaking right over it, as the
+// player makes it running at full speed under the run's physics
+fn spawn_trail(commands: &mut Commands, config: &RunConfig, x: f32) {
+    let heights = jump_heights(config.gravity);
+    let speed = (RUN_SPEED + MOVE_SPEED) * config.speed;
+    let peak = heights
+        .iter()
+        .enumerate()
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map_or(0, |(frame, _)| frame);
+    let start = x - speed * peak as f32;
+    let mut next_coin = 0.0;
+    for (frame, height) in heights.iter().enumerate() {
+        let travelled = speed * frame as f32;
+        if travelled < next_coin {
+            continue;
+        }
+        next_coin = travelled + TRAIL_GAP;
+        commands.spawn(coin_bundle(Vec2::new(start + travelled, GROUND_Y + height)));
+    }
+}
+
+// a barrel standing on the ground at `x`
+pub fn barrel_bundle(x: f32) -> impl Bundle {
+    let half = BARREL_SIZE / 2.0;
+    // the player's feet are half a sprite below GROUND_Y
+    let y = GROUND_Y - 8.0 * PLAYER_SCALE + half.y;
+    (
+        SpriteBundle {
+            sprite: Sprite {
+                color: BARREL_COLOR,
+                custom_size: Some(BARREL_SIZE),
+                ..default()
+            },
+            transform: Transform::from_xyz(x, y, 1.4),
+            ..default()
+        },
+        Barrel,
+        Collider(Rect::from_center_half_size(Vec2::ZERO, half)),
+        RunEntity,
+    )
+}
+
+// stand a barrel on the ground at `x`, with its trail of coins
+pub fn spawn_barrel(commands: &mut Commands, config: &RunConfig, x: f32) {
+    commands.spawn(barrel_bundle(x));
+    spawn_trail(commands, config, x);
+}
+
+// system to stand barrels on the ground just out of view ahead of the camera, each with
+// a trail of coins showing the way over it
+fn spawn_barrels(
+    mut commands: Commands,
+    mut spawner: ResMut<BarrelSpawner>,
+    mut rng: ResMut<RunRng>,
+    balance: Res<Balance>,
+    config: Res<RunConfig>,
+    gate: Res<Gate>,
+    camera_query: Query<(&Transform, &OrthographicProjection), With<ParallaxCameraComponent>>,
+) {
+    let Ok((camera, projection)) = camera_query.get_single() else {
+        return;
+    };
+    if camera.translation.x + projection.area.max.x + SPAWN_MARGIN < spawner.next_x {
+        return;
+    }
+    if gate.covers(spawner.next_x) {
+        spawner.next_x = gate.end;
+        return;
+    }
+    spawn_barrel(&mut commands, &config, spawner.next_x);
+    let (min, max) = balance.barrel_spacing;
+    spawner.next_x += rng.0.gen_range(min..=max)
+        * balance.spacing_at(spawner.next_x)
+        * config.difficulty.spacing();
+}
+
+// system to light barrels a player runs into or a projectile hits, at once
+fn ignite_barrels(
+    mut commands: Commands,
+    barrel_query: Query<(Entity, &GlobalTransform, &Collider), Unlit>,
+    player_query: Query<(&GlobalTransform, &Collider), Vulnerable>,
+    projectile_query: Query<(Entity, &GlobalTransform, &Collider), With<Projectile>>,
+) {
+    let player_rects: Vec<Rect> = player_query
+        .iter()
+        .map(|(transform, collider)| collider.world_rect(transform))
+        .collect();
+    for (barrel, transform, collider) in &barrel_query {
+        let rect = collider.world_rect(transform);
+        let touched = player_rects
+            .iter()
+            .any(|player| !rect.intersect(*player).is_empty());
+        let shot = projectile_query.iter().find(|(_, transform, collider)| {
+            !rect.intersect(collider.world_rect(transform)).is_empty()
+        });
+        if let Some((projectile, _, _)) = shot {
+            commands.entity(projectile).despawn_recursive();
+        }
+        if touched || shot.is_some() {
+            commands
+                .entity(barrel)
+                .insert(Fuse(Timer::from_seconds(0.0, TimerMode::Once)));
+        }
+    }
+}
+
+// system to blow up the barrels whose fuse has burnt down
+fn detonate_barrels(
+    mut commands: Commands,
+    time: Res<GameTime>,
+    mut barrel_query: Query<(Entity, &Transform, &mut Fuse), With<Barrel>>,
+    mut explosions: EventWriter<Explosion>,
+) {
+    for (barrel, transform, mut fuse) in &mut barrel_query {
+        if fuse.0.tick(time.delta()).finished() {
+            commands.entity(barrel).despawn_recursive();
+            explosions.send(Explosion(transform.translation.truncate()));
+        }
+    }
+}
+
+// share of a blast's full force felt at `distance` from it
+fn falloff(distance: f32) -> f32 {
+    (1.0 - distance / BLAST_RADIUS).max(0.0)
+}
+
+// system to throw back and hurt whatever is caught in a blast, and light the barrels
+// around it so they go off shortly after
+#[allow(clippy::too_many_arguments)]
+fn apply_explosions(
+    mut commands: Commands,
+    mut explosions: EventReader<Explosion>,
+    mut player_query: Query<Blasted, PlayerOnly>,
+    mut enemy_query: Query<(Entity, &mut Transform, &mut Health), With<Enemy>>,
+    boss_query: Query<(), With<Boss>>,
+    barrel_query: Query<(Entity, &Transform), (Unlit, Without<Enemy>)>,
+    mut last_hit: ResMut<LastHit>,
+    mut points: EventWriter<AddPoints>,
+    mut dust: EventWriter<DustPuff>,
+    mut sfx: EventWriter<PlaySfx>,
+    mut feedback: EventWriter<Feedback>,
+) {
+    for Explosion(center) in explosions.read() {
+        sfx.send(PlaySfx(Sfx::Explosion));
+        for direction in [-1.0, 1.0] {
+            dust.send(DustPuff {
+                position: *center,
+                direction,
+            });
+        }
+        for (mut player, mut transform, mut health, invincible, second) in &mut player_query {
+            let offset = transform.translation.truncate() - *center;
+            let force = falloff(offset.length());
+            if force > 0.0 && !invincible {
+                // blown away from the blast, backwards when right on top of it
+                let direction = if offset.x > 0.0 { 1.0 } else { -1.0 };
+                player.move_speed = direction * KNOCKBACK * force;
+                player.skid = 0.0;
+                player.on_ground = false;
+                player.state = PlayerState::Falling;
+                transform.scale.y = PLAYER_SCALE;
+                transform.translation.y = transform.translation.y.max(GROUND_Y) + KNOCK_UP * force;
+                health.current -= BLAST_DAMAGE * force;
+                if second {
+                    continue;
+                }
+                last_hit.0 = Some(Hazard::Blast);
+                feedback.send(Feedback::Flash(Color::rgba(1.0, 0.55, 0.1, 0.5)));
+            }
+            let distance = offset.length();
+            if distance < SHAKE_RANGE && !second {
+                feedback.send(Feedback::Shake(SHAKE * (1.0 - distance / SHAKE_RANGE)));
+            }
+        }
+        for (enemy, mut transform, mut health) in &mut enemy_query {
+            let offset = transform.translation.truncate() - *center;
+            let force = falloff(offset.length());
+            if force <= 0.0 {
+                continue;
+            }
+            // bosses stand their ground but still take the hit
+            let boss = boss_query.contains(enemy);
+            if !boss {
+                transform.translation.x += offset.x.signum() * ENEMY_PUSH * force;
+            }
+            health.current -= ENEMY_BLAST_DAMAGE * force;
+            if health.current <= 0.0 {
+                commands.entity(enemy).despawn_recursive();
+                points.send(AddPoints(PointSource::defeated(boss)));
+            }
+        }
+        for (barrel, transform) in &barrel_query {
+            if transform.translation.truncate().distance(*center) < BLAST_RADIUS {
+                commands
+                    .entity(barrel)
+                    .insert(Fuse(Timer::from_seconds(CHAIN_FUSE, TimerMode::Once)));
+            }
+        }
+    }
+}
+
+pub struct BarrelPlugin;
+
+impl Plugin for BarrelPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BarrelSpawner>()
+            .add_event::<Explosion>()
+            .add_systems(OnEnter(GameState::Playing), reset_barrels)
+            .add_systems(
+                Update,
+                (
+                    timed(spawn_barrels).run_if(hazards_enabled),
+                    timed(ignite_barrels),
+                    timed(detonate_barrels),
+                    timed(apply_explosions).in_set(HitSet),
+                )
+                    .chain()
+                    .in_set(GameplaySet),
+            );
+    }
+}
@@ -0,0 +1,104 @@
+// Animates 10k sprites at once to see how much headroom sprite animation leaves, logging
+// the frame rate and how many atlases each frame had to update:
+//   cargo run --release --bin animation_stress -- [sprite count]
+use bevy::diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin};
+use bevy::prelude::*;
+use dinorun_core::{
+    GameTime, MenuAnimation, PlaybackMode, SpriteAnimationPlugin, SpriteAnimator, SpriteClip,
+};
+use rand::Rng;
+
+const DEFAULT_COUNT: usize = 10_000;
+// the player sheet: 16 pixel frames in 5 columns, cycled through its first 6 rows, the
+// ones without gaps
+const SHEET: &str = "player.png";
+const FRAME_SIZE: f32 = 16.0;
+const COLUMNS: usize = 5;
+const ROWS: usize = 6;
+// space the sprites are scattered over
+const FIELD: Vec2 = Vec2::new(1200.0, 700.0);
+
+#[derive(Resource)]
+struct SpriteCount(usize);
+
+fn main() {
+    let count = std::env::args()
+        .nth(1)
+        .and_then(|count| count.parse().ok())
+        .unwrap_or(DEFAULT_COUNT);
+    App::new()
+        .add_plugins(DefaultPlugins.set(ImagePlugin::default_nearest()))
+        .add_plugins((
+            FrameTimeDiagnosticsPlugin,
+            LogDiagnosticsPlugin::default(),
+            SpriteAnimationPlugin,
+        ))
+        // animation reads the run's clock, which doesn't tick here: the sprites are all
+        // animated by real time like the ones in menus
+        .init_resource::<GameTime>()
+        .insert_resource(SpriteCount(count))
+        .add_systems(Startup, spawn_sprites)
+        .add_systems(Last, count_updates)
+        .run();
+}
+
+fn spawn_sprites(
+    mut commands: Commands,
+    count: Res<SpriteCount>,
+    asset_server: Res<AssetServer>,
+    mut layouts: ResMut<Assets<TextureAtlasLayout>>,
+) {
+    commands.spawn(Camera2dBundle::default());
+    let texture = asset_server.load(SHEET);
+    let layout = layouts.add(TextureAtlasLayout::from_grid(
+        Vec2::splat(FRAME_SIZE),
+        COLUMNS,
+        ROWS,
+        None,
+        None,
+    ));
+    let mut rng = rand::thread_rng();
+    for _ in 0..count.0 {
+        // frames of different lengths, so the sprites don't all change on the same frame
+        let frame_time = rng.gen_range(0.05..0.2);
+        let clip = SpriteClip {
+            name: "stress".to_string(),
+            frames: (0..COLUMNS * ROWS).collect(),
+            durations: vec![frame_time; COLUMNS * ROWS],
+            hitboxes: Vec::new(),
+            mode: PlaybackMode::Loop,
+        };
+        let position = (Vec2::new(rng.gen(), rng.gen()) - 0.5) * FIELD;
+        commands.spawn((
+            SpriteSheetBundle {
+                texture: texture.clone(),
+                atlas: TextureAtlas {
+                    layout: layout.clone(),
+                    index: 0,
+                },
+                transform: Transform::from_translation(position.extend(0.0)),
+                ..default()
+            },
+            SpriteAnimator::new(clip),
+            MenuAnimation,
+        ));
+    }
+    info!("Animating {} sprites", count.0);
+}
+
+// system to log how many atlases were updated, once a second
+fn count_updates(
+    time: Res<Time<Real>>,
+    changed: Query<(), Changed<TextureAtlas>>,
+    mut total: Local<usize>,
+    mut frames: Local<usize>,
+    mut since: Local<f32>,
+) {
+    *total += changed.iter().count();
+    *frames += 1;
+    *since += time.delta_seconds();
+    if *since >= 1.0 {
+        info!("{} atlases updated per frame", *total / *frames);
+        (*total, *frames, *since) = (0, 0, 0.0);
+    }
+}
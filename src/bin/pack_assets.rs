@@ -0,0 +1,27 @@
+// Packs the assets folder into the file the game reads its assets from when it sits
+// next to the executable:
+//   cargo run --release --bin pack_assets -- [assets folder] [pack file]
+use dinorun_core::{write_pack, PACK_FILE};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    let mut args = std::env::args().skip(1);
+    let assets = PathBuf::from(args.next().unwrap_or_else(|| "assets".to_string()));
+    let out = PathBuf::from(args.next().unwrap_or_else(|| PACK_FILE.to_string()));
+    match write_pack(&assets, &out) {
+        Ok(count) => {
+            println!(
+                "Packed {} files from {} into {}",
+                count,
+                assets.display(),
+                out.display()
+            );
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("Could not pack {}: {}", assets.display(), err);
+            ExitCode::FAILURE
+        }
+    }
+}
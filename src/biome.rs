@@ -0,0 +1,374 @@
+use crate::game_time::GameTime;
+use crate::gate::{Gate, GateTemplates};
+use crate::mode::{start_run, ActiveMode, RunRng};
+use crate::settings::Settings;
+use crate::toast::Toast;
+use crate::weather::{update_effects, WeatherEffects};
+use crate::{GameState, GameplaySet, PlayerOne, RunEntity, GROUND_Y};
+use bevy::prelude::*;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use bevy_parallax::ParallaxCameraComponent;
+use rand::Rng;
+use serde::Deserialize;
+
+// biomes shipped with the game
+const BIOMES: &str = include_str!("../assets/biomes.ron");
+// size of the texture the darkness vignette is stretched from, and the share of its
+// radius left clear in the middle
+const VIGNETTE_SIZE: u32 = 64;
+const VIGNETTE_CLEAR: f32 = 0.3;
+// share of the way the vignette and the sandstorm haze fade to where they're going
+// each frame, so biomes and storms blend into each other
+const FADE: f32 = 0.04;
+const SAND_COLOR: Color = Color::rgb(0.85, 0.7, 0.45);
+// how thick the sandstorm haze gets
+const SAND_ALPHA: f32 = 0.45;
+// bands of wavering air over hot ground: how many, how high they reach over the ground,
+// and how fast and far they waver
+const SHIMMER_BANDS: u32 = 6;
+const SHIMMER_HEIGHT: f32 = 90.0;
+const SHIMMER_SPEED: f32 = 3.0;
+const SHIMMER_WAVE: f32 = 3.0;
+const SHIMMER_COLOR: Color = Color::rgba(1.0, 0.95, 0.85, 0.08);
+
+// Sandstorms blowing up along a biome: the course between two and how long each lasts,
+// picked at random in these ranges, and pixels per frame they push the player back
+#[derive(Deserialize, Debug, Clone, Copy)]
+pub struct Sandstorms {
+    pub spacing: (f32, f32),
+    pub length: (f32, f32),
+    pub headwind: f32,
+}
+
+// A stretch of course with quirks of its own, as described in assets/biomes.ron
+#[derive(Deserialize, Debug, Clone)]
+pub struct Biome {
+    pub name: String,
+    // length of course it lasts
+    pub length: f32,
+    // how far the darkness closes in, 0 for none
+    #[serde(default)]
+    pub darkness: f32,
+    #[serde(default)]
+    pub heat_shimmer: bool,
+    #[serde(default)]
+    pub sandstorms: Option<Sandstorms>,
+    // share of the usual grip on the ground
+    #[serde(default = "full_grip")]
+    pub grip: f32,
+    // gauntlet at its end, from assets/gates.ron, or the next one in turn
+    #[serde(default)]
+    pub gate: Option<String>,
+}
+
+fn full_grip() -> f32 {
+    1.0
+}
+
+// Every biome, in the order the course runs through them
+#[derive(Resource, Debug, Clone)]
+pub struct Biomes(pub Vec<Biome>);
+
+impl Default for Biomes {
+    fn default() -> Self {
+        match ron::from_str(BIOMES) {
+            Ok(biomes) => Biomes(biomes),
+            Err(err) => {
+                error!("Could not parse the biomes: {}", err);
+                Biomes(Vec::new())
+            }
+        }
+    }
+}
+
+impl Biomes {
+    pub fn get(&self, index: usize) -> Option<&Biome> {
+        self.0.get(index % self.0.len().max(1))
+    }
+}
+
+// Biome the player is in, where the next one starts, and the sandstorm blowing or the
+// one to come
+#[derive(Resource, Debug, Default)]
+pub struct ActiveBiome {
+    pub index: usize,
+    end_x: f32,
+    // where the current or next sandstorm starts and ends
+    storm: (f32, f32),
+}
+
+impl ActiveBiome {
+    fn in_storm(&self, x: f32) -> bool {
+        (self.storm.0..self.storm.1).contains(&x)
+    }
+}
+
+// Full-screen darkness around the player in dark biomes
+#[derive(Component)]
+struct Vignette;
+
+// Full-screen haze of a sandstorm
+#[derive(Component)]
+struct SandHaze;
+
+// Band of wavering air over hot ground, `index` bands up from the ground
+#[derive(Component)]
+struct Shimmer {
+    index: u32,
+}
+
+// transparent in the middle and opaque black at the edges
+fn vignette_image() -> Image {
+    let size = VIGNETTE_SIZE;
+    let center = size as f32 / 2.0;
+    let mut data = Vec::with_capacity((size * size * 4) as usize);
+    for y in 0..size {
+        for x in 0..size {
+            let offset = Vec2::new(x as f32 + 0.5 - center, y as f32 + 0.5 - center);
+            let distance = offset.length() / center;
+            let t = ((distance - VIGNETTE_CLEAR) / (1.0 - VIGNETTE_CLEAR)).clamp(0.0, 1.0);
+            let alpha = t * t * (3.0 - 2.0 * t);
+            data.extend_from_slice(&[0, 0, 0, (alpha * 255.0) as u8]);
+        }
+    }
+    Image::new(
+        Extent3d {
+            width: size,
+            height: size,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD,
+    )
+}
+
+// the first sandstorm of `biome` starting at `from`, or one out of reach past its end
+fn next_storm(biome: Option<&Biome>, from: f32, rng: &mut impl Rng) -> (f32, f32) {
+    let Some(storms) = biome.and_then(|biome| biome.sandstorms) else {
+        return (f32::MAX, f32::MAX);
+    };
+    let start = from + rng.gen_range(storms.spacing.0..=storms.spacing.1);
+    (
+        start,
+        start + rng.gen_range(storms.length.0..=storms.length.1),
+    )
+}
+
+// the gate closing the index-th biome, at `end_x`
+fn schedule_gate(
+    templates: &GateTemplates,
+    biome: Option<&Biome>,
+    index: usize,
+    end_x: f32,
+) -> Gate {
+    let name = biome.and_then(|biome| biome.gate.as_deref());
+    Gate::new(templates, templates.pick(name, index), end_x)
+}
+
+fn reset_biome(
+    mut commands: Commands,
+    biomes: Res<Biomes>,
+    templates: Res<GateTemplates>,
+    mut rng: ResMut<RunRng>,
+) {
+    let first = biomes.get(0);
+    let end_x = first.map_or(f32::MAX, |biome| biome.length);
+    commands.insert_resource(ActiveBiome {
+        index: 0,
+        end_x,
+        storm: next_storm(first, 0.0, &mut rng.0),
+    });
+    commands.insert_resource(schedule_gate(&templates, first, 0, end_x));
+}
+
+// the sandstorm haze and heat shimmer are left out in low-spec mode
+fn spawn_overlays(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let full_screen = Style {
+        position_type: PositionType::Absolute,
+        width: Val::Percent(100.0),
+        height: Val::Percent(100.0),
+        ..default()
+    };
+    if !settings.video.low_spec {
+        commands.spawn((
+            NodeBundle {
+                style: full_screen.clone(),
+                background_color: SAND_COLOR.with_a(0.0).into(),
+                z_index: ZIndex::Global(-1),
+                ..default()
+            },
+            SandHaze,
+            RunEntity,
+        ));
+    }
+    commands.spawn((
+        ImageBundle {
+            style: full_screen,
+            image: UiImage::new(images.add(vignette_image())),
+            background_color: Color::WHITE.with_a(0.0).into(),
+            z_index: ZIndex::Global(-1),
+            ..default()
+        },
+        Vignette,
+        RunEntity,
+    ));
+    if settings.video.low_spec {
+        return;
+    }
+    for index in 0..SHIMMER_BANDS {
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: SHIMMER_COLOR.with_a(0.0),
+                    custom_size: Some(Vec2::new(4000.0, SHIMMER_HEIGHT / SHIMMER_BANDS as f32)),
+                    ..default()
+                },
+                ..default()
+            },
+            Shimmer { index },
+            RunEntity,
+        ));
+    }
+}
+
+// system to move on to the next biome once the player reaches the end of this one, and
+// schedule the gate closing it
+#[allow(clippy::too_many_arguments)]
+fn change_biome(
+    biomes: Res<Biomes>,
+    templates: Res<GateTemplates>,
+    mut active: ResMut<ActiveBiome>,
+    mut gate: ResMut<Gate>,
+    mut rng: ResMut<RunRng>,
+    mode: Res<ActiveMode>,
+    player_query: Query<&Transform, PlayerOne>,
+    mut toasts: EventWriter<Toast>,
+) {
+    let Ok(player) = player_query.get_single() else {
+        return;
+    };
+    let x = mode.progress(player.translation.truncate());
+    if x >= active.end_x {
+        active.index += 1;
+        let Some(biome) = biomes.get(active.index) else {
+            return;
+        };
+        active.end_x += biome.length;
+        *gate = schedule_gate(&templates, Some(biome), active.index, active.end_x);
+        active.storm = next_storm(Some(biome), x, &mut rng.0);
+        toasts.send(Toast(biome.name.clone()));
+    } else if x >= active.storm.1 {
+        active.storm = next_storm(biomes.get(active.index), x, &mut rng.0);
+    }
+}
+
+// system to make the ground slippery and blow sandstorms against the player, on top of
+// the weather
+fn apply_quirks(
+    biomes: Res<Biomes>,
+    mode: Res<ActiveMode>,
+    active: Res<ActiveBiome>,
+    mut effects: ResMut<WeatherEffects>,
+    player_query: Query<&Transform, PlayerOne>,
+) {
+    let (Some(biome), Ok(player)) = (biomes.get(active.index), player_query.get_single()) else {
+        return;
+    };
+    effects.grip *= biome.grip;
+    if let Some(storms) = biome.sandstorms {
+        if active.in_storm(mode.progress(player.translation.truncate())) {
+            effects.wind -= storms.headwind;
+        }
+    }
+}
+
+// system to fade the darkness and the sandstorm haze to the biome's
+fn update_overlays(
+    biomes: Res<Biomes>,
+    mode: Res<ActiveMode>,
+    active: Res<ActiveBiome>,
+    player_query: Query<&Transform, PlayerOne>,
+    mut vignette_query: Query<&mut BackgroundColor, (With<Vignette>, Without<SandHaze>)>,
+    mut haze_query: Query<&mut BackgroundColor, With<SandHaze>>,
+) {
+    let (Some(biome), Ok(player)) = (biomes.get(active.index), player_query.get_single()) else {
+        return;
+    };
+    let fade = |color: &mut BackgroundColor, target: f32| {
+        let alpha = color.0.a();
+        color.0.set_a(alpha + (target - alpha) * FADE);
+    };
+    for mut color in &mut vignette_query {
+        fade(&mut color, biome.darkness);
+    }
+    let along = mode.progress(player.translation.truncate());
+    let storm = biome.sandstorms.is_some() && active.in_storm(along);
+    for mut color in &mut haze_query {
+        fade(&mut color, if storm { SAND_ALPHA } else { 0.0 });
+    }
+}
+
+// system to waver the bands of air over hot ground under the camera
+fn update_shimmer(
+    time: Res<GameTime>,
+    biomes: Res<Biomes>,
+    active: Res<ActiveBiome>,
+    camera_query: Query<&Transform, (With<ParallaxCameraComponent>, Without<Shimmer>)>,
+    mut shimmer_query: Query<(&Shimmer, &mut Transform, &mut Sprite)>,
+) {
+    let Ok(camera) = camera_query.get_single() else {
+        return;
+    };
+    let hot = biomes
+        .get(active.index)
+        .is_some_and(|biome| biome.heat_shimmer);
+    let band_height = SHIMMER_HEIGHT / SHIMMER_BANDS as f32;
+    let t = time.elapsed_seconds() * SHIMMER_SPEED;
+    for (shimmer, mut transform, mut sprite) in &mut shimmer_query {
+        let phase = t + shimmer.index as f32 * 1.3;
+        let y = GROUND_Y - 24.0 + band_height * (shimmer.index as f32 + 0.5);
+        transform.translation = Vec3::new(
+            camera.translation.x + phase.cos() * SHIMMER_WAVE,
+            y + phase.sin() * SHIMMER_WAVE,
+            2.5,
+        );
+        let target = if hot {
+            SHIMMER_COLOR.a() * (0.6 + 0.4 * (phase * 0.7).sin())
+        } else {
+            0.0
+        };
+        let alpha = sprite.color.a();
+        sprite.color.set_a(alpha + (target - alpha) * FADE);
+    }
+}
+
+pub struct BiomePlugin;
+
+impl Plugin for BiomePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Biomes>()
+            .init_resource::<ActiveBiome>()
+            .add_systems(
+                OnEnter(GameState::Playing),
+                (reset_biome.after(start_run), spawn_overlays),
+            )
+            .add_systems(
+                Update,
+                (
+                    change_biome,
+                    apply_quirks.after(update_effects),
+                    update_overlays,
+                    update_shimmer,
+                )
+                    .chain()
+                    .in_set(GameplaySet),
+            );
+    }
+}
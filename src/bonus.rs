@@ -0,0 +1,271 @@
+use crate::coin::coin_bundle;
+use crate::collision::Collider;
+use crate::game_time::GameTime;
+use crate::mode::RunRng;
+use crate::projectile::Projectile;
+use crate::settings::Settings;
+use crate::toast::Toast;
+use crate::{parallax_layers, GameState, GameplaySet, Player, PlayerOne, RunEntity, GROUND_Y};
+use bevy::prelude::*;
+use bevy_parallax::{CreateParallaxEvent, LayerData, ParallaxCameraComponent};
+use rand::Rng;
+
+// distance between two portals, picked at random in this range
+const PORTAL_SPACING: (f32, f32) = (3000.0, 5000.0);
+const PORTAL_SIZE: Vec2 = Vec2::new(20.0, 48.0);
+const PORTAL_COLOR: Color = Color::rgb(0.6, 0.3, 1.0);
+// how long a bonus stage lasts, in seconds
+const BONUS_TIME: f32 = 15.0;
+// where bonus stages are played, far away from anything in the main run
+const BONUS_X: f32 = -100_000.0;
+// coins of the bonus stage, in a wave starting a little ahead of the player
+const BONUS_COINS: u32 = 60;
+const BONUS_COIN_START: f32 = 160.0;
+const BONUS_COIN_GAP: f32 = 24.0;
+// the bonus stage's take on the parallax layers
+const BONUS_TINT: Color = Color::rgb(0.7, 0.6, 1.0);
+
+// Part of the run being played: the main course or a bonus stage reached through a portal
+#[derive(States, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum RunStage {
+    #[default]
+    Main,
+    Bonus,
+}
+
+// the camera following the player, kept apart from the player's transform
+type WorldCamera = (With<ParallaxCameraComponent>, Without<Player>);
+
+// Portal to a bonus stage
+#[derive(Component)]
+struct Portal;
+
+// Marks what only exists during the bonus stage
+#[derive(Component)]
+struct BonusEntity;
+
+#[derive(Component)]
+struct BonusTimerText;
+
+// Where the next portal of the run goes
+#[derive(Resource, Default)]
+struct PortalSpawner {
+    next_x: f32,
+}
+
+// The bonus stage being played and where to go back to once it's over
+#[derive(Resource)]
+struct BonusStage {
+    timer: Timer,
+    return_x: f32,
+    camera_x: f32,
+}
+
+fn reset_spawner(mut commands: Commands) {
+    commands.insert_resource(PortalSpawner {
+        next_x: PORTAL_SPACING.0,
+    });
+}
+
+// system to drop a bonus stage the run ended in, its entities go with the rest of the run
+fn leave_bonus(mut commands: Commands, mut next_stage: ResMut<NextState<RunStage>>) {
+    commands.remove_resource::<BonusStage>();
+    next_stage.set(RunStage::Main);
+}
+
+pub fn bonus_layers(camera_x: f32, low_spec: bool) -> Vec<LayerData> {
+    parallax_layers(camera_x, low_spec)
+        .into_iter()
+        .map(|layer| LayerData {
+            color: BONUS_TINT,
+            flip: (true, false),
+            ..layer
+        })
+        .collect()
+}
+
+// system to place portals ahead of the camera every now and then
+fn spawn_portals(
+    mut commands: Commands,
+    mut spawner: ResMut<PortalSpawner>,
+    mut rng: ResMut<RunRng>,
+    camera_query: Query<(&Transform, &OrthographicProjection), With<ParallaxCameraComponent>>,
+) {
+    let Ok((camera, projection)) = camera_query.get_single() else {
+        return;
+    };
+    if camera.translation.x + projection.area.max.x < spawner.next_x {
+        return;
+    }
+    // standing on the ground next to the player's feet
+    let y = GROUND_Y - 32.0 + PORTAL_SIZE.y / 2.0;
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: PORTAL_COLOR,
+                custom_size: Some(PORTAL_SIZE),
+                ..default()
+            },
+            transform: Transform::from_xyz(spawner.next_x, y, 1.4),
+            ..default()
+        },
+        Portal,
+        Collider(Rect::from_center_size(Vec2::ZERO, PORTAL_SIZE)),
+        RunEntity,
+    ));
+    spawner.next_x += rng.0.gen_range(PORTAL_SPACING.0..=PORTAL_SPACING.1);
+}
+
+// system to make the portals shimmer, other than in low-spec mode
+fn pulse_portals(
+    time: Res<GameTime>,
+    settings: Res<Settings>,
+    mut portal_query: Query<&mut Sprite, With<Portal>>,
+) {
+    if settings.video.low_spec {
+        return;
+    }
+    let alpha = 0.65 + 0.25 * (time.elapsed_seconds() * 4.0).sin();
+    for mut sprite in &mut portal_query {
+        sprite.color.set_a(alpha);
+    }
+}
+
+// system to take the player through a portal they touch, to the bonus stage
+#[allow(clippy::too_many_arguments)]
+fn enter_portal(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    portal_query: Query<(Entity, &GlobalTransform, &Collider), With<Portal>>,
+    mut player_query: Query<(&mut Transform, &GlobalTransform, &Collider), PlayerOne>,
+    mut camera_query: Query<(Entity, &mut Transform), WorldCamera>,
+    projectile_query: Query<Entity, With<Projectile>>,
+    mut create_parallax: EventWriter<CreateParallaxEvent>,
+    mut next_stage: ResMut<NextState<RunStage>>,
+    mut toasts: EventWriter<Toast>,
+) {
+    let (
+        Ok((mut transform, player_transform, player_collider)),
+        Ok((camera, mut camera_transform)),
+    ) = (player_query.get_single_mut(), camera_query.get_single_mut())
+    else {
+        return;
+    };
+    let player_rect = player_collider.world_rect(player_transform);
+    let Some(portal) = portal_query
+        .iter()
+        .find(|(_, transform, collider)| {
+            !collider
+                .world_rect(transform)
+                .intersect(player_rect)
+                .is_empty()
+        })
+        .map(|(portal, ..)| portal)
+    else {
+        return;
+    };
+    commands.entity(portal).despawn_recursive();
+    for projectile in &projectile_query {
+        commands.entity(projectile).despawn_recursive();
+    }
+    commands.insert_resource(BonusStage {
+        timer: Timer::from_seconds(BONUS_TIME, TimerMode::Once),
+        return_x: transform.translation.x,
+        camera_x: camera_transform.translation.x,
+    });
+
+    // the stage starts with the player where the camera has them in the main run
+    let offset = transform.translation.x - camera_transform.translation.x;
+    camera_transform.translation.x = BONUS_X;
+    transform.translation.x = BONUS_X + offset;
+    create_parallax.send(CreateParallaxEvent {
+        layers_data: bonus_layers(BONUS_X, settings.video.low_spec),
+        camera,
+    });
+    for index in 0..BONUS_COINS {
+        let x = transform.translation.x + BONUS_COIN_START + index as f32 * BONUS_COIN_GAP;
+        let y = GROUND_Y + 40.0 + 36.0 * (index as f32 * 0.4).sin();
+        commands.spawn((coin_bundle(Vec2::new(x, y)), BonusEntity));
+    }
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 28.0,
+                color: PORTAL_COLOR,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(8.0),
+            justify_self: JustifySelf::Center,
+            ..default()
+        }),
+        BonusTimerText,
+        BonusEntity,
+        RunEntity,
+    ));
+    next_stage.set(RunStage::Bonus);
+    toasts.send(Toast("Bonus stage!".to_string()));
+}
+
+// system to count the bonus stage down, and go back to the main run at the distance it
+// was left at once the time is up
+#[allow(clippy::too_many_arguments)]
+fn run_bonus(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    time: Res<GameTime>,
+    mut stage: ResMut<BonusStage>,
+    mut player_query: Query<&mut Transform, PlayerOne>,
+    mut camera_query: Query<(Entity, &mut Transform), WorldCamera>,
+    bonus_query: Query<Entity, With<BonusEntity>>,
+    mut text_query: Query<&mut Text, With<BonusTimerText>>,
+    mut create_parallax: EventWriter<CreateParallaxEvent>,
+    mut next_stage: ResMut<NextState<RunStage>>,
+) {
+    stage.timer.tick(time.delta());
+    for mut text in &mut text_query {
+        text.sections[0].value = format!("Bonus {:.0}", stage.timer.remaining_secs().ceil());
+    }
+    if !stage.timer.finished() {
+        return;
+    }
+    let (Ok(mut transform), Ok((camera, mut camera_transform))) =
+        (player_query.get_single_mut(), camera_query.get_single_mut())
+    else {
+        return;
+    };
+    transform.translation.x = stage.return_x;
+    camera_transform.translation.x = stage.camera_x;
+    create_parallax.send(CreateParallaxEvent {
+        layers_data: parallax_layers(stage.camera_x, settings.video.low_spec),
+        camera,
+    });
+    for entity in &bonus_query {
+        commands.entity(entity).despawn_recursive();
+    }
+    next_stage.set(RunStage::Main);
+}
+
+pub struct BonusPlugin;
+
+impl Plugin for BonusPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_state::<RunStage>()
+            .init_resource::<PortalSpawner>()
+            .add_systems(OnEnter(GameState::Playing), reset_spawner)
+            .add_systems(OnExit(GameState::Playing), leave_bonus)
+            .add_systems(
+                Update,
+                (
+                    (spawn_portals, pulse_portals, enter_portal)
+                        .chain()
+                        .run_if(in_state(RunStage::Main)),
+                    run_bonus.run_if(in_state(RunStage::Bonus)),
+                )
+                    .in_set(GameplaySet),
+            );
+    }
+}
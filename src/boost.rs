@@ -0,0 +1,370 @@
+use crate::audio::{PlaySfx, Sfx};
+use crate::barrel::{Barrel, BARREL_SIZE};
+use crate::collision::Collider;
+use crate::enemy::Enemy;
+use crate::feedback::Feedback;
+use crate::game_time::GameTime;
+use crate::health::{Boss, Invincible};
+use crate::hud::{HudAnchor, HudWidget};
+use crate::input::{Action, ActionState};
+use crate::mutators::RunConfig;
+use crate::projectile::Projectile;
+use crate::score::{AddPoints, PointSource};
+use crate::settings::Settings;
+use crate::toast::Toast;
+use crate::{GameState, GameplaySet, Player, PlayerOne, RunEntity, GROUND_Y};
+use bevy::prelude::*;
+use bevy::utils::{HashMap, HashSet};
+use bevy_parallax::ParallaxCameraComponent;
+
+// a projectile passing the player this close, or a barrel cleared this close, is a near-miss
+const NEAR_MISS_DISTANCE: f32 = 24.0;
+// share of the meter a near-miss and a trick (deflecting a projectile) fill
+const NEAR_MISS_CHARGE: f32 = 0.2;
+const TRICK_CHARGE: f32 = 0.3;
+// how long the mega-dash lasts, in seconds, and how much faster it carries the player,
+// in pixels per frame
+const DASH_TIME: f32 = 1.2;
+const DASH_SPEED: f32 = 3.0;
+// how far the view pulls back during the dash, and the share of the way it eases there
+// each frame
+const DASH_ZOOM: f32 = 1.2;
+const ZOOM_EASE: f32 = 0.1;
+// afterimages left behind during the dash: how often, and how long each one lasts
+const GHOST_INTERVAL: f32 = 0.05;
+const GHOST_TIME: f32 = 0.3;
+const DASH_COLOR: Color = Color::rgb(1.0, 0.8, 0.3);
+const METER_WIDTH: f32 = 120.0;
+const METER_HEIGHT: f32 = 8.0;
+const METER_FILL: Color = Color::rgb(0.3, 0.75, 1.0);
+const METER_FULL: Color = Color::rgb(1.0, 0.8, 0.3);
+
+// Charge of the boost meter, from 0 to 1, and the mega-dash it sets off once full
+#[derive(Resource, Default)]
+pub struct Boost {
+    charge: f32,
+    // seconds of mega-dash left
+    dash: f32,
+    // seconds until the next afterimage
+    ghost: f32,
+}
+
+// Projectiles and barrels the player already got credit for, and how close each
+// projectile still around has come
+#[derive(Resource, Default)]
+struct NearMisses {
+    closest: HashMap<Entity, f32>,
+    counted: HashSet<Entity>,
+}
+
+#[derive(Component)]
+struct BoostMeter;
+
+#[derive(Component)]
+struct BoostFill;
+
+// Fading copy of the player left behind by the mega-dash
+#[derive(Component)]
+struct Afterimage(Timer);
+
+fn reset_boost(mut commands: Commands) {
+    commands.insert_resource(Boost::default());
+    commands.insert_resource(NearMisses::default());
+}
+
+fn spawn_meter(mut commands: Commands) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Px(METER_WIDTH),
+                    height: Val::Px(METER_HEIGHT),
+                    ..default()
+                },
+                background_color: Color::rgba(0.1, 0.1, 0.1, 0.8).into(),
+                ..default()
+            },
+            BoostMeter,
+            HudAnchor(HudWidget::Boost),
+            RunEntity,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                NodeBundle {
+                    style: Style {
+                        width: Val::Percent(0.0),
+                        height: Val::Percent(100.0),
+                        ..default()
+                    },
+                    background_color: METER_FILL.into(),
+                    ..default()
+                },
+                BoostFill,
+            ));
+        });
+}
+
+// the meter gains `amount`, telling the player once it's full
+pub fn charge(boost: &mut Boost, amount: f32, toasts: &mut EventWriter<Toast>) {
+    if boost.charge >= 1.0 || boost.dash > 0.0 {
+        return;
+    }
+    boost.charge = (boost.charge + amount).min(1.0);
+    if boost.charge >= 1.0 {
+        toasts.send(Toast("Boost ready!".to_string()));
+    }
+}
+
+// system to charge the meter for projectiles that passed close by without hitting, ones
+// sent back with a dash, and barrels cleared with little room to spare
+fn detect_near_misses(
+    mut boost: ResMut<Boost>,
+    mut near_misses: ResMut<NearMisses>,
+    player_query: Query<(&Player, &GlobalTransform, &Collider), PlayerOne>,
+    projectile_query: Query<(Entity, &Transform, &Projectile)>,
+    barrel_query: Query<(Entity, &Transform), With<Barrel>>,
+    mut toasts: EventWriter<Toast>,
+) {
+    let Ok((player, player_transform, collider)) = player_query.get_single() else {
+        return;
+    };
+    let rect = collider.world_rect(player_transform);
+    let NearMisses { closest, counted } = &mut *near_misses;
+    closest.retain(|entity, _| projectile_query.contains(*entity));
+    counted.retain(|entity| projectile_query.contains(*entity) || barrel_query.contains(*entity));
+    for (projectile, transform, shot) in &projectile_query {
+        if counted.contains(&projectile) {
+            continue;
+        }
+        if shot.deflected() {
+            counted.insert(projectile);
+            charge(&mut boost, TRICK_CHARGE, &mut toasts);
+            continue;
+        }
+        let position = transform.translation.truncate();
+        let gap = (position - position.clamp(rect.min, rect.max)).length();
+        let closest = closest.entry(projectile).or_insert(f32::MAX);
+        *closest = closest.min(gap);
+        // it's behind the player, so it missed
+        if position.x < rect.min.x {
+            counted.insert(projectile);
+            if *closest < NEAR_MISS_DISTANCE {
+                charge(&mut boost, NEAR_MISS_CHARGE, &mut toasts);
+            }
+        }
+    }
+    if player.on_ground {
+        return;
+    }
+    let center = player_transform.translation().x;
+    for (barrel, transform) in &barrel_query {
+        let half = BARREL_SIZE.x / 2.0;
+        let over =
+            (transform.translation.x - half..=transform.translation.x + half).contains(&center);
+        let top = GROUND_Y - 32.0 + BARREL_SIZE.y;
+        if over && !counted.contains(&barrel) && rect.min.y - top < NEAR_MISS_DISTANCE {
+            counted.insert(barrel);
+            charge(&mut boost, NEAR_MISS_CHARGE, &mut toasts);
+        }
+    }
+}
+
+// system to set off the mega-dash when the player boosts with a full meter
+fn start_dash(
+    mut commands: Commands,
+    actions: Res<ActionState>,
+    mut boost: ResMut<Boost>,
+    mut player_query: Query<(Entity, &mut Sprite), PlayerOne>,
+    mut sfx: EventWriter<PlaySfx>,
+    mut feedback: EventWriter<Feedback>,
+) {
+    if !actions.just_pressed(Action::Boost) || boost.charge < 1.0 {
+        return;
+    }
+    let Ok((player, mut sprite)) = player_query.get_single_mut() else {
+        return;
+    };
+    boost.charge = 0.0;
+    boost.dash = DASH_TIME;
+    commands.entity(player).insert(Invincible);
+    sprite.color = DASH_COLOR;
+    sfx.send(PlaySfx(Sfx::Boost));
+    feedback.send(Feedback::Flash(DASH_COLOR.with_a(0.4)));
+}
+
+// system to carry the player forward during the mega-dash, smashing through the
+// obstacles in the way, and leave afterimages behind
+#[allow(clippy::too_many_arguments)]
+fn mega_dash(
+    mut commands: Commands,
+    time: Res<GameTime>,
+    config: Res<RunConfig>,
+    settings: Res<Settings>,
+    mut boost: ResMut<Boost>,
+    mut player_query: Query<Dasher, PlayerOne>,
+    obstacle_query: Query<(Entity, &GlobalTransform, &Collider), Obstacles>,
+    boss_query: Query<(), With<Boss>>,
+    mut points: EventWriter<AddPoints>,
+    mut feedback: EventWriter<Feedback>,
+) {
+    if boost.dash <= 0.0 {
+        return;
+    }
+    let Ok((player, mut transform, global, collider, mut sprite, texture, atlas)) =
+        player_query.get_single_mut()
+    else {
+        return;
+    };
+    boost.dash -= time.delta_seconds();
+    if boost.dash <= 0.0 {
+        commands.entity(player).remove::<Invincible>();
+        sprite.color = settings.cosmetics.skin.color();
+        return;
+    }
+    transform.translation.x += DASH_SPEED * config.speed;
+    let rect = collider.world_rect(global);
+    for (obstacle, obstacle_transform, obstacle_collider) in &obstacle_query {
+        // bosses shrug it off
+        if boss_query.contains(obstacle) {
+            continue;
+        }
+        let hit = !obstacle_collider
+            .world_rect(obstacle_transform)
+            .intersect(rect)
+            .is_empty();
+        if hit {
+            commands.entity(obstacle).despawn_recursive();
+            points.send(AddPoints(PointSource::Smash));
+            feedback.send(Feedback::Shake(4.0));
+        }
+    }
+    boost.ghost -= time.delta_seconds();
+    if boost.ghost > 0.0 {
+        return;
+    }
+    boost.ghost = GHOST_INTERVAL;
+    // a flickering trail is too much for photosensitive players, the tint is enough
+    if settings.accessibility.photosensitivity {
+        return;
+    }
+    let mut ghost = *transform;
+    ghost.translation.z -= 0.01;
+    commands.spawn((
+        SpriteSheetBundle {
+            texture: texture.clone(),
+            atlas: atlas.clone(),
+            sprite: Sprite {
+                color: DASH_COLOR.with_a(0.5),
+                flip_x: sprite.flip_x,
+                ..default()
+            },
+            transform: ghost,
+            ..default()
+        },
+        Afterimage(Timer::from_seconds(GHOST_TIME, TimerMode::Once)),
+        RunEntity,
+    ));
+}
+
+// The player on a mega-dash, with what it takes to leave an afterimage of them
+type Dasher<'a> = (
+    Entity,
+    &'a mut Transform,
+    &'a GlobalTransform,
+    &'a Collider,
+    &'a mut Sprite,
+    &'a Handle<Image>,
+    &'a TextureAtlas,
+);
+
+// Things the mega-dash smashes through
+type Obstacles = (
+    Or<(With<Enemy>, With<Barrel>, With<Projectile>)>,
+    Without<Player>,
+);
+
+fn fade_afterimages(
+    mut commands: Commands,
+    time: Res<GameTime>,
+    mut query: Query<(Entity, &mut Afterimage, &mut Sprite)>,
+) {
+    for (entity, mut afterimage, mut sprite) in &mut query {
+        afterimage.0.tick(time.delta());
+        if afterimage.0.finished() {
+            commands.entity(entity).despawn_recursive();
+        } else {
+            sprite.color.set_a(0.5 * afterimage.0.fraction_remaining());
+        }
+    }
+}
+
+// system to pull the view back during the mega-dash and ease it in again afterwards
+fn zoom_camera(
+    boost: Res<Boost>,
+    mut camera_query: Query<&mut OrthographicProjection, With<ParallaxCameraComponent>>,
+) {
+    let target = if boost.dash > 0.0 { DASH_ZOOM } else { 1.0 };
+    for mut projection in &mut camera_query {
+        let step = (target - projection.scale) * ZOOM_EASE;
+        if step.abs() > 0.001 {
+            projection.scale += step;
+        } else if projection.scale != target {
+            projection.scale = target;
+        }
+    }
+}
+
+// system to fill the meter, glowing once it's full
+fn update_meter(
+    boost: Res<Boost>,
+    mut fill_query: Query<(&mut Style, &mut BackgroundColor), With<BoostFill>>,
+) {
+    if !boost.is_changed() {
+        return;
+    }
+    let level = if boost.dash > 0.0 {
+        boost.dash / DASH_TIME
+    } else {
+        boost.charge
+    };
+    for (mut style, mut background) in &mut fill_query {
+        style.width = Val::Percent(level * 100.0);
+        *background = if boost.charge >= 1.0 || boost.dash > 0.0 {
+            METER_FULL.into()
+        } else {
+            METER_FILL.into()
+        };
+    }
+}
+
+// system to leave the view at its usual zoom once the run is over
+fn reset_zoom(mut camera_query: Query<&mut OrthographicProjection, With<ParallaxCameraComponent>>) {
+    for mut projection in &mut camera_query {
+        projection.scale = 1.0;
+    }
+}
+
+pub struct BoostPlugin;
+
+impl Plugin for BoostPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Boost>()
+            .init_resource::<NearMisses>()
+            .add_systems(OnEnter(GameState::Playing), (reset_boost, spawn_meter))
+            .add_systems(OnExit(GameState::Playing), reset_zoom)
+            .add_systems(
+                Update,
+                (
+                    detect_near_misses,
+                    start_dash,
+                    mega_dash,
+                    fade_afterimages,
+                    zoom_camera,
+                    update_meter,
+                )
+                    .chain()
+                    .in_set(GameplaySet),
+            );
+    }
+}
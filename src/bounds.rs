@@ -0,0 +1,218 @@
+use crate::arena::CameraMode;
+use crate::bonus::RunStage;
+use crate::collision::ShowHitboxes;
+use crate::drop_in::SecondPlayer;
+use crate::health::Health;
+use crate::heatmap::{Hazard, LastHit};
+use crate::mode::ActiveMode;
+use crate::mutators::RunConfig;
+use crate::{GameState, GameplaySet, Player, PlayerOne, PlayerState, GROUND_Y};
+use bevy::prelude::*;
+use bevy_parallax::ParallaxCameraComponent;
+
+// share of the way past the right boundary the player is pushed back each frame
+const PUSH_BACK: f32 = 0.15;
+const WALL_COLOR: Color = Color::rgb(0.9, 0.15, 0.15);
+const LEAD_COLOR: Color = Color::rgb(0.95, 0.8, 0.2);
+
+// Wall closing in from the left, so standing still or running back ends the run
+#[derive(Debug, Clone, Copy)]
+pub struct KillWall {
+    // pixels per frame it advances on its own
+    pub speed: f32,
+    // furthest it lags behind the player; it's dragged along beyond that
+    pub gap: f32,
+}
+
+// Kill plane rising on its own, so the player has to keep climbing
+#[derive(Debug, Clone, Copy)]
+pub struct RisingPlane {
+    // pixels per frame it rises
+    pub speed: f32,
+    // furthest it lags below the player; it's dragged up beyond that
+    pub gap: f32,
+}
+
+// Edges of the world a run is played in, picked by the mode
+#[derive(Debug, Clone, Copy)]
+pub struct WorldBounds {
+    pub kill_wall: Option<KillWall>,
+    // height below which the player has fallen out of the world, where it starts when it rises
+    pub kill_plane: f32,
+    pub rising_plane: Option<RisingPlane>,
+    // furthest the player can get ahead of the camera's center before being eased back
+    pub lead: f32,
+}
+
+impl Default for WorldBounds {
+    fn default() -> Self {
+        WorldBounds {
+            kill_wall: Some(KillWall {
+                speed: 0.5,
+                gap: 480.0,
+            }),
+            kill_plane: GROUND_Y - 200.0,
+            rising_plane: None,
+            lead: 400.0,
+        }
+    }
+}
+
+// Where the kill wall has got to this run
+#[derive(Resource, Debug, Default)]
+struct WallPosition(f32);
+
+// Where the kill plane has got to this run
+#[derive(Resource, Debug, Default)]
+pub struct KillPlane(pub f32);
+
+fn reset_wall(mode: Res<ActiveMode>, mut wall: ResMut<WallPosition>, mut plane: ResMut<KillPlane>) {
+    // the player starts at 0
+    wall.0 = -mode.bounds().kill_wall.map_or(0.0, |wall| wall.gap);
+    plane.0 = mode.bounds().kill_plane;
+}
+
+// system to move the kill wall on, and drag it along behind the first player getting away.
+// It holds still over a boss arena, whose own walls keep the player in.
+fn advance_wall(
+    mode: Res<ActiveMode>,
+    config: Res<RunConfig>,
+    camera_mode: Res<CameraMode>,
+    mut wall: ResMut<WallPosition>,
+    player_query: Query<&Transform, PlayerOne>,
+) {
+    let (Some(kill_wall), Ok(player)) = (mode.bounds().kill_wall, player_query.get_single()) else {
+        return;
+    };
+    if *camera_mode != CameraMode::Scroll {
+        return;
+    }
+    wall.0 = (wall.0 + kill_wall.speed * config.speed).max(player.translation.x - kill_wall.gap);
+}
+
+// system to raise a rising kill plane, and drag it up behind the first player getting away
+fn raise_plane(
+    mode: Res<ActiveMode>,
+    config: Res<RunConfig>,
+    mut plane: ResMut<KillPlane>,
+    player_query: Query<&Transform, PlayerOne>,
+) {
+    let (Some(rising), Ok(player)) = (mode.bounds().rising_plane, player_query.get_single()) else {
+        return;
+    };
+    plane.0 = (plane.0 + rising.speed * config.speed).max(player.translation.y - rising.gap);
+}
+
+// system to take out any player caught by the kill wall or fallen below the kill
+// plane. Where nothing can hurt, a fall just puts the player back on the ground.
+fn enforce_bounds(
+    mode: Res<ActiveMode>,
+    wall: Res<WallPosition>,
+    plane: Res<KillPlane>,
+    mut last_hit: ResMut<LastHit>,
+    mut player_query: Query<(&mut Player, &mut Transform, &mut Health, Has<SecondPlayer>)>,
+) {
+    let bounds = mode.bounds();
+    for (mut player, mut transform, mut health, second) in &mut player_query {
+        let hazard = if transform.translation.y < plane.0 {
+            Hazard::Pit
+        } else if bounds.kill_wall.is_some() && transform.translation.x < wall.0 {
+            Hazard::Wall
+        } else {
+            continue;
+        };
+        if !mode.hazards() {
+            transform.translation.y = GROUND_Y;
+            player.on_ground = true;
+            player.state = PlayerState::Walking;
+            continue;
+        }
+        if health.current > 0.0 {
+            health.current = 0.0;
+            if !second {
+                last_hit.0 = Some(hazard);
+            }
+        }
+    }
+}
+
+// system to ease players outrunning the camera back into view, over a boss arena left
+// to its walls
+fn hold_back_player(
+    mode: Res<ActiveMode>,
+    camera_mode: Res<CameraMode>,
+    camera_query: Query<&Transform, With<ParallaxCameraComponent>>,
+    mut player_query: Query<&mut Transform, (With<Player>, Without<ParallaxCameraComponent>)>,
+) {
+    let Ok(camera) = camera_query.get_single() else {
+        return;
+    };
+    if *camera_mode != CameraMode::Scroll {
+        return;
+    }
+    for mut player in &mut player_query {
+        let ahead = player.translation.x - camera.translation.x - mode.bounds().lead;
+        if ahead > 0.0 {
+            player.translation.x -= ahead * PUSH_BACK;
+        }
+    }
+}
+
+// system to draw the bounds along with the hitboxes
+fn draw_bounds(
+    show: Res<ShowHitboxes>,
+    mode: Res<ActiveMode>,
+    wall: Res<WallPosition>,
+    plane: Res<KillPlane>,
+    camera_query: Query<(&Transform, &OrthographicProjection), With<ParallaxCameraComponent>>,
+    mut gizmos: Gizmos,
+) {
+    let Ok((camera, projection)) = camera_query.get_single() else {
+        return;
+    };
+    if !show.0 {
+        return;
+    }
+    let bounds = mode.bounds();
+    let view = Rect::from_corners(
+        camera.translation.truncate() + projection.area.min,
+        camera.translation.truncate() + projection.area.max,
+    );
+    if bounds.kill_wall.is_some() {
+        gizmos.line_2d(
+            Vec2::new(wall.0, view.min.y),
+            Vec2::new(wall.0, view.max.y),
+            WALL_COLOR,
+        );
+    }
+    gizmos.line_2d(
+        Vec2::new(view.min.x, plane.0),
+        Vec2::new(view.max.x, plane.0),
+        WALL_COLOR,
+    );
+    let lead = camera.translation.x + bounds.lead;
+    gizmos.line_2d(
+        Vec2::new(lead, view.min.y),
+        Vec2::new(lead, view.max.y),
+        LEAD_COLOR,
+    );
+}
+
+pub struct BoundsPlugin;
+
+impl Plugin for BoundsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WallPosition>()
+            .init_resource::<KillPlane>()
+            .add_systems(OnEnter(GameState::Playing), reset_wall)
+            // the bonus stage is played away from the course, the wall waits for the return
+            .add_systems(
+                Update,
+                (advance_wall, raise_plane, enforce_bounds, hold_back_player)
+                    .chain()
+                    .run_if(in_state(RunStage::Main))
+                    .in_set(GameplaySet),
+            )
+            .add_systems(Update, draw_bounds.run_if(in_state(GameState::Playing)));
+    }
+}
@@ -0,0 +1,148 @@
+use crate::balance::Balance;
+use crate::barrel::spawn_barrel;
+use crate::boost::{charge, Boost};
+use crate::coin::coin_bundle;
+use crate::enemy::spawn_enemy;
+use crate::mutators::RunConfig;
+use crate::powerup::{power_up_bundle, PowerUp};
+use crate::swing::{spawn_swing, Swing, SwingKind};
+use crate::toast::Toast;
+use crate::{GameplaySet, PlayerOne, GROUND_Y};
+use bevy::prelude::*;
+
+// how far ahead of the player brushed things appear
+const BRUSH_DISTANCE: f32 = 240.0;
+// height of brushed coins and power-ups above the ground, within jumping reach
+const COIN_HEIGHT: f32 = 48.0;
+
+// Things the brush can place, on the number keys in this order
+#[derive(Debug, Clone, Copy)]
+enum BrushItem {
+    Spitter,
+    Boss,
+    Coin,
+    Barrel,
+    Swing(SwingKind),
+    PowerUp(PowerUp),
+    // fills the boost meter, which has no pickup of its own to place
+    Boost,
+}
+
+const BRUSH_KEYS: [(KeyCode, BrushItem); 9] = [
+    (KeyCode::Digit1, BrushItem::Spitter),
+    (KeyCode::Digit2, BrushItem::Boss),
+    (KeyCode::Digit3, BrushItem::Coin),
+    (KeyCode::Digit4, BrushItem::Barrel),
+    (KeyCode::Digit5, BrushItem::Swing(SwingKind::Vine)),
+    (KeyCode::Digit6, BrushItem::Swing(SwingKind::SpikeBar)),
+    (KeyCode::Digit7, BrushItem::PowerUp(PowerUp::Magnet)),
+    (KeyCode::Digit8, BrushItem::PowerUp(PowerUp::Shield)),
+    (KeyCode::Digit9, BrushItem::Boost),
+];
+
+impl BrushItem {
+    fn name(&self) -> &'static str {
+        match self {
+            BrushItem::Spitter => "spitter",
+            BrushItem::Boss => "boss",
+            BrushItem::Coin => "coin",
+            BrushItem::Barrel => "barrel",
+            BrushItem::Swing(SwingKind::Vine) => "vine",
+            BrushItem::Swing(SwingKind::SpikeBar) => "spike bar",
+            BrushItem::PowerUp(PowerUp::Magnet) => "magnet",
+            BrushItem::PowerUp(PowerUp::Shield) => "shield",
+            BrushItem::Boost => "full boost",
+        }
+    }
+}
+
+// Dev tool placing any spawnable thing in front of the player on demand, to try out
+// hitboxes and animations without waiting for the spawners. Toggled with F8.
+#[derive(Resource, Default)]
+struct Brush(bool);
+
+fn toggle_brush(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut brush: ResMut<Brush>,
+    mut toasts: EventWriter<Toast>,
+) {
+    if keyboard_input.just_pressed(KeyCode::F8) {
+        brush.0 = !brush.0;
+        let state = if brush.0 {
+            let keys: Vec<String> = BRUSH_KEYS
+                .iter()
+                .enumerate()
+                .map(|(index, (_, item))| format!("{} {}", index + 1, item.name()))
+                .collect();
+            format!("on: {}", keys.join(", "))
+        } else {
+            "off".to_string()
+        };
+        toasts.send(Toast(format!("Brush {}", state)));
+    }
+}
+
+// system to spawn the item of each number key pressed just ahead of the player
+#[allow(clippy::too_many_arguments)]
+fn paint(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    brush: Res<Brush>,
+    asset_server: Res<AssetServer>,
+    balance: Res<Balance>,
+    config: Res<RunConfig>,
+    mut boost: ResMut<Boost>,
+    player_query: Query<&Transform, PlayerOne>,
+    mut toasts: EventWriter<Toast>,
+) {
+    if !brush.0 {
+        return;
+    }
+    let Ok(player) = player_query.get_single() else {
+        return;
+    };
+    let x = player.translation.x + BRUSH_DISTANCE;
+    for (key, item) in BRUSH_KEYS {
+        if !keyboard_input.just_pressed(key) {
+            continue;
+        }
+        match item {
+            BrushItem::Spitter => {
+                spawn_enemy(&mut commands, &asset_server, &balance, x, false);
+            }
+            BrushItem::Boss => {
+                spawn_enemy(&mut commands, &asset_server, &balance, x, true);
+            }
+            BrushItem::Coin => {
+                commands.spawn(coin_bundle(Vec2::new(x, GROUND_Y + COIN_HEIGHT)));
+            }
+            BrushItem::Barrel => spawn_barrel(&mut commands, &config, x),
+            BrushItem::Swing(kind) => {
+                let swing = Swing {
+                    kind,
+                    phase: 0.0,
+                    struck: false,
+                    cleared: false,
+                };
+                spawn_swing(&mut commands, swing, x);
+            }
+            BrushItem::PowerUp(power_up) => {
+                commands.spawn(power_up_bundle(
+                    power_up,
+                    Vec2::new(x, GROUND_Y + COIN_HEIGHT),
+                ));
+            }
+            BrushItem::Boost => charge(&mut boost, 1.0, &mut toasts),
+        }
+        info!("Brushed a {} at x = {:.0}", item.name(), x);
+    }
+}
+
+pub struct BrushPlugin;
+
+impl Plugin for BrushPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Brush>()
+            .add_systems(Update, (toggle_brush, paint.in_set(GameplaySet)).chain());
+    }
+}
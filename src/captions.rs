@@ -0,0 +1,105 @@
+use crate::audio::PlaySfx;
+use crate::settings::Settings;
+use bevy::prelude::*;
+
+// how long a caption stays on screen after its cue last played
+const CAPTION_TIME: f32 = 2.5;
+const CAPTION_FONT_SIZE: f32 = 16.0;
+
+// Column at the bottom of the screen that holds the visible captions
+#[derive(Component)]
+struct CaptionArea;
+
+#[derive(Component)]
+struct Caption {
+    text: &'static str,
+    timer: Timer,
+}
+
+fn setup_captions(mut commands: Commands) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(12.0),
+                width: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(4.0),
+                ..default()
+            },
+            z_index: ZIndex::Global(10),
+            ..default()
+        },
+        CaptionArea,
+    ));
+}
+
+// system to show a caption for every captioned sound effect that plays
+fn show_captions(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    mut events: EventReader<PlaySfx>,
+    area_query: Query<Entity, With<CaptionArea>>,
+    mut caption_query: Query<&mut Caption>,
+) {
+    if !settings.accessibility.captions {
+        events.clear();
+        return;
+    }
+    let Ok(area) = area_query.get_single() else {
+        return;
+    };
+    for PlaySfx(sfx) in events.read() {
+        let Some(text) = sfx.caption() else {
+            continue;
+        };
+        // refresh a caption that is already visible instead of stacking duplicates
+        if let Some(mut caption) = caption_query.iter_mut().find(|c| c.text == text) {
+            caption.timer.reset();
+            continue;
+        }
+        let caption = commands
+            .spawn((
+                TextBundle::from_section(
+                    format!("[{}]", text),
+                    TextStyle {
+                        font_size: CAPTION_FONT_SIZE,
+                        color: Color::WHITE,
+                        ..default()
+                    },
+                )
+                .with_background_color(Color::rgba(0.0, 0.0, 0.0, 0.6)),
+                Caption {
+                    text,
+                    timer: Timer::from_seconds(CAPTION_TIME, TimerMode::Once),
+                },
+            ))
+            .id();
+        commands.entity(area).add_child(caption);
+    }
+}
+
+// system to remove captions once their timer runs out
+fn expire_captions(
+    mut commands: Commands,
+    time: Res<Time>,
+    settings: Res<Settings>,
+    mut caption_query: Query<(Entity, &mut Caption)>,
+) {
+    for (entity, mut caption) in &mut caption_query {
+        caption.timer.tick(time.delta());
+        if caption.timer.finished() || !settings.accessibility.captions {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+pub struct CaptionsPlugin;
+
+impl Plugin for CaptionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup_captions)
+            .add_systems(Update, (show_captions, expire_captions));
+    }
+}
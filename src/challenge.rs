@@ -0,0 +1,345 @@
+use crate::game_over::{RunHistory, RunRecord};
+use crate::grade::Grade;
+use crate::input::{Action, ActionState};
+use crate::menu::MenuState;
+use crate::mode::{ActiveMode, GameModes};
+use crate::mutators::Mutator;
+use crate::profile::Profiles;
+use crate::storage;
+use crate::toast::Toast;
+use crate::{GameState, GameplaySet};
+use bevy::prelude::*;
+use std::cmp::Ordering;
+
+// file a challenge is exported to and accepted from
+const CHALLENGE_PATH: &str = "challenge.txt";
+// what a challenge link starts with, followed by its fields as a query string
+const LINK_PREFIX: &str = "dinorun://challenge?";
+// version of the link format, bumped when a field changes meaning
+const LINK_VERSION: u32 = 1;
+// FNV-1a, to fold the inputs of a run into its hash
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+// Fingerprint of every action held on every frame of the current run. Two runs of the
+// same course only share it if they were played exactly alike.
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct InputHash(pub u64);
+
+impl Default for InputHash {
+    fn default() -> Self {
+        InputHash(FNV_OFFSET)
+    }
+}
+
+impl InputHash {
+    fn fold(&mut self, byte: u8) {
+        self.0 = (self.0 ^ byte as u64).wrapping_mul(FNV_PRIME);
+    }
+}
+
+// A finished run handed to a friend to beat: enough to play its course again under the
+// same rules, and the result to compare against
+#[derive(Debug, Clone, PartialEq)]
+pub struct Challenge {
+    // name of the profile that set it
+    pub by: String,
+    pub mode: String,
+    pub seed: u64,
+    pub mutators: Vec<Mutator>,
+    pub score: u32,
+    pub distance: f32,
+    pub grade: Option<Grade>,
+    pub input_hash: u64,
+}
+
+impl Challenge {
+    // the challenge set by `record`, None for runs saved before their seed and inputs
+    // were kept
+    pub fn from_record(record: &RunRecord, by: &str) -> Option<Self> {
+        Some(Challenge {
+            by: by.to_string(),
+            mode: record.mode.clone(),
+            seed: record.seed?,
+            mutators: record.mutators.clone(),
+            score: record.score,
+            distance: record.distance?,
+            grade: record.grade,
+            input_hash: record.input_hash?,
+        })
+    }
+
+    // one line that can be pasted in a chat or saved to a file
+    pub fn to_link(&self) -> String {
+        let mutators: Vec<String> = self.mutators.iter().map(|m| format!("{:?}", m)).collect();
+        let mut fields = vec![
+            ("v", LINK_VERSION.to_string()),
+            ("by", self.by.clone()),
+            ("mode", self.mode.clone()),
+            ("seed", self.seed.to_string()),
+            ("mutators", mutators.join(",")),
+            ("score", self.score.to_string()),
+            ("distance", format!("{:.0}", self.distance)),
+            ("hash", format!("{:016x}", self.input_hash)),
+        ];
+        if let Some(grade) = self.grade {
+            fields.push(("grade", grade.letter().to_string()));
+        }
+        let query: Vec<String> = fields
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, encode(value)))
+            .collect();
+        format!("{}{}", LINK_PREFIX, query.join("&"))
+    }
+
+    pub fn parse(link: &str) -> Result<Self, String> {
+        let query = link
+            .trim()
+            .strip_prefix(LINK_PREFIX)
+            .ok_or("not a challenge link")?;
+        let mut fields = Vec::new();
+        for pair in query.split('&') {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            fields.push((key, decode(value)?));
+        }
+        let field = |key: &str| {
+            fields
+                .iter()
+                .find(|(name, _)| *name == key)
+                .map(|(_, value)| value.as_str())
+                .ok_or(format!("missing {}", key))
+        };
+        let number_error = |key: &str| format!("bad {}", key);
+        let version: u32 = field("v")?.parse().map_err(|_| number_error("v"))?;
+        if version != LINK_VERSION {
+            return Err(format!("made by another version ({})", version));
+        }
+        let mut mutators = Vec::new();
+        for name in field("mutators")?
+            .split(',')
+            .filter(|name| !name.is_empty())
+        {
+            let mutator = Mutator::ALL
+                .into_iter()
+                .find(|mutator| format!("{:?}", mutator) == name)
+                .ok_or(format!("unknown mutator {}", name))?;
+            mutators.push(mutator);
+        }
+        let grade = match field("grade") {
+            Ok(letter) => Some(Grade::from_letter(letter).ok_or(number_error("grade"))?),
+            Err(_) => None,
+        };
+        Ok(Challenge {
+            by: field("by")?.to_string(),
+            mode: field("mode")?.to_string(),
+            seed: field("seed")?.parse().map_err(|_| number_error("seed"))?,
+            mutators,
+            score: field("score")?.parse().map_err(|_| number_error("score"))?,
+            distance: field("distance")?
+                .parse()
+                .map_err(|_| number_error("distance"))?,
+            grade,
+            input_hash: u64::from_str_radix(field("hash")?, 16)
+                .map_err(|_| number_error("hash"))?,
+        })
+    }
+}
+
+// percent-encode everything but letters, digits and a few marks, so names with spaces
+// or ampersands survive the query string
+fn encode(value: &str) -> String {
+    let mut encoded = String::new();
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-_.,".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+fn decode(value: &str) -> Result<String, String> {
+    let mut bytes = Vec::new();
+    let mut rest = value.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' {
+            let hex = tail.get(..2).ok_or("cut short")?;
+            let hex = std::str::from_utf8(hex).map_err(|_| "bad escape")?;
+            bytes.push(u8::from_str_radix(hex, 16).map_err(|_| "bad escape")?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).map_err(|_| "bad text".to_string())
+}
+
+// The challenge being played, None outside of one
+#[derive(Resource, Default)]
+pub struct ActiveChallenge(pub Option<Challenge>);
+
+// Event sent by the menu to export the last run as a challenge, or accept the one in
+// CHALLENGE_PATH
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChallengeRequest {
+    Export,
+    Accept,
+}
+
+// What the duel screen shows: a row per thing compared, with the challenger's result and
+// the player's
+pub struct DuelRow {
+    pub label: &'static str,
+    pub theirs: String,
+    pub yours: String,
+}
+
+// the rows of the duel between `challenge` and the run that answered it, and who won
+pub fn duel(challenge: &Challenge, record: &RunRecord, you: &str) -> (Vec<DuelRow>, String) {
+    let grade = |grade: Option<Grade>| grade.map_or("-", |grade| grade.letter()).to_string();
+    let distance = record.distance.unwrap_or(0.0);
+    let rows = vec![
+        DuelRow {
+            label: "",
+            theirs: challenge.by.clone(),
+            yours: you.to_string(),
+        },
+        DuelRow {
+            label: "Score",
+            theirs: challenge.score.to_string(),
+            yours: record.score.to_string(),
+        },
+        DuelRow {
+            label: "Distance",
+            theirs: format!("{:.0}", challenge.distance),
+            yours: format!("{:.0}", distance),
+        },
+        DuelRow {
+            label: "Grade",
+            theirs: grade(challenge.grade),
+            yours: grade(record.grade),
+        },
+    ];
+    // the score decides, the distance breaks a tie
+    let ordering = (record.score, distance).partial_cmp(&(challenge.score, challenge.distance));
+    let verdict = if record.input_hash == Some(challenge.input_hash) {
+        "Input for input the same run, it's a draw".to_string()
+    } else {
+        match ordering {
+            Some(Ordering::Greater) => "You win!".to_string(),
+            Some(Ordering::Less) => format!("{} wins", challenge.by),
+            _ => "It's a draw".to_string(),
+        }
+    };
+    (rows, verdict)
+}
+
+fn reset_input_hash(mut commands: Commands) {
+    commands.insert_resource(InputHash::default());
+}
+
+// system to fold the actions held this frame into the run's input hash
+fn hash_inputs(actions: Res<ActionState>, mut hash: ResMut<InputHash>) {
+    let mut held = 0u16;
+    // the stats panel keys are left out, showing the panel doesn't make it another run
+    for (bit, action) in Action::ALL.iter().enumerate() {
+        if action.is_gameplay() && actions.pressed(*action) {
+            held |= 1 << bit;
+        }
+    }
+    hash.fold(held as u8);
+    // the actions past the first eight only when held, so runs from before them hash alike
+    if held > 0xff {
+        hash.fold((held >> 8) as u8);
+    }
+    // quantized so the analog stick counts without float noise telling runs apart
+    hash.fold((actions.move_axis() * 8.0).round() as i8 as u8);
+}
+
+// system to export the last run as a challenge, or start the run of the challenge
+// accepted
+#[allow(clippy::too_many_arguments)]
+fn handle_requests(
+    mut requests: EventReader<ChallengeRequest>,
+    history: Res<RunHistory>,
+    profiles: Res<Profiles>,
+    modes: Res<GameModes>,
+    mut active_mode: ResMut<ActiveMode>,
+    mut active: ResMut<ActiveChallenge>,
+    mut game_state: ResMut<NextState<GameState>>,
+    mut menu_state: ResMut<NextState<MenuState>>,
+    mut toasts: EventWriter<Toast>,
+) {
+    for request in requests.read() {
+        match request {
+            ChallengeRequest::Export => {
+                let by = &profiles.active().name;
+                let Some(challenge) = history
+                    .0
+                    .last()
+                    .and_then(|record| Challenge::from_record(record, by))
+                else {
+                    toasts.send(Toast("Finish a run to challenge a friend".to_string()));
+                    continue;
+                };
+                let link = challenge.to_link();
+                info!("Challenge: {}", link);
+                match storage::write(CHALLENGE_PATH, format!("{}\n", link)) {
+                    Ok(()) => {
+                        toasts.send(Toast(format!("Challenge saved to {}", CHALLENGE_PATH)));
+                    }
+                    Err(err) => {
+                        warn!("Could not save the challenge: {}", err);
+                        toasts.send(Toast("Could not save the challenge".to_string()));
+                    }
+                }
+            }
+            ChallengeRequest::Accept => {
+                let challenge = storage::read_to_string(CHALLENGE_PATH)
+                    .map_err(|err| err.to_string())
+                    .and_then(|link| Challenge::parse(&link));
+                let challenge = match challenge {
+                    Ok(challenge) => challenge,
+                    Err(err) => {
+                        warn!("Could not read {}: {}", CHALLENGE_PATH, err);
+                        toasts.send(Toast(format!("No challenge in {}", CHALLENGE_PATH)));
+                        continue;
+                    }
+                };
+                let Some(mode) = modes.get(&challenge.mode) else {
+                    toasts.send(Toast(format!("Unknown mode {}", challenge.mode)));
+                    continue;
+                };
+                toasts.send(Toast(format!(
+                    "Beat {}'s {} points!",
+                    challenge.by, challenge.score
+                )));
+                active_mode.0 = mode;
+                active.0 = Some(challenge);
+                menu_state.set(MenuState::Disabled);
+                game_state.set(GameState::Playing);
+            }
+        }
+    }
+}
+
+// system to leave the challenge once back on the main menu
+fn end_challenge(mut active: ResMut<ActiveChallenge>) {
+    active.0 = None;
+}
+
+pub struct ChallengePlugin;
+
+impl Plugin for ChallengePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InputHash>()
+            .init_resource::<ActiveChallenge>()
+            .add_event::<ChallengeRequest>()
+            .add_systems(OnEnter(GameState::Playing), reset_input_hash)
+            .add_systems(OnEnter(MenuState::Main), end_challenge)
+            .add_systems(Update, hash_inputs.in_set(GameplaySet))
+            .add_systems(Update, handle_requests);
+    }
+}
@@ -0,0 +1,205 @@
+use crate::bounds::KillPlane;
+use crate::coin::coin_bundle;
+use crate::mode::{ActiveMode, RunRng};
+use crate::mutators::RunConfig;
+use crate::{
+    jump_heights, GameState, GameplaySet, Player, PlayerState, RunEntity, GROUND_Y, JUMP_HEIGHT,
+    PLAYER_SCALE,
+};
+use bevy::prelude::*;
+use bevy_parallax::ParallaxCameraComponent;
+use rand::Rng;
+
+// width of the column of cliff a climb goes up, centered on where the player starts
+pub const COLUMN_WIDTH: f32 = 560.0;
+// how far over the top of the view platforms are placed, and under the bottom removed
+const SPAWN_MARGIN: f32 = 128.0;
+// rise from one platform to the next, as a share of how high a jump gets, picked at
+// random in this range
+const PLATFORM_RISE: (f32, f32) = (0.45, 0.75);
+// width of a platform, picked at random in this range
+const PLATFORM_WIDTH: (f32, f32) = (96.0, 176.0);
+const PLATFORM_THICKNESS: f32 = 12.0;
+// the next platform overlaps the last by at least this much, since a jump barely
+// carries the player sideways
+const PLATFORM_OVERLAP: f32 = 32.0;
+// every this many platforms, one has a coin over it
+const COIN_EVERY: u32 = 3;
+// how far a player standing on a platform can be under its stand height and still be
+// on it, as ducking squashes them down
+const FOOTING_SLACK: f32 = 16.0;
+const PLATFORM_COLOR: Color = Color::rgb(0.45, 0.35, 0.28);
+const LAVA_COLOR: Color = Color::rgba(0.95, 0.35, 0.1, 0.85);
+const LAVA_DEPTH: f32 = 2000.0;
+
+// Systems finding what the players stand on, ahead of them moving
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FootingSet;
+
+// Ledge a player can stand on, landed on from above and jumped through from below
+#[derive(Component)]
+struct Platform {
+    half_width: f32,
+    // height a player standing on it is at
+    stand: f32,
+}
+
+// Glowing lava marking the rising kill plane
+#[derive(Component)]
+struct Lava;
+
+// Where the next platform of the climb goes, and the last one it has to be reachable from
+#[derive(Resource, Default)]
+struct PlatformStreamer {
+    next_y: f32,
+    last_x: f32,
+    last_half: f32,
+    placed: u32,
+}
+
+fn vertical(mode: Res<ActiveMode>) -> bool {
+    mode.vertical()
+}
+
+fn reset_climb(mut commands: Commands) {
+    commands.insert_resource(PlatformStreamer {
+        next_y: GROUND_Y,
+        last_x: 0.0,
+        last_half: COLUMN_WIDTH / 2.0,
+        placed: 0,
+    });
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: LAVA_COLOR,
+                custom_size: Some(Vec2::new(COLUMN_WIDTH * 4.0, LAVA_DEPTH)),
+                ..default()
+            },
+            transform: Transform::from_xyz(0.0, GROUND_Y - LAVA_DEPTH, 2.6),
+            ..default()
+        },
+        Lava,
+        RunEntity,
+    ));
+}
+
+// system to place platforms up the column just out of view over the camera, each one in
+// reach of a jump from the last
+fn stream_platforms(
+    mut commands: Commands,
+    mut streamer: ResMut<PlatformStreamer>,
+    mut rng: ResMut<RunRng>,
+    config: Res<RunConfig>,
+    camera_query: Query<(&Transform, &OrthographicProjection), With<ParallaxCameraComponent>>,
+) {
+    let Ok((camera, projection)) = camera_query.get_single() else {
+        return;
+    };
+    let jump = jump_heights(config.gravity)
+        .into_iter()
+        .fold(0.0, f32::max)
+        .min(JUMP_HEIGHT);
+    while camera.translation.y + projection.area.max.y + SPAWN_MARGIN >= streamer.next_y {
+        let rise = jump * rng.0.gen_range(PLATFORM_RISE.0..=PLATFORM_RISE.1);
+        let stand = streamer.next_y + rise;
+        let half = rng.0.gen_range(PLATFORM_WIDTH.0..=PLATFORM_WIDTH.1) / 2.0;
+        let reach = (streamer.last_half + half - PLATFORM_OVERLAP).max(0.0);
+        let x = (streamer.last_x + rng.0.gen_range(-reach..=reach))
+            .clamp(-COLUMN_WIDTH / 2.0 + half, COLUMN_WIDTH / 2.0 - half);
+        // the player's feet are half a sprite below where they stand
+        let top = stand - 8.0 * PLAYER_SCALE;
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: PLATFORM_COLOR,
+                    custom_size: Some(Vec2::new(half * 2.0, PLATFORM_THICKNESS)),
+                    ..default()
+                },
+                transform: Transform::from_xyz(x, top - PLATFORM_THICKNESS / 2.0, 1.4),
+                ..default()
+            },
+            Platform {
+                half_width: half,
+                stand,
+            },
+            RunEntity,
+        ));
+        streamer.placed += 1;
+        if streamer.placed.is_multiple_of(COIN_EVERY) {
+            commands.spawn(coin_bundle(Vec2::new(x, stand)));
+        }
+        streamer.next_y = stand;
+        streamer.last_x = x;
+        streamer.last_half = half;
+    }
+}
+
+// system to stand each player on the highest platform under their feet, or the ground
+// at the foot of the cliff, and drop the ones walking off the edge of one
+fn find_footing(
+    platform_query: Query<(&Platform, &Transform), Without<Player>>,
+    mut player_query: Query<(&mut Player, &Transform)>,
+) {
+    for (mut player, transform) in &mut player_query {
+        let position = transform.translation;
+        let ground = platform_query
+            .iter()
+            .filter(|(platform, platform_transform)| {
+                (position.x - platform_transform.translation.x).abs() <= platform.half_width
+                    && platform.stand <= position.y + FOOTING_SLACK
+            })
+            .map(|(platform, _)| platform.stand)
+            .fold(GROUND_Y, f32::max);
+        player.ground = ground;
+        if player.on_ground && position.y > ground + FOOTING_SLACK {
+            player.on_ground = false;
+            player.state = PlayerState::Falling;
+            info!("Player state: {:?}", player.state);
+        }
+    }
+}
+
+// system to keep the lava's surface on the kill plane
+fn raise_lava(plane: Res<KillPlane>, mut lava_query: Query<&mut Transform, With<Lava>>) {
+    for mut transform in &mut lava_query {
+        transform.translation.y = plane.0 - LAVA_DEPTH / 2.0;
+    }
+}
+
+// system to remove platforms once they're well below the camera
+fn despawn_passed_platforms(
+    mut commands: Commands,
+    camera_query: Query<(&Transform, &OrthographicProjection), With<ParallaxCameraComponent>>,
+    platform_query: Query<(Entity, &Transform), With<Platform>>,
+) {
+    let Ok((camera, projection)) = camera_query.get_single() else {
+        return;
+    };
+    let view_bottom = camera.translation.y + projection.area.min.y;
+    for (entity, transform) in &platform_query {
+        if transform.translation.y < view_bottom - SPAWN_MARGIN {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+pub struct ClimbPlugin;
+
+impl Plugin for ClimbPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PlatformStreamer>()
+            .add_systems(OnEnter(GameState::Playing), reset_climb.run_if(vertical))
+            .add_systems(
+                Update,
+                (
+                    stream_platforms,
+                    find_footing.in_set(FootingSet),
+                    raise_lava,
+                    despawn_passed_platforms,
+                )
+                    .chain()
+                    .run_if(vertical)
+                    .in_set(GameplaySet),
+            );
+    }
+}
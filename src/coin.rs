@@ -0,0 +1,169 @@
+use crate::collision::Collider;
+use crate::game_time::GameTime;
+use crate::gate::Gate;
+use crate::mode::RunRng;
+use crate::mutators::RunConfig;
+use crate::score::{AddPoints, PointSource};
+use crate::timing::timed;
+use crate::{GameState, GameplaySet, Player, RunEntity, GROUND_Y};
+use bevy::prelude::*;
+use bevy_parallax::ParallaxCameraComponent;
+use rand::Rng;
+
+const COIN_SIZE: f32 = 12.0;
+const COIN_COLOR: Color = Color::rgb(1.0, 0.85, 0.2);
+// distance between two rows of coins, picked at random in this range
+const ROW_SPACING: (f32, f32) = (400.0, 800.0);
+const ROW_LENGTH: (u32, u32) = (3, 6);
+const COIN_GAP: f32 = 24.0;
+// how high above the ground rows of coins float, picked at random in this range
+const ROW_HEIGHT: (f32, f32) = (16.0, 110.0);
+// seconds between two coins falling during coin rain
+const RAIN_INTERVAL: f32 = 0.25;
+const RAIN_SPEED: f32 = 120.0;
+
+#[derive(Component)]
+pub struct Coin;
+
+// Coin dropping from the sky during coin rain
+#[derive(Component)]
+pub struct Falling;
+
+// Coins picked up in the current run, banked when it ends
+#[derive(Resource, Default)]
+pub struct RunCoins(pub u32);
+
+// Where the next row of coins goes
+#[derive(Resource, Default)]
+pub struct CoinSpawner {
+    pub next_x: f32,
+    rain: Timer,
+}
+
+fn reset_spawner(mut commands: Commands) {
+    commands.insert_resource(RunCoins::default());
+    commands.insert_resource(CoinSpawner {
+        next_x: ROW_SPACING.0,
+        rain: Timer::from_seconds(RAIN_INTERVAL, TimerMode::Repeating),
+    });
+}
+
+pub fn coin_bundle(position: Vec2) -> impl Bundle {
+    (
+        SpriteBundle {
+            sprite: Sprite {
+                color: COIN_COLOR,
+                custom_size: Some(Vec2::splat(COIN_SIZE)),
+                ..default()
+            },
+            transform: Transform::from_translation(position.extend(1.4)),
+            ..default()
+        },
+        Coin,
+        Collider(Rect::new(
+            -COIN_SIZE / 2.0,
+            -COIN_SIZE / 2.0,
+            COIN_SIZE / 2.0,
+            COIN_SIZE / 2.0,
+        )),
+        RunEntity,
+    )
+}
+
+// system to lay rows of coins out ahead of the camera, and drop more during coin rain
+fn spawn_coins(
+    mut commands: Commands,
+    time: Res<GameTime>,
+    config: Res<RunConfig>,
+    mut spawner: ResMut<CoinSpawner>,
+    mut rng: ResMut<RunRng>,
+    gate: Res<Gate>,
+    camera_query: Query<(&Transform, &OrthographicProjection), With<ParallaxCameraComponent>>,
+) {
+    let Ok((camera, projection)) = camera_query.get_single() else {
+        return;
+    };
+    let view = projection.area;
+    let view_right = camera.translation.x + view.max.x;
+    // the gate closing the biome has a reward of its own
+    if gate.covers(spawner.next_x) {
+        spawner.next_x = gate.end;
+    }
+    if view_right >= spawner.next_x {
+        let height = GROUND_Y + rng.0.gen_range(ROW_HEIGHT.0..=ROW_HEIGHT.1);
+        let length = rng.0.gen_range(ROW_LENGTH.0..=ROW_LENGTH.1);
+        for index in 0..length {
+            let x = spawner.next_x + index as f32 * COIN_GAP;
+            commands.spawn(coin_bundle(Vec2::new(x, height)));
+        }
+        spawner.next_x += length as f32 * COIN_GAP + rng.0.gen_range(ROW_SPACING.0..=ROW_SPACING.1);
+    }
+    if config.coin_rain && spawner.rain.tick(time.delta()).just_finished() {
+        let x = camera.translation.x + rng.0.gen_range(view.min.x..=view.max.x);
+        let y = camera.translation.y + view.max.y + COIN_SIZE;
+        commands.spawn((coin_bundle(Vec2::new(x, y)), Falling));
+    }
+}
+
+fn fall(
+    mut commands: Commands,
+    time: Res<GameTime>,
+    mut query: Query<(Entity, &mut Transform), With<Falling>>,
+) {
+    for (entity, mut transform) in &mut query {
+        transform.translation.y -= RAIN_SPEED * time.delta_seconds();
+        if transform.translation.y < GROUND_Y - 32.0 {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+// system to pick up the coins any player touches, for the score they share, and clear
+// the ones left behind
+fn collect_coins(
+    mut commands: Commands,
+    coin_query: Query<(Entity, &GlobalTransform, &Collider), With<Coin>>,
+    player_query: Query<(&GlobalTransform, &Collider), With<Player>>,
+    camera_query: Query<(&Transform, &OrthographicProjection), With<ParallaxCameraComponent>>,
+    mut points: EventWriter<AddPoints>,
+    mut coins: ResMut<RunCoins>,
+) {
+    let player_rects: Vec<Rect> = player_query
+        .iter()
+        .map(|(transform, collider)| collider.world_rect(transform))
+        .collect();
+    let view_left = camera_query
+        .get_single()
+        .map_or(f32::MIN, |(camera, projection)| {
+            camera.translation.x + projection.area.min.x
+        });
+    for (entity, transform, collider) in &coin_query {
+        let rect = collider.world_rect(transform);
+        if player_rects
+            .iter()
+            .any(|player| !rect.intersect(*player).is_empty())
+        {
+            commands.entity(entity).despawn_recursive();
+            points.send(AddPoints(PointSource::Coin));
+            coins.0 += 1;
+        } else if rect.max.x < view_left {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+pub struct CoinPlugin;
+
+impl Plugin for CoinPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CoinSpawner>()
+            .init_resource::<RunCoins>()
+            .add_systems(OnEnter(GameState::Playing), reset_spawner)
+            .add_systems(
+                Update,
+                (timed(spawn_coins), timed(fall), timed(collect_coins))
+                    .chain()
+                    .in_set(GameplaySet),
+            );
+    }
+}
@@ -0,0 +1,79 @@
+#[cfg(feature = "dev_tools")]
+use crate::toast::Toast;
+use bevy::prelude::*;
+
+// Box an entity collides with, in its sprite's pixels around the sprite center;
+// scaled and moved along with the entity's transform
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Collider(pub Rect);
+
+impl Collider {
+    // the box in world space for an entity at `transform`
+    pub fn world_rect(&self, transform: &GlobalTransform) -> Rect {
+        let (scale, _, translation) = transform.to_scale_rotation_translation();
+        let center = translation.truncate();
+        let scale = scale.truncate();
+        Rect::from_corners(center + self.0.min * scale, center + self.0.max * scale)
+    }
+}
+
+// Collider of a child moved by its parent turning or swinging, whose GlobalTransform is
+// worked out again as soon as the parent moves instead of when transforms are propagated
+// at the end of the frame. The parent has to be a root entity.
+#[derive(Component)]
+pub struct SyncedCollider;
+
+// system to bring the GlobalTransform of synced colliders in line with where their
+// parent has just been moved to; order it between moving the parent and checking hits
+pub fn sync_colliders(
+    parent_query: Query<&Transform, Without<SyncedCollider>>,
+    mut collider_query: Query<(&Parent, &Transform, &mut GlobalTransform), With<SyncedCollider>>,
+) {
+    for (parent, transform, mut global) in &mut collider_query {
+        if let Ok(parent_transform) = parent_query.get(parent.get()) {
+            *global = GlobalTransform::from(*parent_transform).mul_transform(*transform);
+        }
+    }
+}
+
+// Whether hitboxes are drawn over the sprites, toggled with F3 in dev builds
+#[derive(Resource, Default)]
+pub struct ShowHitboxes(pub bool);
+
+#[cfg(feature = "dev_tools")]
+fn toggle_hitboxes(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut show: ResMut<ShowHitboxes>,
+    mut toasts: EventWriter<Toast>,
+) {
+    if keyboard_input.just_pressed(KeyCode::F3) {
+        show.0 = !show.0;
+        let state = if show.0 { "shown" } else { "hidden" };
+        toasts.send(Toast(format!("Hitboxes {}", state)));
+    }
+}
+
+fn draw_hitboxes(
+    show: Res<ShowHitboxes>,
+    query: Query<(&Collider, &GlobalTransform)>,
+    mut gizmos: Gizmos,
+) {
+    if !show.0 {
+        return;
+    }
+    for (collider, transform) in &query {
+        let rect = collider.world_rect(transform);
+        gizmos.rect_2d(rect.center(), 0.0, rect.size(), Color::LIME_GREEN);
+    }
+}
+
+pub struct CollisionPlugin;
+
+impl Plugin for CollisionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ShowHitboxes>()
+            .add_systems(Update, draw_hitboxes);
+        #[cfg(feature = "dev_tools")]
+        app.add_systems(Update, toggle_hitboxes.before(draw_hitboxes));
+    }
+}
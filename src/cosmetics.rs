@@ -0,0 +1,305 @@
+use crate::animation::{AnimationLibrary, MenuAnimation, SpriteAnimator};
+use crate::menu::MenuState;
+use crate::settings::Settings;
+use crate::{Player, PLAYER_SHEET, PLAYER_SPRITE};
+use bevy::prelude::*;
+use bevy::render::camera::RenderTarget;
+use bevy::render::render_resource::{
+    Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
+};
+use bevy::render::view::RenderLayers;
+use serde::{Deserialize, Serialize};
+
+// render layer only the shop preview camera sees, keeping the mannequin out of the world
+const PREVIEW_LAYER: u8 = 1;
+// size of the preview image in the shop, in pixels
+const PREVIEW_SIZE: u32 = 160;
+const PREVIEW_SCALE: f32 = 7.0;
+const PREVIEW_BACKGROUND: Color = Color::rgb(0.25, 0.2, 0.3);
+// clips the mannequin cycles through, and how long it shows each of them
+const PREVIEW_CLIPS: [&str; 3] = ["walk", "run", "jump"];
+const PREVIEW_CLIP_TIME: f32 = 2.0;
+// top of the player's head in the sprite, hats sit on it
+const HEAD_TOP: Vec2 = Vec2::new(1.5, 7.0);
+const HAT_Z: f32 = 0.05;
+
+// Tint of the player's sprite
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Skin {
+    #[default]
+    Classic,
+    Ember,
+    Frost,
+    Shadow,
+}
+
+impl Skin {
+    pub const ALL: [Skin; 4] = [Skin::Classic, Skin::Ember, Skin::Frost, Skin::Shadow];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Skin::Classic => "Classic",
+            Skin::Ember => "Ember",
+            Skin::Frost => "Frost",
+            Skin::Shadow => "Shadow",
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            Skin::Classic => Color::WHITE,
+            Skin::Ember => Color::rgb(1.0, 0.6, 0.45),
+            Skin::Frost => Color::rgb(0.6, 0.85, 1.0),
+            Skin::Shadow => Color::rgb(0.45, 0.4, 0.55),
+        }
+    }
+}
+
+// What the player wears on their head
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Hat {
+    #[default]
+    None,
+    Cap,
+    Crown,
+    Topper,
+}
+
+impl Hat {
+    pub const ALL: [Hat; 4] = [Hat::None, Hat::Cap, Hat::Crown, Hat::Topper];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Hat::None => "No hat",
+            Hat::Cap => "Cap",
+            Hat::Crown => "Crown",
+            Hat::Topper => "Top hat",
+        }
+    }
+
+    // color and size of the hat, in sprite pixels
+    fn look(&self) -> Option<(Color, Vec2)> {
+        match self {
+            Hat::None => None,
+            Hat::Cap => Some((Color::rgb(0.85, 0.2, 0.2), Vec2::new(6.0, 2.0))),
+            Hat::Crown => Some((Color::rgb(1.0, 0.8, 0.2), Vec2::new(4.0, 3.0))),
+            Hat::Topper => Some((Color::rgb(0.1, 0.1, 0.12), Vec2::new(4.0, 5.0))),
+        }
+    }
+}
+
+// The skin and hat the player runs with
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct Cosmetics {
+    pub skin: Skin,
+    pub hat: Hat,
+}
+
+// Event asking the shop preview to wear these, sent as items are hovered in the shop
+#[derive(Event)]
+pub struct TryOn(pub Cosmetics);
+
+// Hat sprite, a child of whoever wears it
+#[derive(Component)]
+struct HatSprite;
+
+fn hat_bundle(hat: Hat) -> Option<impl Bundle> {
+    let (color, size) = hat.look()?;
+    let position = HEAD_TOP + Vec2::new(0.0, size.y / 2.0);
+    Some((
+        SpriteBundle {
+            sprite: Sprite {
+                color,
+                custom_size: Some(size),
+                ..default()
+            },
+            transform: Transform::from_translation(position.extend(HAT_Z)),
+            ..default()
+        },
+        HatSprite,
+    ))
+}
+
+// system to dress each new player in the equipped skin and hat
+fn dress_player(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    mut player_query: Query<(Entity, &mut Sprite), Added<Player>>,
+) {
+    for (player, mut sprite) in &mut player_query {
+        sprite.color = settings.cosmetics.skin.color();
+        if let Some(hat) = hat_bundle(settings.cosmetics.hat) {
+            commands.entity(player).with_children(|parent| {
+                parent.spawn(hat);
+            });
+        }
+    }
+}
+
+// The player character shown in the shop, wearing what's hovered
+#[derive(Component)]
+struct Mannequin {
+    worn: Cosmetics,
+    clip: usize,
+    timer: Timer,
+}
+
+// Camera drawing the mannequin into the shop's preview image
+#[derive(Component)]
+struct PreviewCamera;
+
+// what the shop spawns for its preview, gone with the shop
+type PreviewParts = Or<(With<PreviewCamera>, With<Mannequin>)>;
+
+fn preview_image() -> Image {
+    let size = Extent3d {
+        width: PREVIEW_SIZE,
+        height: PREVIEW_SIZE,
+        ..default()
+    };
+    let mut image = Image {
+        texture_descriptor: TextureDescriptor {
+            label: Some("shop_preview"),
+            size,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::Bgra8UnormSrgb,
+            mip_level_count: 1,
+            sample_count: 1,
+            usage: TextureUsages::TEXTURE_BINDING
+                | TextureUsages::COPY_DST
+                | TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        },
+        ..default()
+    };
+    // zero filled until the camera first draws into it
+    image.resize(size);
+    image
+}
+
+// set up the preview camera and its mannequin, returning the image they render to
+pub fn spawn_preview(
+    commands: &mut Commands,
+    images: &mut Assets<Image>,
+    asset_server: &AssetServer,
+    library: &AnimationLibrary,
+    cosmetics: Cosmetics,
+) -> Handle<Image> {
+    let image = images.add(preview_image());
+    commands.spawn((
+        Camera2dBundle {
+            camera: Camera {
+                // drawn before the main camera, whose UI shows the result
+                order: -1,
+                target: RenderTarget::Image(image.clone()),
+                clear_color: ClearColorConfig::Custom(PREVIEW_BACKGROUND),
+                ..default()
+            },
+            ..default()
+        },
+        PreviewCamera,
+        RenderLayers::layer(PREVIEW_LAYER),
+    ));
+    let clip = library.clip(PREVIEW_CLIPS[0]).cloned().unwrap_or_default();
+    let mut mannequin = commands.spawn((
+        SpriteSheetBundle {
+            texture: asset_server.load(PLAYER_SPRITE),
+            atlas: TextureAtlas {
+                layout: asset_server.load(format!("{}#layout", PLAYER_SHEET)),
+                index: 0,
+            },
+            sprite: Sprite {
+                color: cosmetics.skin.color(),
+                ..default()
+            },
+            transform: Transform::from_scale(Vec3::splat(PREVIEW_SCALE)),
+            ..default()
+        },
+        SpriteAnimator::new(clip),
+        MenuAnimation,
+        Mannequin {
+            worn: cosmetics,
+            clip: 0,
+            timer: Timer::from_seconds(PREVIEW_CLIP_TIME, TimerMode::Repeating),
+        },
+        RenderLayers::layer(PREVIEW_LAYER),
+    ));
+    if let Some(hat) = hat_bundle(cosmetics.hat) {
+        mannequin.with_children(|parent| {
+            parent.spawn((hat, RenderLayers::layer(PREVIEW_LAYER)));
+        });
+    }
+    image
+}
+
+fn despawn_preview(mut commands: Commands, query: Query<Entity, PreviewParts>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// system to put what was last asked to be tried on on the mannequin
+fn try_on(
+    mut events: EventReader<TryOn>,
+    mut commands: Commands,
+    mut mannequin_query: Query<(Entity, &mut Mannequin, &mut Sprite, Option<&Children>)>,
+    hat_query: Query<(), With<HatSprite>>,
+) {
+    let Some(TryOn(cosmetics)) = events.read().last() else {
+        return;
+    };
+    let cosmetics = *cosmetics;
+    let Ok((entity, mut mannequin, mut sprite, children)) = mannequin_query.get_single_mut() else {
+        return;
+    };
+    if mannequin.worn == cosmetics {
+        return;
+    }
+    sprite.color = cosmetics.skin.color();
+    if mannequin.worn.hat != cosmetics.hat {
+        for &child in children.into_iter().flatten() {
+            if hat_query.contains(child) {
+                commands.entity(child).despawn_recursive();
+            }
+        }
+        if let Some(hat) = hat_bundle(cosmetics.hat) {
+            commands.entity(entity).with_children(|parent| {
+                parent.spawn((hat, RenderLayers::layer(PREVIEW_LAYER)));
+            });
+        }
+    }
+    mannequin.worn = cosmetics;
+}
+
+// system to have the mannequin go through its clips one after the other
+fn cycle_preview_clips(
+    time: Res<Time<Real>>,
+    library: Res<AnimationLibrary>,
+    mut mannequin_query: Query<(&mut Mannequin, &mut SpriteAnimator)>,
+) {
+    for (mut mannequin, mut animator) in &mut mannequin_query {
+        mannequin.timer.tick(time.delta());
+        if mannequin.timer.just_finished() {
+            mannequin.clip = (mannequin.clip + 1) % PREVIEW_CLIPS.len();
+        }
+        // also picks the first clip up once the sheet has loaded
+        let Some(clip) = library.clip(PREVIEW_CLIPS[mannequin.clip]) else {
+            continue;
+        };
+        if animator.target().name != clip.name {
+            animator.play(clip.clone());
+        }
+    }
+}
+
+pub struct CosmeticsPlugin;
+
+impl Plugin for CosmeticsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<TryOn>()
+            .add_systems(OnExit(MenuState::Shop), despawn_preview)
+            .add_systems(OnExit(MenuState::Gallery), despawn_preview)
+            .add_systems(Update, (dress_player, try_on, cycle_preview_clips));
+    }
+}
@@ -0,0 +1,287 @@
+use crate::balance::Balance;
+use crate::settings::Settings;
+use crate::validate::{validate_balance, Problem};
+use bevy::prelude::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+// the preview always lays out the same sample course, so only the settings change it
+const PREVIEW_SEED: u64 = 7;
+// length of course each preview strip covers, and its size on screen
+const PREVIEW_LENGTH: f32 = 6000.0;
+const STRIP_WIDTH: f32 = 420.0;
+const STRIP_HEIGHT: f32 = 28.0;
+const STRIP_COLOR: Color = Color::rgba(0.1, 0.1, 0.15, 0.8);
+const ENEMY_COLOR: Color = Color::rgb(0.45, 0.8, 0.3);
+const BOSS_COLOR: Color = Color::rgb(0.75, 0.3, 0.85);
+const BARREL_COLOR: Color = Color::rgb(0.85, 0.35, 0.15);
+const SWING_COLOR: Color = Color::rgb(0.8, 0.2, 0.25);
+
+// Custom difficulty picked in the menu, on top of the balance's own difficulty curve
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct CustomDifficulty {
+    // scale of how many enemies and barrels a stretch of course holds, 1 as balanced
+    pub hazard_density: f32,
+    // scale of how many of the enemies are bosses
+    pub boss_frequency: f32,
+}
+
+impl Default for CustomDifficulty {
+    fn default() -> Self {
+        CustomDifficulty {
+            hazard_density: 1.0,
+            boss_frequency: 1.0,
+        }
+    }
+}
+
+// A custom difficulty setting the menu adjusts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DifficultyKnob {
+    HazardDensity,
+    BossFrequency,
+}
+
+impl DifficultyKnob {
+    pub const ALL: [DifficultyKnob; 2] =
+        [DifficultyKnob::HazardDensity, DifficultyKnob::BossFrequency];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DifficultyKnob::HazardDensity => "Hazard density",
+            DifficultyKnob::BossFrequency => "Boss frequency",
+        }
+    }
+
+    // lowest and highest value, and the step the menu moves it by
+    fn range(&self) -> (f32, f32, f32) {
+        match self {
+            DifficultyKnob::HazardDensity => (0.5, 2.0, 0.1),
+            DifficultyKnob::BossFrequency => (0.5, 3.0, 0.25),
+        }
+    }
+}
+
+impl CustomDifficulty {
+    pub fn get(&self, knob: DifficultyKnob) -> f32 {
+        match knob {
+            DifficultyKnob::HazardDensity => self.hazard_density,
+            DifficultyKnob::BossFrequency => self.boss_frequency,
+        }
+    }
+
+    // move `knob` by `steps` of its step, past either end back round to the other
+    pub fn adjust(&mut self, knob: DifficultyKnob, steps: i32) {
+        let (min, max, step) = knob.range();
+        let count = ((max - min) / step).round() as i32 + 1;
+        let current = ((self.get(knob) - min) / step).round() as i32;
+        let value = min + (current + steps).rem_euclid(count) as f32 * step;
+        match knob {
+            DifficultyKnob::HazardDensity => self.hazard_density = value,
+            DifficultyKnob::BossFrequency => self.boss_frequency = value,
+        }
+    }
+
+    // factor applied to the gap between two enemies, or two barrels
+    pub fn spacing(&self) -> f32 {
+        1.0 / self.hazard_density
+    }
+
+    // every this many enemies the next one is a boss
+    pub fn boss_every(&self, balance: &Balance) -> u32 {
+        ((balance.boss_every as f32 / self.boss_frequency).round() as u32).max(1)
+    }
+}
+
+// Something the spawners put on the course
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Hazard {
+    Enemy,
+    Boss,
+    Barrel,
+    Swing,
+}
+
+// the hazards the spawners would lay out between `from` and `from + PREVIEW_LENGTH`,
+// by their distance from `from`. Rolls the gaps the way the spawners do, with a rng of
+// its own for each rather than the one they share.
+fn sample_course(
+    balance: &Balance,
+    difficulty: &CustomDifficulty,
+    from: f32,
+) -> Vec<(f32, Hazard)> {
+    let to = from + PREVIEW_LENGTH;
+    let mut hazards = Vec::new();
+    let mut rng = StdRng::seed_from_u64(PREVIEW_SEED);
+    let mut x = balance.enemy_spacing.1;
+    let mut spawned = 0u32;
+    while x < to {
+        spawned += 1;
+        if x >= from {
+            let boss = spawned.is_multiple_of(difficulty.boss_every(balance));
+            hazards.push((x - from, if boss { Hazard::Boss } else { Hazard::Enemy }));
+        }
+        let (min, max) = balance.enemy_spacing;
+        x += rng.gen_range(min..=max) * balance.spacing_at(x) * difficulty.spacing();
+    }
+    let mut rng = StdRng::seed_from_u64(PREVIEW_SEED + 1);
+    let mut x = balance.barrel_spacing.1;
+    while x < to {
+        if x >= from {
+            hazards.push((x - from, Hazard::Barrel));
+        }
+        let (min, max) = balance.barrel_spacing;
+        x += rng.gen_range(min..=max) * balance.spacing_at(x) * difficulty.spacing();
+    }
+    let mut rng = StdRng::seed_from_u64(PREVIEW_SEED + 2);
+    let mut x = balance.swing_spacing.1;
+    while x < to {
+        if x >= from {
+            hazards.push((x - from, Hazard::Swing));
+        }
+        let (min, max) = balance.swing_spacing;
+        x += rng.gen_range(min..=max) * balance.spacing_at(x) * difficulty.spacing();
+    }
+    hazards
+}
+
+// what the spawn tables check says about the barrels once packed closer, None when
+// they can all be cleared comfortably
+fn warning(balance: &Balance, difficulty: &CustomDifficulty) -> Option<&'static str> {
+    let mut packed = balance.clone();
+    packed.barrel_spacing.0 *= difficulty.spacing();
+    let findings = validate_balance(&packed);
+    if findings.iter().any(|f| f.problem == Problem::Impossible) {
+        Some("Some barrels come too close together to jump")
+    } else if !findings.is_empty() {
+        Some("Some barrels need frame-perfect jumps")
+    } else {
+        None
+    }
+}
+
+// Box the difficulty menu shows the preview strips in, redrawn as the settings change
+#[derive(Component)]
+pub struct DifficultyPreview;
+
+pub fn preview_bundle() -> impl Bundle {
+    (
+        NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(4.0),
+                margin: UiRect::vertical(Val::Px(6.0)),
+                ..default()
+            },
+            ..default()
+        },
+        DifficultyPreview,
+    )
+}
+
+fn label(parent: &mut ChildBuilder, text: impl Into<String>, color: Color) {
+    parent.spawn(TextBundle::from_section(
+        text,
+        TextStyle {
+            font_size: 12.0,
+            color,
+            ..default()
+        },
+    ));
+}
+
+// one strip of course with a marker per hazard, a boss taller than an enemy and a
+// barrel shorter
+fn strip(parent: &mut ChildBuilder, hazards: &[(f32, Hazard)]) {
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Px(STRIP_WIDTH),
+                height: Val::Px(STRIP_HEIGHT),
+                ..default()
+            },
+            background_color: STRIP_COLOR.into(),
+            ..default()
+        })
+        .with_children(|strip| {
+            for (x, hazard) in hazards {
+                let (size, color) = match hazard {
+                    Hazard::Enemy => (Vec2::new(6.0, 14.0), ENEMY_COLOR),
+                    Hazard::Boss => (Vec2::new(10.0, 24.0), BOSS_COLOR),
+                    Hazard::Barrel => (Vec2::new(8.0, 8.0), BARREL_COLOR),
+                    Hazard::Swing => (Vec2::new(4.0, 20.0), SWING_COLOR),
+                };
+                strip.spawn(NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        left: Val::Px(x / PREVIEW_LENGTH * (STRIP_WIDTH - size.x)),
+                        bottom: Val::Px(2.0),
+                        width: Val::Px(size.x),
+                        height: Val::Px(size.y),
+                        ..default()
+                    },
+                    background_color: color.into(),
+                    ..default()
+                });
+            }
+        });
+}
+
+// system to lay out the sample course at the start of a run and once the difficulty
+// curve has peaked, under the current settings
+fn draw_preview(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    balance: Res<Balance>,
+    preview_query: Query<Entity, With<DifficultyPreview>>,
+    added_query: Query<(), Added<DifficultyPreview>>,
+) {
+    if !settings.is_changed() && added_query.is_empty() {
+        return;
+    }
+    let difficulty = settings.difficulty;
+    let peak = balance.difficulty.last().map_or(0.0, |step| step.distance);
+    for preview in &preview_query {
+        commands
+            .entity(preview)
+            .despawn_descendants()
+            .with_children(|parent| {
+                for from in [0.0, peak] {
+                    let hazards = sample_course(&balance, &difficulty, from);
+                    let count = |kind: Hazard| hazards.iter().filter(|(_, h)| *h == kind).count();
+                    let stretch = if from > 0.0 {
+                        format!("{:.0} px from {:.0} on", PREVIEW_LENGTH, from)
+                    } else {
+                        format!("First {:.0} px", PREVIEW_LENGTH)
+                    };
+                    label(
+                        parent,
+                        format!(
+                            "{}: {} enemies, {} bosses, {} barrels, {} swings",
+                            stretch,
+                            count(Hazard::Enemy),
+                            count(Hazard::Boss),
+                            count(Hazard::Barrel),
+                            count(Hazard::Swing),
+                        ),
+                        Color::rgb(0.8, 0.8, 0.8),
+                    );
+                    strip(parent, &hazards);
+                }
+                if let Some(warning) = warning(&balance, &difficulty) {
+                    label(parent, warning, Color::rgb(1.0, 0.55, 0.3));
+                }
+            });
+    }
+}
+
+pub struct DifficultyPlugin;
+
+impl Plugin for DifficultyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, draw_preview);
+    }
+}
@@ -0,0 +1,398 @@
+use crate::exit::FlushSaves;
+use crate::idle::{Idle, IDLE_FRAME_LIMIT};
+use crate::settings::Settings;
+use crate::toast::Toast;
+use bevy::prelude::*;
+use bevy::render::camera::{ClearColorConfig, ScalingMode, Viewport};
+use bevy::render::view::RenderLayers;
+use bevy::time::TimeSystem;
+use bevy::utils::Instant;
+use bevy::window::{
+    MonitorSelection, PresentMode, PrimaryWindow, WindowMode, WindowMoved, WindowPosition,
+    WindowResized,
+};
+use bevy::winit::WinitWindows;
+use bevy_parallax::ParallaxCameraComponent;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+// size of the world area the game is drawn for, in world units
+const VIEW_WIDTH: f32 = 640.0;
+const VIEW_HEIGHT: f32 = 320.0;
+
+// largest scale the world is drawn at in low-spec mode, letterboxed beyond
+const LOW_SPEC_SCALE: f32 = 2.0;
+
+// render layer only the UI camera looks at, so it draws no world sprites or gizmos
+const UI_LAYER: u8 = 31;
+
+// wait for the window to settle this long before saving its new geometry,
+// so dragging or resizing doesn't write the settings file every frame
+const SAVE_DELAY: f32 = 0.5;
+
+// Window geometry restored at startup
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct WindowSettings {
+    // top left corner in physical pixels, None lets the OS place the window
+    pub position: Option<IVec2>,
+    pub width: f32,
+    pub height: f32,
+    // index of the monitor the window was last on
+    pub monitor: Option<usize>,
+    pub mode: WindowMode,
+}
+
+impl Default for WindowSettings {
+    fn default() -> Self {
+        WindowSettings {
+            position: None,
+            width: 640.0,
+            height: 320.0,
+            monitor: None,
+            mode: WindowMode::Windowed,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Vsync {
+    #[default]
+    On,
+    Off,
+    // vsync that lets late frames through instead of waiting a whole refresh, where supported
+    Adaptive,
+}
+
+impl Vsync {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Vsync::On => "On",
+            Vsync::Off => "Off",
+            Vsync::Adaptive => "Adaptive",
+        }
+    }
+
+    pub fn next(&self) -> Vsync {
+        match self {
+            Vsync::On => Vsync::Off,
+            Vsync::Off => Vsync::Adaptive,
+            Vsync::Adaptive => Vsync::On,
+        }
+    }
+
+    fn present_mode(&self) -> PresentMode {
+        match self {
+            Vsync::On => PresentMode::Fifo,
+            Vsync::Off => PresentMode::AutoNoVsync,
+            // falls back to regular vsync where relaxed fifo is unavailable
+            Vsync::Adaptive => PresentMode::AutoVsync,
+        }
+    }
+}
+
+// frame rate caps offered in the video menu, None is uncapped
+const FRAME_LIMITS: [Option<u32>; 5] = [None, Some(30), Some(60), Some(120), Some(144)];
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct VideoSettings {
+    pub vsync: Vsync,
+    // sleep at the end of each frame to hold this many frames per second
+    pub frame_limit: Option<u32>,
+    // trade tearing and smoothness for the shortest time from input to screen: vsync
+    // off, the frame limit waits before input is read instead of after the frame is
+    // simulated, and frames aren't rendered in parallel with the next (from the next
+    // start)
+    pub low_latency: bool,
+    // for weak machines: no dust, weather, biome haze and shimmer or shimmering portals,
+    // the view scaled up at most LOW_SPEC_SCALE times and the mountains left out of the
+    // parallax (from the next run)
+    pub low_spec: bool,
+}
+
+impl VideoSettings {
+    pub fn next_frame_limit(&self) -> Option<u32> {
+        let index = FRAME_LIMITS
+            .iter()
+            .position(|limit| *limit == self.frame_limit)
+            .map_or(0, |index| index + 1);
+        FRAME_LIMITS[index % FRAME_LIMITS.len()]
+    }
+}
+
+// Event to switch to the next window mode, as Alt+Enter / F11 do
+#[derive(Event, Debug, Clone, Copy)]
+pub struct CycleWindowMode;
+
+impl WindowSettings {
+    // primary window as it was when the game was last closed
+    pub fn window(&self) -> Window {
+        let position = match (self.position, self.monitor) {
+            (Some(position), _) => WindowPosition::At(position),
+            (None, Some(index)) => WindowPosition::Centered(MonitorSelection::Index(index)),
+            (None, None) => WindowPosition::Automatic,
+        };
+        Window {
+            title: "Platformer".to_string(),
+            resolution: (self.width, self.height).into(),
+            position,
+            mode: self.mode,
+            ..default()
+        }
+    }
+}
+
+// Countdown until the changed window geometry gets written to the settings
+#[derive(Resource, Default)]
+struct PendingWindowSave {
+    timer: Option<Timer>,
+    // where the window was last moved to
+    position: Option<IVec2>,
+}
+
+// system to notice the window being moved, resized or switched to fullscreen
+fn track_window(
+    mut moved_events: EventReader<WindowMoved>,
+    mut resized_events: EventReader<WindowResized>,
+    window_query: Query<Ref<Window>, With<PrimaryWindow>>,
+    mut pending: ResMut<PendingWindowSave>,
+) {
+    let moved = moved_events.read().last().map(|event| event.position);
+    let resized = resized_events.read().count() > 0;
+    let mode_changed = window_query
+        .get_single()
+        .is_ok_and(|window| window.is_changed() && !window.is_added());
+    if moved.is_some() {
+        pending.position = moved;
+    }
+    if moved.is_some() || resized || mode_changed {
+        pending.timer = Some(Timer::from_seconds(SAVE_DELAY, TimerMode::Once));
+    }
+}
+
+// system to copy the window geometry into the settings once it stopped changing
+fn save_window(
+    time: Res<Time>,
+    mut pending: ResMut<PendingWindowSave>,
+    window_query: Query<(Entity, &Window), With<PrimaryWindow>>,
+    // missing when the game runs without winit, as headless
+    winit_windows: Option<NonSend<WinitWindows>>,
+    mut settings: ResMut<Settings>,
+    mut flush: EventReader<FlushSaves>,
+) {
+    // quitting writes the geometry straight away rather than wait for it to settle
+    let flushing = flush.read().count() > 0;
+    let Some(timer) = pending.timer.as_mut() else {
+        return;
+    };
+    if !timer.tick(time.delta()).finished() && !flushing {
+        return;
+    }
+    pending.timer = None;
+    let Ok((entity, window)) = window_query.get_single() else {
+        return;
+    };
+    let mut window_settings = settings.window.clone();
+    window_settings.mode = window.mode;
+    // keep the windowed geometry while fullscreen so leaving fullscreen restores it
+    if window.mode == WindowMode::Windowed {
+        window_settings.width = window.width();
+        window_settings.height = window.height();
+        if pending.position.is_some() {
+            window_settings.position = pending.position;
+        }
+    }
+    if let Some(winit_window) = winit_windows
+        .as_ref()
+        .and_then(|windows| windows.get_window(entity))
+    {
+        let current = winit_window.current_monitor();
+        window_settings.monitor = winit_window
+            .available_monitors()
+            .position(|monitor| Some(monitor) == current);
+    }
+    if settings.window != window_settings {
+        settings.window = window_settings;
+    }
+}
+
+// the window mode Alt+Enter / F11 switches to from `mode`
+fn next_mode(mode: WindowMode) -> WindowMode {
+    match mode {
+        WindowMode::Windowed => WindowMode::BorderlessFullscreen,
+        WindowMode::BorderlessFullscreen => WindowMode::Fullscreen,
+        WindowMode::Fullscreen | WindowMode::SizedFullscreen => WindowMode::Windowed,
+    }
+}
+
+pub fn mode_name(mode: WindowMode) -> &'static str {
+    match mode {
+        WindowMode::Windowed => "Windowed",
+        WindowMode::BorderlessFullscreen => "Borderless fullscreen",
+        WindowMode::Fullscreen | WindowMode::SizedFullscreen => "Fullscreen",
+    }
+}
+
+// system to cycle windowed, borderless fullscreen and exclusive fullscreen
+fn toggle_fullscreen(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut cycle_events: EventReader<CycleWindowMode>,
+    mut window_query: Query<&mut Window, With<PrimaryWindow>>,
+    mut toasts: EventWriter<Toast>,
+) {
+    let alt = keyboard_input.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]);
+    let alt_enter = alt && keyboard_input.just_pressed(KeyCode::Enter);
+    let requested = cycle_events.read().count() > 0;
+    if !(alt_enter || keyboard_input.just_pressed(KeyCode::F11) || requested) {
+        return;
+    }
+    let Ok(mut window) = window_query.get_single_mut() else {
+        return;
+    };
+    window.mode = next_mode(window.mode);
+    toasts.send(Toast(mode_name(window.mode).to_string()));
+}
+
+// Camera drawing the HUD and menus over the whole window, on top of the world camera.
+// Keeping them apart lets the world camera be letterboxed, moved or zoomed without
+// dragging the UI along.
+#[derive(Component)]
+pub struct UiCamera;
+
+fn spawn_ui_camera(mut commands: Commands) {
+    commands.spawn((
+        Camera2dBundle {
+            camera: Camera {
+                // after the world camera, keeping what it drew
+                order: 1,
+                clear_color: ClearColorConfig::None,
+                ..default()
+            },
+            ..default()
+        },
+        RenderLayers::layer(UI_LAYER),
+        IsDefaultUiCamera,
+        UiCamera,
+    ));
+}
+
+// system to fit the world camera into the window: the view is scaled up by the largest
+// whole multiple that fits and letterboxed, so pixel art stays crisp in any window mode
+fn pixel_perfect_viewport(
+    settings: Res<Settings>,
+    window_query: Query<Ref<Window>, With<PrimaryWindow>>,
+    mut camera_query: Query<
+        (&mut Camera, &mut OrthographicProjection),
+        With<ParallaxCameraComponent>,
+    >,
+) {
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+    if !(window.is_changed() || settings.is_changed()) {
+        return;
+    }
+    let window_size = Vec2::new(
+        window.physical_width() as f32,
+        window.physical_height() as f32,
+    );
+    if window_size.x < 1.0 || window_size.y < 1.0 {
+        return;
+    }
+    let fit = (window_size.x / VIEW_WIDTH).min(window_size.y / VIEW_HEIGHT);
+    // windows smaller than the view fall back to a fractional scale
+    let mut scale = if fit >= 1.0 { fit.floor() } else { fit };
+    if settings.video.low_spec {
+        scale = scale.min(LOW_SPEC_SCALE);
+    }
+    let size = (Vec2::new(VIEW_WIDTH, VIEW_HEIGHT) * scale).round();
+    let position = ((window_size - size) / 2.0).floor();
+    for (mut camera, mut projection) in &mut camera_query {
+        let viewport = Viewport {
+            physical_position: position.as_uvec2(),
+            physical_size: size.as_uvec2(),
+            ..default()
+        };
+        let unchanged = camera.viewport.as_ref().is_some_and(|current| {
+            current.physical_position == viewport.physical_position
+                && current.physical_size == viewport.physical_size
+        });
+        if !unchanged {
+            camera.viewport = Some(viewport);
+        }
+        if !matches!(projection.scaling_mode, ScalingMode::Fixed { .. }) {
+            projection.scaling_mode = ScalingMode::Fixed {
+                width: VIEW_WIDTH,
+                height: VIEW_HEIGHT,
+            };
+        }
+    }
+}
+
+// system to apply the vsync setting to the window
+fn apply_vsync(settings: Res<Settings>, mut window_query: Query<&mut Window, With<PrimaryWindow>>) {
+    if !settings.is_changed() {
+        return;
+    }
+    let present_mode = if settings.video.low_latency {
+        PresentMode::AutoNoVsync
+    } else {
+        settings.video.vsync.present_mode()
+    };
+    for mut window in &mut window_query {
+        if window.present_mode != present_mode {
+            window.present_mode = present_mode;
+        }
+    }
+}
+
+// system to sleep away the rest of the frame when a frame limit is set, or the game idles
+fn pace_frame(settings: Res<Settings>, idle: Res<Idle>, mut frame_start: Local<Option<Instant>>) {
+    let limit = if idle.0 {
+        Some(IDLE_FRAME_LIMIT)
+    } else {
+        settings.video.frame_limit
+    };
+    if let (Some(limit), Some(start)) = (limit, *frame_start) {
+        let target = Duration::from_secs_f64(1.0 / limit.max(1) as f64);
+        let elapsed = start.elapsed();
+        if elapsed < target {
+            // the page can't be put to sleep, the browser paces its frames itself
+            #[cfg(not(target_arch = "wasm32"))]
+            std::thread::sleep(target - elapsed);
+        }
+    }
+    *frame_start = Some(Instant::now());
+}
+
+pub struct DisplayPlugin;
+
+impl Plugin for DisplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PendingWindowSave>()
+            .add_event::<CycleWindowMode>()
+            .add_systems(Startup, spawn_ui_camera)
+            .add_systems(
+                Update,
+                (
+                    toggle_fullscreen,
+                    apply_vsync,
+                    pixel_perfect_viewport,
+                    track_window,
+                    save_window,
+                )
+                    .chain(),
+            )
+            .add_systems(
+                Last,
+                pace_frame.run_if(|settings: Res<Settings>| !settings.video.low_latency),
+            )
+            .add_systems(
+                First,
+                pace_frame
+                    .before(TimeSystem)
+                    .run_if(|settings: Res<Settings>| settings.video.low_latency),
+            );
+    }
+}
@@ -0,0 +1,173 @@
+use crate::animation::AnimationLibrary;
+use crate::bonus::RunStage;
+use crate::health::Health;
+use crate::input::{read_gamepad, Action, ActionSet, ActionState, ClaimedGamepads};
+use crate::mode::ActiveMode;
+use crate::mutators::RunConfig;
+use crate::settings::Settings;
+use crate::toast::Toast;
+use crate::{player_bundle, GameState, GameplaySet, PlayerOne};
+use bevy::prelude::*;
+use bevy_parallax::ParallaxCameraComponent;
+
+// how far behind the first player the second one joins
+const JOIN_BEHIND: f32 = 80.0;
+// tint telling the second player apart
+const SECOND_TINT: Color = Color::rgb(0.6, 0.8, 1.0);
+// room kept between the second player and the left edge of the view
+const EDGE_GAP: f32 = 24.0;
+
+// A player who dropped in on another's run, driven by the gamepad they joined with.
+// They share the score but have health of their own; the run still ends with the first
+// player's.
+#[derive(Component)]
+pub struct SecondPlayer {
+    gamepad: Gamepad,
+}
+
+// Gamepad the first player used last, whose Start pauses rather than joins
+#[derive(Resource, Default)]
+struct FirstGamepad(Option<Gamepad>);
+
+// system to let a second player join a run by pressing Start on a gamepad the first
+// player isn't using. Runs before the gamepads are read, so the press doesn't pause.
+#[allow(clippy::too_many_arguments)]
+fn join(
+    mut commands: Commands,
+    mode: Res<ActiveMode>,
+    game_state: Res<State<GameState>>,
+    stage: Res<State<RunStage>>,
+    button_input: Res<ButtonInput<GamepadButton>>,
+    mut claimed: ResMut<ClaimedGamepads>,
+    mut first: ResMut<FirstGamepad>,
+    asset_server: Res<AssetServer>,
+    library: Res<AnimationLibrary>,
+    config: Res<RunConfig>,
+    first_query: Query<&Transform, PlayerOne>,
+    second_query: Query<(), With<SecondPlayer>>,
+    mut toasts: EventWriter<Toast>,
+) {
+    let mut open = *game_state.get() == GameState::Playing
+        && *stage.get() == RunStage::Main
+        && mode.drop_in()
+        && second_query.is_empty();
+    for button in button_input.get_just_pressed() {
+        let gamepad = button.gamepad;
+        if claimed.0.contains(&gamepad) {
+            continue;
+        }
+        let joining =
+            open && button.button_type == GamepadButtonType::Start && first.0 != Some(gamepad);
+        let (true, Ok(leader)) = (joining, first_query.get_single()) else {
+            first.0 = Some(gamepad);
+            continue;
+        };
+        let x = leader.translation.x - JOIN_BEHIND;
+        commands
+            .spawn(player_bundle(
+                &asset_server,
+                &library,
+                config.player_health,
+                x,
+            ))
+            .insert((
+                Sprite {
+                    color: SECOND_TINT,
+                    ..default()
+                },
+                SecondPlayer { gamepad },
+                ActionState::default(),
+            ));
+        claimed.0.insert(gamepad);
+        toasts.send(Toast("Player 2 joined".to_string()));
+        open = false;
+    }
+}
+
+// gamepad adapter for the second player, into their own actions. Either player can
+// pause the run they share.
+fn second_player_actions(
+    button_input: Res<ButtonInput<GamepadButton>>,
+    button_axes: Res<Axis<GamepadButton>>,
+    axes: Res<Axis<GamepadAxis>>,
+    settings: Res<Settings>,
+    mut shared: ResMut<ActionState>,
+    mut query: Query<(&SecondPlayer, &mut ActionState)>,
+) {
+    for (second, mut actions) in &mut query {
+        actions.begin();
+        read_gamepad(
+            second.gamepad,
+            &button_input,
+            &button_axes,
+            &axes,
+            settings.gamepad,
+            &mut actions,
+        );
+        // not the press that joined, which is still held on the next frame
+        let start = GamepadButton::new(second.gamepad, GamepadButtonType::Start);
+        if button_input.just_pressed(start) {
+            shared.press(Action::Pause);
+        }
+    }
+}
+
+// system to frame both players with the one camera: it keeps following the first, and
+// the second is pushed along by the left edge of the view rather than left behind
+fn frame_players(
+    camera_query: Query<(&Transform, &OrthographicProjection), With<ParallaxCameraComponent>>,
+    mut second_query: Query<&mut Transform, (With<SecondPlayer>, Without<ParallaxCameraComponent>)>,
+) {
+    let Ok((camera, projection)) = camera_query.get_single() else {
+        return;
+    };
+    let left = camera.translation.x + projection.area.min.x + EDGE_GAP;
+    for mut transform in &mut second_query {
+        transform.translation.x = transform.translation.x.max(left);
+    }
+}
+
+// system to drop the second player once out of health or their gamepad is unplugged,
+// handing the gamepad back so they can join again
+fn drop_out(
+    mut commands: Commands,
+    gamepads: Res<Gamepads>,
+    mut claimed: ResMut<ClaimedGamepads>,
+    second_query: Query<(Entity, &SecondPlayer, &Health)>,
+    mut toasts: EventWriter<Toast>,
+) {
+    for (entity, second, health) in &second_query {
+        let unplugged = !gamepads.contains(second.gamepad);
+        if health.current > 0.0 && !unplugged {
+            continue;
+        }
+        commands.entity(entity).despawn_recursive();
+        claimed.0.remove(&second.gamepad);
+        let message = if unplugged {
+            "Player 2 left"
+        } else {
+            "Player 2 is out, press Start to join again"
+        };
+        toasts.send(Toast(message.to_string()));
+    }
+}
+
+// the second player leaves with the run, their gamepad goes back to driving the menus
+fn release_gamepads(mut claimed: ResMut<ClaimedGamepads>) {
+    claimed.0.clear();
+}
+
+pub struct DropInPlugin;
+
+impl Plugin for DropInPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FirstGamepad>()
+            .add_systems(PreUpdate, join.in_set(ActionSet::Begin))
+            .add_systems(PreUpdate, second_player_actions.in_set(ActionSet::Collect))
+            .add_systems(OnExit(GameState::Playing), release_gamepads)
+            .add_systems(
+                Update,
+                (frame_players.run_if(in_state(RunStage::Main)), drop_out).in_set(GameplaySet),
+            );
+    }
+}
@@ -0,0 +1,83 @@
+use crate::game_time::GameTime;
+use crate::settings::Settings;
+use crate::{GameplaySet, RunEntity};
+use bevy::prelude::*;
+
+// specks in one puff, how far apart they start and how long they stay up
+const SPECKS: u32 = 5;
+const SPECK_SIZE: f32 = 4.0;
+const SPREAD: f32 = 10.0;
+const LIFETIME: f32 = 0.4;
+// how fast specks drift up and out, in pixels per second
+const RISE: f32 = 30.0;
+const DRIFT: f32 = 40.0;
+const DUST_COLOR: Color = Color::rgb(0.8, 0.72, 0.6);
+
+// Event asking for a little cloud of dust kicked up at a point, drifting towards `direction`
+#[derive(Event, Debug, Clone, Copy)]
+pub struct DustPuff {
+    pub position: Vec2,
+    pub direction: f32,
+}
+
+#[derive(Component)]
+struct DustSpeck {
+    velocity: Vec2,
+    timer: Timer,
+}
+
+// no dust in low-spec mode
+fn spawn_puffs(mut commands: Commands, settings: Res<Settings>, mut events: EventReader<DustPuff>) {
+    for puff in events.read() {
+        if settings.video.low_spec {
+            continue;
+        }
+        for index in 0..SPECKS {
+            // spread evenly across the puff, the outer specks drifting further
+            let share = index as f32 / (SPECKS - 1) as f32 - 0.5;
+            let position = puff.position + Vec2::new(share * SPREAD, 0.0);
+            commands.spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: DUST_COLOR,
+                        custom_size: Some(Vec2::splat(SPECK_SIZE)),
+                        ..default()
+                    },
+                    transform: Transform::from_translation(position.extend(1.6)),
+                    ..default()
+                },
+                DustSpeck {
+                    velocity: Vec2::new(puff.direction * DRIFT * (1.0 + share), RISE),
+                    timer: Timer::from_seconds(LIFETIME, TimerMode::Once),
+                },
+                RunEntity,
+            ));
+        }
+    }
+}
+
+// system to drift the specks away while they fade out
+fn drift_specks(
+    mut commands: Commands,
+    time: Res<GameTime>,
+    mut speck_query: Query<(Entity, &mut DustSpeck, &mut Transform, &mut Sprite)>,
+) {
+    for (entity, mut speck, mut transform, mut sprite) in &mut speck_query {
+        speck.timer.tick(time.delta());
+        if speck.timer.finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        transform.translation += (speck.velocity * time.delta_seconds()).extend(0.0);
+        sprite.color.set_a(1.0 - speck.timer.fraction());
+    }
+}
+
+pub struct DustPlugin;
+
+impl Plugin for DustPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<DustPuff>()
+            .add_systems(Update, (spawn_puffs, drift_specks).in_set(GameplaySet));
+    }
+}
@@ -0,0 +1,169 @@
+use crate::animation::AsepriteSheet;
+use crate::balance::Balance;
+use crate::collision::Collider;
+use crate::gate::Gate;
+use crate::health::{Boss, Health};
+use crate::mode::{hazards_enabled, RunRng};
+use crate::mutators::RunConfig;
+use crate::projectile::{ProjectileKind, Shooter};
+use crate::startup::{DeferredLoad, GameplayAssets};
+use crate::timing::timed;
+use crate::{GameState, GameplaySet, RunEntity, GROUND_Y, PLAYER_SCALE};
+use bevy::prelude::*;
+use bevy_parallax::ParallaxCameraComponent;
+use rand::Rng;
+
+// loose spitter frames, packed into an atlas as they load
+const SPITTER_SHEET: &str = "spitter.sheet.ron";
+// how far past the right edge of the view enemies spawn
+const SPAWN_MARGIN: f32 = 64.0;
+const BOSS_SCALE: f32 = 8.0;
+
+#[derive(Component)]
+pub struct Enemy;
+
+// Where the next enemy of the run goes, and how many came before it
+#[derive(Resource, Default)]
+pub struct EnemySpawner {
+    pub next_x: f32,
+    pub spawned: u32,
+}
+
+fn reset_spawner(mut commands: Commands, balance: Res<Balance>) {
+    commands.insert_resource(EnemySpawner {
+        next_x: balance.enemy_spacing.1,
+        spawned: 0,
+    });
+}
+
+// spawn a spitter, or a boss spitter, standing on the ground at `x`
+pub fn spawn_enemy(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    balance: &Balance,
+    x: f32,
+    boss: bool,
+) -> Entity {
+    let scale = if boss { BOSS_SCALE } else { PLAYER_SCALE };
+    let mut enemy = commands.spawn((
+        SpriteSheetBundle {
+            texture: asset_server.load(format!("{}#image", SPITTER_SHEET)),
+            atlas: TextureAtlas {
+                layout: asset_server.load(format!("{}#layout", SPITTER_SHEET)),
+                index: 0,
+            },
+            sprite: Sprite {
+                // face the player coming from the left
+                flip_x: true,
+                ..default()
+            },
+            transform: Transform {
+                translation: Vec3::new(x, GROUND_Y + (scale - PLAYER_SCALE) * 8.0, 1.5),
+                scale: Vec3::splat(scale),
+                ..default()
+            },
+            ..default()
+        },
+        Enemy,
+        Collider(Rect::new(-5.0, -8.0, 5.0, 6.0)),
+        RunEntity,
+    ));
+    if boss {
+        enemy.insert((
+            Health::new(balance.boss_health),
+            Boss {
+                name: "Grand Spitter".to_string(),
+                segments: 10,
+            },
+            Shooter {
+                kind: ProjectileKind::Rock,
+                timer: Timer::from_seconds(balance.boss_shot_interval, TimerMode::Repeating),
+            },
+        ));
+    } else {
+        enemy.insert((
+            Health::new(balance.spitter_health),
+            Shooter {
+                kind: ProjectileKind::Glob,
+                timer: Timer::from_seconds(balance.spitter_shot_interval, TimerMode::Repeating),
+            },
+        ));
+    }
+    enemy.id()
+}
+
+// system to place enemies just out of view ahead of the camera
+#[allow(clippy::too_many_arguments)]
+fn spawn_enemies(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut spawner: ResMut<EnemySpawner>,
+    mut rng: ResMut<RunRng>,
+    balance: Res<Balance>,
+    config: Res<RunConfig>,
+    gate: Res<Gate>,
+    camera_query: Query<(&Transform, &OrthographicProjection), With<ParallaxCameraComponent>>,
+) {
+    let Ok((camera, projection)) = camera_query.get_single() else {
+        return;
+    };
+    let view_right = camera.translation.x + projection.area.max.x;
+    if view_right + SPAWN_MARGIN < spawner.next_x {
+        return;
+    }
+    // the gate closing the biome brings its own
+    if gate.covers(spawner.next_x) {
+        spawner.next_x = gate.end;
+        return;
+    }
+    spawner.spawned += 1;
+    let boss = spawner
+        .spawned
+        .is_multiple_of(config.difficulty.boss_every(&balance));
+    spawn_enemy(&mut commands, &asset_server, &balance, spawner.next_x, boss);
+    let (min, max) = balance.enemy_spacing;
+    spawner.next_x += rng.0.gen_range(min..=max)
+        * balance.spacing_at(spawner.next_x)
+        * config.difficulty.spacing();
+}
+
+// system to remove enemies once they're well behind the camera
+fn despawn_passed_enemies(
+    mut commands: Commands,
+    camera_query: Query<(&Transform, &OrthographicProjection), With<ParallaxCameraComponent>>,
+    enemy_query: Query<(Entity, &Transform), With<Enemy>>,
+) {
+    let Ok((camera, projection)) = camera_query.get_single() else {
+        return;
+    };
+    let view_left = camera.translation.x + projection.area.min.x;
+    for (entity, transform) in &enemy_query {
+        if transform.translation.x < view_left - SPAWN_MARGIN * BOSS_SCALE {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+// system to start loading the spitters' sheet before the first one is met
+fn preload_sheet(asset_server: Res<AssetServer>, mut assets: ResMut<GameplayAssets>) {
+    let sheet: Handle<AsepriteSheet> = asset_server.load(SPITTER_SHEET);
+    assets.0.push(sheet.untyped());
+}
+
+pub struct EnemyPlugin;
+
+impl Plugin for EnemyPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EnemySpawner>()
+            .add_systems(OnEnter(GameState::Playing), reset_spawner)
+            .add_systems(Update, preload_sheet.in_set(DeferredLoad))
+            .add_systems(
+                Update,
+                (
+                    timed(spawn_enemies).run_if(hazards_enabled),
+                    timed(despawn_passed_enemies),
+                )
+                    .in_set(GameplaySet),
+            );
+    }
+}
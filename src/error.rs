@@ -0,0 +1,151 @@
+use crate::asset_pack::LoadedPack;
+use crate::balance::Balance;
+use crate::game_over::RunHistory;
+use crate::menu::MenuState;
+use crate::save::{SaveData, SaveError};
+use crate::settings::Settings;
+use bevy::ecs::system::SystemId;
+use bevy::prelude::*;
+use std::fmt;
+use std::path::PathBuf;
+
+// Something the game can't start without going wrong
+#[derive(Debug)]
+pub enum GameError {
+    // the asset folder isn't where bevy looks for it
+    MissingAssets(PathBuf),
+    Balance(ron::error::SpannedError),
+    // the balance parses but can't be played with
+    InvalidBalance(String),
+    Save(SaveError),
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GameError::MissingAssets(path) => {
+                write!(f, "the game files are missing from {}", path.display())
+            }
+            GameError::Balance(error) => write!(f, "invalid balance: {}", error),
+            GameError::InvalidBalance(error) => write!(f, "invalid balance: {}", error),
+            GameError::Save(error) => write!(f, "could not load the save: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for GameError {}
+
+impl From<SaveError> for GameError {
+    fn from(error: SaveError) -> Self {
+        GameError::Save(error)
+    }
+}
+
+// The error shown on the error screen
+#[derive(Resource, Debug)]
+pub struct LastError(pub GameError);
+
+// Event to try loading the game's data again after it failed
+#[derive(Event, Debug, Clone, Copy)]
+pub struct RetryLoad;
+
+// The one-shot system loading the game's data
+#[derive(Resource)]
+struct LoadSystem(SystemId);
+
+// folder bevy loads assets from, found the same way bevy's file asset reader does
+#[cfg(not(target_arch = "wasm32"))]
+fn asset_root() -> PathBuf {
+    let base = std::env::var_os("BEVY_ASSET_ROOT")
+        .or_else(|| std::env::var_os("CARGO_MANIFEST_DIR"))
+        .map(PathBuf::from)
+        .or_else(|| {
+            std::env::current_exe()
+                .ok()?
+                .parent()
+                .map(|dir| dir.to_path_buf())
+        })
+        .unwrap_or_default();
+    base.join("assets")
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn check_assets(pack: Option<&LoadedPack>) -> Result<(), GameError> {
+    if crate::asset_pack::is_bundled(pack, std::path::Path::new(crate::PLAYER_SHEET)) {
+        return Ok(());
+    }
+    let root = asset_root();
+    if root.join(crate::PLAYER_SHEET).is_file() {
+        Ok(())
+    } else {
+        Err(GameError::MissingAssets(root))
+    }
+}
+
+// on the web the assets are fetched over http, and a missing one only shows up once loaded
+#[cfg(target_arch = "wasm32")]
+fn check_assets(_pack: Option<&LoadedPack>) -> Result<(), GameError> {
+    Ok(())
+}
+
+// system to load everything the game needs before it can be played
+fn load_game_data(
+    mut commands: Commands,
+    pack: Option<Res<LoadedPack>>,
+    settings: Res<Settings>,
+) -> Result<(), GameError> {
+    // the spawners and previews read the balance on the error screen too, so it goes in
+    // before anything else can fail. The cache only stands in for a remote balance that's
+    // still configured.
+    commands.insert_resource(Balance::load(settings.balance_url.is_some())?);
+    check_assets(pack.as_deref())?;
+    let save = SaveData::load()?;
+    commands.insert_resource(RunHistory(save.runs));
+    commands.insert_resource(save.profiles.validated());
+    Ok(())
+}
+
+// system to show the error screen when loading failed, or leave it once a retry worked
+fn report_error(
+    In(result): In<Result<(), GameError>>,
+    mut commands: Commands,
+    menu_state: Res<State<MenuState>>,
+    mut next_menu_state: ResMut<NextState<MenuState>>,
+) {
+    match result {
+        Ok(()) => {
+            commands.remove_resource::<LastError>();
+            if *menu_state.get() == MenuState::Error {
+                next_menu_state.set(MenuState::Main);
+            }
+        }
+        Err(err) => {
+            error!("{}", err);
+            commands.insert_resource(LastError(err));
+            next_menu_state.set(MenuState::Error);
+        }
+    }
+}
+
+fn start_load(mut commands: Commands, load: Res<LoadSystem>) {
+    commands.run_system(load.0);
+}
+
+// system to load again when the player asks for a retry
+fn retry_load(mut commands: Commands, mut events: EventReader<RetryLoad>, load: Res<LoadSystem>) {
+    if events.read().count() > 0 {
+        commands.run_system(load.0);
+    }
+}
+
+pub struct ErrorPlugin;
+
+impl Plugin for ErrorPlugin {
+    fn build(&self, app: &mut App) {
+        let load = app.world.register_system(load_game_data.pipe(report_error));
+        app.insert_resource(LoadSystem(load))
+            .add_event::<RetryLoad>()
+            .add_systems(Startup, start_load)
+            .add_systems(Update, retry_load);
+    }
+}
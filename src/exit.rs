@@ -0,0 +1,115 @@
+use crate::GameState;
+use bevy::app::AppExit;
+use bevy::prelude::*;
+use bevy::window::WindowCloseRequested;
+
+// real seconds the screen takes to fade out on the way out
+const EXIT_FADE: f32 = 0.4;
+// frames the systems writing saves get after being asked to flush, before quitting
+const FLUSH_FRAMES: u32 = 2;
+
+// Event asking the game to quit: the run under way is left, the saves are written and
+// the screen fades out first
+#[derive(Event)]
+pub struct RequestExit;
+
+// Event sent right before quitting, for anything holding a save back to write it now
+#[derive(Event)]
+pub struct FlushSaves;
+
+// Quit under way
+#[derive(Resource)]
+struct Exiting {
+    fade: Timer,
+    // frames since the saves were asked to flush
+    flushed: Option<u32>,
+}
+
+#[derive(Component)]
+struct ExitFade;
+
+// system to turn closing the window into a request to quit, so the saves get written
+// before it goes. Only reached when WindowPlugin leaves closing to the game.
+fn request_on_close(
+    mut close_events: EventReader<WindowCloseRequested>,
+    mut requests: EventWriter<RequestExit>,
+) {
+    if close_events.read().count() > 0 {
+        requests.send(RequestExit);
+    }
+}
+
+// system to start quitting: leaving the run banks its coins, and the screen starts fading
+fn begin_exit(
+    mut commands: Commands,
+    exiting: Option<Res<Exiting>>,
+    mut requests: EventReader<RequestExit>,
+    game_state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if requests.read().count() == 0 || exiting.is_some() {
+        return;
+    }
+    info!("Exiting");
+    if *game_state.get() == GameState::Playing {
+        next_state.set(GameState::Menu);
+    }
+    commands.insert_resource(Exiting {
+        fade: Timer::from_seconds(EXIT_FADE, TimerMode::Once),
+        flushed: None,
+    });
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                ..default()
+            },
+            background_color: Color::NONE.into(),
+            z_index: ZIndex::Global(i32::MAX),
+            ..default()
+        },
+        ExitFade,
+    ));
+}
+
+// system to fade the screen out, then flush the saves and quit a couple of frames later
+fn finish_exit(
+    time: Res<Time<Real>>,
+    exiting: Option<ResMut<Exiting>>,
+    mut fade_query: Query<&mut BackgroundColor, With<ExitFade>>,
+    mut flush: EventWriter<FlushSaves>,
+    mut exit: EventWriter<AppExit>,
+) {
+    let Some(mut exiting) = exiting else {
+        return;
+    };
+    let alpha = exiting.fade.tick(time.delta()).fraction();
+    for mut color in &mut fade_query {
+        color.0 = Color::rgba(0.0, 0.0, 0.0, alpha);
+    }
+    if !exiting.fade.finished() {
+        return;
+    }
+    match exiting.flushed {
+        None => {
+            flush.send(FlushSaves);
+            exiting.flushed = Some(0);
+        }
+        Some(frames) if frames < FLUSH_FRAMES => exiting.flushed = Some(frames + 1),
+        Some(_) => {
+            exit.send(AppExit);
+        }
+    }
+}
+
+pub struct ExitPlugin;
+
+impl Plugin for ExitPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<RequestExit>()
+            .add_event::<FlushSaves>()
+            .add_systems(Update, (request_on_close, begin_exit, finish_exit).chain());
+    }
+}
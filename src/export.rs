@@ -0,0 +1,226 @@
+use crate::game_over::{RunHistory, RunRecord};
+use crate::grade::Grade;
+use crate::history::date;
+use crate::mode::RunOutcome;
+use crate::profile::Profiles;
+use crate::save::SaveData;
+use crate::storage;
+use crate::toast::Toast;
+use bevy::prelude::*;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+// files the runs and lifetime stats are exported to, next to the save
+const RUNS_CSV: &str = "runs.csv";
+const LIFETIME_CSV: &str = "lifetime.csv";
+const STATS_JSON: &str = "stats.json";
+
+// What run data is exported as, for spreadsheets or scripts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    // runs.csv with a row per run, lifetime.csv with a row per stat
+    Csv,
+    // stats.json with both
+    Json,
+}
+
+impl ExportFormat {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "CSV",
+            ExportFormat::Json => "JSON",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        [ExportFormat::Csv, ExportFormat::Json]
+            .into_iter()
+            .find(|format| format.name().eq_ignore_ascii_case(name))
+    }
+}
+
+// Event asking for the run history and lifetime stats to be exported
+#[derive(Event, Debug, Clone, Copy)]
+pub struct ExportStats(pub ExportFormat);
+
+// What a profile has built up over every run
+#[derive(Serialize)]
+struct ProfileStats<'a> {
+    name: &'a str,
+    coins: u32,
+    best_grades: &'a BTreeMap<String, Grade>,
+}
+
+// Totals over every run kept
+#[derive(Serialize)]
+struct Lifetime<'a> {
+    runs: usize,
+    won: usize,
+    lost: usize,
+    time_up: usize,
+    total_score: u64,
+    best_score: u32,
+    total_distance: f32,
+    best_distance: f32,
+    profiles: Vec<ProfileStats<'a>>,
+}
+
+impl<'a> Lifetime<'a> {
+    fn new(runs: &[RunRecord], profiles: &'a Profiles) -> Self {
+        let count = |outcome: RunOutcome| runs.iter().filter(|run| run.outcome == outcome).count();
+        let distances = runs.iter().filter_map(|run| run.distance);
+        Lifetime {
+            runs: runs.len(),
+            won: count(RunOutcome::Won),
+            lost: count(RunOutcome::Lost),
+            time_up: count(RunOutcome::TimeUp),
+            total_score: runs.iter().map(|run| u64::from(run.score)).sum(),
+            best_score: runs.iter().map(|run| run.score).max().unwrap_or(0),
+            total_distance: distances
+                .clone()
+                .fold(0.0, |total, distance| total + distance),
+            best_distance: distances.fold(0.0, f32::max),
+            profiles: profiles
+                .list
+                .iter()
+                .map(|profile| ProfileStats {
+                    name: &profile.name,
+                    coins: profile.coins,
+                    best_grades: &profile.best_grades,
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct StatsFile<'a> {
+    lifetime: Lifetime<'a>,
+    runs: &'a [RunRecord],
+}
+
+// a field quoted if it has to be, doubling its quotes
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn csv_row(fields: &[String]) -> String {
+    let fields: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+    format!("{}\n", fields.join(","))
+}
+
+fn runs_csv(runs: &[RunRecord]) -> String {
+    let header = [
+        "date", "mode", "outcome", "score", "distance", "grade", "seed", "mutators",
+    ];
+    let mut csv = csv_row(&header.map(str::to_string));
+    for run in runs {
+        let mutators: Vec<String> = run.mutators.iter().map(|m| format!("{:?}", m)).collect();
+        csv.push_str(&csv_row(&[
+            run.date.map(date).unwrap_or_default(),
+            run.mode.clone(),
+            format!("{:?}", run.outcome),
+            run.score.to_string(),
+            run.distance.map_or(String::new(), |d| format!("{:.0}", d)),
+            run.grade.map_or("", |grade| grade.letter()).to_string(),
+            run.seed.map_or(String::new(), |seed| seed.to_string()),
+            mutators.join(";"),
+        ]));
+    }
+    csv
+}
+
+fn lifetime_csv(lifetime: &Lifetime) -> String {
+    let mut rows = vec![
+        ("runs".to_string(), lifetime.runs.to_string()),
+        ("won".to_string(), lifetime.won.to_string()),
+        ("lost".to_string(), lifetime.lost.to_string()),
+        ("time_up".to_string(), lifetime.time_up.to_string()),
+        ("total_score".to_string(), lifetime.total_score.to_string()),
+        ("best_score".to_string(), lifetime.best_score.to_string()),
+        (
+            "total_distance".to_string(),
+            format!("{:.0}", lifetime.total_distance),
+        ),
+        (
+            "best_distance".to_string(),
+            format!("{:.0}", lifetime.best_distance),
+        ),
+    ];
+    for profile in &lifetime.profiles {
+        rows.push((format!("{} coins", profile.name), profile.coins.to_string()));
+        for (mode, grade) in profile.best_grades {
+            rows.push((
+                format!("{} best grade in {}", profile.name, mode),
+                grade.letter().to_string(),
+            ));
+        }
+    }
+    let mut csv = csv_row(&["stat".to_string(), "value".to_string()]);
+    for (stat, value) in rows {
+        csv.push_str(&csv_row(&[stat, value]));
+    }
+    csv
+}
+
+// writes the runs and lifetime stats in `format`, returning the files written
+fn write_stats(
+    runs: &[RunRecord],
+    profiles: &Profiles,
+    format: ExportFormat,
+) -> Result<Vec<&'static str>, String> {
+    let lifetime = Lifetime::new(runs, profiles);
+    let files = match format {
+        ExportFormat::Csv => vec![
+            (RUNS_CSV, runs_csv(runs)),
+            (LIFETIME_CSV, lifetime_csv(&lifetime)),
+        ],
+        ExportFormat::Json => {
+            let file = StatsFile { lifetime, runs };
+            let json = serde_json::to_string_pretty(&file).map_err(|err| err.to_string())?;
+            vec![(STATS_JSON, json)]
+        }
+    };
+    for (path, contents) in &files {
+        storage::write(path, contents).map_err(|err| format!("{}: {}", path, err))?;
+    }
+    Ok(files.into_iter().map(|(path, _)| path).collect())
+}
+
+// exports what's in the save without starting the game: `cargo run -- export [csv|json]`
+pub fn export_saved(format: ExportFormat) -> Result<Vec<&'static str>, String> {
+    let save = SaveData::load().map_err(|err| err.to_string())?;
+    write_stats(&save.runs, &save.profiles, format)
+}
+
+// system to export the runs kept when asked from the stats screen
+fn handle_exports(
+    mut events: EventReader<ExportStats>,
+    history: Res<RunHistory>,
+    profiles: Res<Profiles>,
+    mut toasts: EventWriter<Toast>,
+) {
+    for ExportStats(format) in events.read() {
+        let message = match write_stats(&history.0, &profiles, *format) {
+            Ok(paths) => format!("Stats saved to {}", paths.join(" and ")),
+            Err(err) => {
+                error!("Could not export stats: {}", err);
+                "Could not export stats".to_string()
+            }
+        };
+        toasts.send(Toast(message));
+    }
+}
+
+pub struct ExportPlugin;
+
+impl Plugin for ExportPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ExportStats>()
+            .add_systems(Update, handle_exports);
+    }
+}
@@ -0,0 +1,227 @@
+use crate::settings::Settings;
+use bevy::prelude::*;
+use bevy::transform::TransformSystem;
+use bevy_parallax::ParallaxCameraComponent;
+
+const FLASH_TIME: f32 = 0.25;
+// photosensitive replacement for flashes: a faint glow that fades in and out slowly
+const GLOW_TIME: f32 = 1.0;
+const GLOW_ALPHA: f32 = 0.15;
+const WARNING_TIME: f32 = 1.5;
+const WARNING_BLINK_TIME: f32 = 0.1;
+// how fast a camera shake dies down, in pixels per second
+const SHAKE_DECAY: f32 = 30.0;
+// photosensitive replacement for a shake: a fraction of the jolt, swaying slowly
+const SWAY_SHARE: f32 = 0.3;
+const SWAY_SPEED: f32 = 0.1;
+
+// Visual feedback requested by gameplay and telegraph systems. The photosensitivity
+// setting decides how each one is actually shown.
+#[derive(Event, Debug, Clone)]
+pub enum Feedback {
+    // full screen flash in the given color
+    Flash(Color),
+    // blinking banner warning about an incoming hazard
+    Warning(String),
+    // jolt of the world camera, throwing the view up to this many pixels
+    Shake(f32),
+}
+
+// Full screen overlay used by flashes and glows
+#[derive(Component)]
+struct Overlay;
+
+#[derive(Component)]
+struct OverlayEffect {
+    kind: OverlayKind,
+    timer: Timer,
+}
+
+enum OverlayKind {
+    Flash(Color),
+    Glow(Color),
+}
+
+// Camera shake playing out, and the offset it currently adds to the world camera
+#[derive(Resource, Default)]
+struct CameraShake {
+    strength: f32,
+    offset: Vec2,
+    // a slow sway rather than a jolt, for photosensitive players
+    gentle: bool,
+}
+
+#[derive(Component)]
+struct WarningBanner {
+    timer: Timer,
+    blink: Option<Timer>,
+}
+
+fn setup_feedback(mut commands: Commands) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                ..default()
+            },
+            background_color: Color::NONE.into(),
+            z_index: ZIndex::Global(5),
+            ..default()
+        },
+        Overlay,
+    ));
+}
+
+// system to turn feedback requests into effects, filtered through the photosensitivity setting
+fn apply_feedback(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    mut shake: ResMut<CameraShake>,
+    mut events: EventReader<Feedback>,
+    overlay_query: Query<Entity, With<Overlay>>,
+    banner_query: Query<Entity, With<WarningBanner>>,
+) {
+    let photosensitive = settings.accessibility.photosensitivity;
+    let Ok(overlay) = overlay_query.get_single() else {
+        return;
+    };
+    for event in events.read() {
+        match event {
+            Feedback::Flash(color) => {
+                let (kind, time) = if photosensitive {
+                    (OverlayKind::Glow(*color), GLOW_TIME)
+                } else {
+                    (OverlayKind::Flash(*color), FLASH_TIME)
+                };
+                commands.entity(overlay).insert(OverlayEffect {
+                    kind,
+                    timer: Timer::from_seconds(time, TimerMode::Once),
+                });
+            }
+            Feedback::Warning(text) => {
+                for banner in &banner_query {
+                    commands.entity(banner).despawn_recursive();
+                }
+                // photosensitive players get a steady banner instead of a strobing one
+                let blink = (!photosensitive)
+                    .then(|| Timer::from_seconds(WARNING_BLINK_TIME, TimerMode::Repeating));
+                commands.spawn((
+                    TextBundle::from_section(
+                        format!("! {} !", text),
+                        TextStyle {
+                            font_size: 20.0,
+                            color: Color::rgb(1.0, 0.85, 0.3),
+                            ..default()
+                        },
+                    )
+                    .with_style(Style {
+                        position_type: PositionType::Absolute,
+                        top: Val::Px(40.0),
+                        align_self: AlignSelf::Center,
+                        justify_self: JustifySelf::Center,
+                        ..default()
+                    }),
+                    WarningBanner {
+                        timer: Timer::from_seconds(WARNING_TIME, TimerMode::Once),
+                        blink,
+                    },
+                ));
+            }
+            Feedback::Shake(strength) => {
+                let strength = if photosensitive {
+                    strength * SWAY_SHARE
+                } else {
+                    *strength
+                };
+                shake.strength = shake.strength.max(strength);
+                shake.gentle = photosensitive;
+            }
+        }
+    }
+}
+
+// system to animate the overlay for the active effect
+fn update_overlay(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut OverlayEffect, &mut BackgroundColor), With<Overlay>>,
+) {
+    for (entity, mut effect, mut background) in &mut query {
+        effect.timer.tick(time.delta());
+        let t = effect.timer.fraction();
+        background.0 = match effect.kind {
+            OverlayKind::Flash(color) => color.with_a(color.a() * (1.0 - t)),
+            OverlayKind::Glow(color) => color.with_a(GLOW_ALPHA * (std::f32::consts::PI * t).sin()),
+        };
+        if effect.timer.finished() {
+            background.0 = Color::NONE;
+            commands.entity(entity).remove::<OverlayEffect>();
+        }
+    }
+}
+
+// system to blink and expire the warning banner
+fn update_warning_banner(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut WarningBanner, &mut Visibility)>,
+) {
+    for (entity, mut banner, mut visibility) in &mut query {
+        banner.timer.tick(time.delta());
+        if banner.timer.finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        if let Some(blink) = banner.blink.as_mut() {
+            if blink.tick(time.delta()).just_finished() {
+                *visibility = match *visibility {
+                    Visibility::Hidden => Visibility::Inherited,
+                    _ => Visibility::Hidden,
+                };
+            }
+        }
+    }
+}
+
+// system to jolt the world camera around while a shake lasts. The offset of the last
+// frame is taken back out first so the camera keeps following the run.
+fn shake_camera(
+    time: Res<Time>,
+    mut shake: ResMut<CameraShake>,
+    mut camera_query: Query<&mut Transform, With<ParallaxCameraComponent>>,
+) {
+    let Ok(mut transform) = camera_query.get_single_mut() else {
+        return;
+    };
+    if shake.strength <= 0.0 && shake.offset == Vec2::ZERO {
+        return;
+    }
+    transform.translation -= shake.offset.extend(0.0);
+    shake.strength = (shake.strength - SHAKE_DECAY * time.delta_seconds()).max(0.0);
+    let mut t = time.elapsed_seconds();
+    if shake.gentle {
+        t *= SWAY_SPEED;
+    }
+    shake.offset = Vec2::new((t * 71.0).sin(), (t * 53.0).cos()) * shake.strength;
+    transform.translation += shake.offset.extend(0.0);
+}
+
+pub struct FeedbackPlugin;
+
+impl Plugin for FeedbackPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Feedback>()
+            .init_resource::<CameraShake>()
+            .add_systems(Startup, setup_feedback)
+            .add_systems(
+                Update,
+                (apply_feedback, update_overlay, update_warning_banner).chain(),
+            )
+            .add_systems(
+                PostUpdate,
+                shake_camera.before(TransformSystem::TransformPropagate),
+            );
+    }
+}
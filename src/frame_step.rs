@@ -0,0 +1,77 @@
+#[cfg(feature = "dev_tools")]
+use crate::toast::Toast;
+use bevy::prelude::*;
+
+// speeds the game clock can be set to, as a fraction of real time
+#[cfg(feature = "dev_tools")]
+const TIME_SCALES: [f32; 6] = [0.1, 0.25, 0.5, 1.0, 1.5, 2.0];
+
+// Debug controls for looking at the simulation one tick at a time: F9 freezes and
+// unfreezes it, F10 runs a single frame of gameplay while frozen, which is the game's
+// tick as its physics move by the frame, with the clock moved on by one fixed timestep.
+// Only dev builds read the keys, release builds never freeze.
+#[derive(Resource, Default)]
+pub struct FrameStep {
+    frozen: bool,
+    // run gameplay for this frame only
+    step: bool,
+}
+
+impl FrameStep {
+    // whether this frame is a single step taken while frozen
+    pub fn stepping(&self) -> bool {
+        self.frozen && self.step
+    }
+}
+
+// run condition for the gameplay systems, false while frozen between steps
+pub fn simulation_running(step: Res<FrameStep>) -> bool {
+    !step.frozen || step.step
+}
+
+// system to read the debug keys, before anything that depends on them runs
+#[cfg(feature = "dev_tools")]
+fn frame_step_controls(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut step: ResMut<FrameStep>,
+    mut time: ResMut<Time<Virtual>>,
+    mut toasts: EventWriter<Toast>,
+) {
+    step.step = false;
+    if keyboard_input.just_pressed(KeyCode::F9) {
+        step.frozen = !step.frozen;
+        let state = if step.frozen {
+            "frozen, F10 steps one tick"
+        } else {
+            "running"
+        };
+        toasts.send(Toast(format!("Simulation {}", state)));
+    }
+    if step.frozen && keyboard_input.just_pressed(KeyCode::F10) {
+        step.step = true;
+    }
+    // minus and equals slow the clock down and speed it up
+    let current = TIME_SCALES
+        .iter()
+        .position(|scale| *scale >= time.relative_speed())
+        .unwrap_or(TIME_SCALES.len() - 1);
+    let wanted = if keyboard_input.just_pressed(KeyCode::Minus) {
+        current.saturating_sub(1)
+    } else if keyboard_input.just_pressed(KeyCode::Equal) {
+        (current + 1).min(TIME_SCALES.len() - 1)
+    } else {
+        return;
+    };
+    time.set_relative_speed(TIME_SCALES[wanted]);
+    toasts.send(Toast(format!("Time scale x{}", TIME_SCALES[wanted])));
+}
+
+pub struct FrameStepPlugin;
+
+impl Plugin for FrameStepPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FrameStep>();
+        #[cfg(feature = "dev_tools")]
+        app.add_systems(PreUpdate, frame_step_controls);
+    }
+}
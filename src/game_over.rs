@@ -0,0 +1,121 @@
+use crate::challenge::{ActiveChallenge, InputHash};
+use crate::grade::{Grade, RunGraded};
+use crate::menu::MenuState;
+use crate::mode::{track_progress, ActiveMode, RunOutcome, RunProgress, RunSeed};
+use crate::mutators::{Mutator, RunConfig};
+use crate::score::{Combo, Score};
+use crate::toast::Toast;
+use crate::web::unix_time;
+use crate::{GameState, GameplaySet};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+// finished runs kept, the oldest dropped past it
+const MAX_HISTORY: usize = 100;
+
+// One finished run: how it ended, its score and the mode and mutators it was played with
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RunRecord {
+    pub mode: String,
+    pub outcome: RunOutcome,
+    pub score: u32,
+    pub mutators: Vec<Mutator>,
+    // seed of the run's course, missing from runs saved before it was kept
+    #[serde(default)]
+    pub seed: Option<u64>,
+    // missing from runs saved before runs were graded
+    #[serde(default)]
+    pub grade: Option<Grade>,
+    // what a challenge made from the run needs, missing from runs saved before challenges
+    #[serde(default)]
+    pub distance: Option<f32>,
+    #[serde(default)]
+    pub input_hash: Option<u64>,
+    // when it was played, in seconds since the epoch, missing from runs saved before then
+    #[serde(default)]
+    pub date: Option<u64>,
+}
+
+impl RunRecord {
+    fn summary(&self) -> String {
+        let mut summary = format!(
+            "{} Score {} in {}",
+            self.outcome.name(),
+            self.score,
+            self.mode
+        );
+        if let Some(grade) = self.grade {
+            summary = format!("{}, grade {}", summary, grade.letter());
+        }
+        if self.mutators.is_empty() {
+            return summary;
+        }
+        let mutators: Vec<&str> = self.mutators.iter().map(Mutator::name).collect();
+        format!("{} ({})", summary, mutators.join(", "))
+    }
+}
+
+// The last MAX_HISTORY runs finished, oldest first
+#[derive(Resource, Default)]
+pub struct RunHistory(pub Vec<RunRecord>);
+
+// system to end the run once the mode's rules say it's over
+#[allow(clippy::too_many_arguments)]
+fn check_game_over(
+    mode: Res<ActiveMode>,
+    progress: Res<RunProgress>,
+    score: Res<Score>,
+    combo: Res<Combo>,
+    config: Res<RunConfig>,
+    seed: Res<RunSeed>,
+    input_hash: Res<InputHash>,
+    challenge: Res<ActiveChallenge>,
+    mut history: ResMut<RunHistory>,
+    mut game_state: ResMut<NextState<GameState>>,
+    mut menu_state: ResMut<NextState<MenuState>>,
+    mut toasts: EventWriter<Toast>,
+    mut graded: EventWriter<RunGraded>,
+) {
+    let Some(outcome) = mode.outcome(&progress) else {
+        return;
+    };
+    let grade = Grade::rate(progress.distance, score.0, combo.best_multiplier());
+    let record = RunRecord {
+        mode: mode.name().to_string(),
+        outcome,
+        score: score.0,
+        mutators: config.mutators.clone(),
+        seed: Some(seed.0),
+        grade: Some(grade),
+        distance: Some(progress.distance),
+        input_hash: Some(input_hash.0),
+        date: Some(unix_time().as_secs()),
+    };
+    graded.send(RunGraded {
+        mode: record.mode.clone(),
+        grade,
+    });
+    info!("{}", record.summary());
+    toasts.send(Toast(record.summary()));
+    history.0.push(record);
+    let excess = history.0.len().saturating_sub(MAX_HISTORY);
+    history.0.drain(..excess);
+    game_state.set(GameState::Menu);
+    // a challenge ends on the comparison with the run it was set by
+    if challenge.0.is_some() {
+        menu_state.set(MenuState::Duel);
+    } else {
+        menu_state.set(MenuState::Main);
+    }
+}
+
+pub struct GameOverPlugin;
+
+impl Plugin for GameOverPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RunHistory>().add_systems(
+            Update,
+            check_game_over.after(track_progress).in_set(GameplaySet),
+        );
+    }
+}
@@ -0,0 +1,72 @@
+use crate::frame_step::FrameStep;
+use crate::GameState;
+use bevy::prelude::*;
+use std::time::Duration;
+
+// The clock of the run: it only moves on frames the gameplay systems run, so it stands
+// still while paused or frozen by the frame step controls, and it follows the time scale
+// set with those controls. Everything that happens in a run (animations, spawners,
+// shooting, the combo) times itself with this rather than Time, which keeps going for
+// menus, toasts and other things shown around the run.
+#[derive(Resource, Default)]
+pub struct GameTime {
+    delta: Duration,
+    elapsed: Duration,
+}
+
+impl GameTime {
+    pub fn delta(&self) -> Duration {
+        self.delta
+    }
+
+    pub fn delta_seconds(&self) -> f32 {
+        self.delta.as_secs_f32()
+    }
+
+    // time played in the current run
+    pub fn elapsed_seconds(&self) -> f32 {
+        self.elapsed.as_secs_f32()
+    }
+}
+
+// Runs right before GameplaySet and under the same conditions, see DinorunGamePlugin
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GameClockSet;
+
+// system to stop the clock, until it's advanced on a frame gameplay runs on
+fn stop_game_time(mut game_time: ResMut<GameTime>) {
+    game_time.delta = Duration::ZERO;
+}
+
+// system to move the clock on by the frame's time, or by one tick of the fixed timestep
+// on a frame stepped through while frozen, so each step moves timers and animations
+// along by the same amount however long it took to press F10
+fn advance_game_time(
+    time: Res<Time<Virtual>>,
+    fixed: Res<Time<Fixed>>,
+    step: Res<FrameStep>,
+    mut game_time: ResMut<GameTime>,
+) {
+    let delta = if step.stepping() {
+        fixed.timestep().mul_f64(time.relative_speed_f64())
+    } else {
+        time.delta()
+    };
+    game_time.delta = delta;
+    game_time.elapsed += delta;
+}
+
+fn reset_game_time(mut game_time: ResMut<GameTime>) {
+    *game_time = GameTime::default();
+}
+
+pub struct GameTimePlugin;
+
+impl Plugin for GameTimePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameTime>()
+            .add_systems(OnEnter(GameState::Playing), reset_game_time)
+            .add_systems(First, stop_game_time)
+            .add_systems(Update, advance_game_time.in_set(GameClockSet));
+    }
+}
@@ -0,0 +1,156 @@
+use crate::balance::Balance;
+use crate::barrel::spawn_barrel;
+use crate::bonus::RunStage;
+use crate::coin::coin_bundle;
+use crate::enemy::spawn_enemy;
+use crate::feedback::Feedback;
+use crate::mode::ActiveMode;
+use crate::mutators::RunConfig;
+use crate::{GameplaySet, GROUND_Y};
+use bevy::prelude::*;
+use bevy_parallax::ParallaxCameraComponent;
+use serde::Deserialize;
+
+// gauntlets shipped with the game
+const GATES: &str = include_str!("../assets/gates.ron");
+// how far past the right edge of the view a gate is laid out
+const SPAWN_MARGIN: f32 = 128.0;
+// the reward row floats this high over the ground, its coins this far apart
+const REWARD_HEIGHT: f32 = 40.0;
+const REWARD_GAP: f32 = 24.0;
+
+// Something standing in a gate, this far from its start
+#[derive(Deserialize, Debug, Clone, Copy)]
+pub enum GateObstacle {
+    Spitter(f32),
+    Barrel(f32),
+}
+
+// A gauntlet as described in assets/gates.ron
+#[derive(Deserialize, Debug, Clone)]
+pub struct GateTemplate {
+    pub name: String,
+    // length of course it takes up, reward included
+    pub length: f32,
+    pub obstacles: Vec<GateObstacle>,
+    // coins waiting at the end
+    pub reward: u32,
+}
+
+#[derive(Resource, Debug, Clone)]
+pub struct GateTemplates(pub Vec<GateTemplate>);
+
+impl Default for GateTemplates {
+    fn default() -> Self {
+        match ron::from_str(GATES) {
+            Ok(gates) => GateTemplates(gates),
+            Err(err) => {
+                error!("Could not parse the gates: {}", err);
+                GateTemplates(Vec::new())
+            }
+        }
+    }
+}
+
+impl GateTemplates {
+    // the gate named `name`, or the index-th one in turn without a name
+    pub fn pick(&self, name: Option<&str>, index: usize) -> Option<usize> {
+        match name {
+            Some(name) => self.0.iter().position(|gate| gate.name == name),
+            None => (!self.0.is_empty()).then(|| index % self.0.len()),
+        }
+    }
+}
+
+// Gauntlet closing the current biome, from where it starts to the boundary
+#[derive(Resource, Debug, Default)]
+pub struct Gate {
+    pub start: f32,
+    pub end: f32,
+    pub template: Option<usize>,
+    placed: bool,
+}
+
+impl Gate {
+    // the gate ending at `end` with the template at `template`, if there's one
+    pub fn new(templates: &GateTemplates, template: Option<usize>, end: f32) -> Self {
+        let length = template
+            .and_then(|index| templates.0.get(index))
+            .map_or(0.0, |gate| gate.length);
+        Gate {
+            start: end - length,
+            end,
+            template,
+            placed: false,
+        }
+    }
+
+    // whether `x` is inside the gate, for the course's own spawners to leave it clear
+    pub fn covers(&self, x: f32) -> bool {
+        self.template.is_some() && (self.start..self.end).contains(&x)
+    }
+}
+
+// system to lay the gate out once the camera gets close to it, its obstacles left out
+// of runs without hazards but its reward kept
+#[allow(clippy::too_many_arguments)]
+fn place_gate(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    balance: Res<Balance>,
+    config: Res<RunConfig>,
+    mode: Res<ActiveMode>,
+    templates: Res<GateTemplates>,
+    mut gate: ResMut<Gate>,
+    camera_query: Query<(&Transform, &OrthographicProjection), With<ParallaxCameraComponent>>,
+    mut feedback: EventWriter<Feedback>,
+) {
+    let Ok((camera, projection)) = camera_query.get_single() else {
+        return;
+    };
+    if gate.placed || camera.translation.x + projection.area.max.x + SPAWN_MARGIN < gate.start {
+        return;
+    }
+    let Some(template) = gate.template.and_then(|index| templates.0.get(index)) else {
+        return;
+    };
+    gate.placed = true;
+    if mode.hazards() {
+        for obstacle in &template.obstacles {
+            match *obstacle {
+                GateObstacle::Spitter(offset) => {
+                    spawn_enemy(
+                        &mut commands,
+                        &asset_server,
+                        &balance,
+                        gate.start + offset,
+                        false,
+                    );
+                }
+                GateObstacle::Barrel(offset) => {
+                    spawn_barrel(&mut commands, &config, gate.start + offset);
+                }
+            }
+        }
+        feedback.send(Feedback::Warning(format!("{}!", template.name)));
+    }
+    for index in 0..template.reward {
+        let x = gate.end - (template.reward - index) as f32 * REWARD_GAP;
+        commands.spawn(coin_bundle(Vec2::new(x, GROUND_Y + REWARD_HEIGHT)));
+    }
+}
+
+pub struct GatePlugin;
+
+impl Plugin for GatePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GateTemplates>()
+            .init_resource::<Gate>()
+            .add_systems(
+                Update,
+                place_gate
+                    .run_if(in_state(RunStage::Main))
+                    .in_set(GameplaySet),
+            );
+    }
+}
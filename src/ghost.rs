@@ -0,0 +1,246 @@
+use crate::game_time::GameTime;
+use crate::mode::{track_progress, ActiveMode, RunOutcome, RunProgress};
+use crate::profile::Profiles;
+use crate::settings::Settings;
+use crate::toast::Toast;
+use crate::{GameState, GameplaySet, Player, PlayerOne, RunEntity};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// author ghosts shipped with the game, by the name of the mode whose course they run
+const AUTHOR_GHOSTS: &str = include_str!("../assets/ghosts.ron");
+// where `cargo run --features dev_tools -- author` writes the ghosts it records, to ship
+// with the next build
+#[cfg(feature = "dev_tools")]
+const AUTHOR_GHOSTS_PATH: &str = "assets/ghosts.ron";
+// seconds of run time between two recorded positions
+const SAMPLE_INTERVAL: f32 = 0.1;
+// coins for beating an author ghost, the first time only
+const AUTHOR_REWARD: u32 = 500;
+const GHOST_COLOR: Color = Color::rgba(0.6, 0.8, 1.0, 0.4);
+
+// A developer's run of a fixed course, raced against as a target time
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AuthorGhost {
+    // seconds the run took to clear the course
+    pub time: f32,
+    // where the player was every SAMPLE_INTERVAL seconds
+    pub path: Vec<(f32, f32)>,
+}
+
+impl AuthorGhost {
+    // where the ghost is `elapsed` seconds into the run, None once it's past the finish
+    fn position_at(&self, elapsed: f32) -> Option<Vec2> {
+        let at = elapsed / SAMPLE_INTERVAL;
+        let index = at as usize;
+        let from = Vec2::from(*self.path.get(index)?);
+        let to = self.path.get(index + 1).map_or(from, |to| Vec2::from(*to));
+        Some(from.lerp(to, at.fract()))
+    }
+}
+
+// Every author ghost known, by mode name
+#[derive(Resource, Debug, Clone, Default)]
+pub struct AuthorGhosts(pub BTreeMap<String, AuthorGhost>);
+
+impl AuthorGhosts {
+    fn bundled() -> Self {
+        ron::from_str(AUTHOR_GHOSTS)
+            .map(AuthorGhosts)
+            .unwrap_or_else(|err| {
+                warn!("Ignoring the bundled author ghosts: {}", err);
+                AuthorGhosts::default()
+            })
+    }
+}
+
+// Records every fixed course cleared as its author ghost, replacing the one before. Put
+// in by `cargo run --features dev_tools -- author`.
+#[derive(Resource)]
+pub struct AuthorRecording;
+
+// The run's race against an author ghost, and the path it takes itself
+#[derive(Resource, Default)]
+struct Race {
+    ghost: Option<AuthorGhost>,
+    path: Vec<(f32, f32)>,
+    finished: bool,
+}
+
+// Translucent copy of the player running the author's path
+#[derive(Component)]
+struct GhostRunner;
+
+// The ghost, with what it takes to look like the player
+type Runner<'a> = (
+    &'a mut Transform,
+    &'a mut Handle<Image>,
+    &'a mut TextureAtlas,
+    &'a mut Visibility,
+);
+
+// system to line up the author ghost of the course, if there is one and racing it is on
+fn start_race(
+    mut commands: Commands,
+    mode: Res<ActiveMode>,
+    ghosts: Res<AuthorGhosts>,
+    settings: Res<Settings>,
+) {
+    let ghost = settings
+        .race_author
+        .then(|| ghosts.0.get(mode.name()).cloned())
+        .flatten();
+    if let Some(ghost) = &ghost {
+        commands.spawn((
+            SpriteSheetBundle {
+                sprite: Sprite {
+                    color: GHOST_COLOR,
+                    ..default()
+                },
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            GhostRunner,
+            RunEntity,
+        ));
+        info!(
+            "Racing the author ghost of {} at {:.1}s",
+            mode.name(),
+            ghost.time
+        );
+    }
+    commands.insert_resource(Race { ghost, ..default() });
+}
+
+// system to note where player one is, for a run that might become an author ghost
+fn record_path(
+    time: Res<GameTime>,
+    recording: Option<Res<AuthorRecording>>,
+    mut race: ResMut<Race>,
+    player_query: Query<&Transform, PlayerOne>,
+) {
+    let Ok(transform) = player_query.get_single() else {
+        return;
+    };
+    if recording.is_none() {
+        return;
+    }
+    while race.path.len() as f32 * SAMPLE_INTERVAL <= time.elapsed_seconds() {
+        race.path.push(transform.translation.truncate().into());
+    }
+}
+
+// system to move the ghost along the author's path, looking like player one does now
+fn move_ghost(
+    progress: Res<RunProgress>,
+    race: Res<Race>,
+    player_query: Query<(&Transform, &Handle<Image>, &TextureAtlas), PlayerOne>,
+    mut ghost_query: Query<Runner, (With<GhostRunner>, Without<Player>)>,
+) {
+    let (Some(ghost), Ok((player, texture, atlas))) = (&race.ghost, player_query.get_single())
+    else {
+        return;
+    };
+    for (mut transform, mut ghost_texture, mut ghost_atlas, mut visibility) in &mut ghost_query {
+        let Some(position) = ghost.position_at(progress.elapsed) else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+        *visibility = Visibility::Visible;
+        // just behind the player, who stays in front when they overlap
+        *transform = Transform {
+            translation: position.extend(player.translation.z - 0.02),
+            ..*player
+        };
+        if *ghost_texture != *texture {
+            *ghost_texture = texture.clone();
+        }
+        *ghost_atlas = atlas.clone();
+    }
+}
+
+// system to settle the race once the course is cleared: beating the author's time pays
+// out the first time, and in authoring builds the run becomes the new author ghost
+fn finish_race(
+    mode: Res<ActiveMode>,
+    progress: Res<RunProgress>,
+    mut race: ResMut<Race>,
+    mut ghosts: ResMut<AuthorGhosts>,
+    mut profiles: ResMut<Profiles>,
+    recording: Option<Res<AuthorRecording>>,
+    mut toasts: EventWriter<Toast>,
+) {
+    if race.finished || mode.outcome(&progress) != Some(RunOutcome::Won) {
+        return;
+    }
+    race.finished = true;
+    if let Some(ghost) = &race.ghost {
+        let name = mode.name().to_string();
+        let profile = profiles.active_mut();
+        if progress.elapsed >= ghost.time {
+            toasts.send(Toast(format!("The dev's {:.1}s still stands", ghost.time)));
+        } else if profile.beaten_authors.insert(name) {
+            profile.coins += AUTHOR_REWARD;
+            toasts.send(Toast(format!(
+                "You beat the dev's {:.1}s! +{} coins",
+                ghost.time, AUTHOR_REWARD
+            )));
+        } else {
+            toasts.send(Toast(format!("Beat the dev's {:.1}s again", ghost.time)));
+        }
+    }
+    // only a fixed course can be raced again the same way
+    if recording.is_none() || mode.seed().is_none() {
+        return;
+    }
+    let ghost = AuthorGhost {
+        time: progress.elapsed,
+        path: std::mem::take(&mut race.path),
+    };
+    ghosts.0.insert(mode.name().to_string(), ghost);
+    save_author_ghosts(&ghosts);
+    toasts.send(Toast(format!(
+        "Author ghost of {} saved: {:.1}s",
+        mode.name(),
+        progress.elapsed
+    )));
+}
+
+#[cfg(feature = "dev_tools")]
+fn save_author_ghosts(ghosts: &AuthorGhosts) {
+    let contents = match ron::ser::to_string_pretty(
+        &ghosts.0,
+        ron::ser::PrettyConfig::default().compact_arrays(true),
+    ) {
+        Ok(contents) => contents,
+        Err(err) => {
+            error!("Could not serialize the author ghosts: {}", err);
+            return;
+        }
+    };
+    if let Err(err) = crate::storage::write(AUTHOR_GHOSTS_PATH, contents) {
+        error!("Could not write {}: {}", AUTHOR_GHOSTS_PATH, err);
+    }
+}
+
+// recording is only put in by dev builds, which are the only ones writing to assets/
+#[cfg(not(feature = "dev_tools"))]
+fn save_author_ghosts(_ghosts: &AuthorGhosts) {}
+
+pub struct GhostPlugin;
+
+impl Plugin for GhostPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(AuthorGhosts::bundled())
+            .init_resource::<Race>()
+            .add_systems(OnEnter(GameState::Playing), start_race)
+            .add_systems(
+                Update,
+                (record_path, move_ghost, finish_race)
+                    .chain()
+                    .after(track_progress)
+                    .in_set(GameplaySet),
+            );
+    }
+}
@@ -0,0 +1,182 @@
+use crate::feedback::Feedback;
+use crate::health::{Health, HitSet};
+use crate::heatmap::{Hazard, LastHit};
+use crate::input::{Action, ActionState};
+use crate::mode::track_progress;
+use crate::powerup::{ActivePowerUps, PowerUp};
+use crate::prompt::{Prompt, PromptKey, PromptPart};
+use crate::score::Combo;
+use crate::toast::Toast;
+use crate::{GameState, GameplaySet, PlayerOne, RunEntity};
+use bevy::prelude::*;
+
+// a hit breaking a combo at least this big while the shield is up gives the player the choice
+const GRACE_MULTIPLIER: u32 = 3;
+// how fast the game runs while the player makes up their mind
+const SLOW_MOTION: f32 = 0.1;
+// real seconds the player has to spend the shield before the hit lands
+const DECISION_WINDOW: f32 = 1.2;
+
+// Hit held off in slow motion while the player decides whether to spend the shield on it
+#[derive(Resource)]
+struct Grace {
+    damage: f32,
+    window: Timer,
+    // speed of the clock before it was slowed down
+    speed: f32,
+}
+
+// Health player one had after the last frame's hits, to tell new ones by
+#[derive(Resource, Default)]
+struct LastHealth(Option<f32>);
+
+#[derive(Component)]
+struct GracePrompt;
+
+fn reset_grace(mut commands: Commands) {
+    commands.insert_resource(LastHealth::default());
+}
+
+// system to put the clock back if the run ends while a hit is held off
+fn end_grace(mut commands: Commands, grace: Option<Res<Grace>>, mut time: ResMut<Time<Virtual>>) {
+    if let Some(grace) = grace {
+        time.set_relative_speed(grace.speed);
+        commands.remove_resource::<Grace>();
+    }
+}
+
+// system to catch player one being hit: with the shield up it takes small hits, and holds
+// off ones breaking a big combo for the player to choose; without it, the combo breaks
+#[allow(clippy::too_many_arguments)]
+fn catch_hits(
+    mut commands: Commands,
+    grace: Option<ResMut<Grace>>,
+    mut last_health: ResMut<LastHealth>,
+    mut player_query: Query<&mut Health, PlayerOne>,
+    mut combo: ResMut<Combo>,
+    mut active: ResMut<ActivePowerUps>,
+    mut time: ResMut<Time<Virtual>>,
+    last_hit: Res<LastHit>,
+    mut toasts: EventWriter<Toast>,
+    mut feedback: EventWriter<Feedback>,
+) {
+    let Ok(mut health) = player_query.get_single_mut() else {
+        return;
+    };
+    let Some(before) = last_health.0 else {
+        last_health.0 = Some(health.current);
+        return;
+    };
+    let damage = before - health.current;
+    // falling out of the world or into the kill wall can't be shielded against
+    let fatal = matches!(last_hit.0, Some(Hazard::Pit | Hazard::Wall)) && health.current <= 0.0;
+    if damage <= 0.0 || fatal {
+        last_health.0 = Some(health.current);
+        return;
+    }
+    // more hits while one is held off are held off with it
+    if let Some(mut grace) = grace {
+        grace.damage += damage;
+        health.current = before;
+        return;
+    }
+    if !active.is_active(PowerUp::Shield) {
+        combo.break_off();
+        last_health.0 = Some(health.current);
+        return;
+    }
+    health.current = before;
+    if combo.multiplier() < GRACE_MULTIPLIER {
+        active.end(PowerUp::Shield);
+        toasts.send(Toast("Shield broke".to_string()));
+        return;
+    }
+    commands.insert_resource(Grace {
+        damage,
+        window: Timer::from_seconds(DECISION_WINDOW, TimerMode::Once),
+        speed: time.relative_speed(),
+    });
+    time.set_relative_speed(SLOW_MOTION);
+    feedback.send(Feedback::Flash(Color::rgba(0.3, 0.6, 1.0, 0.4)));
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 20.0,
+                color: Color::WHITE,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Percent(35.0),
+            align_self: AlignSelf::Center,
+            justify_self: JustifySelf::Center,
+            ..default()
+        }),
+        Prompt(vec![
+            PromptPart::Text("Saved by a hair? "),
+            PromptPart::Glyph(PromptKey::Action(Action::Jump)),
+            PromptPart::Text(" Use shield"),
+        ]),
+        GracePrompt,
+        RunEntity,
+    ));
+}
+
+// system to settle a held off hit: spending the shield keeps the combo going, letting the
+// window run out lands the hit and loses the shield all the same
+#[allow(clippy::too_many_arguments)]
+fn decide(
+    mut commands: Commands,
+    grace: Option<ResMut<Grace>>,
+    real_time: Res<Time<Real>>,
+    actions: Res<ActionState>,
+    mut time: ResMut<Time<Virtual>>,
+    mut last_health: ResMut<LastHealth>,
+    mut player_query: Query<&mut Health, PlayerOne>,
+    mut combo: ResMut<Combo>,
+    mut active: ResMut<ActivePowerUps>,
+    prompt_query: Query<Entity, With<GracePrompt>>,
+    mut toasts: EventWriter<Toast>,
+) {
+    let Some(mut grace) = grace else {
+        return;
+    };
+    let used = actions.just_pressed(Action::Jump);
+    if !used && !grace.window.tick(real_time.delta()).finished() {
+        return;
+    }
+    time.set_relative_speed(grace.speed);
+    active.end(PowerUp::Shield);
+    if used {
+        toasts.send(Toast("Saved by a hair!".to_string()));
+    } else if let Ok(mut health) = player_query.get_single_mut() {
+        health.current -= grace.damage;
+        last_health.0 = Some(health.current);
+        combo.break_off();
+        toasts.send(Toast("Too late!".to_string()));
+    }
+    for entity in &prompt_query {
+        commands.entity(entity).despawn_recursive();
+    }
+    commands.remove_resource::<Grace>();
+}
+
+pub struct GracePlugin;
+
+impl Plugin for GracePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LastHealth>()
+            .add_systems(OnEnter(GameState::Playing), reset_grace)
+            .add_systems(OnExit(GameState::Playing), end_grace)
+            .add_systems(
+                Update,
+                (catch_hits, decide)
+                    .chain()
+                    .after(HitSet)
+                    .before(track_progress)
+                    .in_set(GameplaySet),
+            );
+    }
+}
@@ -0,0 +1,161 @@
+use crate::profile::Profiles;
+use crate::GameState;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+// what a run is rated on: a point for every this many pixels covered and points scored,
+// and COMBO_WEIGHT for every step of the best multiplier reached
+const DISTANCE_PER_POINT: f32 = 100.0;
+const SCORE_PER_POINT: f32 = 100.0;
+const COMBO_WEIGHT: f32 = 10.0;
+// rating needed for each grade above C
+const B_RATING: f32 = 45.0;
+const A_RATING: f32 = 90.0;
+const S_RATING: f32 = 150.0;
+// the stamp lands from STAMP_SIZE.0 down to STAMP_SIZE.1 in STAMP_LAND seconds, then
+// stays up until STAMP_TIME, fading out over the last STAMP_FADE
+const STAMP_SIZE: (f32, f32) = (180.0, 72.0);
+const STAMP_LAND: f32 = 0.2;
+const STAMP_TIME: f32 = 3.0;
+const STAMP_FADE: f32 = 0.5;
+
+// Letter grade of a finished run, worst first
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Grade {
+    C,
+    B,
+    A,
+    S,
+}
+
+impl Grade {
+    // the grade of a run that covered `distance`, scored `score` points and reached a
+    // multiplier of `best_multiplier` at most
+    pub fn rate(distance: f32, score: u32, best_multiplier: u32) -> Self {
+        let rating = distance / DISTANCE_PER_POINT
+            + score as f32 / SCORE_PER_POINT
+            + best_multiplier.saturating_sub(1) as f32 * COMBO_WEIGHT;
+        if rating >= S_RATING {
+            Grade::S
+        } else if rating >= A_RATING {
+            Grade::A
+        } else if rating >= B_RATING {
+            Grade::B
+        } else {
+            Grade::C
+        }
+    }
+
+    pub fn letter(&self) -> &'static str {
+        match self {
+            Grade::C => "C",
+            Grade::B => "B",
+            Grade::A => "A",
+            Grade::S => "S",
+        }
+    }
+
+    pub fn from_letter(letter: &str) -> Option<Self> {
+        [Grade::C, Grade::B, Grade::A, Grade::S]
+            .into_iter()
+            .find(|grade| grade.letter() == letter)
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            Grade::C => Color::rgb(0.7, 0.7, 0.7),
+            Grade::B => Color::rgb(0.8, 0.5, 0.25),
+            Grade::A => Color::rgb(0.75, 0.85, 1.0),
+            Grade::S => Color::rgb(1.0, 0.8, 0.2),
+        }
+    }
+}
+
+// Event sent when a run ends with the grade it got in the mode it was played in
+#[derive(Event, Debug, Clone)]
+pub struct RunGraded {
+    pub mode: String,
+    pub grade: Grade,
+}
+
+// Grade letter stamped over the screen at the end of a run
+#[derive(Component)]
+struct Stamp(Timer);
+
+// system to stamp the grade of the run that just ended on the screen, and keep it as the
+// profile's best in that mode if it beats the one before
+fn stamp_grade(
+    mut commands: Commands,
+    mut events: EventReader<RunGraded>,
+    mut profiles: ResMut<Profiles>,
+    stamp_query: Query<Entity, With<Stamp>>,
+) {
+    for RunGraded { mode, grade } in events.read() {
+        let best = profiles.active().best_grades.get(mode);
+        if best.is_none_or(|best| grade > best) {
+            profiles
+                .active_mut()
+                .best_grades
+                .insert(mode.clone(), *grade);
+        }
+        for stamp in &stamp_query {
+            commands.entity(stamp).despawn_recursive();
+        }
+        commands.spawn((
+            TextBundle::from_section(
+                grade.letter(),
+                TextStyle {
+                    font_size: STAMP_SIZE.0,
+                    color: grade.color(),
+                    ..default()
+                },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                top: Val::Percent(20.0),
+                right: Val::Percent(12.0),
+                ..default()
+            }),
+            Stamp(Timer::from_seconds(STAMP_TIME, TimerMode::Once)),
+        ));
+    }
+}
+
+// system to slam the stamp down onto the screen, then fade it out
+fn animate_stamp(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    mut stamp_query: Query<(Entity, &mut Stamp, &mut Text)>,
+) {
+    for (entity, mut stamp, mut text) in &mut stamp_query {
+        stamp.0.tick(time.delta());
+        if stamp.0.finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        let elapsed = stamp.0.elapsed_secs();
+        let landed = (elapsed / STAMP_LAND).min(1.0);
+        let fade = ((STAMP_TIME - elapsed) / STAMP_FADE).min(1.0);
+        let style = &mut text.sections[0].style;
+        style.font_size = STAMP_SIZE.0 + (STAMP_SIZE.1 - STAMP_SIZE.0) * landed;
+        style.color.set_a(fade);
+    }
+}
+
+// system to take the stamp of the last run away once the next one starts, when it was
+// restarted before the stamp faded
+fn clear_stamp(mut commands: Commands, stamp_query: Query<Entity, With<Stamp>>) {
+    for stamp in &stamp_query {
+        commands.entity(stamp).despawn_recursive();
+    }
+}
+
+pub struct GradePlugin;
+
+impl Plugin for GradePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<RunGraded>()
+            .add_systems(OnEnter(GameState::Playing), clear_stamp)
+            .add_systems(Update, (stamp_grade, animate_stamp).chain());
+    }
+}
@@ -0,0 +1,213 @@
+use crate::RunEntity;
+use bevy::prelude::*;
+use bevy::sprite::Anchor;
+
+// size of the bars floating over damaged enemies, in the enemy's sprite pixels
+const FLOATING_BAR_SIZE: Vec2 = Vec2::new(12.0, 1.5);
+// how far above the sprite center the floating bar sits, in sprite pixels
+const FLOATING_BAR_OFFSET: f32 = 10.0;
+const BOSS_BAR_WIDTH: f32 = 320.0;
+const BOSS_BAR_HEIGHT: f32 = 10.0;
+const BAR_BACKGROUND: Color = Color::rgba(0.1, 0.1, 0.1, 0.8);
+const BAR_FILL: Color = Color::rgb(0.85, 0.2, 0.2);
+
+// Systems hurting the players, anything reacting to hits goes after them
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct HitSet;
+
+// Keeps a player from being hurt, or thrown around by blasts, while present
+#[derive(Component)]
+pub struct Invincible;
+
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Health {
+    pub current: f32,
+    pub max: f32,
+}
+
+impl Health {
+    pub fn new(max: f32) -> Self {
+        Health { current: max, max }
+    }
+
+    pub fn fraction(&self) -> f32 {
+        if self.max > 0.0 {
+            (self.current / self.max).clamp(0.0, 1.0)
+        } else {
+            0.0
+        }
+    }
+}
+
+// Marks an enemy whose health is shown in the big bar at the top of the screen
+#[derive(Component, Debug, Clone)]
+pub struct Boss {
+    pub name: String,
+    // the bar is split into this many segments
+    pub segments: usize,
+}
+
+// Bar at the top of the screen tracking the boss entity's health
+#[derive(Component)]
+struct BossBar(Entity);
+
+// One segment of a boss bar, the index-th from the left
+#[derive(Component)]
+struct BossBarSegment {
+    bar: Entity,
+    index: usize,
+}
+
+// Fill of the small bar floating over a damaged enemy, the entity it belongs to
+#[derive(Component)]
+struct FloatingBarFill(Entity);
+
+// system to pin a segmented health bar to the top of the screen for every new boss
+fn spawn_boss_bars(mut commands: Commands, boss_query: Query<(Entity, &Boss), Added<Boss>>) {
+    for (boss, Boss { name, segments }) in &boss_query {
+        commands
+            .spawn((
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        top: Val::Px(8.0),
+                        width: Val::Percent(100.0),
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        row_gap: Val::Px(2.0),
+                        ..default()
+                    },
+                    z_index: ZIndex::Global(8),
+                    ..default()
+                },
+                BossBar(boss),
+                RunEntity,
+            ))
+            .with_children(|parent| {
+                parent.spawn(TextBundle::from_section(
+                    name.clone(),
+                    TextStyle {
+                        font_size: 14.0,
+                        color: Color::WHITE,
+                        ..default()
+                    },
+                ));
+                let bar = parent.parent_entity();
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            width: Val::Px(BOSS_BAR_WIDTH),
+                            height: Val::Px(BOSS_BAR_HEIGHT),
+                            column_gap: Val::Px(2.0),
+                            padding: UiRect::all(Val::Px(1.0)),
+                            ..default()
+                        },
+                        background_color: BAR_BACKGROUND.into(),
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        for index in 0..(*segments).max(1) {
+                            parent.spawn((
+                                NodeBundle {
+                                    style: Style {
+                                        flex_grow: 1.0,
+                                        height: Val::Percent(100.0),
+                                        ..default()
+                                    },
+                                    background_color: BAR_FILL.into(),
+                                    ..default()
+                                },
+                                BossBarSegment { bar, index },
+                            ));
+                        }
+                    });
+            });
+    }
+}
+
+// system to empty boss bar segments as the boss loses health, and remove the bar with the boss
+fn update_boss_bars(
+    mut commands: Commands,
+    bar_query: Query<(Entity, &BossBar)>,
+    boss_query: Query<(Ref<Health>, &Boss)>,
+    mut segment_query: Query<(&BossBarSegment, &mut BackgroundColor)>,
+) {
+    for (bar, BossBar(boss)) in &bar_query {
+        let Ok((health, Boss { segments, .. })) = boss_query.get(*boss) else {
+            commands.entity(bar).despawn_recursive();
+            continue;
+        };
+        if !health.is_changed() {
+            continue;
+        }
+        let filled = (health.fraction() * *segments as f32).ceil() as usize;
+        for (segment, mut color) in &mut segment_query {
+            if segment.bar == bar {
+                color.0 = if segment.index < filled {
+                    BAR_FILL
+                } else {
+                    Color::NONE
+                };
+            }
+        }
+    }
+}
+
+// system to float a small health bar over enemies once they've taken damage
+fn update_floating_bars(
+    mut commands: Commands,
+    health_query: Query<(Entity, Ref<Health>), Without<Boss>>,
+    mut fill_query: Query<(&FloatingBarFill, &mut Sprite)>,
+) {
+    for (entity, health) in &health_query {
+        if !health.is_changed() || health.current >= health.max {
+            continue;
+        }
+        let width = FLOATING_BAR_SIZE.x * health.fraction();
+        if let Some((_, mut sprite)) = fill_query
+            .iter_mut()
+            .find(|(FloatingBarFill(owner), _)| *owner == entity)
+        {
+            sprite.custom_size = Some(Vec2::new(width, FLOATING_BAR_SIZE.y));
+            continue;
+        }
+        let bar = commands
+            .spawn(SpriteBundle {
+                sprite: Sprite {
+                    color: BAR_BACKGROUND,
+                    custom_size: Some(FLOATING_BAR_SIZE),
+                    ..default()
+                },
+                transform: Transform::from_xyz(0.0, FLOATING_BAR_OFFSET, 0.1),
+                ..default()
+            })
+            .with_children(|parent| {
+                parent.spawn((
+                    SpriteBundle {
+                        sprite: Sprite {
+                            color: BAR_FILL,
+                            custom_size: Some(Vec2::new(width, FLOATING_BAR_SIZE.y)),
+                            anchor: Anchor::CenterLeft,
+                            ..default()
+                        },
+                        transform: Transform::from_xyz(-FLOATING_BAR_SIZE.x / 2.0, 0.0, 0.1),
+                        ..default()
+                    },
+                    FloatingBarFill(entity),
+                ));
+            })
+            .id();
+        commands.entity(entity).add_child(bar);
+    }
+}
+
+pub struct HealthPlugin;
+
+impl Plugin for HealthPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (spawn_boss_bars, update_boss_bars, update_floating_bars).chain(),
+        );
+    }
+}
@@ -0,0 +1,279 @@
+use crate::mode::{track_progress, ActiveMode, RunOutcome, RunProgress};
+use crate::storage;
+use crate::{GameState, GameplaySet};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+const STATS_PATH: &str = "death_stats.ron";
+// length of the stretches of course deaths are counted in
+const CHUNK_LENGTH: f32 = 500.0;
+// chunks shown in the heatmap, deaths further along are counted in the last one
+const SHOWN_CHUNKS: u32 = 20;
+const CELL_SIZE: f32 = 18.0;
+const COLD: Color = Color::rgb(0.12, 0.12, 0.18);
+const HOT: Color = Color::rgb(0.95, 0.2, 0.15);
+
+// What hurt the player last
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hazard {
+    Glob,
+    Rock,
+    Blast,
+    // a swinging vine or spike bar
+    Swing,
+    // caught by the kill wall
+    Wall,
+    // fell out of the world
+    Pit,
+    // the run was lost without anything hitting the player
+    Other,
+}
+
+impl Hazard {
+    const ALL: [Hazard; 7] = [
+        Hazard::Glob,
+        Hazard::Rock,
+        Hazard::Blast,
+        Hazard::Swing,
+        Hazard::Wall,
+        Hazard::Pit,
+        Hazard::Other,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Hazard::Glob => "Glob",
+            Hazard::Rock => "Rock",
+            Hazard::Blast => "Blast",
+            Hazard::Swing => "Swing",
+            Hazard::Wall => "Wall",
+            Hazard::Pit => "Pit",
+            Hazard::Other => "Other",
+        }
+    }
+}
+
+// The hazard that last hurt the player this run
+#[derive(Resource, Default)]
+pub struct LastHit(pub Option<Hazard>);
+
+// How many runs were lost to a hazard in one chunk of the course
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct DeathCount {
+    hazard: Hazard,
+    chunk: u32,
+    deaths: u32,
+}
+
+// Every run lost so far, counted by what ended it and where, kept in STATS_PATH
+#[derive(Resource, Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+struct DeathStats {
+    counts: Vec<DeathCount>,
+}
+
+impl DeathStats {
+    fn load() -> Self {
+        let Ok(contents) = storage::read_to_string(STATS_PATH) else {
+            return DeathStats::default();
+        };
+        ron::from_str(&contents).unwrap_or_else(|err| {
+            warn!("Ignoring {}: {}", STATS_PATH, err);
+            DeathStats::default()
+        })
+    }
+
+    fn save(&self) {
+        let contents = match ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()) {
+            Ok(contents) => contents,
+            Err(err) => {
+                error!("Could not serialize the death stats: {}", err);
+                return;
+            }
+        };
+        if let Err(err) = storage::write(STATS_PATH, contents) {
+            error!("Could not write {}: {}", STATS_PATH, err);
+        }
+    }
+
+    fn record(&mut self, hazard: Hazard, chunk: u32) {
+        match self
+            .counts
+            .iter_mut()
+            .find(|count| count.hazard == hazard && count.chunk == chunk)
+        {
+            Some(count) => count.deaths += 1,
+            None => self.counts.push(DeathCount {
+                hazard,
+                chunk,
+                deaths: 1,
+            }),
+        }
+    }
+
+    // deaths to `hazard` in the shown chunk `chunk`, the last one also counting all beyond
+    fn deaths(&self, hazard: Hazard, chunk: u32) -> u32 {
+        self.counts
+            .iter()
+            .filter(|count| count.hazard == hazard)
+            .filter(|count| count.chunk.min(SHOWN_CHUNKS - 1) == chunk)
+            .map(|count| count.deaths)
+            .sum()
+    }
+}
+
+// Grid of deaths by hazard and chunk, toggled with F1
+#[derive(Component)]
+struct Heatmap;
+
+fn load_death_stats(mut commands: Commands) {
+    commands.insert_resource(DeathStats::load());
+}
+
+fn reset_last_hit(mut last_hit: ResMut<LastHit>) {
+    last_hit.0 = None;
+}
+
+// system to count the run in the stats the moment it's lost
+fn record_death(
+    mode: Res<ActiveMode>,
+    progress: Res<RunProgress>,
+    last_hit: Res<LastHit>,
+    mut stats: ResMut<DeathStats>,
+) {
+    if mode.outcome(&progress) != Some(RunOutcome::Lost) {
+        return;
+    }
+    let chunk = (progress.distance / CHUNK_LENGTH) as u32;
+    stats.record(last_hit.0.unwrap_or(Hazard::Other), chunk);
+}
+
+// system to write the stats back to disk after a death was counted
+fn save_death_stats(stats: Res<DeathStats>) {
+    if stats.is_changed() && !stats.is_added() {
+        stats.save();
+    }
+}
+
+// system to show or hide the heatmap with F1, rebuilding it when the stats change
+fn toggle_heatmap(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    stats: Res<DeathStats>,
+    heatmap_query: Query<Entity, With<Heatmap>>,
+) {
+    let shown = !heatmap_query.is_empty();
+    let toggled = keyboard_input.just_pressed(KeyCode::F1);
+    let refreshed = shown && stats.is_changed();
+    if !(toggled || refreshed) {
+        return;
+    }
+    for heatmap in &heatmap_query {
+        commands.entity(heatmap).despawn_recursive();
+    }
+    if shown && toggled {
+        return;
+    }
+    let most = stats
+        .counts
+        .iter()
+        .map(|count| count.deaths)
+        .max()
+        .unwrap_or(0);
+    let text = |value: String| {
+        TextBundle::from_section(
+            value,
+            TextStyle {
+                font_size: 12.0,
+                color: Color::WHITE,
+                ..default()
+            },
+        )
+    };
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(60.0),
+                    align_self: AlignSelf::Center,
+                    justify_self: JustifySelf::Center,
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(2.0),
+                    padding: UiRect::all(Val::Px(8.0)),
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.85).into(),
+                z_index: ZIndex::Global(20),
+                ..default()
+            },
+            Heatmap,
+        ))
+        .with_children(|parent| {
+            parent.spawn(text(format!(
+                "Deaths by hazard, every {} along the course",
+                CHUNK_LENGTH
+            )));
+            for hazard in Hazard::ALL {
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            column_gap: Val::Px(2.0),
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|row| {
+                        row.spawn(text(hazard.name().to_string()).with_style(Style {
+                            width: Val::Px(48.0),
+                            ..default()
+                        }));
+                        for chunk in 0..SHOWN_CHUNKS {
+                            let deaths = stats.deaths(hazard, chunk);
+                            let heat = if most > 0 {
+                                deaths as f32 / most as f32
+                            } else {
+                                0.0
+                            };
+                            let color = Color::rgb_from_array(
+                                COLD.rgb_to_vec3().lerp(HOT.rgb_to_vec3(), heat),
+                            );
+                            row.spawn(NodeBundle {
+                                style: Style {
+                                    width: Val::Px(CELL_SIZE),
+                                    height: Val::Px(CELL_SIZE),
+                                    justify_content: JustifyContent::Center,
+                                    align_items: AlignItems::Center,
+                                    ..default()
+                                },
+                                background_color: color.into(),
+                                ..default()
+                            })
+                            .with_children(|cell| {
+                                if deaths > 0 {
+                                    cell.spawn(text(deaths.to_string()));
+                                }
+                            });
+                        }
+                    });
+            }
+        });
+}
+
+pub struct HeatmapPlugin;
+
+impl Plugin for HeatmapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LastHit>()
+            .init_resource::<DeathStats>()
+            .add_systems(Startup, load_death_stats)
+            .add_systems(OnEnter(GameState::Playing), reset_last_hit)
+            .add_systems(
+                Update,
+                record_death.after(track_progress).in_set(GameplaySet),
+            )
+            .add_systems(Update, toggle_heatmap)
+            .add_systems(Last, save_death_stats);
+    }
+}
@@ -0,0 +1,115 @@
+use crate::game_over::RunRecord;
+use bevy::prelude::*;
+
+// size of a sparkline on the stats screen, and of the dot marking each run on it
+const SPARK_WIDTH: f32 = 400.0;
+const SPARK_HEIGHT: f32 = 48.0;
+const DOT_SIZE: f32 = 3.0;
+const SPARK_COLOR: Color = Color::rgba(0.1, 0.1, 0.15, 0.8);
+// runs averaged together for the trend line, and compared with the ones before them
+// for the trend
+const TREND_WINDOW: usize = 10;
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+// one value per run, oldest first
+pub fn scores(runs: &[RunRecord]) -> Vec<f32> {
+    runs.iter().map(|run| run.score as f32).collect()
+}
+
+// runs saved before their distance was kept are left out
+pub fn distances(runs: &[RunRecord]) -> Vec<f32> {
+    runs.iter().filter_map(|run| run.distance).collect()
+}
+
+// average of each run with the ones before it, up to TREND_WINDOW of them
+fn moving_average(values: &[f32]) -> Vec<f32> {
+    (0..values.len())
+        .map(|end| {
+            let window = &values[(end + 1).saturating_sub(TREND_WINDOW)..=end];
+            window.iter().sum::<f32>() / window.len() as f32
+        })
+        .collect()
+}
+
+// how much better the last TREND_WINDOW runs did than the ones before them, as a share,
+// None until there are enough runs to compare
+pub fn trend(values: &[f32]) -> Option<f32> {
+    if values.len() < TREND_WINDOW * 2 {
+        return None;
+    }
+    let average = |window: &[f32]| window.iter().sum::<f32>() / window.len() as f32;
+    let recent = average(&values[values.len() - TREND_WINDOW..]);
+    let before = average(&values[values.len() - TREND_WINDOW * 2..values.len() - TREND_WINDOW]);
+    (before > 0.0).then(|| recent / before - 1.0)
+}
+
+// day a run was played on, as year-month-day
+pub fn date(seconds: u64) -> String {
+    // days since 1970-01-01 to a civil date, after Howard Hinnant's civil_from_days
+    let days = (seconds / SECONDS_PER_DAY) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+// a dot per run, from the oldest on the left to the latest on the right, with the moving
+// average drawn over them in a brighter line
+pub fn spawn_sparkline(commands: &mut Commands, values: &[f32], color: Color) -> Entity {
+    let max = values.iter().copied().fold(0.0, f32::max).max(1.0);
+    let step = if values.len() > 1 {
+        (SPARK_WIDTH - DOT_SIZE) / (values.len() - 1) as f32
+    } else {
+        0.0
+    };
+    let dot = |index: usize, value: f32, size: f32, color: Color| NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            left: Val::Px(index as f32 * step),
+            bottom: Val::Px(value / max * (SPARK_HEIGHT - size)),
+            width: Val::Px(size),
+            height: Val::Px(size),
+            ..default()
+        },
+        background_color: color.into(),
+        ..default()
+    };
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Px(SPARK_WIDTH),
+                height: Val::Px(SPARK_HEIGHT),
+                ..default()
+            },
+            background_color: SPARK_COLOR.into(),
+            ..default()
+        })
+        .with_children(|parent| {
+            for (index, value) in values.iter().enumerate() {
+                parent.spawn(dot(index, *value, DOT_SIZE, color.with_a(0.5)));
+            }
+            // the average joined up by a dot every pixel or so between two runs
+            let average = moving_average(values);
+            for (index, pair) in average.windows(2).enumerate() {
+                let segments = step.ceil().max(1.0) as usize;
+                for segment in 0..segments {
+                    let t = segment as f32 / segments as f32;
+                    let value = pair[0] + (pair[1] - pair[0]) * t;
+                    let mut line = dot(index, value, 2.0, Color::WHITE);
+                    line.style.left = Val::Px((index as f32 + t) * step);
+                    parent.spawn(line);
+                }
+            }
+        })
+        .id()
+}
@@ -0,0 +1,344 @@
+use crate::settings::Settings;
+use bevy::prelude::*;
+use bevy::ui::RelativeCursorPosition;
+use serde::{Deserialize, Serialize};
+
+// sizes a widget can be cycled through
+const SCALES: [f32; 5] = [0.75, 1.0, 1.25, 1.5, 2.0];
+// gap between a widget and the edges of the screen by default
+const MARGIN: f32 = 8.0;
+// height of the score line, which the combo row sits under by default
+const SCORE_HEIGHT: f32 = 20.0;
+// how far from its anchor a widget can be dragged
+const MAX_OFFSET: f32 = 240.0;
+
+// The parts of the HUD that can be moved around and scaled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HudWidget {
+    Score,
+    Combo,
+    Boost,
+    KillFeed,
+}
+
+impl HudWidget {
+    pub const ALL: [HudWidget; 4] = [
+        HudWidget::Score,
+        HudWidget::Combo,
+        HudWidget::Boost,
+        HudWidget::KillFeed,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            HudWidget::Score => "Score",
+            HudWidget::Combo => "Combo",
+            HudWidget::Boost => "Boost",
+            HudWidget::KillFeed => "Kill feed",
+        }
+    }
+}
+
+// Corner or edge of the screen a widget is kept to
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Anchor {
+    TopLeft,
+    TopCenter,
+    TopRight,
+    BottomLeft,
+    BottomCenter,
+    BottomRight,
+}
+
+impl Anchor {
+    const ALL: [Anchor; 6] = [
+        Anchor::TopLeft,
+        Anchor::TopCenter,
+        Anchor::TopRight,
+        Anchor::BottomRight,
+        Anchor::BottomCenter,
+        Anchor::BottomLeft,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Anchor::TopLeft => "Top left",
+            Anchor::TopCenter => "Top",
+            Anchor::TopRight => "Top right",
+            Anchor::BottomLeft => "Bottom left",
+            Anchor::BottomCenter => "Bottom",
+            Anchor::BottomRight => "Bottom right",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        let index = Self::ALL.iter().position(|anchor| anchor == self);
+        Self::ALL[index.map_or(0, |index| index + 1) % Self::ALL.len()]
+    }
+
+    // 0 on the left, 1 in the middle, 2 on the right
+    fn column(&self) -> u8 {
+        match self {
+            Anchor::TopLeft | Anchor::BottomLeft => 0,
+            Anchor::TopCenter | Anchor::BottomCenter => 1,
+            Anchor::TopRight | Anchor::BottomRight => 2,
+        }
+    }
+
+    fn top(&self) -> bool {
+        matches!(self, Anchor::TopLeft | Anchor::TopCenter | Anchor::TopRight)
+    }
+
+    fn from_parts(column: u8, top: bool) -> Self {
+        match (column, top) {
+            (0, true) => Anchor::TopLeft,
+            (1, true) => Anchor::TopCenter,
+            (_, true) => Anchor::TopRight,
+            (0, false) => Anchor::BottomLeft,
+            (1, false) => Anchor::BottomCenter,
+            (_, false) => Anchor::BottomRight,
+        }
+    }
+}
+
+// Where a widget sits: its anchor, how far in from it (toward the middle of the screen,
+// or right and down of the middle for centered ones) and how big
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct WidgetLayout {
+    pub anchor: Anchor,
+    pub offset: (f32, f32),
+    pub scale: f32,
+}
+
+impl WidgetLayout {
+    pub fn next_scale(&self) -> f32 {
+        let index = SCALES.iter().position(|scale| *scale == self.scale);
+        SCALES[index.map_or(1, |index| (index + 1) % SCALES.len())]
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct HudLayout {
+    pub score: WidgetLayout,
+    pub combo: WidgetLayout,
+    pub boost: WidgetLayout,
+    pub kill_feed: WidgetLayout,
+}
+
+impl Default for HudLayout {
+    fn default() -> Self {
+        HudLayout {
+            score: WidgetLayout {
+                anchor: Anchor::TopLeft,
+                offset: (MARGIN, MARGIN),
+                scale: 1.0,
+            },
+            combo: WidgetLayout {
+                anchor: Anchor::TopLeft,
+                offset: (MARGIN, MARGIN + SCORE_HEIGHT),
+                scale: 1.0,
+            },
+            boost: WidgetLayout {
+                anchor: Anchor::BottomCenter,
+                offset: (0.0, MARGIN),
+                scale: 1.0,
+            },
+            kill_feed: WidgetLayout {
+                anchor: Anchor::BottomRight,
+                offset: (MARGIN, MARGIN),
+                scale: 1.0,
+            },
+        }
+    }
+}
+
+impl HudLayout {
+    pub fn get(&self, widget: HudWidget) -> &WidgetLayout {
+        match widget {
+            HudWidget::Score => &self.score,
+            HudWidget::Combo => &self.combo,
+            HudWidget::Boost => &self.boost,
+            HudWidget::KillFeed => &self.kill_feed,
+        }
+    }
+
+    pub fn get_mut(&mut self, widget: HudWidget) -> &mut WidgetLayout {
+        match widget {
+            HudWidget::Score => &mut self.score,
+            HudWidget::Combo => &mut self.combo,
+            HudWidget::Boost => &mut self.boost,
+            HudWidget::KillFeed => &mut self.kill_feed,
+        }
+    }
+}
+
+// Root node of a HUD widget, placed by the layout in the settings
+#[derive(Component, Debug, Clone, Copy)]
+pub struct HudAnchor(pub HudWidget);
+
+// Full screen node the HUD layout editor's widgets are dragged around in
+#[derive(Component)]
+pub struct HudCanvas;
+
+// Widget being dragged in the editor, where it was grabbed from its center and where
+// it would be dropped
+#[derive(Resource)]
+struct Dragging {
+    entity: Entity,
+    widget: HudWidget,
+    grab: Vec2,
+    layout: WidgetLayout,
+}
+
+// `style` moved to place a node of `size` as `layout` has it. Nodes scale about their
+// center, so it's pushed in from the edges it's anchored to by what it grows past them.
+fn place(layout: &WidgetLayout, size: Vec2, style: &Style) -> Style {
+    let grown = (layout.scale - 1.0) * size / 2.0;
+    let (x, y) = layout.offset;
+    let mut placed = Style {
+        left: Val::Auto,
+        right: Val::Auto,
+        top: Val::Auto,
+        bottom: Val::Auto,
+        margin: UiRect::default(),
+        ..style.clone()
+    };
+    match layout.anchor.column() {
+        0 => placed.left = Val::Px(x + grown.x),
+        1 => {
+            placed.left = Val::Percent(50.0);
+            placed.margin.left = Val::Px(x - size.x / 2.0);
+        }
+        _ => placed.right = Val::Px(x + grown.x),
+    }
+    if layout.anchor.top() {
+        placed.top = Val::Px(y + grown.y);
+    } else {
+        placed.bottom = Val::Px(y + grown.y);
+    }
+    placed
+}
+
+// the layout putting a widget of `size` scaled by `scale` with its center at `center`,
+// on a screen of `screen` size, anchored to the nearest part of the screen
+fn layout_at(center: Vec2, size: Vec2, scale: f32, screen: Vec2) -> WidgetLayout {
+    let half = size * scale / 2.0;
+    let column = (center.x / screen.x * 3.0).clamp(0.0, 2.0) as u8;
+    let top = center.y < screen.y / 2.0;
+    let x = match column {
+        0 => center.x - half.x,
+        1 => center.x - screen.x / 2.0,
+        _ => screen.x - center.x - half.x,
+    };
+    let y = if top {
+        center.y - half.y
+    } else {
+        screen.y - center.y - half.y
+    };
+    let min_x = if column == 1 { -MAX_OFFSET } else { 0.0 };
+    WidgetLayout {
+        anchor: Anchor::from_parts(column, top),
+        offset: (
+            x.clamp(min_x, MAX_OFFSET).round(),
+            y.clamp(0.0, MAX_OFFSET).round(),
+        ),
+        scale,
+    }
+}
+
+// system to place and scale every HUD widget as the layout has it, as they're spawned,
+// resized or the layout changes
+fn place_widgets(
+    settings: Res<Settings>,
+    dragging: Option<Res<Dragging>>,
+    mut widget_query: Query<(Entity, &HudAnchor, Ref<Node>, &mut Style, &mut Transform)>,
+) {
+    for (entity, HudAnchor(widget), node, mut style, mut transform) in &mut widget_query {
+        if !(settings.is_changed() || node.is_changed()) {
+            continue;
+        }
+        // the one being dragged is placed where it would be dropped instead
+        if dragging.as_ref().is_some_and(|drag| drag.entity == entity) {
+            continue;
+        }
+        let layout = settings.hud_layout.get(*widget);
+        let placed = place(layout, node.size(), &style);
+        if *style != placed {
+            *style = placed;
+        }
+        let scale = Vec3::new(layout.scale, layout.scale, 1.0);
+        if transform.scale != scale {
+            transform.scale = scale;
+        }
+    }
+}
+
+// system to drag widgets around the HUD layout editor with the mouse, snapping them to
+// the nearest corner or edge, and keep where they're dropped
+fn drag_widgets(
+    mut commands: Commands,
+    mouse: Res<ButtonInput<MouseButton>>,
+    mut settings: ResMut<Settings>,
+    dragging: Option<ResMut<Dragging>>,
+    canvas_query: Query<(&RelativeCursorPosition, &Node), With<HudCanvas>>,
+    mut widget_query: Query<(Entity, &HudAnchor, &Node, &GlobalTransform, &mut Style)>,
+) {
+    let Ok((cursor, canvas)) = canvas_query.get_single() else {
+        if dragging.is_some() {
+            commands.remove_resource::<Dragging>();
+        }
+        return;
+    };
+    let screen = canvas.size();
+    let Some(cursor) = cursor.normalized.map(|position| position * screen) else {
+        return;
+    };
+    match dragging {
+        None => {
+            if !mouse.just_pressed(MouseButton::Left) {
+                return;
+            }
+            let grabbed = widget_query
+                .iter()
+                .find(|(_, HudAnchor(widget), node, transform, _)| {
+                    let scale = settings.hud_layout.get(*widget).scale;
+                    Rect::from_center_size(transform.translation().truncate(), node.size() * scale)
+                        .contains(cursor)
+                });
+            if let Some((entity, HudAnchor(widget), _, transform, _)) = grabbed {
+                commands.insert_resource(Dragging {
+                    entity,
+                    widget: *widget,
+                    grab: cursor - transform.translation().truncate(),
+                    layout: *settings.hud_layout.get(*widget),
+                });
+            }
+        }
+        Some(mut drag) => {
+            if mouse.just_released(MouseButton::Left) {
+                *settings.hud_layout.get_mut(drag.widget) = drag.layout;
+                commands.remove_resource::<Dragging>();
+                return;
+            }
+            let Ok((_, _, node, _, mut style)) = widget_query.get_mut(drag.entity) else {
+                commands.remove_resource::<Dragging>();
+                return;
+            };
+            let layout = layout_at(cursor - drag.grab, node.size(), drag.layout.scale, screen);
+            if layout != drag.layout {
+                drag.layout = layout;
+                *style = place(&layout, node.size(), &style);
+            }
+        }
+    }
+}
+
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PostUpdate, place_widgets.before(bevy::ui::UiSystem::Layout))
+            .add_systems(Update, drag_widgets);
+    }
+}
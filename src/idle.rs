@@ -0,0 +1,95 @@
+use crate::pause::PauseState;
+use crate::GameState;
+use bevy::input::gamepad::GamepadEvent;
+use bevy::input::keyboard::KeyboardInput;
+use bevy::input::mouse::{MouseButtonInput, MouseMotion, MouseWheel};
+use bevy::input::touch::TouchInput;
+use bevy::prelude::*;
+
+// seconds without input in a menu or paused before the screen dims
+const IDLE_TIMEOUT: f32 = 180.0;
+// frame rate while dimmed, whatever the frame limit setting
+pub const IDLE_FRAME_LIMIT: u32 = 10;
+const DIM_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.75);
+
+// Whether the game has sat in a menu or paused without input for a while
+#[derive(Resource, Default, PartialEq, Eq)]
+pub struct Idle(pub bool);
+
+// Black veil over everything while idle
+#[derive(Component)]
+struct DimOverlay;
+
+// system to go idle after IDLE_TIMEOUT seconds without input in a menu or paused, and
+// wake up on any input. A run being played never goes idle.
+#[allow(clippy::too_many_arguments)]
+fn track_idle(
+    time: Res<Time<Real>>,
+    game_state: Res<State<GameState>>,
+    pause_state: Res<State<PauseState>>,
+    mut quiet_for: Local<f32>,
+    mut idle: ResMut<Idle>,
+    mut keys: EventReader<KeyboardInput>,
+    mut mouse_buttons: EventReader<MouseButtonInput>,
+    mut mouse_motion: EventReader<MouseMotion>,
+    mut mouse_wheel: EventReader<MouseWheel>,
+    mut touches: EventReader<TouchInput>,
+    mut gamepad: EventReader<GamepadEvent>,
+) {
+    let input = keys.read().count()
+        + mouse_buttons.read().count()
+        + mouse_motion.read().count()
+        + mouse_wheel.read().count()
+        + touches.read().count()
+        + gamepad.read().count()
+        > 0;
+    let unattended =
+        *game_state.get() == GameState::Menu || *pause_state.get() == PauseState::Paused;
+    if input || !unattended {
+        *quiet_for = 0.0;
+    } else {
+        *quiet_for += time.delta_seconds();
+    }
+    idle.set_if_neq(Idle(*quiet_for >= IDLE_TIMEOUT));
+}
+
+// system to dim the screen while idle
+fn dim_screen(
+    mut commands: Commands,
+    idle: Res<Idle>,
+    overlay_query: Query<Entity, With<DimOverlay>>,
+) {
+    if !idle.is_changed() {
+        return;
+    }
+    for overlay in &overlay_query {
+        commands.entity(overlay).despawn_recursive();
+    }
+    if !idle.0 {
+        return;
+    }
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                ..default()
+            },
+            background_color: DIM_COLOR.into(),
+            z_index: ZIndex::Global(100),
+            ..default()
+        },
+        DimOverlay,
+    ));
+}
+
+pub struct IdlePlugin;
+
+impl Plugin for IdlePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Idle>()
+            .add_systems(PreUpdate, track_idle)
+            .add_systems(Update, dim_screen);
+    }
+}
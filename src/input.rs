@@ -0,0 +1,538 @@
+mod gamepad;
+
+pub use gamepad::{read_gamepad, GamepadKind, GamepadTuning, StickAxis};
+
+use crate::settings::Settings;
+use bevy::input::mouse::MouseWheel;
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+use bevy::ui::UiSystem;
+use bevy::utils::{HashMap, HashSet};
+use serde::{Deserialize, Serialize};
+
+// how long a single notch of scrolling down keeps the duck action held
+const SCROLL_DUCK_TIME: f32 = 0.3;
+
+// Things the player can do, independent of the device used to do them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Action {
+    Jump,
+    Run,
+    Duck,
+    MoveLeft,
+    MoveRight,
+    // set off the mega-dash once the boost meter is full
+    Boost,
+    Pause,
+    // tear the run down and start another, from a run or the screen it ended on
+    Restart,
+    // a higher jump once the upgrade is bought, a plain one until then. Bound to a chord
+    // on every preset.
+    SuperJump,
+    // show or hide the stats panel, and move it to the next corner
+    StatsPanel,
+    StatsCorner,
+}
+
+impl Action {
+    pub const ALL: [Action; 11] = [
+        Action::Jump,
+        Action::Run,
+        Action::Duck,
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::Boost,
+        Action::Pause,
+        Action::Restart,
+        Action::SuperJump,
+        Action::StatsPanel,
+        Action::StatsCorner,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::Jump => "Jump",
+            Action::Run => "Run",
+            Action::Duck => "Duck",
+            Action::MoveLeft => "Move left",
+            Action::MoveRight => "Move right",
+            Action::Boost => "Boost",
+            Action::Pause => "Pause",
+            Action::Restart => "Restart",
+            Action::SuperJump => "Super jump",
+            Action::StatsPanel => "Stats panel",
+            Action::StatsCorner => "Stats corner",
+        }
+    }
+
+    // whether the action plays the run, rather than only changing what's on screen
+    pub fn is_gameplay(&self) -> bool {
+        !matches!(self, Action::StatsPanel | Action::StatsCorner)
+    }
+}
+
+// keys that can start a chord, held down before its key
+const MODIFIERS: [KeyCode; 6] = [
+    KeyCode::ShiftLeft,
+    KeyCode::ShiftRight,
+    KeyCode::ControlLeft,
+    KeyCode::ControlRight,
+    KeyCode::AltLeft,
+    KeyCode::AltRight,
+];
+
+pub fn is_modifier(key: KeyCode) -> bool {
+    MODIFIERS.contains(&key)
+}
+
+// A key pressed while a modifier is held, e.g. Shift+Space
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Chord {
+    pub modifier: KeyCode,
+    pub key: KeyCode,
+}
+
+impl Chord {
+    pub fn name(&self) -> String {
+        format!("{}+{}", key_name(self.modifier), key_name(self.key))
+    }
+}
+
+// Named sets of keyboard bindings selectable from the controls menu
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ControlPreset {
+    #[default]
+    Default,
+    LeftHanded,
+    Wasd,
+    OneHanded,
+    // bindings were changed by hand
+    Custom,
+}
+
+impl ControlPreset {
+    // presets offered in the controls menu, in cycling order
+    pub const SELECTABLE: [ControlPreset; 4] = [
+        ControlPreset::Default,
+        ControlPreset::LeftHanded,
+        ControlPreset::Wasd,
+        ControlPreset::OneHanded,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ControlPreset::Default => "Default",
+            ControlPreset::LeftHanded => "Left-handed",
+            ControlPreset::Wasd => "WASD",
+            ControlPreset::OneHanded => "One-handed",
+            ControlPreset::Custom => "Custom",
+        }
+    }
+
+    // the preset after this one in the controls menu
+    pub fn next(&self) -> ControlPreset {
+        let index = Self::SELECTABLE
+            .iter()
+            .position(|preset| preset == self)
+            .map_or(0, |index| index + 1);
+        Self::SELECTABLE[index % Self::SELECTABLE.len()]
+    }
+}
+
+// Keyboard bindings for every action. While a chord is held its key counts for the chord
+// alone, not for the actions the key is bound to on its own; the modifier keeps counting
+// for its own action, so Shift+Space doesn't stop Shift running.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InputMap {
+    pub preset: ControlPreset,
+    bindings: HashMap<Action, Vec<KeyCode>>,
+    // missing from maps saved before chords, filled in by bind_missing
+    #[serde(default)]
+    chords: HashMap<Action, Vec<Chord>>,
+}
+
+impl Default for InputMap {
+    fn default() -> Self {
+        InputMap::preset(ControlPreset::Default)
+    }
+}
+
+impl InputMap {
+    pub fn preset(preset: ControlPreset) -> Self {
+        use KeyCode::*;
+        let bindings: [(Action, Vec<KeyCode>); 10] = match preset {
+            ControlPreset::Default | ControlPreset::Custom => [
+                (Action::Jump, vec![Space]),
+                (Action::Run, vec![ShiftLeft]),
+                (Action::Duck, vec![ArrowDown]),
+                (Action::MoveLeft, vec![ArrowLeft]),
+                (Action::MoveRight, vec![ArrowRight]),
+                (Action::Boost, vec![KeyX]),
+                (Action::Pause, vec![Escape, KeyP]),
+                (Action::Restart, vec![KeyR]),
+                (Action::StatsPanel, vec![F6]),
+                (Action::StatsCorner, vec![F7]),
+            ],
+            // movement under the left hand, actions under the right
+            ControlPreset::LeftHanded => [
+                (Action::Jump, vec![Numpad0, Enter]),
+                (Action::Run, vec![ShiftRight]),
+                (Action::Duck, vec![KeyS]),
+                (Action::MoveLeft, vec![KeyA]),
+                (Action::MoveRight, vec![KeyD]),
+                (Action::Boost, vec![Numpad1]),
+                (Action::Pause, vec![Escape, KeyP]),
+                (Action::Restart, vec![KeyR]),
+                (Action::StatsPanel, vec![F6]),
+                (Action::StatsCorner, vec![F7]),
+            ],
+            ControlPreset::Wasd => [
+                (Action::Jump, vec![KeyW, Space]),
+                (Action::Run, vec![ShiftLeft]),
+                (Action::Duck, vec![KeyS]),
+                (Action::MoveLeft, vec![KeyA]),
+                (Action::MoveRight, vec![KeyD]),
+                (Action::Boost, vec![KeyE]),
+                (Action::Pause, vec![Escape, KeyP]),
+                (Action::Restart, vec![KeyR]),
+                (Action::StatsPanel, vec![F6]),
+                (Action::StatsCorner, vec![F7]),
+            ],
+            // everything around the arrow keys
+            ControlPreset::OneHanded => [
+                (Action::Jump, vec![ArrowUp]),
+                (Action::Run, vec![ShiftRight, ControlRight]),
+                (Action::Duck, vec![ArrowDown]),
+                (Action::MoveLeft, vec![ArrowLeft]),
+                (Action::MoveRight, vec![ArrowRight]),
+                (Action::Boost, vec![End]),
+                (Action::Pause, vec![Escape]),
+                (Action::Restart, vec![Delete]),
+                (Action::StatsPanel, vec![F6]),
+                (Action::StatsCorner, vec![F7]),
+            ],
+        };
+        // the run key held with the jump key
+        let (modifier, key) = match preset {
+            ControlPreset::Default | ControlPreset::Custom | ControlPreset::Wasd => {
+                (ShiftLeft, Space)
+            }
+            ControlPreset::LeftHanded => (ShiftRight, Numpad0),
+            ControlPreset::OneHanded => (ShiftRight, ArrowUp),
+        };
+        InputMap {
+            preset,
+            bindings: bindings.into_iter().collect(),
+            chords: [(Action::SuperJump, vec![Chord { modifier, key }])]
+                .into_iter()
+                .collect(),
+        }
+    }
+
+    pub fn keys(&self, action: Action) -> &[KeyCode] {
+        self.bindings.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn chords(&self, action: Action) -> &[Chord] {
+        self.chords.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    // every binding of an action by name, single keys first
+    pub fn binding_names(&self, action: Action) -> Vec<String> {
+        let keys = self.keys(action).iter().map(|key| key_name(*key));
+        keys.chain(self.chords(action).iter().map(Chord::name))
+            .collect()
+    }
+
+    // actions held with the keys `pressed` tells are down, chords taking their keys
+    // from the actions bound to those keys alone
+    pub fn held(&self, pressed: impl Fn(KeyCode) -> bool) -> Vec<Action> {
+        let mut held = Vec::new();
+        let mut taken = Vec::new();
+        for action in Action::ALL {
+            for chord in self.chords(action) {
+                if pressed(chord.modifier) && pressed(chord.key) {
+                    held.push(action);
+                    taken.push(chord.key);
+                }
+            }
+        }
+        for action in Action::ALL {
+            let free = |key: &&KeyCode| !taken.contains(*key);
+            if self
+                .keys(action)
+                .iter()
+                .filter(free)
+                .any(|key| pressed(*key))
+            {
+                held.push(action);
+            }
+        }
+        held
+    }
+
+    // bind the actions a map saved before they existed to the keys of its preset, where
+    // those keys are still free
+    pub fn bind_missing(&mut self) {
+        let preset = InputMap::preset(self.preset);
+        for action in Action::ALL {
+            if self.bindings.contains_key(&action) {
+                continue;
+            }
+            let free: Vec<KeyCode> = preset
+                .keys(action)
+                .iter()
+                .filter(|key| !self.bindings.values().any(|keys| keys.contains(key)))
+                .copied()
+                .collect();
+            self.bindings.insert(action, free);
+        }
+        for action in Action::ALL {
+            if self.chords.contains_key(&action) {
+                continue;
+            }
+            let free: Vec<Chord> = preset
+                .chords(action)
+                .iter()
+                .filter(|chord| !self.chords.values().any(|chords| chords.contains(chord)))
+                .copied()
+                .collect();
+            self.chords.insert(action, free);
+        }
+    }
+
+    // replace the bindings of an action with a single key, taking it away from other actions
+    pub fn rebind(&mut self, action: Action, key: KeyCode) {
+        for keys in self.bindings.values_mut() {
+            keys.retain(|bound| *bound != key);
+        }
+        self.bindings.insert(action, vec![key]);
+        self.chords.insert(action, Vec::new());
+        self.preset = ControlPreset::Custom;
+    }
+
+    // replace the bindings of an action with a chord, taking it away from other actions.
+    // The chord's keys stay bound to whatever they're bound to on their own.
+    pub fn rebind_chord(&mut self, action: Action, chord: Chord) {
+        for chords in self.chords.values_mut() {
+            chords.retain(|bound| *bound != chord);
+        }
+        self.chords.insert(action, vec![chord]);
+        self.bindings.insert(action, Vec::new());
+        self.preset = ControlPreset::Custom;
+    }
+}
+
+// human readable name of a key, e.g. "A" rather than "KeyA"
+pub fn key_name(key: KeyCode) -> String {
+    let name = format!("{:?}", key);
+    match name
+        .strip_prefix("Key")
+        .or_else(|| name.strip_prefix("Digit"))
+    {
+        Some(short) => short.to_string(),
+        None => name,
+    }
+}
+
+// Actions held by the player this frame, filled in by the input adapters or whichever
+// other provider drives the player. Gameplay only ever reads this, so every way of
+// controlling the player goes through the same movement code. A second player carries
+// their own as a component.
+#[derive(Resource, Component, Default, Debug)]
+pub struct ActionState {
+    pressed: HashSet<Action>,
+    previous: HashSet<Action>,
+    // analog horizontal movement, from -1 (left) to 1 (right)
+    axis: f32,
+}
+
+impl ActionState {
+    // roll last frame's actions over, before this frame's are pressed
+    pub fn begin(&mut self) {
+        self.previous = std::mem::take(&mut self.pressed);
+        self.axis = 0.0;
+    }
+
+    pub fn press(&mut self, action: Action) {
+        self.pressed.insert(action);
+    }
+
+    // push the movement axis, the furthest push of the frame wins
+    pub fn push_axis(&mut self, value: f32) {
+        if value.abs() > self.axis.abs() {
+            self.axis = value.clamp(-1.0, 1.0);
+        }
+    }
+
+    // how far and which way to move, from -1 (left) to 1 (right)
+    pub fn move_axis(&self) -> f32 {
+        let right = self.pressed(Action::MoveRight) as i32 as f32;
+        let left = self.pressed(Action::MoveLeft) as i32 as f32;
+        let digital = right - left;
+        if digital.abs() > self.axis.abs() {
+            digital
+        } else {
+            self.axis
+        }
+    }
+
+    // drop everything but pausing, for a provider taking over the player from the devices
+    pub fn release_movement(&mut self) {
+        self.pressed.retain(|action| *action == Action::Pause);
+        self.axis = 0.0;
+    }
+
+    pub fn pressed(&self, action: Action) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.pressed.contains(&action) && !self.previous.contains(&action)
+    }
+
+    pub fn just_released(&self, action: Action) -> bool {
+        !self.pressed.contains(&action) && self.previous.contains(&action)
+    }
+}
+
+// Kind of device the player used most recently, so prompts can show matching glyphs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum InputDevice {
+    #[default]
+    Keyboard,
+    Mouse,
+    Gamepad(GamepadKind),
+}
+
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LastInputDevice(pub InputDevice);
+
+// What drives the player: the local devices, or a provider standing in for them
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ActionSource {
+    #[default]
+    Devices,
+    // the built in bot, see autopilot.rs
+    Autopilot,
+}
+
+// Gamepads a second player joined with, which no longer drive the first
+#[derive(Resource, Debug, Default)]
+pub struct ClaimedGamepads(pub HashSet<Gamepad>);
+
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ActionSet {
+    // roll over last frame's actions
+    Begin,
+    // input adapters translate device input into actions
+    Collect,
+    // the provider selected by ActionSource, when it isn't the devices, replaces what
+    // they collected
+    Provide,
+}
+
+fn begin_actions(mut actions: ResMut<ActionState>) {
+    actions.begin();
+}
+
+// keyboard adapter: press every action bound to a held key or chord
+fn keyboard_actions(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    settings: Res<Settings>,
+    mut actions: ResMut<ActionState>,
+    mut last_device: ResMut<LastInputDevice>,
+) {
+    for action in settings.controls.held(|key| keyboard_input.pressed(key)) {
+        actions.press(action);
+    }
+    if keyboard_input.get_just_pressed().next().is_some() {
+        last_device.set_if_neq(LastInputDevice(InputDevice::Keyboard));
+    }
+}
+
+// Keeps the duck action held for a moment after the wheel was scrolled down
+#[derive(Resource)]
+struct ScrollDuck(Timer);
+
+impl Default for ScrollDuck {
+    fn default() -> Self {
+        let mut timer = Timer::from_seconds(SCROLL_DUCK_TIME, TimerMode::Once);
+        timer.tick(timer.duration());
+        ScrollDuck(timer)
+    }
+}
+
+// mouse adapter: left click jumps, holding right click runs, middle click boosts and
+// scrolling down ducks. A left click on a button of the UI is left to the button, until
+// it's let go, so clicking Play doesn't start the run with a jump.
+#[allow(clippy::too_many_arguments)]
+fn mouse_actions(
+    time: Res<Time>,
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    mut wheel_events: EventReader<MouseWheel>,
+    mut scroll_duck: ResMut<ScrollDuck>,
+    mut actions: ResMut<ActionState>,
+    mut last_device: ResMut<LastInputDevice>,
+    interaction_query: Query<&Interaction>,
+    mut ui_click: Local<bool>,
+) {
+    if mouse_input.get_just_pressed().next().is_some() {
+        last_device.set_if_neq(LastInputDevice(InputDevice::Mouse));
+    }
+    if mouse_input.just_pressed(MouseButton::Left) {
+        *ui_click = interaction_query
+            .iter()
+            .any(|interaction| *interaction == Interaction::Pressed);
+    }
+    if !mouse_input.pressed(MouseButton::Left) {
+        *ui_click = false;
+    }
+    if mouse_input.pressed(MouseButton::Left) && !*ui_click {
+        actions.press(Action::Jump);
+    }
+    if mouse_input.pressed(MouseButton::Right) {
+        actions.press(Action::Run);
+    }
+    if mouse_input.pressed(MouseButton::Middle) {
+        actions.press(Action::Boost);
+    }
+    scroll_duck.0.tick(time.delta());
+    if wheel_events.read().filter(|event| event.y < 0.0).count() > 0 {
+        scroll_duck.0.reset();
+    }
+    if !scroll_duck.0.finished() {
+        actions.press(Action::Duck);
+    }
+}
+
+pub struct ActionsPlugin;
+
+impl Plugin for ActionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ActionState>()
+            .init_resource::<ScrollDuck>()
+            .init_resource::<LastInputDevice>()
+            .init_resource::<ActionSource>()
+            .init_resource::<ClaimedGamepads>()
+            .configure_sets(
+                PreUpdate,
+                (ActionSet::Begin, ActionSet::Collect, ActionSet::Provide)
+                    .chain()
+                    .after(InputSystem),
+            )
+            .add_systems(PreUpdate, begin_actions.in_set(ActionSet::Begin))
+            .add_systems(
+                PreUpdate,
+                (
+                    keyboard_actions,
+                    // after the UI has seen the click
+                    mouse_actions.after(UiSystem::Focus),
+                    gamepad::gamepad_connections,
+                    gamepad::gamepad_actions,
+                )
+                    .chain()
+                    .in_set(ActionSet::Collect),
+            );
+    }
+}
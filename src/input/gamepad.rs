@@ -0,0 +1,222 @@
+use super::{Action, ActionState, ClaimedGamepads, InputDevice, LastInputDevice};
+use crate::settings::Settings;
+use crate::toast::Toast;
+use bevy::input::gamepad::{GamepadConnection, GamepadConnectionEvent};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+// deadzones and trigger thresholds offered in the controls menu, in cycling order
+const THRESHOLD_STEPS: [f32; 7] = [0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8];
+
+// Direction of the left stick, each with a deadzone of its own
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StickAxis {
+    Horizontal,
+    Vertical,
+}
+
+impl StickAxis {
+    pub fn name(&self) -> &'static str {
+        match self {
+            StickAxis::Horizontal => "horizontal",
+            StickAxis::Vertical => "vertical",
+        }
+    }
+}
+
+// How far sticks and triggers have to travel before they count. Worn or cheap pads rest
+// a little off center, which used to read as phantom moves and runs.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct GamepadTuning {
+    // share of the stick's travel ignored around the center, per axis
+    pub deadzone_x: f32,
+    pub deadzone_y: f32,
+    // share of the trigger's travel needed to run
+    pub trigger_threshold: f32,
+}
+
+impl Default for GamepadTuning {
+    fn default() -> Self {
+        GamepadTuning {
+            deadzone_x: 0.5,
+            deadzone_y: 0.5,
+            trigger_threshold: 0.5,
+        }
+    }
+}
+
+// the step after `value`, wrapping around
+fn next_step(value: f32) -> f32 {
+    THRESHOLD_STEPS
+        .into_iter()
+        .find(|step| *step > value + 0.01)
+        .unwrap_or(THRESHOLD_STEPS[0])
+}
+
+impl GamepadTuning {
+    pub fn deadzone(&self, axis: StickAxis) -> f32 {
+        match axis {
+            StickAxis::Horizontal => self.deadzone_x,
+            StickAxis::Vertical => self.deadzone_y,
+        }
+    }
+
+    pub fn cycle_deadzone(&mut self, axis: StickAxis) {
+        match axis {
+            StickAxis::Horizontal => self.deadzone_x = next_step(self.deadzone_x),
+            StickAxis::Vertical => self.deadzone_y = next_step(self.deadzone_y),
+        }
+    }
+
+    pub fn cycle_trigger_threshold(&mut self) {
+        self.trigger_threshold = next_step(self.trigger_threshold);
+    }
+}
+
+// Button layout family of a gamepad, used to pick prompt glyphs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GamepadKind {
+    Xbox,
+    PlayStation,
+}
+
+impl GamepadKind {
+    // guess the layout from the name the OS reports, defaulting to Xbox style
+    fn from_name(name: &str) -> Self {
+        let name = name.to_lowercase();
+        let playstation = [
+            "playstation",
+            "dualshock",
+            "dualsense",
+            "sony",
+            "ps3",
+            "ps4",
+            "ps5",
+        ];
+        if playstation.iter().any(|hint| name.contains(hint)) {
+            GamepadKind::PlayStation
+        } else {
+            GamepadKind::Xbox
+        }
+    }
+}
+
+// press the actions held on `gamepad` into `actions`
+pub fn read_gamepad(
+    gamepad: Gamepad,
+    button_input: &ButtonInput<GamepadButton>,
+    button_axes: &Axis<GamepadButton>,
+    axes: &Axis<GamepadAxis>,
+    tuning: GamepadTuning,
+    actions: &mut ActionState,
+) {
+    let pressed = |button_type| button_input.pressed(GamepadButton::new(gamepad, button_type));
+    let axis = |axis_type| {
+        axes.get(GamepadAxis::new(gamepad, axis_type))
+            .unwrap_or(0.0)
+    };
+    let stick_x = axis(GamepadAxisType::LeftStickX);
+    let stick_y = axis(GamepadAxisType::LeftStickY);
+    // read from how far the trigger is pulled, not bevy's fixed press threshold
+    let trigger = button_axes
+        .get(GamepadButton::new(
+            gamepad,
+            GamepadButtonType::RightTrigger2,
+        ))
+        .unwrap_or(0.0);
+    // the stick's travel past the deadzone, rescaled to the whole axis. It only moves the
+    // axis: pressing the move actions too would have them win over a partial push.
+    if stick_x.abs() > tuning.deadzone_x {
+        let travel = (stick_x.abs() - tuning.deadzone_x) / (1.0 - tuning.deadzone_x);
+        actions.push_axis(travel.min(1.0) * stick_x.signum());
+    }
+
+    // the right bumper makes a jump a super jump, as a chord does on the keyboard
+    if pressed(GamepadButtonType::South) {
+        if pressed(GamepadButtonType::RightTrigger) {
+            actions.press(Action::SuperJump);
+        } else {
+            actions.press(Action::Jump);
+        }
+    }
+    if pressed(GamepadButtonType::West) || trigger > tuning.trigger_threshold {
+        actions.press(Action::Run);
+    }
+    if pressed(GamepadButtonType::DPadDown) || stick_y < -tuning.deadzone_y {
+        actions.press(Action::Duck);
+    }
+    if pressed(GamepadButtonType::DPadLeft) {
+        actions.press(Action::MoveLeft);
+    }
+    if pressed(GamepadButtonType::DPadRight) {
+        actions.press(Action::MoveRight);
+    }
+    if pressed(GamepadButtonType::North) {
+        actions.press(Action::Boost);
+    }
+    if pressed(GamepadButtonType::Start) {
+        actions.press(Action::Pause);
+    }
+    if pressed(GamepadButtonType::Select) {
+        actions.press(Action::Restart);
+    }
+}
+
+// gamepad adapter: every connected gamepad feeds the action layer, so a pad plugged in
+// mid-run works immediately. Pads claimed by a second player drive only them.
+#[allow(clippy::too_many_arguments)]
+pub(super) fn gamepad_actions(
+    gamepads: Res<Gamepads>,
+    claimed: Res<ClaimedGamepads>,
+    button_input: Res<ButtonInput<GamepadButton>>,
+    button_axes: Res<Axis<GamepadButton>>,
+    axes: Res<Axis<GamepadAxis>>,
+    settings: Res<Settings>,
+    mut actions: ResMut<ActionState>,
+    mut last_device: ResMut<LastInputDevice>,
+) {
+    for gamepad in gamepads.iter() {
+        if claimed.0.contains(&gamepad) {
+            continue;
+        }
+        read_gamepad(
+            gamepad,
+            &button_input,
+            &button_axes,
+            &axes,
+            settings.gamepad,
+            &mut actions,
+        );
+        if button_input
+            .get_just_pressed()
+            .any(|button| button.gamepad == gamepad)
+        {
+            let kind = GamepadKind::from_name(gamepads.name(gamepad).unwrap_or_default());
+            last_device.set_if_neq(LastInputDevice(InputDevice::Gamepad(kind)));
+        }
+    }
+}
+
+// system to announce gamepads being plugged in or removed and switch prompts to match
+pub(super) fn gamepad_connections(
+    mut events: EventReader<GamepadConnectionEvent>,
+    mut toasts: EventWriter<Toast>,
+    mut last_device: ResMut<LastInputDevice>,
+) {
+    for event in events.read() {
+        match &event.connection {
+            GamepadConnection::Connected(info) => {
+                toasts.send(Toast(format!("Controller connected: {}", info.name)));
+                let kind = GamepadKind::from_name(&info.name);
+                last_device.set_if_neq(LastInputDevice(InputDevice::Gamepad(kind)));
+            }
+            GamepadConnection::Disconnected => {
+                toasts.send(Toast("Controller disconnected".to_string()));
+                if matches!(last_device.0, InputDevice::Gamepad(_)) {
+                    last_device.set_if_neq(LastInputDevice(InputDevice::Keyboard));
+                }
+            }
+        }
+    }
+}
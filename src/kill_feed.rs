@@ -0,0 +1,137 @@
+use crate::game_time::GameTime;
+use crate::hud::{HudAnchor, HudWidget};
+use crate::score::{PointSource, Scored};
+use crate::stream::HudText;
+use crate::{GameState, RunEntity};
+use bevy::prelude::*;
+
+// how many entries the feed shows at most, the oldest dropping off first
+const FEED_LENGTH: usize = 5;
+// seconds an entry stays up, the last of them fading out
+const ENTRY_TIME: f32 = 3.0;
+const ENTRY_FADE: f32 = 0.6;
+const FEED_FONT_SIZE: f32 = 12.0;
+
+// Column of the latest point sources, newest at the bottom
+#[derive(Component)]
+struct KillFeed;
+
+// Line of the feed, counting the points from one source scored back to back
+#[derive(Component)]
+struct FeedEntry {
+    source: PointSource,
+    count: u32,
+    points: u32,
+    timer: Timer,
+}
+
+impl FeedEntry {
+    fn text(&self) -> String {
+        if self.count > 1 {
+            format!("{} x{} +{}", self.source.name(), self.count, self.points)
+        } else {
+            format!("{} +{}", self.source.name(), self.points)
+        }
+    }
+}
+
+fn spawn_kill_feed(mut commands: Commands) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::FlexEnd,
+                row_gap: Val::Px(2.0),
+                ..default()
+            },
+            ..default()
+        },
+        KillFeed,
+        HudAnchor(HudWidget::KillFeed),
+        RunEntity,
+    ));
+}
+
+// system to add the points scored to the feed, counting them into the newest entry
+// while they keep coming from the same source
+fn feed_scores(
+    mut commands: Commands,
+    mut scored_events: EventReader<Scored>,
+    feed_query: Query<(Entity, Option<&Children>), With<KillFeed>>,
+    mut entry_query: Query<(&mut FeedEntry, &mut Text)>,
+) {
+    let Ok((feed, children)) = feed_query.get_single() else {
+        return;
+    };
+    let mut entries: Vec<Entity> = children.map_or(Vec::new(), |children| children.to_vec());
+    for scored in scored_events.read() {
+        let newest = entries
+            .last()
+            .and_then(|entity| entry_query.get_mut(*entity).ok())
+            .filter(|(entry, _)| entry.source == scored.source);
+        if let Some((mut entry, mut text)) = newest {
+            entry.count += 1;
+            entry.points += scored.points;
+            entry.timer.reset();
+            text.sections[0].value = entry.text();
+            text.sections[0].style.color.set_a(1.0);
+            continue;
+        }
+        let entry = FeedEntry {
+            source: scored.source,
+            count: 1,
+            points: scored.points,
+            timer: Timer::from_seconds(ENTRY_TIME, TimerMode::Once),
+        };
+        let entity = commands
+            .spawn((
+                TextBundle::from_section(
+                    entry.text(),
+                    TextStyle {
+                        font_size: FEED_FONT_SIZE,
+                        color: Color::WHITE,
+                        ..default()
+                    },
+                ),
+                HudText(FEED_FONT_SIZE),
+                entry,
+            ))
+            .id();
+        commands.entity(feed).add_child(entity);
+        entries.push(entity);
+        if entries.len() > FEED_LENGTH {
+            commands.entity(entries.remove(0)).despawn_recursive();
+        }
+    }
+}
+
+// system to fade entries out and drop them once they've been up long enough
+fn expire_entries(
+    mut commands: Commands,
+    time: Res<GameTime>,
+    mut entry_query: Query<(Entity, &mut FeedEntry, &mut Text)>,
+) {
+    for (entity, mut entry, mut text) in &mut entry_query {
+        if entry.timer.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        let alpha = (entry.timer.remaining_secs() / ENTRY_FADE).min(1.0);
+        text.sections[0].style.color.set_a(alpha);
+    }
+}
+
+pub struct KillFeedPlugin;
+
+impl Plugin for KillFeedPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Playing), spawn_kill_feed)
+            .add_systems(
+                Update,
+                (feed_scores, expire_entries)
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
@@ -0,0 +1,85 @@
+use crate::input::{Action, ActionSet, ActionState};
+use bevy::diagnostic::{Diagnostic, DiagnosticMeasurement, DiagnosticPath, DiagnosticsStore};
+use bevy::prelude::*;
+use bevy::render::{Extract, ExtractSchedule, Render, RenderApp, RenderSet};
+use bevy::utils::Instant;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+// diagnostic the timing HUD shows the input latency from
+pub const INPUT_LATENCY: DiagnosticPath = DiagnosticPath::const_new("input/latency");
+
+// Start of the frame an action was pressed on, None on frames without a new press
+#[derive(Resource, Default, Clone, Copy)]
+struct InputStamp(Option<Instant>);
+
+// Latencies measured by the render world since the main world last collected them
+#[derive(Resource, Clone, Default)]
+struct LatencySamples(Arc<Mutex<Vec<Duration>>>);
+
+// system to stamp the frames on which an action was pressed, with the time the frame's
+// input was read
+fn stamp_input(time: Res<Time<Real>>, actions: Res<ActionState>, mut stamp: ResMut<InputStamp>) {
+    let pressed = Action::ALL
+        .into_iter()
+        .any(|action| actions.just_pressed(action));
+    stamp.0 = time.last_update().filter(|_| pressed);
+}
+
+// system copying the frame's stamp over to the render world, every frame so an old stamp
+// is never measured twice
+fn extract_stamp(mut commands: Commands, stamp: Extract<Res<InputStamp>>) {
+    commands.insert_resource(**stamp);
+}
+
+// system to measure, once the frame was handed to the display, how long after its input
+// was read. The time the display takes to scan it out isn't known, so isn't counted.
+fn measure_latency(stamp: Option<Res<InputStamp>>, samples: Res<LatencySamples>) {
+    let Some(start) = stamp.and_then(|stamp| stamp.0) else {
+        return;
+    };
+    if let Ok(mut samples) = samples.0.lock() {
+        samples.push(start.elapsed());
+    }
+}
+
+// system to hand the latencies measured to bevy's diagnostics
+fn record_latency(samples: Res<LatencySamples>, mut store: ResMut<DiagnosticsStore>) {
+    let Ok(mut samples) = samples.0.lock() else {
+        return;
+    };
+    if store.get(&INPUT_LATENCY).is_none() {
+        store.add(Diagnostic::new(INPUT_LATENCY).with_suffix("ms"));
+    }
+    let Some(diagnostic) = store.get_mut(&INPUT_LATENCY) else {
+        return;
+    };
+    let now = Instant::now();
+    for latency in samples.drain(..) {
+        diagnostic.add_measurement(DiagnosticMeasurement {
+            time: now,
+            value: latency.as_secs_f64() * 1000.0,
+        });
+    }
+}
+
+pub struct LatencyPlugin;
+
+impl Plugin for LatencyPlugin {
+    fn build(&self, app: &mut App) {
+        let samples = LatencySamples::default();
+        app.init_resource::<InputStamp>()
+            .init_resource::<DiagnosticsStore>()
+            .insert_resource(samples.clone())
+            .add_systems(PreUpdate, stamp_input.after(ActionSet::Provide))
+            .add_systems(Last, record_latency);
+        // nothing to measure without a renderer
+        let Ok(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app
+            .insert_resource(samples)
+            .add_systems(ExtractSchedule, extract_stamp)
+            .add_systems(Render, measure_latency.in_set(RenderSet::Cleanup));
+    }
+}
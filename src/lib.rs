@@ -0,0 +1,946 @@
+//! The dinorun game as a library: add [`DinorunGamePlugin`] to an app that
+//! already has bevy's `DefaultPlugins` to embed the whole game.
+
+mod animation;
+mod arena;
+mod asset_pack;
+mod audio;
+mod autopilot;
+mod balance;
+mod barrel;
+mod biome;
+mod bonus;
+mod boost;
+mod bounds;
+#[cfg(feature = "dev_tools")]
+mod brush;
+mod captions;
+mod challenge;
+mod climb;
+mod coin;
+mod collision;
+mod cosmetics;
+mod difficulty;
+mod display;
+mod drop_in;
+mod dust;
+mod enemy;
+mod error;
+mod exit;
+mod export;
+mod feedback;
+mod frame_step;
+mod game_over;
+mod game_time;
+mod gate;
+mod ghost;
+mod grace;
+mod grade;
+mod health;
+mod heatmap;
+mod history;
+mod hud;
+mod idle;
+mod input;
+mod kill_feed;
+mod latency;
+#[cfg(feature = "dev_tools")]
+mod live_reload;
+mod locale;
+mod menu;
+mod mode;
+mod mutators;
+mod narration;
+mod news;
+mod occlusion;
+mod pause;
+mod powerup;
+mod profile;
+mod projectile;
+mod prompt;
+mod recovery;
+mod restart;
+mod rubber_band;
+mod run_info;
+mod save;
+mod score;
+mod screenshot;
+mod settings;
+#[cfg(feature = "dev_tools")]
+mod snapshot;
+mod startup;
+mod stats_panel;
+mod storage;
+mod stream;
+mod swing;
+mod timing;
+mod toast;
+mod tutorial;
+mod upgrades;
+mod validate;
+mod weather;
+mod web;
+
+use animation::AmbientClips;
+use arena::{ArenaPlugin, CameraMode};
+use audio::{PlaySfx, Sfx, SfxPlugin};
+use autopilot::AutopilotPlugin;
+use balance::BalancePlugin;
+use barrel::BarrelPlugin;
+use bevy::prelude::*;
+use bevy_parallax::{
+    CreateParallaxEvent, LayerData, LayerRepeat, LayerSpeed, ParallaxCameraComponent,
+    ParallaxMoveEvent, ParallaxPlugin, RepeatStrategy,
+};
+use biome::BiomePlugin;
+use bonus::BonusPlugin;
+use boost::BoostPlugin;
+use bounds::BoundsPlugin;
+#[cfg(feature = "dev_tools")]
+use brush::BrushPlugin;
+use captions::CaptionsPlugin;
+use challenge::ChallengePlugin;
+use climb::{ClimbPlugin, FootingSet};
+use coin::CoinPlugin;
+use collision::{Collider, CollisionPlugin};
+use cosmetics::CosmeticsPlugin;
+use difficulty::DifficultyPlugin;
+use display::DisplayPlugin;
+use drop_in::{DropInPlugin, SecondPlayer};
+use dust::{DustPlugin, DustPuff};
+use enemy::EnemyPlugin;
+use error::ErrorPlugin;
+use exit::ExitPlugin;
+use export::ExportPlugin;
+use feedback::FeedbackPlugin;
+use frame_step::{simulation_running, FrameStepPlugin};
+use game_over::GameOverPlugin;
+use gate::GatePlugin;
+use ghost::GhostPlugin;
+use grace::GracePlugin;
+use grade::GradePlugin;
+use health::{Health, HealthPlugin};
+use heatmap::HeatmapPlugin;
+use hud::HudPlugin;
+use idle::IdlePlugin;
+use input::{Action, ActionState, ActionsPlugin};
+use kill_feed::KillFeedPlugin;
+use latency::LatencyPlugin;
+#[cfg(feature = "dev_tools")]
+use live_reload::LiveReloadPlugin;
+use locale::LocalePlugin;
+use menu::MenuPlugin;
+use mode::{ActiveMode, GameModePlugin};
+use mutators::{configure_run, MutatorsPlugin, RunConfig};
+use narration::NarrationPlugin;
+use news::NewsPlugin;
+use occlusion::OcclusionPlugin;
+use pause::{PausePlugin, PauseState};
+use powerup::PowerUpPlugin;
+use profile::ProfilePlugin;
+use projectile::ProjectilePlugin;
+use prompt::PromptPlugin;
+use recovery::RecoveryPlugin;
+use restart::RestartPlugin;
+use rubber_band::{RubberBand, RubberBandPlugin};
+use run_info::RunInfoPlugin;
+use save::SavePlugin;
+use score::ScorePlugin;
+use screenshot::ScreenshotPlugin;
+use serde::{Deserialize, Serialize};
+use settings::SettingsPlugin;
+#[cfg(feature = "dev_tools")]
+use snapshot::SnapshotPlugin;
+use startup::{DeferredLoad, GameplayAssets, StartupPlugin};
+use stats_panel::StatsPanelPlugin;
+use stream::StreamPlugin;
+use swing::SwingPlugin;
+use timing::timed;
+#[cfg(feature = "dev_tools")]
+use timing::TimingPlugin;
+use toast::ToastPlugin;
+use tutorial::TutorialPlugin;
+use upgrades::UpgradesPlugin;
+use weather::{WeatherEffects, WeatherPlugin};
+
+pub use animation::{
+    AnimationLibrary, AsepriteSheet, MenuAnimation, PlaybackMode, SpriteAnimationPlugin,
+    SpriteAnimator, SpriteClip,
+};
+pub use asset_pack::{write_pack, AssetPackPlugin, PACK_FILE};
+pub use balance::Balance;
+pub use display::WindowSettings;
+pub use export::{export_saved, ExportFormat};
+pub use game_time::{GameClockSet, GameTime, GameTimePlugin};
+#[cfg(feature = "dev_tools")]
+pub use ghost::AuthorRecording;
+pub use input::ActionSource;
+pub use settings::Settings;
+pub use validate::{validate_balance, Finding, Problem};
+pub use web::WebPlugin;
+
+const PLAYER_SPRITE: &str = "player.png";
+// frames, clips and hitboxes of the player spritesheet, as exported from Aseprite
+const PLAYER_SHEET: &str = "player.aseprite.json";
+const BACKGROUND: &str = "background-sunset/sky.png";
+const FLOOR: &str = "background-sunset/ground.png";
+const MOUNTAINS: &str = "background-sunset/mountains.png";
+const FOREGROUND: &str = "background-sunset/foreground.png";
+
+// clips the player breaks a long idle with now and then, when the sheet has them, and
+// the shortest and longest seconds idle before each
+const IDLE_CLIPS: [&str; 2] = ["idle_blink", "idle_look"];
+const IDLE_WAIT: (f32, f32) = (3.0, 7.0);
+// slower than this, in pixels per frame, counts as standing still
+const STILL_SPEED: f32 = 0.05;
+
+// frames of the walk and run cycles where a foot touches the ground
+const FOOTSTEP_FRAMES: [usize; 4] = [0, 6, 12, 16];
+
+const GROUND_Y: f32 = -64.0;
+const PLAYER_SCALE: f32 = 4.0;
+// vertical scale of the player sprite while ducking
+const DUCK_SCALE: f32 = 2.5;
+const WALK_SPEED: f32 = 1.0;
+const RUN_SPEED: f32 = 1.5;
+const GRAVITY: f32 = 9.8;
+// Jumping parameters
+const JUMP_HEIGHT: f32 = 122.0;
+const JUMP_SPEED: f32 = 9.8 * 1.5;
+// how much higher a super jump goes, once unlocked
+const SUPER_JUMP_SCALE: f32 = 1.5;
+// Moving left and right, in pixels per frame: the player speeds up and slows down
+// rather than snapping to full speed, and turning around faster than SKID_SPEED skids
+// to a halt first, with little control, before heading the other way
+const MOVE_SPEED: f32 = 2.0;
+const MOVE_ACCEL: f32 = 0.25;
+const SKID_SPEED: f32 = 1.2;
+const SKID_TIME: f32 = 0.2;
+const SKID_DECEL: f32 = 0.15;
+// share of the usual acceleration left while skidding
+const SKID_CONTROL: f32 = 0.2;
+
+// how far the camera looks up while the player rises, and down while they fall fast
+const LOOK_UP: f32 = 24.0;
+const LOOK_DOWN: f32 = 16.0;
+// falling more than this per frame counts as falling fast
+const FAST_FALL: f32 = 6.0;
+// share of the way to the wanted height the camera covers each frame
+const LOOK_EASE: f32 = 0.08;
+// how far, and how many times a second, the camera sways while held over a boss arena
+const ARENA_DRIFT: f32 = 6.0;
+const ARENA_DRIFT_RATE: f32 = 0.4;
+// how far above the player the camera looks on a climb, to show more of what's ahead
+const CLIMB_LOOK: f32 = 80.0;
+
+// distance the player has to cover between two milestone fanfares
+const MILESTONE_DISTANCE: f32 = 1000.0;
+
+// Top level game state
+#[derive(States, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum GameState {
+    #[default]
+    Menu,
+    Playing,
+    // passed through for a frame by a quick restart, to tear the run down and start anew
+    Restarting,
+}
+
+// Systems that simulate a run; they only tick while playing, not paused and not frozen
+// by the frame step controls
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GameplaySet;
+
+// Marks entities that belong to the current run, despawned when leaving GameState::Playing
+#[derive(Component)]
+pub struct RunEntity;
+
+// Last milestone reached in the current run
+#[derive(Resource, Default)]
+struct LastMilestone(u32);
+
+// Event sent when the player covers another MILESTONE_DISTANCE, with how many they have
+#[derive(Event, Debug, Clone, Copy)]
+pub struct Milestone(pub u32);
+
+// Player state
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(dead_code)]
+enum PlayerState {
+    Idle,
+    Walking,
+    Jumping,
+    Running,
+    Falling,
+    Ducking,
+    Skidding,
+}
+
+// Player component
+#[derive(Component)]
+struct Player {
+    on_ground: bool,
+    state: PlayerState,
+    // pixels per frame the player moves left (negative) or right on top of the scrolling
+    move_speed: f32,
+    // seconds of skid left
+    skid: f32,
+    // height the jump under way rises to
+    jump_top: f32,
+    // height the player stands at on the ground: GROUND_Y, or a platform's on a climb
+    ground: f32,
+}
+
+// The player whose run it is, leaving out a second player who dropped in
+type PlayerOne = (With<Player>, Without<SecondPlayer>);
+
+// Keeps the player spritesheet loaded
+#[derive(Resource)]
+struct PlayerSheet(#[allow(dead_code)] Handle<AsepriteSheet>);
+
+// system to load the player's clips and set up the transitions between them
+fn load_player_animations(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut library: ResMut<AnimationLibrary>,
+    mut assets: ResMut<GameplayAssets>,
+) {
+    let sheet = asset_server.load(PLAYER_SHEET);
+    assets.0.push(sheet.clone().untyped());
+    commands.insert_resource(PlayerSheet(sheet));
+    library.set_transition("fall", "walk", "land");
+    library.set_transition("jump", "walk", "land");
+    library.set_transition("run", "idle", "skid");
+}
+
+// name of the clip the player plays in `state`, None keeps the current one
+fn player_clip(state: &PlayerState) -> Option<&'static str> {
+    match state {
+        PlayerState::Walking => Some("walk"),
+        PlayerState::Running => Some("run"),
+        PlayerState::Jumping => Some("jump"),
+        PlayerState::Falling => Some("fall"),
+        PlayerState::Idle => Some("idle"),
+        PlayerState::Skidding => Some("skid"),
+        PlayerState::Ducking => None,
+    }
+}
+
+// system to switch each player's animation to the one for its current state,
+// going through the transition clip between the two if the library has one
+fn change_animation(
+    library: Res<AnimationLibrary>,
+    mut player_query: Query<(&Player, &mut SpriteAnimator)>,
+) {
+    for (player, mut animator) in &mut player_query {
+        let Some(clip) = player_clip(&player.state).and_then(|name| library.clip(name)) else {
+            continue;
+        };
+        if animator.target().name == clip.name {
+            continue;
+        }
+        if let Some(transition) = library.transition(&animator.clip().name, &clip.name) {
+            animator.play_through(transition.clone(), clip.clone());
+            continue;
+        }
+        match player.state {
+            // keep the stride when switching between walking and running
+            PlayerState::Walking | PlayerState::Running => animator.play_in_step(clip.clone()),
+            _ => animator.play(clip.clone()),
+        }
+    }
+}
+
+// system to play a footstep whenever a grounded player's feet hit the floor
+fn player_footsteps(
+    player_query: Query<(&Player, Ref<TextureAtlas>)>,
+    mut sfx: EventWriter<PlaySfx>,
+) {
+    for (player, atlas) in &player_query {
+        if atlas.is_changed() && player.on_ground && FOOTSTEP_FRAMES.contains(&atlas.index) {
+            sfx.send(PlaySfx(Sfx::Footstep));
+        }
+    }
+}
+
+// system to move the players to the right with a speed that depends on their state. In a
+// boss arena or on a climb they stay put instead, kept between its walls.
+fn advance_player(
+    config: Res<RunConfig>,
+    camera_mode: Res<CameraMode>,
+    mut player_query: Query<(&Player, &mut Transform, Option<&RubberBand>)>,
+) {
+    for (player, mut transform, band) in &mut player_query {
+        if let CameraMode::Arena { left, right, .. } | CameraMode::Climb { left, right } =
+            *camera_mode
+        {
+            transform.translation.x = transform.translation.x.clamp(left, right);
+            continue;
+        }
+        let catch_up = band.map_or(1.0, |band| band.speed);
+        transform.translation.x += config.speed
+            * catch_up
+            * match player.state {
+                PlayerState::Running => RUN_SPEED,
+                PlayerState::Idle => 0.0,
+                _ => WALK_SPEED,
+            };
+    }
+}
+
+// system to continuously move the parallax layers by sending a ParallaxMoveEvent
+// knowing that there is only one world camera in the scene. While the player is in the
+// air the camera also drifts up or down a little, which the layers follow at their own
+// vertical speeds for a sense of height. Over a boss arena the camera stops scrolling and
+// only sways gently around the arena, and on a climb it follows the player up instead.
+fn move_camera_system(
+    camera_query: Query<(Entity, &Transform), With<ParallaxCameraComponent>>,
+    mut move_event_writer: EventWriter<ParallaxMoveEvent>,
+    player_query: Query<(&Player, &Transform), (PlayerOne, Without<ParallaxCameraComponent>)>,
+    config: Res<RunConfig>,
+    camera_mode: Res<CameraMode>,
+    time: Res<GameTime>,
+    mut last_y: Local<Option<f32>>,
+) {
+    let (Ok((player, transform)), Ok((camera, camera_transform))) =
+        (player_query.get_single(), camera_query.get_single())
+    else {
+        return;
+    };
+    if let CameraMode::Climb { left, right } = *camera_mode {
+        let target = Vec2::new(
+            (left + right) / 2.0,
+            transform.translation.y - GROUND_Y + CLIMB_LOOK,
+        );
+        move_event_writer.send(ParallaxMoveEvent {
+            camera_move_speed: (target - camera_transform.translation.truncate()) * LOOK_EASE,
+            camera,
+        });
+        return;
+    }
+    let mut camera_move_speed = Vec2::new(WALK_SPEED, 0.0);
+    if player.state == PlayerState::Running {
+        camera_move_speed = Vec2::new(RUN_SPEED, 0.0);
+    }
+    camera_move_speed *= config.speed;
+    if let CameraMode::Arena { center, .. } = *camera_mode {
+        let phase = time.elapsed_seconds() * ARENA_DRIFT_RATE * std::f32::consts::TAU;
+        let sway = center + ARENA_DRIFT * phase.sin();
+        camera_move_speed.x = (sway - camera_transform.translation.x) * LOOK_EASE;
+    }
+
+    // the camera sits at y = 0 with the player on the ground
+    let rise = last_y.map_or(0.0, |last_y| transform.translation.y - last_y);
+    *last_y = Some(transform.translation.y);
+    let look = if player.on_ground {
+        0.0
+    } else if rise > 0.0 {
+        LOOK_UP
+    } else if rise < -FAST_FALL {
+        -LOOK_DOWN
+    } else {
+        camera_transform.translation.y
+    };
+    camera_move_speed.y = (look - camera_transform.translation.y) * LOOK_EASE;
+
+    move_event_writer.send(ParallaxMoveEvent {
+        camera_move_speed,
+        camera,
+    });
+}
+
+fn setup(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    mut create_parallax: EventWriter<CreateParallaxEvent>,
+) {
+    // Setup your game here (camera, player, etc.)
+    let camera = commands
+        .spawn(Camera2dBundle {
+            camera_2d: Camera2d, // setup 2d camera
+            ..default()
+        })
+        .insert(ParallaxCameraComponent::default())
+        .id();
+
+    create_parallax.send(CreateParallaxEvent {
+        layers_data: parallax_layers(0.0, settings.video.low_spec),
+        camera,
+    });
+}
+
+// the ground and what's in front of it keep still vertically, under the player's feet,
+// while the layers further back follow the camera's look up and down more and more.
+// The layers are laid out around a camera at `camera_x`.
+// the mountains are left out in low-spec mode
+pub fn parallax_layers(camera_x: f32, low_spec: bool) -> Vec<LayerData> {
+    let scale = Vec2::new(4.0, 4.0);
+    let mut layers = vec![
+        LayerData {
+            path: FOREGROUND.to_string(),
+            speed: LayerSpeed::Bidirectional(0.1, 0.0),
+            repeat: LayerRepeat::horizontally(RepeatStrategy::Same),
+            tile_size: Vec2::new(288.0, 192.0),
+            cols: 1,
+            rows: 1,
+            scale,
+            z: 2.0,
+            position: Vec2::new(camera_x, scale.y * -32.0),
+            ..Default::default()
+        },
+        LayerData {
+            path: FLOOR.to_string(),
+            speed: LayerSpeed::Bidirectional(0.4, 0.0),
+            repeat: LayerRepeat::horizontally(RepeatStrategy::Same),
+            tile_size: Vec2::new(288.0, 192.0),
+            cols: 1,
+            rows: 1,
+            scale,
+            z: 1.0,
+            position: Vec2::new(camera_x, scale.y * -32.0),
+            ..Default::default()
+        },
+        LayerData {
+            path: MOUNTAINS.to_string(),
+            speed: LayerSpeed::Bidirectional(0.9, 0.6),
+            repeat: LayerRepeat::horizontally(RepeatStrategy::Same),
+            tile_size: Vec2::new(288.0, 192.0),
+            cols: 1,
+            rows: 1,
+            scale,
+            z: 0.4,
+            position: Vec2::new(camera_x, scale.y * -32.0),
+            ..Default::default()
+        },
+        LayerData {
+            path: BACKGROUND.to_string(),
+            speed: LayerSpeed::Bidirectional(1.0, 0.9),
+            repeat: LayerRepeat::horizontally(RepeatStrategy::Same),
+            tile_size: Vec2::new(288.0, 192.0),
+            cols: 1,
+            rows: 1,
+            scale,
+            z: 0.0,
+            position: Vec2::new(camera_x, scale.y * -32.0),
+            ..Default::default()
+        },
+    ];
+    if low_spec {
+        layers.retain(|layer| layer.path != MOUNTAINS);
+    }
+    layers
+}
+
+// the layers of a climb: the ground and what's in front of it are left behind below,
+// and the layers further back repeat upwards as well, following the camera up more and
+// more slowly the closer they are
+fn climb_layers(low_spec: bool) -> Vec<LayerData> {
+    let mut layers = parallax_layers(0.0, low_spec);
+    for layer in &mut layers {
+        if let LayerSpeed::Bidirectional(x, y) = layer.speed {
+            if y > 0.0 {
+                layer.speed = LayerSpeed::Bidirectional(x, y.max(0.8));
+                layer.repeat = LayerRepeat::both(RepeatStrategy::Same);
+            }
+        }
+    }
+    layers
+}
+
+// system to move the camera back to the start and rebuild the parallax layers for a new run
+fn reset_camera(
+    settings: Res<Settings>,
+    mode: Res<ActiveMode>,
+    mut camera_query: Query<(Entity, &mut Transform), With<ParallaxCameraComponent>>,
+    mut create_parallax: EventWriter<CreateParallaxEvent>,
+) {
+    let Ok((camera, mut transform)) = camera_query.get_single_mut() else {
+        return;
+    };
+    transform.translation.x = 0.0;
+    transform.translation.y = 0.0;
+    let layers_data = if mode.vertical() {
+        climb_layers(settings.video.low_spec)
+    } else {
+        parallax_layers(0.0, settings.video.low_spec)
+    };
+    create_parallax.send(CreateParallaxEvent {
+        layers_data,
+        camera,
+    });
+}
+
+// system to despawn everything that belongs to the run that just ended
+fn despawn_run(mut commands: Commands, query: Query<Entity, With<RunEntity>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// the player's sprite, animation, hitbox and health, standing on the ground at `x`
+fn player_bundle(
+    asset_server: &AssetServer,
+    library: &AnimationLibrary,
+    health: f32,
+    x: f32,
+) -> impl Bundle {
+    // Player entity from a spritesheet, laid out as described by its Aseprite export
+    let texture = asset_server.load(PLAYER_SPRITE);
+    let texture_atlas_layout = asset_server.load(format!("{}#layout", PLAYER_SHEET));
+    (
+        SpriteSheetBundle {
+            texture,
+            atlas: TextureAtlas {
+                layout: texture_atlas_layout,
+                index: 0,
+            },
+            transform: Transform {
+                translation: Vec3::new(x, GROUND_Y, 1.5),
+                scale: Vec3::splat(PLAYER_SCALE),
+                ..default()
+            },
+            ..default()
+        },
+        // until the sheet has loaded, change_animation picks the clip up once it's there
+        SpriteAnimator::new(library.clip("walk").cloned().unwrap_or_default()),
+        AmbientClips::new("idle", &IDLE_CLIPS, IDLE_WAIT),
+        // the whole sprite until the animation supplies the hitbox of the current pose
+        Collider(Rect::new(-8.0, -8.0, 8.0, 8.0)),
+        Health::new(health),
+        Player {
+            on_ground: true,
+            state: PlayerState::Walking,
+            move_speed: 0.0,
+            skid: 0.0,
+            jump_top: GROUND_Y + JUMP_HEIGHT,
+            ground: GROUND_Y,
+        },
+        RunEntity,
+    )
+}
+
+fn spawn_player(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    library: Res<AnimationLibrary>,
+    config: Res<RunConfig>,
+) {
+    commands.insert_resource(LastMilestone::default());
+    commands.spawn(player_bundle(
+        &asset_server,
+        &library,
+        config.player_health,
+        0.0,
+    ));
+}
+
+// system to move each player by the actions of their own: the shared ones for the
+// first player, those of the device they joined with for a second
+fn player_movement(
+    time: Res<GameTime>,
+    actions: Res<ActionState>,
+    mut player_position: Query<(&mut Player, &mut Transform, Option<&ActionState>)>,
+    mut sfx: EventWriter<PlaySfx>,
+    mut dust: EventWriter<DustPuff>,
+    config: Res<RunConfig>,
+    weather: Res<WeatherEffects>,
+) {
+    for (mut player, mut transform, own_actions) in &mut player_position {
+        let actions = own_actions.unwrap_or(&actions);
+        // duck while the action is held on the ground, squashing the sprite down onto the floor
+        let jump = actions.pressed(Action::Jump) || actions.pressed(Action::SuperJump);
+        if player.on_ground && actions.pressed(Action::Duck) && !jump {
+            if player.state != PlayerState::Ducking {
+                player.state = PlayerState::Ducking;
+                info!("Player state: {:?}", player.state);
+            }
+            transform.scale.y = DUCK_SCALE;
+            transform.translation.y = player.ground - (PLAYER_SCALE - DUCK_SCALE) * 8.0;
+        } else if player.state == PlayerState::Ducking {
+            transform.scale.y = PLAYER_SCALE;
+            transform.translation.y = player.ground;
+            player.state = if actions.pressed(Action::Run) {
+                PlayerState::Running
+            } else {
+                PlayerState::Walking
+            };
+            info!("Player state: {:?}", player.state);
+        }
+        if jump {
+            if player.on_ground {
+                let super_jump = config.super_jump && actions.pressed(Action::SuperJump);
+                let height = JUMP_HEIGHT * if super_jump { SUPER_JUMP_SCALE } else { 1.0 };
+                player.jump_top = player.ground + height;
+                player.on_ground = false;
+                player.state = PlayerState::Jumping;
+                info!("Player state: {:?}", player.state);
+                sfx.send(PlaySfx(Sfx::Jump));
+                transform.translation.y += JUMP_SPEED;
+            } else if player.state == PlayerState::Jumping {
+                transform.translation.y += JUMP_SPEED;
+                if transform.translation.y >= player.jump_top {
+                    transform.translation.y = player.jump_top;
+                    player.state = PlayerState::Falling;
+                    info!("Player state: {:?}", player.state);
+                }
+            }
+        }
+        // move left or right, slower with a stick pushed only part of the way. Speeding up
+        // and slowing down take longer on slick ground.
+        let target = MOVE_SPEED * actions.move_axis();
+        let accel = MOVE_ACCEL * weather.grip;
+        let reversing = target * player.move_speed < 0.0;
+        if reversing
+            && player.on_ground
+            && player.skid <= 0.0
+            && player.move_speed.abs() > SKID_SPEED
+        {
+            player.skid = SKID_TIME;
+            player.state = PlayerState::Skidding;
+            info!("Player state: {:?}", player.state);
+            sfx.send(PlaySfx(Sfx::Skid));
+            dust.send(DustPuff {
+                position: Vec2::new(transform.translation.x, GROUND_Y - 30.0),
+                direction: -player.move_speed.signum(),
+            });
+        }
+        if player.skid > 0.0 {
+            player.skid -= time.delta_seconds();
+            let slowed = (player.move_speed.abs() - SKID_DECEL * weather.grip).max(0.0);
+            player.move_speed = slowed * player.move_speed.signum();
+            player.move_speed += (target - player.move_speed).clamp(-accel, accel) * SKID_CONTROL;
+            if player.skid <= 0.0 && player.state == PlayerState::Skidding {
+                player.state = if actions.pressed(Action::Run) {
+                    PlayerState::Running
+                } else {
+                    PlayerState::Walking
+                };
+                info!("Player state: {:?}", player.state);
+            }
+        } else {
+            player.move_speed += (target - player.move_speed).clamp(-accel, accel);
+        }
+        // the wind pushes the player along whatever they do
+        transform.translation.x += (player.move_speed + weather.wind) * config.speed;
+
+        // change player state based on the run action
+        if actions.just_pressed(Action::Run) {
+            // change player state to running
+            player.state = PlayerState::Running;
+            info!("Player state: {:?}", player.state);
+        } else if actions.just_released(Action::Run) {
+            // change player state to walking
+            player.state = PlayerState::Walking;
+            info!("Player state: {:?}", player.state);
+        }
+
+        // if the player is on the ground, change the player state to walking
+        if transform.translation.y <= player.ground && !player.on_ground {
+            player.on_ground = true;
+            transform.translation.y = player.ground;
+            player.state = PlayerState::Walking;
+        }
+    }
+}
+
+// system to play a fanfare every time the player covers another MILESTONE_DISTANCE
+fn check_milestones(
+    mode: Res<ActiveMode>,
+    mut last_milestone: ResMut<LastMilestone>,
+    player_query: Query<&Transform, PlayerOne>,
+    mut sfx: EventWriter<PlaySfx>,
+    mut milestones: EventWriter<Milestone>,
+) {
+    let Ok(transform) = player_query.get_single() else {
+        return;
+    };
+    let along = mode.progress(transform.translation.truncate());
+    let milestone = (along.max(0.0) / MILESTONE_DISTANCE) as u32;
+    if milestone > last_milestone.0 {
+        last_milestone.0 = milestone;
+        info!("Milestone reached: {}", milestone);
+        sfx.send(PlaySfx(Sfx::MilestoneFanfare));
+        milestones.send(Milestone(milestone));
+    }
+}
+
+// how far a held jump lifts the feet off the ground, frame by frame until landing,
+// stepped the way player_movement and apply_gravity move the player
+fn jump_heights(gravity: f32) -> Vec<f32> {
+    let mut heights = Vec::new();
+    let mut y = 0.0;
+    let mut rising = true;
+    loop {
+        if rising {
+            y += JUMP_SPEED;
+            if y >= JUMP_HEIGHT {
+                y = JUMP_HEIGHT;
+                rising = false;
+            }
+        }
+        y -= GRAVITY * gravity;
+        if y <= 0.0 {
+            return heights;
+        }
+        heights.push(y);
+    }
+}
+
+// system to let a player standing still in a boss arena or on a climb, where the course
+// doesn't carry them along, fall idle, and walk on again once they move or the arena opens up
+fn settle_idle(
+    actions: Res<ActionState>,
+    camera_mode: Res<CameraMode>,
+    mut query: Query<(&mut Player, Option<&ActionState>)>,
+) {
+    let arena = matches!(
+        *camera_mode,
+        CameraMode::Arena { .. } | CameraMode::Climb { .. }
+    );
+    for (mut player, own_actions) in &mut query {
+        let actions = own_actions.unwrap_or(&actions);
+        let still = arena
+            && player.on_ground
+            && player.move_speed.abs() < STILL_SPEED
+            && actions.move_axis() == 0.0
+            && !actions.pressed(Action::Run);
+        match player.state {
+            PlayerState::Walking if still => player.state = PlayerState::Idle,
+            PlayerState::Idle if !still => player.state = PlayerState::Walking,
+            _ => continue,
+        }
+        info!("Player state: {:?}", player.state);
+    }
+}
+
+// apply gravity to the players in the air
+fn apply_gravity(config: Res<RunConfig>, mut query: Query<(&Player, &mut Transform)>) {
+    for (player, mut transform) in &mut query {
+        if !player.on_ground {
+            transform.translation.y -= GRAVITY * config.gravity;
+        }
+    }
+}
+
+/// Everything that makes up the game: states, input, menus, audio and the run itself.
+///
+/// Expects `DefaultPlugins` (with `ImagePlugin::default_nearest()` for crisp pixel art)
+/// to be added first. If a [`Settings`] resource is inserted before this plugin it is
+/// used as is, otherwise the settings are loaded from `settings.ron`.
+pub struct DinorunGamePlugin;
+
+impl Plugin for DinorunGamePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_state::<GameState>()
+            .configure_sets(
+                Update,
+                (GameClockSet, GameplaySet)
+                    .chain()
+                    .run_if(in_state(GameState::Playing))
+                    .run_if(in_state(PauseState::Unpaused))
+                    .run_if(simulation_running),
+            )
+            .add_plugins((
+                ParallaxPlugin,
+                SpriteAnimationPlugin,
+                CollisionPlugin,
+                SettingsPlugin,
+                DisplayPlugin,
+                SfxPlugin,
+                CaptionsPlugin,
+                FeedbackPlugin,
+                ActionsPlugin,
+                NarrationPlugin,
+                MenuPlugin,
+                ToastPlugin,
+                PausePlugin,
+                PromptPlugin,
+                TutorialPlugin,
+            ))
+            .add_plugins((
+                BalancePlugin,
+                GameTimePlugin,
+                GameModePlugin,
+                MutatorsPlugin,
+                EnemyPlugin,
+                HealthPlugin,
+                ProjectilePlugin,
+                ScorePlugin,
+                CoinPlugin,
+                GameOverPlugin,
+                SavePlugin,
+                RecoveryPlugin,
+                ErrorPlugin,
+                StatsPanelPlugin,
+                CosmeticsPlugin,
+            ))
+            .add_plugins((
+                UpgradesPlugin,
+                RunInfoPlugin,
+                ScreenshotPlugin,
+                IdlePlugin,
+                DustPlugin,
+                HeatmapPlugin,
+                GradePlugin,
+                StreamPlugin,
+                NewsPlugin,
+                RubberBandPlugin,
+                ChallengePlugin,
+                DifficultyPlugin,
+                RestartPlugin,
+                LocalePlugin,
+                DropInPlugin,
+            ))
+            .add_plugins((
+                ProfilePlugin,
+                ExportPlugin,
+                HudPlugin,
+                StartupPlugin,
+                ExitPlugin,
+                KillFeedPlugin,
+                GhostPlugin,
+            ))
+            // things met along the course
+            .add_plugins((
+                OcclusionPlugin,
+                BonusPlugin,
+                PowerUpPlugin,
+                GracePlugin,
+                BarrelPlugin,
+                SwingPlugin,
+                ArenaPlugin,
+                ClimbPlugin,
+                WeatherPlugin,
+                BoostPlugin,
+                BiomePlugin,
+                GatePlugin,
+                BoundsPlugin,
+            ))
+            // debug tools
+            .add_plugins((FrameStepPlugin, LatencyPlugin, AutopilotPlugin))
+            .add_event::<Milestone>()
+            .add_systems(Startup, setup)
+            .add_systems(Update, load_player_animations.in_set(DeferredLoad))
+            .add_systems(
+                OnEnter(GameState::Playing),
+                (reset_camera, spawn_player.after(configure_run)),
+            )
+            .add_systems(OnExit(GameState::Playing), despawn_run)
+            .add_systems(
+                Update,
+                (
+                    timed(player_footsteps),
+                    timed(advance_player),
+                    timed(move_camera_system),
+                    timed(player_movement).after(FootingSet),
+                    timed(apply_gravity),
+                    timed(settle_idle),
+                    timed(change_animation),
+                    timed(check_milestones),
+                )
+                    .in_set(GameplaySet),
+            );
+        #[cfg(feature = "dev_tools")]
+        app.add_plugins((LiveReloadPlugin, BrushPlugin, SnapshotPlugin, TimingPlugin));
+    }
+}
@@ -0,0 +1,113 @@
+use crate::bonus::{bonus_layers, RunStage};
+use crate::parallax_layers;
+use crate::settings::Settings;
+use bevy::prelude::*;
+use bevy_parallax::{CreateParallaxEvent, ParallaxCameraComponent};
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::SystemTime;
+
+// where the layer textures are read from while developing
+const ASSET_ROOT: &str = "assets";
+// how often the layer textures are checked for changes, in seconds
+const POLL_INTERVAL: f32 = 0.5;
+
+// Last change seen to each parallax layer texture on disk. Polled rather than watched:
+// bevy's file watcher pulls in a crate of its own, and a few textures are cheap to check.
+#[derive(Resource)]
+struct WatchedLayers {
+    modified: HashMap<String, SystemTime>,
+    timer: Timer,
+}
+
+impl Default for WatchedLayers {
+    fn default() -> Self {
+        WatchedLayers {
+            modified: HashMap::new(),
+            timer: Timer::from_seconds(POLL_INTERVAL, TimerMode::Repeating),
+        }
+    }
+}
+
+fn modified(path: &str) -> Option<SystemTime> {
+    Path::new(ASSET_ROOT)
+        .join(path)
+        .metadata()
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+// system to reload the layer textures changed on disk since they were last checked
+fn poll_layers(
+    time: Res<Time<Real>>,
+    asset_server: Res<AssetServer>,
+    mut watched: ResMut<WatchedLayers>,
+) {
+    if !watched.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+    // every layer, left out in low-spec mode or not
+    for layer in parallax_layers(0.0, false) {
+        let Some(modified) = modified(&layer.path) else {
+            continue;
+        };
+        match watched.modified.insert(layer.path.clone(), modified) {
+            Some(seen) if seen != modified => {
+                info!("Reloading {}", layer.path);
+                asset_server.reload(layer.path);
+            }
+            _ => {}
+        }
+    }
+}
+
+// system to rebuild the parallax layers around the camera once a reloaded texture is in,
+// so a texture that changed size gets tiled anew
+fn rebuild_layers(
+    settings: Res<Settings>,
+    mut events: EventReader<AssetEvent<Image>>,
+    asset_server: Res<AssetServer>,
+    stage: Res<State<RunStage>>,
+    camera_query: Query<(Entity, &Transform), With<ParallaxCameraComponent>>,
+    mut create_parallax: EventWriter<CreateParallaxEvent>,
+) {
+    let layers: Vec<String> = parallax_layers(0.0, false)
+        .into_iter()
+        .map(|layer| layer.path)
+        .collect();
+    let reloaded = events
+        .read()
+        .filter(|event| match event {
+            AssetEvent::Modified { id } => asset_server
+                .get_path(*id)
+                .is_some_and(|path| layers.contains(&path.path().to_string_lossy().to_string())),
+            _ => false,
+        })
+        .count();
+    if reloaded == 0 {
+        return;
+    }
+    let Ok((camera, transform)) = camera_query.get_single() else {
+        return;
+    };
+    let camera_x = transform.translation.x;
+    let layers_data = match stage.get() {
+        RunStage::Main => parallax_layers(camera_x, settings.video.low_spec),
+        RunStage::Bonus => bonus_layers(camera_x, settings.video.low_spec),
+    };
+    create_parallax.send(CreateParallaxEvent {
+        layers_data,
+        camera,
+    });
+}
+
+// Picks up edits to the parallax layer textures while the game runs, built in with the
+// `dev_tools` feature
+pub struct LiveReloadPlugin;
+
+impl Plugin for LiveReloadPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WatchedLayers>()
+            .add_systems(Update, (poll_layers, rebuild_layers).chain());
+    }
+}
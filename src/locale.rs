@@ -0,0 +1,174 @@
+use crate::settings::Settings;
+use crate::toast::Toast;
+use bevy::asset::LoadState;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+// Language the game is shown in
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Language {
+    #[default]
+    English,
+    Russian,
+    Ukrainian,
+    Japanese,
+    ChineseSimplified,
+    Korean,
+}
+
+impl Language {
+    pub const ALL: [Language; 6] = [
+        Language::English,
+        Language::Russian,
+        Language::Ukrainian,
+        Language::Japanese,
+        Language::ChineseSimplified,
+        Language::Korean,
+    ];
+
+    // in the language itself, so it can be found without reading the current one
+    pub fn name(&self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::Russian => "Русский",
+            Language::Ukrainian => "Українська",
+            Language::Japanese => "日本語",
+            Language::ChineseSimplified => "简体中文",
+            Language::Korean => "한국어",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        let index = Self::ALL.iter().position(|language| language == self);
+        Self::ALL[index.map_or(0, |index| index + 1) % Self::ALL.len()]
+    }
+
+    // font with the language's glyphs, and latin ones for names and numbers, None where
+    // bevy's built-in font has them all. Text can only use one font, so each of these
+    // covers every script shown alongside the language.
+    fn font(&self) -> Option<&'static str> {
+        match self {
+            Language::English => None,
+            Language::Russian | Language::Ukrainian => Some("fonts/NotoSans-Regular.ttf"),
+            Language::Japanese => Some("fonts/NotoSansJP-Regular.otf"),
+            Language::ChineseSimplified => Some("fonts/NotoSansSC-Regular.otf"),
+            Language::Korean => Some("fonts/NotoSansKR-Regular.otf"),
+        }
+    }
+}
+
+// Font every text is drawn in, for the language it was loaded for. None while it's
+// bevy's built-in font: for English, or as the fallback when the language's font failed
+// to load.
+#[derive(Resource, Default)]
+pub struct UiFont {
+    language: Language,
+    font: Option<Handle<Font>>,
+    // the language's font has loaded, or failed to and was dropped
+    settled: bool,
+}
+
+// Event sent once the language picked is ready to be shown, its font loaded or fallen
+// back from, for every system owning text to rebuild it in that language
+#[derive(Event, Debug, Clone, Copy)]
+pub struct Relocalize;
+
+impl UiFont {
+    fn handle(&self) -> Handle<Font> {
+        self.font.clone().unwrap_or_default()
+    }
+}
+
+// system to load the font of the language picked in the settings
+fn select_font(
+    settings: Res<Settings>,
+    asset_server: Res<AssetServer>,
+    mut ui_font: ResMut<UiFont>,
+) {
+    if !settings.is_changed() || (ui_font.language == settings.language && !settings.is_added()) {
+        return;
+    }
+    let language = settings.language;
+    *ui_font = UiFont {
+        language,
+        font: language.font().map(|path| asset_server.load(path)),
+        settled: false,
+    };
+}
+
+// system to fall back to the built-in font when the language's one can't be loaded,
+// rather than show nothing
+fn check_font(
+    asset_server: Res<AssetServer>,
+    mut ui_font: ResMut<UiFont>,
+    mut toasts: EventWriter<Toast>,
+    mut relocalize: EventWriter<Relocalize>,
+) {
+    if ui_font.settled {
+        return;
+    }
+    let Some(font) = &ui_font.font else {
+        ui_font.settled = true;
+        relocalize.send(Relocalize);
+        return;
+    };
+    match asset_server.get_load_state(font) {
+        Some(LoadState::Loaded) => {
+            ui_font.settled = true;
+            relocalize.send(Relocalize);
+        }
+        Some(LoadState::Failed) => {
+            let path = ui_font.language.font().unwrap_or_default();
+            warn!("Could not load {}, falling back to the built-in font", path);
+            toasts.send(Toast(format!("Font missing: assets/{}", path)));
+            ui_font.font = None;
+            ui_font.settled = true;
+            relocalize.send(Relocalize);
+        }
+        _ => {}
+    }
+}
+
+// system to draw every text in the UI font once it's loaded: all of them when the
+// language switches, and new or rewritten ones as they come
+fn apply_font(
+    ui_font: Res<UiFont>,
+    mut relocalize: EventReader<Relocalize>,
+    mut text_query: Query<&mut Text>,
+) {
+    let all = relocalize.read().count() > 0;
+    if !ui_font.settled {
+        return;
+    }
+    let font = ui_font.handle();
+    for mut text in &mut text_query {
+        if !(all || text.is_changed()) {
+            continue;
+        }
+        if text
+            .sections
+            .iter()
+            .all(|section| section.style.font == font)
+        {
+            continue;
+        }
+        for section in &mut text.sections {
+            section.style.font = font.clone();
+        }
+    }
+}
+
+pub struct LocalePlugin;
+
+impl Plugin for LocalePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<UiFont>()
+            .add_event::<Relocalize>()
+            .add_systems(
+                PostUpdate,
+                (select_font, check_font, apply_font)
+                    .chain()
+                    .before(bevy::ui::UiSystem::Layout),
+            );
+    }
+}
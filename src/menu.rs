@@ -0,0 +1,1446 @@
+use crate::animation::AnimationLibrary;
+use crate::challenge::{duel, ActiveChallenge, ChallengeRequest};
+use crate::cosmetics::{spawn_preview, Hat, Skin, TryOn};
+use crate::difficulty::{preview_bundle, DifficultyKnob};
+use crate::display::{mode_name, CycleWindowMode};
+use crate::error::{LastError, RetryLoad};
+use crate::exit::RequestExit;
+use crate::export::{ExportFormat, ExportStats};
+use crate::game_over::RunHistory;
+use crate::history::{date, distances, scores, spawn_sparkline, trend};
+use crate::hud::{HudAnchor, HudCanvas, HudLayout, HudWidget};
+use crate::input::{is_modifier, Action, Chord, InputMap, StickAxis};
+use crate::locale::Relocalize;
+use crate::mode::{ActiveMode, GameModes};
+use crate::mutators::Mutator;
+use crate::narration::Narrate;
+use crate::pause::PauseState;
+use crate::profile::{Profiles, SwitchProfile};
+use crate::prompt::{Prompt, PromptKey, PromptPart};
+use crate::settings::Settings;
+use crate::upgrades::{Locked, Upgrade};
+use crate::GameState;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::ui::RelativeCursorPosition;
+
+const TITLE_FONT_SIZE: f32 = 32.0;
+const ITEM_FONT_SIZE: f32 = 18.0;
+const ITEM_COLOR: Color = Color::rgba(0.1, 0.1, 0.15, 0.8);
+const FOCUSED_ITEM_COLOR: Color = Color::rgba(0.85, 0.45, 0.2, 0.9);
+const SCREEN_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.35);
+
+// Which menu screen is shown, either in GameState::Menu or over a paused run
+#[derive(States, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum MenuState {
+    #[default]
+    Main,
+    Mutators,
+    // hazard density and boss frequency, with a preview of the course they make
+    Difficulty,
+    // skins and hats, tried on by a preview of the player
+    Shop,
+    // permanent upgrades bought with banked coins
+    Upgrades,
+    // skins, hats and the trophies of the active profile, on show on the shop's preview
+    Gallery,
+    Settings,
+    Controls,
+    Video,
+    // where the HUD widgets sit and how big, dragged around or cycled through
+    HudLayout,
+    Pause,
+    // loading the game failed, shown instead of the main menu
+    Error,
+    // score and distance of the runs kept, and how they trend
+    Stats,
+    // a challenge's run side by side with the run that answered it
+    Duel,
+    Disabled,
+}
+
+// What an item of the gallery puts on show
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Exhibit {
+    Skin(Skin),
+    Hat(Hat),
+    // best grade of the active profile in the mode with this name
+    Trophy(&'static str),
+}
+
+// What a menu item does when activated
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+enum MenuAction {
+    // start a run of the registered mode with this name
+    Play(&'static str),
+    // save the last run as a challenge for a friend
+    ExportChallenge,
+    AcceptChallenge,
+    OpenMutators,
+    ToggleMutator(Mutator),
+    OpenDifficulty,
+    // step a custom difficulty setting up, or with the left and right arrows either way
+    AdjustDifficulty(DifficultyKnob),
+    OpenShop,
+    EquipSkin(Skin),
+    EquipHat(Hat),
+    OpenUpgrades,
+    BuyUpgrade(Upgrade),
+    OpenStats,
+    // write the runs and lifetime stats to files
+    ExportStats(ExportFormat),
+    OpenGallery,
+    // focusing one shows it, there's nothing to activate
+    ShowExhibit(Exhibit),
+    // play as the next profile, or a new one
+    CycleProfile,
+    NewProfile,
+    OpenSettings,
+    OpenControls,
+    OpenVideo,
+    OpenHudLayout,
+    CycleHudAnchor(HudWidget),
+    CycleHudScale(HudWidget),
+    ResetHudLayout,
+    CycleLanguage,
+    CycleDisplayMode,
+    CycleVsync,
+    CycleFrameLimit,
+    ToggleLowLatency,
+    ToggleLowSpec,
+    ToggleCaptions,
+    ToggleNarration,
+    TogglePhotosensitivity,
+    ToggleStatsPanel,
+    CycleStatsCorner,
+    ToggleStreamMode,
+    ToggleRubberBanding,
+    ToggleRaceAuthor,
+    CycleWebcamMargin,
+    CyclePreset,
+    Rebind(Action),
+    CycleDeadzone(StickAxis),
+    CycleTriggerThreshold,
+    Back(MenuState),
+    Resume,
+    QuitToMenu,
+    Retry,
+    Quit,
+}
+
+impl MenuAction {
+    fn label(&self, settings: &Settings, profiles: &Profiles, rebinding: &Rebinding) -> String {
+        let on_off = |enabled: bool| if enabled { "On" } else { "Off" };
+        match self {
+            MenuAction::Play(mode) => match profiles.active().best_grades.get(*mode) {
+                Some(best) => format!("Play {} (best {})", mode, best.letter()),
+                None => format!("Play {}", mode),
+            },
+            MenuAction::ExportChallenge => "Challenge a friend".to_string(),
+            MenuAction::AcceptChallenge => "Accept a challenge".to_string(),
+            MenuAction::OpenMutators => "Mutators".to_string(),
+            MenuAction::ToggleMutator(mutator) => format!(
+                "{}: {} (score x{})",
+                mutator.name(),
+                on_off(settings.mutators.contains(mutator)),
+                mutator.score_multiplier()
+            ),
+            MenuAction::OpenDifficulty => "Custom difficulty".to_string(),
+            MenuAction::AdjustDifficulty(knob) => format!(
+                "< {}: {:.2}x >",
+                knob.name(),
+                settings.difficulty.get(*knob)
+            ),
+            MenuAction::OpenShop => "Shop".to_string(),
+            MenuAction::EquipSkin(skin) if settings.cosmetics.skin == *skin => {
+                format!("Skin: {} (equipped)", skin.name())
+            }
+            MenuAction::EquipSkin(skin) => format!("Skin: {}", skin.name()),
+            MenuAction::EquipHat(hat) if settings.cosmetics.hat == *hat => {
+                format!("Hat: {} (equipped)", hat.name())
+            }
+            MenuAction::EquipHat(hat) => format!("Hat: {}", hat.name()),
+            MenuAction::OpenUpgrades => "Upgrades".to_string(),
+            MenuAction::OpenStats => "Stats".to_string(),
+            MenuAction::ExportStats(format) => format!("Export as {}", format.name()),
+            MenuAction::OpenGallery => "Gallery".to_string(),
+            MenuAction::ShowExhibit(Exhibit::Skin(skin)) => format!("Skin: {}", skin.name()),
+            MenuAction::ShowExhibit(Exhibit::Hat(hat)) => format!("Hat: {}", hat.name()),
+            MenuAction::ShowExhibit(Exhibit::Trophy(mode)) => {
+                match profiles.active().best_grades.get(*mode) {
+                    Some(best) => format!("{} trophy: {}", mode, best.letter()),
+                    None => format!("{} trophy", mode),
+                }
+            }
+            MenuAction::CycleProfile => format!("Profile: {}", profiles.active().name),
+            MenuAction::NewProfile => "New profile".to_string(),
+            MenuAction::BuyUpgrade(upgrade) => {
+                let profile = profiles.active();
+                let level = profile.upgrades.level(*upgrade);
+                let state = match profile.upgrades.check(*upgrade, profile.coins) {
+                    Ok(cost) => format!("{} coins", cost),
+                    Err(Locked::MaxedOut) => "max".to_string(),
+                    Err(Locked::Requires(required, required_level)) => {
+                        format!("needs {} {}", required.name(), required_level)
+                    }
+                    Err(Locked::Costs(cost)) => format!("{} coins, too few", cost),
+                };
+                format!(
+                    "{} {}/{} ({})",
+                    upgrade.name(),
+                    level,
+                    upgrade.max_level(),
+                    state
+                )
+            }
+            MenuAction::OpenSettings => "Settings".to_string(),
+            MenuAction::OpenControls => "Controls".to_string(),
+            MenuAction::OpenVideo => "Video".to_string(),
+            MenuAction::OpenHudLayout => "HUD layout".to_string(),
+            MenuAction::CycleHudAnchor(widget) => format!(
+                "{}: {}",
+                widget.name(),
+                settings.hud_layout.get(*widget).anchor.name()
+            ),
+            MenuAction::CycleHudScale(widget) => format!(
+                "{} size: {:.0}%",
+                widget.name(),
+                settings.hud_layout.get(*widget).scale * 100.0
+            ),
+            MenuAction::ResetHudLayout => "Reset layout".to_string(),
+            MenuAction::CycleLanguage => format!("Language: {}", settings.language.name()),
+            MenuAction::CycleDisplayMode => {
+                format!("Display: {}", mode_name(settings.window.mode))
+            }
+            MenuAction::CycleVsync => format!("VSync: {}", settings.video.vsync.name()),
+            MenuAction::CycleFrameLimit => match settings.video.frame_limit {
+                Some(limit) => format!("Frame limit: {} fps", limit),
+                None => "Frame limit: Off".to_string(),
+            },
+            MenuAction::ToggleLowLatency => {
+                format!("Low latency: {}", on_off(settings.video.low_latency))
+            }
+            MenuAction::ToggleLowSpec => {
+                format!("Low-spec mode: {}", on_off(settings.video.low_spec))
+            }
+            MenuAction::ToggleStatsPanel => {
+                format!("Stats panel: {}", on_off(settings.stats_panel.visible))
+            }
+            MenuAction::CycleStatsCorner => {
+                format!("Stats panel corner: {}", settings.stats_panel.corner.name())
+            }
+            MenuAction::ToggleStreamMode => {
+                format!("Stream mode: {}", on_off(settings.stream.enabled))
+            }
+            MenuAction::ToggleRubberBanding => {
+                format!("Race rubber-banding: {}", on_off(settings.rubber_banding))
+            }
+            MenuAction::ToggleRaceAuthor => {
+                format!("Race the dev: {}", on_off(settings.race_author))
+            }
+            MenuAction::CycleWebcamMargin => match settings.stream.webcam {
+                Some(corner) => format!("Webcam margin: {}", corner.name()),
+                None => "Webcam margin: Off".to_string(),
+            },
+            MenuAction::ToggleCaptions => {
+                format!("Captions: {}", on_off(settings.accessibility.captions))
+            }
+            MenuAction::ToggleNarration => {
+                format!("Narration: {}", on_off(settings.accessibility.narration))
+            }
+            MenuAction::TogglePhotosensitivity => format!(
+                "Photosensitive mode: {}",
+                on_off(settings.accessibility.photosensitivity)
+            ),
+            MenuAction::CyclePreset => format!("Preset: {}", settings.controls.preset.name()),
+            MenuAction::Rebind(action) if rebinding.0 == Some(*action) => {
+                format!("{}: press a key or chord", action.name())
+            }
+            MenuAction::Rebind(action) => format!(
+                "{}: {}",
+                action.name(),
+                settings.controls.binding_names(*action).join(" / ")
+            ),
+            MenuAction::CycleDeadzone(axis) => format!(
+                "Stick deadzone, {}: {:.0}%",
+                axis.name(),
+                settings.gamepad.deadzone(*axis) * 100.0
+            ),
+            MenuAction::CycleTriggerThreshold => format!(
+                "Trigger threshold: {:.0}%",
+                settings.gamepad.trigger_threshold * 100.0
+            ),
+            MenuAction::Back(_) => "Back".to_string(),
+            MenuAction::Resume => "Resume".to_string(),
+            MenuAction::QuitToMenu => "Quit to menu".to_string(),
+            MenuAction::Retry => "Retry".to_string(),
+            MenuAction::Quit => "Quit".to_string(),
+        }
+    }
+}
+
+// Root of the current menu screen, despawned when leaving it
+#[derive(Component)]
+struct OnMenuScreen;
+
+// Position of a menu item on its screen
+#[derive(Component)]
+struct MenuItem(usize);
+
+// Index of the focused item on the current screen
+#[derive(Resource, Default)]
+struct MenuFocus {
+    index: usize,
+    count: usize,
+}
+
+// Action waiting for a key press to be bound to it in the controls menu
+#[derive(Resource, Default)]
+struct Rebinding(Option<Action>);
+
+fn spawn_menu(
+    commands: &mut Commands,
+    settings: &Settings,
+    profiles: &Profiles,
+    rebinding: &Rebinding,
+    title: &str,
+    actions: &[MenuAction],
+) -> Entity {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    row_gap: Val::Px(6.0),
+                    ..default()
+                },
+                background_color: SCREEN_COLOR.into(),
+                ..default()
+            },
+            OnMenuScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                title,
+                TextStyle {
+                    font_size: TITLE_FONT_SIZE,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            ));
+            for (index, action) in actions.iter().enumerate() {
+                parent
+                    .spawn((
+                        ButtonBundle {
+                            style: Style {
+                                width: Val::Px(260.0),
+                                padding: UiRect::all(Val::Px(4.0)),
+                                justify_content: JustifyContent::Center,
+                                ..default()
+                            },
+                            background_color: ITEM_COLOR.into(),
+                            ..default()
+                        },
+                        MenuItem(index),
+                        *action,
+                    ))
+                    .with_children(|button| {
+                        button.spawn(TextBundle::from_section(
+                            action.label(settings, profiles, rebinding),
+                            TextStyle {
+                                font_size: ITEM_FONT_SIZE,
+                                color: Color::WHITE,
+                                ..default()
+                            },
+                        ));
+                    });
+            }
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 12.0,
+                        color: Color::rgb(0.8, 0.8, 0.8),
+                        ..default()
+                    },
+                ),
+                Prompt(vec![
+                    PromptPart::Glyph(PromptKey::Confirm),
+                    PromptPart::Text(" Select   "),
+                    PromptPart::Glyph(PromptKey::Back),
+                    PromptPart::Text(" Back"),
+                ]),
+            ));
+        })
+        .id()
+}
+
+fn open_screen(
+    commands: &mut Commands,
+    settings: &Settings,
+    profiles: &Profiles,
+    title: &str,
+    actions: &[MenuAction],
+    narrate: &mut EventWriter<Narrate>,
+) {
+    spawn_menu(
+        commands,
+        settings,
+        profiles,
+        &Rebinding::default(),
+        title,
+        actions,
+    );
+    commands.insert_resource(MenuFocus {
+        index: 0,
+        count: actions.len(),
+    });
+    narrate.send(Narrate::new(title));
+}
+
+fn main_menu_setup(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    profiles: Res<Profiles>,
+    modes: Res<GameModes>,
+    mut narrate: EventWriter<Narrate>,
+) {
+    let mut actions: Vec<MenuAction> = modes.names().map(MenuAction::Play).collect();
+    actions.extend([
+        MenuAction::ExportChallenge,
+        MenuAction::AcceptChallenge,
+        MenuAction::OpenMutators,
+        MenuAction::OpenDifficulty,
+        MenuAction::OpenShop,
+        MenuAction::OpenUpgrades,
+        MenuAction::OpenStats,
+        MenuAction::OpenGallery,
+        MenuAction::CycleProfile,
+        MenuAction::NewProfile,
+        MenuAction::OpenSettings,
+        MenuAction::Quit,
+    ]);
+    open_screen(
+        &mut commands,
+        &settings,
+        &profiles,
+        "Dino Run",
+        &actions,
+        &mut narrate,
+    );
+}
+
+fn mutators_menu_setup(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    profiles: Res<Profiles>,
+    mut narrate: EventWriter<Narrate>,
+) {
+    let mut actions: Vec<MenuAction> = Mutator::ALL.map(MenuAction::ToggleMutator).to_vec();
+    actions.push(MenuAction::Back(MenuState::Main));
+    open_screen(
+        &mut commands,
+        &settings,
+        &profiles,
+        "Mutators",
+        &actions,
+        &mut narrate,
+    );
+}
+
+// the difficulty screen shows the sample course its settings make between the title and
+// the list
+fn difficulty_menu_setup(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    profiles: Res<Profiles>,
+    mut narrate: EventWriter<Narrate>,
+) {
+    let mut actions: Vec<MenuAction> = DifficultyKnob::ALL
+        .map(MenuAction::AdjustDifficulty)
+        .to_vec();
+    actions.push(MenuAction::Back(MenuState::Main));
+    let screen = spawn_menu(
+        &mut commands,
+        &settings,
+        &profiles,
+        &Rebinding::default(),
+        "Custom difficulty",
+        &actions,
+    );
+    commands.insert_resource(MenuFocus {
+        index: 0,
+        count: actions.len(),
+    });
+    narrate.send(Narrate::new("Custom difficulty"));
+    let preview = commands.spawn(preview_bundle()).id();
+    commands.entity(screen).insert_children(1, &[preview]);
+}
+
+// the shop lists every skin and hat, with the preview of the player between the title
+// and the list
+fn shop_menu_setup(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    profiles: Res<Profiles>,
+    mut images: ResMut<Assets<Image>>,
+    asset_server: Res<AssetServer>,
+    library: Res<AnimationLibrary>,
+    mut narrate: EventWriter<Narrate>,
+) {
+    let mut actions: Vec<MenuAction> = Skin::ALL.map(MenuAction::EquipSkin).to_vec();
+    actions.extend(Hat::ALL.map(MenuAction::EquipHat));
+    actions.push(MenuAction::Back(MenuState::Main));
+    let screen = spawn_menu(
+        &mut commands,
+        &settings,
+        &profiles,
+        &Rebinding::default(),
+        "Shop",
+        &actions,
+    );
+    commands.insert_resource(MenuFocus {
+        index: 0,
+        count: actions.len(),
+    });
+    narrate.send(Narrate::new("Shop"));
+
+    let preview = spawn_preview(
+        &mut commands,
+        &mut images,
+        &asset_server,
+        &library,
+        settings.cosmetics,
+    );
+    let image = commands.spawn(preview_image_bundle(preview)).id();
+    commands.entity(screen).insert_children(1, &[image]);
+}
+
+fn preview_image_bundle(preview: Handle<Image>) -> ImageBundle {
+    ImageBundle {
+        style: Style {
+            width: Val::Px(160.0),
+            height: Val::Px(160.0),
+            margin: UiRect::vertical(Val::Px(6.0)),
+            ..default()
+        },
+        image: UiImage::new(preview),
+        ..default()
+    }
+}
+
+// Line under the gallery's preview naming the trophy on show
+#[derive(Component)]
+struct Plaque;
+
+// the gallery lists the skins, hats and the trophy of every mode the active profile has
+// a grade in, showing the focused one on the shop's preview of the player
+#[allow(clippy::too_many_arguments)]
+fn gallery_menu_setup(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    profiles: Res<Profiles>,
+    modes: Res<GameModes>,
+    mut images: ResMut<Assets<Image>>,
+    asset_server: Res<AssetServer>,
+    library: Res<AnimationLibrary>,
+    mut narrate: EventWriter<Narrate>,
+) {
+    let mut exhibits: Vec<Exhibit> = Skin::ALL.map(Exhibit::Skin).to_vec();
+    // no hat isn't much of an exhibit
+    exhibits.extend(Hat::ALL[1..].iter().copied().map(Exhibit::Hat));
+    let best_grades = &profiles.active().best_grades;
+    exhibits.extend(
+        modes
+            .names()
+            .filter(|mode| best_grades.contains_key(*mode))
+            .map(Exhibit::Trophy),
+    );
+    let mut actions: Vec<MenuAction> = exhibits.into_iter().map(MenuAction::ShowExhibit).collect();
+    actions.push(MenuAction::Back(MenuState::Main));
+    let screen = spawn_menu(
+        &mut commands,
+        &settings,
+        &profiles,
+        &Rebinding::default(),
+        "Gallery",
+        &actions,
+    );
+    commands.insert_resource(MenuFocus {
+        index: 0,
+        count: actions.len(),
+    });
+    narrate.send(Narrate::new("Gallery"));
+
+    let preview = spawn_preview(
+        &mut commands,
+        &mut images,
+        &asset_server,
+        &library,
+        settings.cosmetics,
+    );
+    let image = commands.spawn(preview_image_bundle(preview)).id();
+    let plaque = commands
+        .spawn((
+            TextBundle::from_section(
+                "",
+                TextStyle {
+                    font_size: ITEM_FONT_SIZE,
+                    ..default()
+                },
+            ),
+            Plaque,
+        ))
+        .id();
+    commands.entity(screen).insert_children(1, &[image, plaque]);
+}
+
+// system to have the shop or gallery preview wear the focused skin or hat, on top of
+// what's equipped
+fn preview_hovered(
+    focus: Res<MenuFocus>,
+    settings: Res<Settings>,
+    item_query: Query<(&MenuItem, &MenuAction)>,
+    mut try_on: EventWriter<TryOn>,
+) {
+    if !(focus.is_changed() || settings.is_changed()) {
+        return;
+    }
+    let mut cosmetics = settings.cosmetics;
+    match item_query.iter().find(|(item, _)| item.0 == focus.index) {
+        Some((_, MenuAction::EquipSkin(skin)))
+        | Some((_, MenuAction::ShowExhibit(Exhibit::Skin(skin)))) => cosmetics.skin = *skin,
+        Some((_, MenuAction::EquipHat(hat)))
+        | Some((_, MenuAction::ShowExhibit(Exhibit::Hat(hat)))) => cosmetics.hat = *hat,
+        _ => {}
+    }
+    try_on.send(TryOn(cosmetics));
+}
+
+// system to put the focused trophy on the gallery's plaque, in the color of its grade.
+// The player in front of it wears what's equipped.
+fn show_trophy(
+    focus: Res<MenuFocus>,
+    profiles: Res<Profiles>,
+    item_query: Query<(&MenuItem, &MenuAction)>,
+    mut plaque_query: Query<&mut Text, With<Plaque>>,
+) {
+    if !focus.is_changed() {
+        return;
+    }
+    let trophy = match item_query.iter().find(|(item, _)| item.0 == focus.index) {
+        Some((_, MenuAction::ShowExhibit(Exhibit::Trophy(mode)))) => profiles
+            .active()
+            .best_grades
+            .get(*mode)
+            .map(|best| (*mode, *best)),
+        _ => None,
+    };
+    for mut text in &mut plaque_query {
+        let section = &mut text.sections[0];
+        match trophy {
+            Some((mode, best)) => {
+                section.value = format!("Best in {}: grade {}", mode, best.letter());
+                section.style.color = best.color();
+            }
+            None => section.value.clear(),
+        }
+    }
+}
+
+// Line of the upgrades screen with the coins the active profile has to spend
+#[derive(Component)]
+struct WalletText;
+
+fn wallet_line(profiles: &Profiles) -> String {
+    let profile = profiles.active();
+    format!("{}: {} coins", profile.name, profile.coins)
+}
+
+fn upgrades_menu_setup(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    profiles: Res<Profiles>,
+    mut narrate: EventWriter<Narrate>,
+) {
+    let mut actions: Vec<MenuAction> = Upgrade::ALL.map(MenuAction::BuyUpgrade).to_vec();
+    actions.push(MenuAction::Back(MenuState::Main));
+    let screen = spawn_menu(
+        &mut commands,
+        &settings,
+        &profiles,
+        &Rebinding::default(),
+        "Upgrades",
+        &actions,
+    );
+    commands.insert_resource(MenuFocus {
+        index: 0,
+        count: actions.len(),
+    });
+    narrate.send(Narrate::new("Upgrades"));
+    narrate.send(Narrate::new(wallet_line(&profiles)));
+
+    let wallet = commands
+        .spawn((
+            TextBundle::from_section(
+                wallet_line(&profiles),
+                TextStyle {
+                    font_size: ITEM_FONT_SIZE,
+                    color: Color::rgb(1.0, 0.85, 0.2),
+                    ..default()
+                },
+            ),
+            WalletText,
+        ))
+        .id();
+    commands.entity(screen).insert_children(1, &[wallet]);
+}
+
+// system to keep the coin count of the upgrades screen current as upgrades are bought
+fn update_wallet(profiles: Res<Profiles>, mut text_query: Query<&mut Text, With<WalletText>>) {
+    if !profiles.is_changed() {
+        return;
+    }
+    for mut text in &mut text_query {
+        text.sections[0].value = wallet_line(&profiles);
+    }
+}
+
+fn settings_menu_setup(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    profiles: Res<Profiles>,
+    mut narrate: EventWriter<Narrate>,
+) {
+    open_screen(
+        &mut commands,
+        &settings,
+        &profiles,
+        "Settings",
+        &[
+            MenuAction::CycleLanguage,
+            MenuAction::OpenControls,
+            MenuAction::OpenVideo,
+            MenuAction::OpenHudLayout,
+            MenuAction::ToggleStatsPanel,
+            MenuAction::CycleStatsCorner,
+            MenuAction::ToggleStreamMode,
+            MenuAction::CycleWebcamMargin,
+            MenuAction::ToggleRubberBanding,
+            MenuAction::ToggleRaceAuthor,
+            MenuAction::ToggleCaptions,
+            MenuAction::ToggleNarration,
+            MenuAction::TogglePhotosensitivity,
+            MenuAction::Back(MenuState::Main),
+        ],
+        &mut narrate,
+    );
+}
+
+fn controls_menu_setup(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    profiles: Res<Profiles>,
+    mut narrate: EventWriter<Narrate>,
+) {
+    let mut actions = vec![MenuAction::CyclePreset];
+    actions.extend(Action::ALL.map(MenuAction::Rebind));
+    actions.extend([
+        MenuAction::CycleDeadzone(StickAxis::Horizontal),
+        MenuAction::CycleDeadzone(StickAxis::Vertical),
+        MenuAction::CycleTriggerThreshold,
+    ]);
+    actions.push(MenuAction::Back(MenuState::Settings));
+    open_screen(
+        &mut commands,
+        &settings,
+        &profiles,
+        "Controls",
+        &actions,
+        &mut narrate,
+    );
+}
+
+fn video_menu_setup(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    profiles: Res<Profiles>,
+    mut narrate: EventWriter<Narrate>,
+) {
+    open_screen(
+        &mut commands,
+        &settings,
+        &profiles,
+        "Video",
+        &[
+            MenuAction::CycleDisplayMode,
+            MenuAction::CycleVsync,
+            MenuAction::CycleFrameLimit,
+            MenuAction::ToggleLowLatency,
+            MenuAction::ToggleLowSpec,
+            MenuAction::Back(MenuState::Settings),
+        ],
+        &mut narrate,
+    );
+}
+
+// stand-in for a HUD widget on the layout screen, placed where the widget would be
+fn hud_preview_bundle() -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            padding: UiRect::axes(Val::Px(8.0), Val::Px(4.0)),
+            border: UiRect::all(Val::Px(1.0)),
+            ..default()
+        },
+        background_color: Color::rgba(1.0, 0.8, 0.2, 0.25).into(),
+        border_color: Color::rgb(1.0, 0.8, 0.2).into(),
+        ..default()
+    }
+}
+
+fn hud_layout_menu_setup(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    profiles: Res<Profiles>,
+    mut narrate: EventWriter<Narrate>,
+) {
+    let mut actions = Vec::new();
+    for widget in HudWidget::ALL {
+        actions.push(MenuAction::CycleHudAnchor(widget));
+        actions.push(MenuAction::CycleHudScale(widget));
+    }
+    actions.push(MenuAction::ResetHudLayout);
+    actions.push(MenuAction::Back(MenuState::Settings));
+    let screen = spawn_menu(
+        &mut commands,
+        &settings,
+        &profiles,
+        &Rebinding::default(),
+        "HUD layout",
+        &actions,
+    );
+    commands.insert_resource(MenuFocus {
+        index: 0,
+        count: actions.len(),
+    });
+    narrate.send(Narrate::new("HUD layout"));
+
+    commands
+        .entity(screen)
+        .insert((HudCanvas, RelativeCursorPosition::default()))
+        .with_children(|parent| {
+            for widget in HudWidget::ALL {
+                parent
+                    .spawn((hud_preview_bundle(), HudAnchor(widget)))
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(
+                            widget.name(),
+                            TextStyle {
+                                font_size: ITEM_FONT_SIZE,
+                                color: Color::WHITE,
+                                ..default()
+                            },
+                        ));
+                    });
+            }
+        });
+}
+
+fn pause_menu_setup(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    profiles: Res<Profiles>,
+    mut narrate: EventWriter<Narrate>,
+) {
+    open_screen(
+        &mut commands,
+        &settings,
+        &profiles,
+        "Paused",
+        &[MenuAction::Resume, MenuAction::QuitToMenu],
+        &mut narrate,
+    );
+}
+
+fn error_menu_setup(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    profiles: Res<Profiles>,
+    error: Option<Res<LastError>>,
+    mut narrate: EventWriter<Narrate>,
+) {
+    let title = match error {
+        Some(error) => format!("Something went wrong: {}", error.0),
+        None => "Something went wrong".to_string(),
+    };
+    open_screen(
+        &mut commands,
+        &settings,
+        &profiles,
+        &title,
+        &[MenuAction::Retry, MenuAction::Quit],
+        &mut narrate,
+    );
+}
+
+// the duel screen puts the challenge and the run that answered it in three columns
+// under the verdict
+fn duel_menu_setup(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    profiles: Res<Profiles>,
+    challenge: Res<ActiveChallenge>,
+    history: Res<RunHistory>,
+    mut menu_state: ResMut<NextState<MenuState>>,
+    mut narrate: EventWriter<Narrate>,
+) {
+    let (Some(challenge), Some(record)) = (&challenge.0, history.0.last()) else {
+        menu_state.set(MenuState::Main);
+        return;
+    };
+    let (rows, verdict) = duel(challenge, record, &profiles.active().name);
+    let screen = spawn_menu(
+        &mut commands,
+        &settings,
+        &profiles,
+        &Rebinding::default(),
+        &verdict,
+        &[MenuAction::Back(MenuState::Main)],
+    );
+    commands.insert_resource(MenuFocus { index: 0, count: 1 });
+    narrate.send(Narrate::new(verdict.clone()));
+
+    let style = TextStyle {
+        font_size: ITEM_FONT_SIZE,
+        ..default()
+    };
+    let cell = |commands: &mut Commands, text: &str, color: Color| {
+        commands
+            .spawn(
+                TextBundle::from_section(
+                    text,
+                    TextStyle {
+                        color,
+                        ..style.clone()
+                    },
+                )
+                .with_style(Style {
+                    width: Val::Px(140.0),
+                    ..default()
+                }),
+            )
+            .id()
+    };
+    let mut lines = Vec::new();
+    for row in &rows {
+        if !row.label.is_empty() {
+            narrate.send(Narrate(format!(
+                "{}: {} against {}",
+                row.label, row.yours, row.theirs
+            )));
+        }
+        let cells = [
+            cell(&mut commands, row.label, Color::rgb(0.7, 0.7, 0.7)),
+            cell(&mut commands, &row.theirs, Color::WHITE),
+            cell(&mut commands, &row.yours, Color::rgb(1.0, 0.85, 0.2)),
+        ];
+        let line = commands
+            .spawn(NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Row,
+                    margin: UiRect::vertical(Val::Px(2.0)),
+                    ..default()
+                },
+                ..default()
+            })
+            .push_children(&cells)
+            .id();
+        lines.push(line);
+    }
+    let table = commands
+        .spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Column,
+                margin: UiRect::vertical(Val::Px(8.0)),
+                ..default()
+            },
+            ..default()
+        })
+        .push_children(&lines)
+        .id();
+    commands.entity(screen).insert_children(1, &[table]);
+}
+
+// the stats screen charts the score and distance of every run kept, oldest on the left,
+// with a line for the average and how the latest runs compare to the ones before
+fn stats_menu_setup(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    profiles: Res<Profiles>,
+    history: Res<RunHistory>,
+    mut narrate: EventWriter<Narrate>,
+) {
+    let actions = [
+        MenuAction::ExportStats(ExportFormat::Csv),
+        MenuAction::ExportStats(ExportFormat::Json),
+        MenuAction::Back(MenuState::Main),
+    ];
+    let screen = spawn_menu(
+        &mut commands,
+        &settings,
+        &profiles,
+        &Rebinding::default(),
+        "Stats",
+        &actions,
+    );
+    commands.insert_resource(MenuFocus {
+        index: 0,
+        count: actions.len(),
+    });
+    narrate.send(Narrate::new("Stats"));
+
+    let runs = &history.0;
+    let line = |commands: &mut Commands, text: String, color: Color| {
+        commands
+            .spawn(TextBundle::from_section(
+                text,
+                TextStyle {
+                    font_size: 14.0,
+                    color,
+                    ..default()
+                },
+            ))
+            .id()
+    };
+    let mut lines = Vec::new();
+    let summary = match runs.last() {
+        None => "No runs yet".to_string(),
+        Some(last) => {
+            let played = last.date.map_or(String::new(), |seconds| {
+                format!(", the last on {}", date(seconds))
+            });
+            format!("{} runs{}", runs.len(), played)
+        }
+    };
+    narrate.send(Narrate::new(summary.clone()));
+    lines.push(line(&mut commands, summary, Color::WHITE));
+    let charts = [
+        ("Score", scores(runs), Color::rgb(1.0, 0.85, 0.2)),
+        ("Distance", distances(runs), Color::rgb(0.4, 0.75, 1.0)),
+    ];
+    for (label, values, color) in charts {
+        if values.is_empty() {
+            continue;
+        }
+        let best = values.iter().copied().fold(0.0, f32::max);
+        let mut text = format!("{}: best {:.0}", label, best);
+        if let Some(trend) = trend(&values) {
+            text = format!(
+                "{}, last 10 runs {:+.0}% on the 10 before",
+                text,
+                trend * 100.0
+            );
+        }
+        narrate.send(Narrate::new(text.clone()));
+        lines.push(line(&mut commands, text, color));
+        lines.push(spawn_sparkline(&mut commands, &values, color));
+    }
+    let charts = commands
+        .spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(4.0),
+                margin: UiRect::vertical(Val::Px(8.0)),
+                ..default()
+            },
+            ..default()
+        })
+        .push_children(&lines)
+        .id();
+    commands.entity(screen).insert_children(1, &[charts]);
+}
+
+fn despawn_screen(mut commands: Commands, query: Query<Entity, With<OnMenuScreen>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// What menu items ask of other parts of the game
+#[derive(SystemParam)]
+struct MenuRequests<'w> {
+    cycle_window_mode: EventWriter<'w, CycleWindowMode>,
+    retry_load: EventWriter<'w, RetryLoad>,
+    challenge: EventWriter<'w, ChallengeRequest>,
+    switch_profile: EventWriter<'w, SwitchProfile>,
+    export_stats: EventWriter<'w, ExportStats>,
+    exit: EventWriter<'w, RequestExit>,
+}
+
+// system to move the focus with the arrow keys, a gamepad or the mouse and activate the
+// focused item. The back button activates the screen's Back or Resume item.
+#[allow(clippy::too_many_arguments)]
+fn menu_navigation(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepad_input: Res<ButtonInput<GamepadButton>>,
+    mut focus: ResMut<MenuFocus>,
+    interaction_query: Query<(&Interaction, &MenuItem), Changed<Interaction>>,
+    item_query: Query<(&MenuItem, &MenuAction)>,
+    mut settings: ResMut<Settings>,
+    mut profiles: ResMut<Profiles>,
+    mut rebinding: ResMut<Rebinding>,
+    mut game_state: ResMut<NextState<GameState>>,
+    mut menu_state: ResMut<NextState<MenuState>>,
+    mut pause_state: ResMut<NextState<PauseState>>,
+    modes: Res<GameModes>,
+    mut active_mode: ResMut<ActiveMode>,
+    mut requests: MenuRequests,
+) {
+    // while waiting for a new binding the next key press goes to it, with the modifier
+    // held as a chord, and a modifier on its own once released. Escape cancels.
+    if let Some(action) = rebinding.0 {
+        let key = keyboard_input
+            .get_just_pressed()
+            .find(|key| !is_modifier(**key));
+        let modifier = keyboard_input
+            .get_pressed()
+            .find(|key| is_modifier(**key))
+            .copied();
+        match (key, modifier) {
+            (Some(KeyCode::Escape), _) => rebinding.0 = None,
+            (Some(key), Some(modifier)) => {
+                let chord = Chord {
+                    modifier,
+                    key: *key,
+                };
+                settings.controls.rebind_chord(action, chord);
+                rebinding.0 = None;
+            }
+            (Some(key), None) => {
+                settings.controls.rebind(action, *key);
+                rebinding.0 = None;
+            }
+            (None, _) => {
+                let released = keyboard_input
+                    .get_just_released()
+                    .find(|key| is_modifier(**key));
+                if let Some(modifier) = released {
+                    settings.controls.rebind(action, *modifier);
+                    rebinding.0 = None;
+                }
+            }
+        }
+        return;
+    }
+    let gamepad_pressed = |button_type| {
+        gamepad_input
+            .get_just_pressed()
+            .any(|button| button.button_type == button_type)
+    };
+    let mut activate = false;
+    for (interaction, item) in &interaction_query {
+        match interaction {
+            Interaction::Hovered => focus.index = item.0,
+            Interaction::Pressed => {
+                focus.index = item.0;
+                activate = true;
+            }
+            Interaction::None => {}
+        }
+    }
+    if focus.count > 0 {
+        if keyboard_input.just_pressed(KeyCode::ArrowDown)
+            || gamepad_pressed(GamepadButtonType::DPadDown)
+        {
+            focus.index = (focus.index + 1) % focus.count;
+        }
+        if keyboard_input.just_pressed(KeyCode::ArrowUp)
+            || gamepad_pressed(GamepadButtonType::DPadUp)
+        {
+            focus.index = (focus.index + focus.count - 1) % focus.count;
+        }
+    }
+    // Alt+Enter toggles fullscreen rather than selecting
+    let alt = keyboard_input.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]);
+    if (keyboard_input.just_pressed(KeyCode::Enter) && !alt)
+        || gamepad_pressed(GamepadButtonType::South)
+    {
+        activate = true;
+    }
+    // left and right move the focused setting when it's one to slide
+    let slide = if keyboard_input.just_pressed(KeyCode::ArrowLeft)
+        || gamepad_pressed(GamepadButtonType::DPadLeft)
+    {
+        -1
+    } else if keyboard_input.just_pressed(KeyCode::ArrowRight)
+        || gamepad_pressed(GamepadButtonType::DPadRight)
+    {
+        1
+    } else {
+        0
+    };
+    if slide != 0 {
+        let focused = item_query.iter().find(|(item, _)| item.0 == focus.index);
+        if let Some((_, MenuAction::AdjustDifficulty(knob))) = focused {
+            settings.difficulty.adjust(*knob, slide);
+            return;
+        }
+    }
+    let back =
+        keyboard_input.just_pressed(KeyCode::Escape) || gamepad_pressed(GamepadButtonType::East);
+    let action = if back {
+        item_query
+            .iter()
+            .map(|(_, action)| action)
+            .find(|action| matches!(action, MenuAction::Back(_) | MenuAction::Resume))
+    } else if activate {
+        item_query
+            .iter()
+            .find(|(item, _)| item.0 == focus.index)
+            .map(|(_, action)| action)
+    } else {
+        None
+    };
+    let Some(action) = action else {
+        return;
+    };
+    match action {
+        MenuAction::Play(name) => {
+            if let Some(mode) = modes.get(name) {
+                active_mode.0 = mode;
+            }
+            menu_state.set(MenuState::Disabled);
+            game_state.set(GameState::Playing);
+        }
+        MenuAction::ExportChallenge => {
+            requests.challenge.send(ChallengeRequest::Export);
+        }
+        MenuAction::AcceptChallenge => {
+            requests.challenge.send(ChallengeRequest::Accept);
+        }
+        MenuAction::OpenMutators => menu_state.set(MenuState::Mutators),
+        MenuAction::ToggleMutator(mutator) => {
+            if let Some(index) = settings.mutators.iter().position(|m| m == mutator) {
+                settings.mutators.remove(index);
+            } else {
+                settings.mutators.push(*mutator);
+            }
+        }
+        MenuAction::OpenDifficulty => menu_state.set(MenuState::Difficulty),
+        MenuAction::AdjustDifficulty(knob) => settings.difficulty.adjust(*knob, 1),
+        MenuAction::OpenShop => menu_state.set(MenuState::Shop),
+        MenuAction::EquipSkin(skin) => settings.cosmetics.skin = *skin,
+        MenuAction::EquipHat(hat) => settings.cosmetics.hat = *hat,
+        MenuAction::OpenUpgrades => menu_state.set(MenuState::Upgrades),
+        MenuAction::OpenStats => menu_state.set(MenuState::Stats),
+        MenuAction::ExportStats(format) => {
+            requests.export_stats.send(ExportStats(*format));
+        }
+        MenuAction::OpenGallery => menu_state.set(MenuState::Gallery),
+        MenuAction::ShowExhibit(_) => {}
+        MenuAction::CycleProfile => {
+            let next = (profiles.active + 1) % profiles.list.len();
+            requests.switch_profile.send(SwitchProfile(next));
+        }
+        MenuAction::NewProfile => {
+            requests
+                .switch_profile
+                .send(SwitchProfile(profiles.list.len()));
+        }
+        MenuAction::BuyUpgrade(upgrade) => {
+            let profile = profiles.active_mut();
+            profile.upgrades.buy(*upgrade, &mut profile.coins);
+        }
+        MenuAction::OpenSettings => menu_state.set(MenuState::Settings),
+        MenuAction::OpenControls => menu_state.set(MenuState::Controls),
+        MenuAction::OpenVideo => menu_state.set(MenuState::Video),
+        MenuAction::OpenHudLayout => menu_state.set(MenuState::HudLayout),
+        MenuAction::CycleHudAnchor(widget) => {
+            let layout = settings.hud_layout.get_mut(*widget);
+            layout.anchor = layout.anchor.next();
+        }
+        MenuAction::CycleHudScale(widget) => {
+            let layout = settings.hud_layout.get_mut(*widget);
+            layout.scale = layout.next_scale();
+        }
+        MenuAction::ResetHudLayout => settings.hud_layout = HudLayout::default(),
+        MenuAction::CycleLanguage => settings.language = settings.language.next(),
+        MenuAction::CycleDisplayMode => {
+            requests.cycle_window_mode.send(CycleWindowMode);
+        }
+        MenuAction::CycleVsync => settings.video.vsync = settings.video.vsync.next(),
+        MenuAction::CycleFrameLimit => {
+            settings.video.frame_limit = settings.video.next_frame_limit();
+        }
+        MenuAction::ToggleLowLatency => {
+            settings.video.low_latency = !settings.video.low_latency;
+        }
+        MenuAction::ToggleLowSpec => settings.video.low_spec = !settings.video.low_spec,
+        MenuAction::ToggleStatsPanel => {
+            settings.stats_panel.visible = !settings.stats_panel.visible;
+        }
+        MenuAction::CycleStatsCorner => {
+            settings.stats_panel.corner = settings.stats_panel.corner.next();
+        }
+        MenuAction::ToggleStreamMode => settings.stream.enabled = !settings.stream.enabled,
+        MenuAction::CycleWebcamMargin => settings.stream.webcam = settings.stream.next_webcam(),
+        MenuAction::ToggleRubberBanding => settings.rubber_banding = !settings.rubber_banding,
+        MenuAction::ToggleRaceAuthor => settings.race_author = !settings.race_author,
+        MenuAction::ToggleCaptions => {
+            settings.accessibility.captions = !settings.accessibility.captions;
+        }
+        MenuAction::ToggleNarration => {
+            settings.accessibility.narration = !settings.accessibility.narration;
+        }
+        MenuAction::TogglePhotosensitivity => {
+            settings.accessibility.photosensitivity = !settings.accessibility.photosensitivity;
+        }
+        MenuAction::CyclePreset => {
+            settings.controls = InputMap::preset(settings.controls.preset.next());
+        }
+        MenuAction::Rebind(action) => rebinding.0 = Some(*action),
+        MenuAction::CycleDeadzone(axis) => settings.gamepad.cycle_deadzone(*axis),
+        MenuAction::CycleTriggerThreshold => settings.gamepad.cycle_trigger_threshold(),
+        MenuAction::Back(screen) => menu_state.set(*screen),
+        MenuAction::Resume => {
+            menu_state.set(MenuState::Disabled);
+            pause_state.set(PauseState::Unpaused);
+        }
+        MenuAction::QuitToMenu => {
+            menu_state.set(MenuState::Main);
+            game_state.set(GameState::Menu);
+        }
+        MenuAction::Retry => {
+            requests.retry_load.send(RetryLoad);
+        }
+        MenuAction::Quit => {
+            requests.exit.send(RequestExit);
+        }
+    }
+}
+
+// system to highlight the focused item and read it out when the focus moves
+fn update_focus(
+    focus: Res<MenuFocus>,
+    settings: Res<Settings>,
+    profiles: Res<Profiles>,
+    rebinding: Res<Rebinding>,
+    mut item_query: Query<(&MenuItem, &MenuAction, &mut BackgroundColor)>,
+    mut narrate: EventWriter<Narrate>,
+) {
+    if !focus.is_changed() {
+        return;
+    }
+    for (item, action, mut background) in &mut item_query {
+        if item.0 == focus.index {
+            *background = FOCUSED_ITEM_COLOR.into();
+            narrate.send(Narrate(format!(
+                "{}, {} of {}",
+                action.label(&settings, &profiles, &rebinding),
+                item.0 + 1,
+                focus.count
+            )));
+        } else {
+            *background = ITEM_COLOR.into();
+        }
+    }
+}
+
+// system to refresh item labels after a setting was toggled or a binding changed, and
+// rebuild them all once a new language is ready
+fn update_labels(
+    settings: Res<Settings>,
+    profiles: Res<Profiles>,
+    rebinding: Res<Rebinding>,
+    mut relocalize: EventReader<Relocalize>,
+    item_query: Query<(&MenuAction, &Children), With<MenuItem>>,
+    mut text_query: Query<&mut Text>,
+    mut narrate: EventWriter<Narrate>,
+) {
+    let relocalized = relocalize.read().count() > 0;
+    let changed = settings.is_changed() || profiles.is_changed() || rebinding.is_changed();
+    if !relocalized && (!changed || settings.is_added() || profiles.is_added()) {
+        return;
+    }
+    for (action, children) in &item_query {
+        let label = action.label(&settings, &profiles, &rebinding);
+        for &child in children {
+            if let Ok(mut text) = text_query.get_mut(child) {
+                if relocalized {
+                    text.sections[0].value = label.clone();
+                } else if text.sections[0].value != label {
+                    narrate.send(Narrate::new(label.clone()));
+                    text.sections[0].value = label.clone();
+                }
+            }
+        }
+    }
+}
+
+pub struct MenuPlugin;
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_state::<MenuState>()
+            .init_resource::<MenuFocus>()
+            .init_resource::<Rebinding>()
+            .add_systems(OnEnter(MenuState::Main), main_menu_setup)
+            .add_systems(OnExit(MenuState::Main), despawn_screen)
+            .add_systems(OnEnter(MenuState::Mutators), mutators_menu_setup)
+            .add_systems(OnExit(MenuState::Mutators), despawn_screen)
+            .add_systems(OnEnter(MenuState::Difficulty), difficulty_menu_setup)
+            .add_systems(OnExit(MenuState::Difficulty), despawn_screen)
+            .add_systems(OnEnter(MenuState::Shop), shop_menu_setup)
+            .add_systems(OnExit(MenuState::Shop), despawn_screen)
+            .add_systems(
+                Update,
+                preview_hovered
+                    .after(menu_navigation)
+                    .run_if(in_state(MenuState::Shop).or_else(in_state(MenuState::Gallery))),
+            )
+            .add_systems(OnEnter(MenuState::Gallery), gallery_menu_setup)
+            .add_systems(OnExit(MenuState::Gallery), despawn_screen)
+            .add_systems(
+                Update,
+                show_trophy
+                    .after(menu_navigation)
+                    .run_if(in_state(MenuState::Gallery)),
+            )
+            .add_systems(OnEnter(MenuState::Upgrades), upgrades_menu_setup)
+            .add_systems(OnExit(MenuState::Upgrades), despawn_screen)
+            .add_systems(
+                Update,
+                update_wallet
+                    .after(menu_navigation)
+                    .run_if(in_state(MenuState::Upgrades)),
+            )
+            .add_systems(OnEnter(MenuState::Settings), settings_menu_setup)
+            .add_systems(OnExit(MenuState::Settings), despawn_screen)
+            .add_systems(OnEnter(MenuState::Controls), controls_menu_setup)
+            .add_systems(OnExit(MenuState::Controls), despawn_screen)
+            .add_systems(OnEnter(MenuState::Video), video_menu_setup)
+            .add_systems(OnExit(MenuState::Video), despawn_screen)
+            .add_systems(OnEnter(MenuState::HudLayout), hud_layout_menu_setup)
+            .add_systems(OnExit(MenuState::HudLayout), despawn_screen)
+            .add_systems(OnEnter(MenuState::Pause), pause_menu_setup)
+            .add_systems(OnExit(MenuState::Pause), despawn_screen)
+            .add_systems(OnEnter(MenuState::Error), error_menu_setup)
+            .add_systems(OnExit(MenuState::Error), despawn_screen)
+            .add_systems(OnEnter(MenuState::Stats), stats_menu_setup)
+            .add_systems(OnExit(MenuState::Stats), despawn_screen)
+            .add_systems(OnEnter(MenuState::Duel), duel_menu_setup)
+            .add_systems(OnExit(MenuState::Duel), despawn_screen)
+            .add_systems(
+                Update,
+                (menu_navigation, update_focus, update_labels)
+                    .chain()
+                    .run_if(in_state(GameState::Menu).or_else(in_state(PauseState::Paused))),
+            );
+    }
+}
@@ -0,0 +1,343 @@
+use crate::bonus::RunStage;
+use crate::bounds::{KillWall, RisingPlane, WorldBounds};
+use crate::challenge::ActiveChallenge;
+use crate::game_time::GameTime;
+use crate::health::Health;
+use crate::restart::RestartSeed;
+use crate::web::unix_time;
+use crate::{GameState, GameplaySet, PlayerOne, GROUND_Y};
+use bevy::prelude::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+// length of a time trial, in seconds
+const TIME_TRIAL_LENGTH: f32 = 120.0;
+// distance to the end of the campaign course
+const CAMPAIGN_DISTANCE: f32 = 10000.0;
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+// How a run ended
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunOutcome {
+    // the player ran out of health
+    Lost,
+    // the clock ran out
+    TimeUp,
+    // the player reached the end of the course
+    Won,
+}
+
+impl RunOutcome {
+    pub fn name(&self) -> &'static str {
+        match self {
+            RunOutcome::Lost => "Game over!",
+            RunOutcome::TimeUp => "Time's up!",
+            RunOutcome::Won => "Course cleared!",
+        }
+    }
+}
+
+// How far the current run got, what modes decide its outcome from
+#[derive(Resource, Debug, Clone, Copy, Default)]
+pub struct RunProgress {
+    // seconds spent playing
+    pub elapsed: f32,
+    pub distance: f32,
+    pub health: f32,
+}
+
+// The rules of one kind of run. Systems ask the active mode instead of checking which
+// mode is being played, so adding a mode is implementing this and registering it.
+pub trait GameMode: Send + Sync + 'static {
+    // shown in the menu and stored with the run's score, unique among the registered modes
+    fn name(&self) -> &'static str;
+
+    // whether the run has enemies and projectiles
+    fn hazards(&self) -> bool {
+        true
+    }
+
+    // factor applied to every point scored, on top of the mutators
+    fn score_multiplier(&self) -> f32 {
+        1.0
+    }
+
+    // seed the run's spawns are drawn from, a fresh one every run when None
+    fn seed(&self) -> Option<u64> {
+        None
+    }
+
+    // whether a second player can join in the middle of a run, see drop_in.rs
+    fn drop_in(&self) -> bool {
+        false
+    }
+
+    // kill wall, kill plane and how far ahead the player can get
+    fn bounds(&self) -> WorldBounds {
+        WorldBounds::default()
+    }
+
+    // whether the run climbs up a cliff instead of running along, see climb.rs
+    fn vertical(&self) -> bool {
+        false
+    }
+
+    // how far along the course `position` is, what the run's distance, milestones and
+    // biomes go by
+    fn progress(&self, position: Vec2) -> f32 {
+        position.x
+    }
+
+    // how the run ends given how far it got, None while it goes on
+    fn outcome(&self, progress: &RunProgress) -> Option<RunOutcome> {
+        (self.hazards() && progress.health <= 0.0).then_some(RunOutcome::Lost)
+    }
+}
+
+// enemies and projectiles until the player runs out of health
+struct Endless;
+
+impl GameMode for Endless {
+    fn name(&self) -> &'static str {
+        "Endless"
+    }
+
+    fn drop_in(&self) -> bool {
+        true
+    }
+}
+
+// the same course for everyone playing on the same day
+struct Daily;
+
+impl GameMode for Daily {
+    fn name(&self) -> &'static str {
+        "Daily"
+    }
+
+    fn seed(&self) -> Option<u64> {
+        Some(unix_time().as_secs() / SECONDS_PER_DAY)
+    }
+}
+
+// as many points as possible before the clock runs out
+struct TimeTrial;
+
+impl GameMode for TimeTrial {
+    fn name(&self) -> &'static str {
+        "Time Trial"
+    }
+
+    // the wall keeps up a quicker pace, in keeping with the clock
+    fn bounds(&self) -> WorldBounds {
+        WorldBounds {
+            kill_wall: Some(KillWall {
+                speed: 0.8,
+                gap: 360.0,
+            }),
+            ..default()
+        }
+    }
+
+    fn outcome(&self, progress: &RunProgress) -> Option<RunOutcome> {
+        if progress.health <= 0.0 {
+            Some(RunOutcome::Lost)
+        } else if progress.elapsed >= TIME_TRIAL_LENGTH {
+            Some(RunOutcome::TimeUp)
+        } else {
+            None
+        }
+    }
+}
+
+// just running and collecting coins, nothing can hurt the player
+struct Zen;
+
+impl GameMode for Zen {
+    fn name(&self) -> &'static str {
+        "Zen"
+    }
+
+    fn hazards(&self) -> bool {
+        false
+    }
+
+    // no hurry either
+    fn bounds(&self) -> WorldBounds {
+        WorldBounds {
+            kill_wall: None,
+            ..default()
+        }
+    }
+}
+
+// a fixed course with a finish line
+struct Campaign;
+
+impl GameMode for Campaign {
+    fn name(&self) -> &'static str {
+        "Campaign"
+    }
+
+    fn seed(&self) -> Option<u64> {
+        Some(0)
+    }
+
+    fn outcome(&self, progress: &RunProgress) -> Option<RunOutcome> {
+        if progress.health <= 0.0 {
+            Some(RunOutcome::Lost)
+        } else if progress.distance >= CAMPAIGN_DISTANCE {
+            Some(RunOutcome::Won)
+        } else {
+            None
+        }
+    }
+}
+
+// climbing platforms up a cliff ahead of a rising kill plane
+struct CliffAscent;
+
+impl GameMode for CliffAscent {
+    fn name(&self) -> &'static str {
+        "Cliff Ascent"
+    }
+
+    fn bounds(&self) -> WorldBounds {
+        WorldBounds {
+            kill_wall: None,
+            rising_plane: Some(RisingPlane {
+                speed: 0.35,
+                gap: 420.0,
+            }),
+            ..default()
+        }
+    }
+
+    fn vertical(&self) -> bool {
+        true
+    }
+
+    // the height climbed
+    fn progress(&self, position: Vec2) -> f32 {
+        position.y - GROUND_Y
+    }
+}
+
+// Every mode that can be played, in menu order
+#[derive(Resource, Default)]
+pub struct GameModes(Vec<Arc<dyn GameMode>>);
+
+impl GameModes {
+    pub fn register(&mut self, mode: impl GameMode) {
+        self.0.push(Arc::new(mode));
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<dyn GameMode>> {
+        self.0.iter().find(|mode| mode.name() == name).cloned()
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.0.iter().map(|mode| mode.name())
+    }
+}
+
+// The mode being played, or to be played next
+#[derive(Resource, Clone)]
+pub struct ActiveMode(pub Arc<dyn GameMode>);
+
+impl Default for ActiveMode {
+    fn default() -> Self {
+        ActiveMode(Arc::new(Endless))
+    }
+}
+
+impl std::ops::Deref for ActiveMode {
+    type Target = dyn GameMode;
+
+    fn deref(&self) -> &Self::Target {
+        self.0.as_ref()
+    }
+}
+
+// Random numbers for the run's spawns, seeded by the active mode
+#[derive(Resource)]
+pub struct RunRng(pub StdRng);
+
+impl Default for RunRng {
+    fn default() -> Self {
+        RunRng(StdRng::from_entropy())
+    }
+}
+
+// run condition for systems that spawn or resolve anything dangerous
+pub fn hazards_enabled(mode: Res<ActiveMode>, stage: Res<State<RunStage>>) -> bool {
+    mode.hazards() && *stage.get() == RunStage::Main
+}
+
+// Seed the current run's RunRng started from, enough to play its course again
+#[derive(Resource, Debug, Clone, Copy, Default)]
+pub struct RunSeed(pub u64);
+
+pub fn start_run(
+    mut commands: Commands,
+    mode: Res<ActiveMode>,
+    challenge: Res<ActiveChallenge>,
+    mut restart: ResMut<RestartSeed>,
+) {
+    // a challenge is played on the course of the run that set it, and a restart asked to
+    // replay its course on the one before
+    let seed = challenge
+        .0
+        .as_ref()
+        .map(|challenge| challenge.seed)
+        .or(restart.0.take())
+        .or(mode.seed())
+        .unwrap_or_else(|| rand::thread_rng().gen());
+    commands.insert_resource(RunSeed(seed));
+    commands.insert_resource(RunRng(StdRng::seed_from_u64(seed)));
+    commands.insert_resource(RunProgress::default());
+}
+
+// system to keep track of how far the run got
+pub fn track_progress(
+    time: Res<GameTime>,
+    mode: Res<ActiveMode>,
+    mut progress: ResMut<RunProgress>,
+    player_query: Query<(&Transform, &Health), PlayerOne>,
+) {
+    let Ok((transform, health)) = player_query.get_single() else {
+        return;
+    };
+    progress.elapsed = time.elapsed_seconds();
+    progress.distance = mode.progress(transform.translation.truncate()).max(0.0);
+    progress.health = health.current;
+}
+
+pub struct GameModePlugin;
+
+impl Plugin for GameModePlugin {
+    fn build(&self, app: &mut App) {
+        let mut modes = GameModes::default();
+        modes.register(Endless);
+        modes.register(Daily);
+        modes.register(TimeTrial);
+        modes.register(Zen);
+        modes.register(Campaign);
+        modes.register(CliffAscent);
+        app.insert_resource(modes)
+            .init_resource::<ActiveMode>()
+            .init_resource::<RunRng>()
+            .init_resource::<RunSeed>()
+            .init_resource::<RunProgress>()
+            .add_systems(OnEnter(GameState::Playing), start_run)
+            // bonus stages are played away from the course, the distance is kept from before
+            .add_systems(
+                Update,
+                track_progress
+                    .run_if(in_state(RunStage::Main))
+                    .in_set(GameplaySet),
+            );
+    }
+}
@@ -0,0 +1,144 @@
+use crate::challenge::ActiveChallenge;
+use crate::difficulty::CustomDifficulty;
+use crate::mode::ActiveMode;
+use crate::profile::Profiles;
+use crate::settings::Settings;
+use crate::upgrades::Upgrades;
+use crate::GameState;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+// health the player starts a run with
+const PLAYER_HEALTH: f32 = 3.0;
+// how close coins have to be for a magnet to pull them in
+const MAGNET_RADIUS: f32 = 96.0;
+
+// Optional rule changes picked before a run, each trading difficulty for score
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Mutator {
+    DoubleSpeed,
+    LowGravity,
+    OneHitDeath,
+    CoinRain,
+}
+
+impl Mutator {
+    pub const ALL: [Mutator; 4] = [
+        Mutator::DoubleSpeed,
+        Mutator::LowGravity,
+        Mutator::OneHitDeath,
+        Mutator::CoinRain,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Mutator::DoubleSpeed => "Double speed",
+            Mutator::LowGravity => "Low gravity",
+            Mutator::OneHitDeath => "One-hit death",
+            Mutator::CoinRain => "Coin rain",
+        }
+    }
+
+    // factor applied to every point scored while the mutator is active
+    pub fn score_multiplier(&self) -> f32 {
+        match self {
+            Mutator::DoubleSpeed => 1.5,
+            Mutator::LowGravity => 0.8,
+            Mutator::OneHitDeath => 2.0,
+            Mutator::CoinRain => 0.5,
+        }
+    }
+
+    fn apply(&self, config: &mut RunConfig) {
+        match self {
+            Mutator::DoubleSpeed => config.speed *= 2.0,
+            Mutator::LowGravity => config.gravity *= 0.5,
+            Mutator::OneHitDeath => config.player_health = 1.0,
+            Mutator::CoinRain => config.coin_rain = true,
+        }
+        config.score_multiplier *= self.score_multiplier();
+    }
+}
+
+// Tuning of the current run, the defaults adjusted by the active mutators
+#[derive(Resource, Debug, Clone)]
+pub struct RunConfig {
+    // scale of the player's and the camera's speed
+    pub speed: f32,
+    // scale of the pull bringing the player back down
+    pub gravity: f32,
+    pub player_health: f32,
+    // coins keep falling from the sky
+    pub coin_rain: bool,
+    // scale of how long power-ups last
+    pub power_up_duration: f32,
+    pub magnet_radius: f32,
+    // whether the super jump action jumps higher than a plain jump
+    pub super_jump: bool,
+    pub score_multiplier: f32,
+    pub mutators: Vec<Mutator>,
+    // how the spawners pack hazards, picked in the menu
+    pub difficulty: CustomDifficulty,
+}
+
+impl Default for RunConfig {
+    fn default() -> Self {
+        RunConfig {
+            speed: 1.0,
+            gravity: 1.0,
+            player_health: PLAYER_HEALTH,
+            coin_rain: false,
+            power_up_duration: 1.0,
+            magnet_radius: MAGNET_RADIUS,
+            super_jump: false,
+            score_multiplier: 1.0,
+            mutators: Vec::new(),
+            difficulty: CustomDifficulty::default(),
+        }
+    }
+}
+
+impl RunConfig {
+    // the upgrades go first so a mutator's rule, like one-hit death, has the last word
+    pub fn new(mutators: &[Mutator], upgrades: &Upgrades) -> Self {
+        let mut config = RunConfig::default();
+        upgrades.apply(&mut config);
+        for mutator in Mutator::ALL {
+            if mutators.contains(&mutator) {
+                mutator.apply(&mut config);
+                config.mutators.push(mutator);
+            }
+        }
+        config
+    }
+}
+
+// system to set the run up with the active profile's upgrades, the mutators picked in the
+// menu and the mode's scoring
+pub fn configure_run(
+    settings: Res<Settings>,
+    profiles: Res<Profiles>,
+    mode: Res<ActiveMode>,
+    challenge: Res<ActiveChallenge>,
+    mut config: ResMut<RunConfig>,
+) {
+    *config = match &challenge.0 {
+        // both sides of a challenge run under its mutators, without upgrades and at the
+        // balance's own difficulty
+        Some(challenge) => RunConfig::new(&challenge.mutators, &Upgrades::default()),
+        None => RunConfig {
+            difficulty: settings.difficulty,
+            ..RunConfig::new(&settings.mutators, &profiles.active().upgrades)
+        },
+    };
+    config.score_multiplier *= mode.score_multiplier();
+}
+
+pub struct MutatorsPlugin;
+
+impl Plugin for MutatorsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RunConfig>()
+            .add_systems(OnEnter(GameState::Playing), configure_run);
+    }
+}
@@ -0,0 +1,95 @@
+use crate::settings::Settings;
+use crate::GameState;
+use bevy::prelude::*;
+
+// held with Ctrl, turns narration on and off from anywhere
+const TOGGLE_KEY: KeyCode = KeyCode::KeyN;
+
+// Event sent whenever something should be read out to the player
+#[derive(Event, Debug, Clone)]
+pub struct Narrate(pub String);
+
+impl Narrate {
+    pub fn new(text: impl Into<String>) -> Self {
+        Narrate(text.into())
+    }
+}
+
+// Text-to-speech backend, only available when built with the `tts` feature
+#[cfg(feature = "tts")]
+struct Narrator(tts::Tts);
+
+#[cfg(feature = "tts")]
+fn setup_narrator(world: &mut World) {
+    match tts::Tts::default() {
+        Ok(tts) => world.insert_non_send_resource(Narrator(tts)),
+        Err(err) => warn!("Text-to-speech unavailable: {}", err),
+    }
+}
+
+// system to turn narration on and off with Ctrl+N, from the first frame: the option in
+// the menus can't be found without seeing them
+fn toggle_narration(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut settings: ResMut<Settings>,
+    mut narrate: EventWriter<Narrate>,
+) {
+    let ctrl = keyboard_input.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+    if !ctrl || !keyboard_input.just_pressed(TOGGLE_KEY) {
+        return;
+    }
+    let accessibility = &mut settings.accessibility;
+    accessibility.narration = !accessibility.narration;
+    if accessibility.narration {
+        narrate.send(Narrate::new("Narration on"));
+    }
+}
+
+// system to announce game state transitions
+fn narrate_state_transitions(
+    mut transitions: EventReader<StateTransitionEvent<GameState>>,
+    mut narrate: EventWriter<Narrate>,
+) {
+    for transition in transitions.read() {
+        let text = match transition.after {
+            GameState::Menu => "Main menu",
+            GameState::Playing => "Run started",
+            GameState::Restarting => "Restarting",
+        };
+        narrate.send(Narrate::new(text));
+    }
+}
+
+// system to speak queued narration, interrupting whatever was being said
+fn speak(
+    settings: Res<Settings>,
+    mut events: EventReader<Narrate>,
+    #[cfg(feature = "tts")] mut narrator: Option<NonSendMut<Narrator>>,
+) {
+    if !settings.accessibility.narration {
+        events.clear();
+        return;
+    }
+    for Narrate(text) in events.read() {
+        debug!("Narration: {}", text);
+        #[cfg(feature = "tts")]
+        if let Some(narrator) = narrator.as_mut() {
+            if let Err(err) = narrator.0.speak(text, true) {
+                warn!("Could not narrate \"{}\": {}", text, err);
+            }
+        }
+    }
+}
+
+pub struct NarrationPlugin;
+
+impl Plugin for NarrationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Narrate>().add_systems(
+            Update,
+            (toggle_narration, narrate_state_transitions, speak).chain(),
+        );
+        #[cfg(feature = "tts")]
+        app.add_systems(Startup, setup_narrator);
+    }
+}
@@ -0,0 +1,155 @@
+#[cfg(not(target_arch = "wasm32"))]
+use crate::balance::fetch;
+use crate::menu::MenuState;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::settings::Settings;
+use bevy::prelude::*;
+#[cfg(not(target_arch = "wasm32"))]
+use bevy::tasks::{block_on, futures_lite::future, IoTaskPool, Task};
+#[cfg(not(target_arch = "wasm32"))]
+use std::io;
+
+// news shipped with the game, shown until a fetched one arrives
+const BUNDLED_NEWS: &str = include_str!("../assets/news.md");
+// lines shown at most, the rest of a long feed is cut off
+const MAX_LINES: usize = 16;
+const PANEL_WIDTH: f32 = 240.0;
+
+// The news feed, in the markdown-ish shape of assets/news.md
+#[derive(Resource)]
+struct News(String);
+
+impl Default for News {
+    fn default() -> Self {
+        News(BUNDLED_NEWS.to_string())
+    }
+}
+
+// Remote news being downloaded in the background. The web build has no sockets to
+// download with and keeps to the bundled news.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Resource)]
+struct NewsFetch(Task<io::Result<String>>);
+
+// "What's new" panel on the side of the main menu
+#[derive(Component)]
+struct NewsPanel;
+
+// one line of the feed as text, with its font size and color: `#` and `##` headings,
+// `-` and `*` bullets and plain lines, with `**` and `__` emphasis markers dropped
+fn render_line(line: &str) -> (String, f32, Color) {
+    let line = line.trim().replace("**", "").replace("__", "");
+    if let Some(heading) = line.strip_prefix("## ") {
+        (heading.to_string(), 14.0, Color::rgb(0.9, 0.85, 0.6))
+    } else if let Some(heading) = line.strip_prefix("# ") {
+        (heading.to_string(), 18.0, Color::rgb(1.0, 0.8, 0.3))
+    } else if let Some(item) = line.strip_prefix("- ").or(line.strip_prefix("* ")) {
+        (format!("\u{2022} {}", item), 12.0, Color::WHITE)
+    } else {
+        (line, 12.0, Color::rgb(0.8, 0.8, 0.8))
+    }
+}
+
+// system to start downloading the news, if a feed is configured
+#[cfg(not(target_arch = "wasm32"))]
+fn start_fetch(mut commands: Commands, settings: Res<Settings>) {
+    let Some(url) = settings.news_url.clone() else {
+        return;
+    };
+    let task = IoTaskPool::get().spawn(async move { fetch(&url) });
+    commands.insert_resource(NewsFetch(task));
+}
+
+// system to switch to the downloaded news once it arrives
+#[cfg(not(target_arch = "wasm32"))]
+fn finish_fetch(mut commands: Commands, mut fetch: ResMut<NewsFetch>, mut news: ResMut<News>) {
+    let Some(result) = block_on(future::poll_once(&mut fetch.0)) else {
+        return;
+    };
+    commands.remove_resource::<NewsFetch>();
+    match result {
+        Ok(contents) if !contents.trim().is_empty() => news.0 = contents,
+        Ok(_) => warn!("Ignoring the remote news: empty"),
+        Err(err) => warn!("Could not fetch the news: {}", err),
+    }
+}
+
+fn despawn_panel(mut commands: Commands, panel_query: Query<Entity, With<NewsPanel>>) {
+    for panel in &panel_query {
+        commands.entity(panel).despawn_recursive();
+    }
+}
+
+// lay the news out on a panel next to the main menu
+fn spawn_panel(commands: &mut Commands, news: &str) {
+    let lines = news.lines().skip_while(|line| line.trim().is_empty());
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(24.0),
+                    right: Val::Px(24.0),
+                    width: Val::Px(PANEL_WIDTH),
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(3.0),
+                    padding: UiRect::all(Val::Px(8.0)),
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.4).into(),
+                z_index: ZIndex::Global(2),
+                ..default()
+            },
+            NewsPanel,
+        ))
+        .with_children(|parent| {
+            for line in lines.take(MAX_LINES) {
+                let (text, font_size, color) = render_line(line);
+                parent.spawn(TextBundle::from_section(
+                    text,
+                    TextStyle {
+                        font_size,
+                        color,
+                        ..default()
+                    },
+                ));
+            }
+        });
+}
+
+fn show_news(mut commands: Commands, news: Res<News>) {
+    spawn_panel(&mut commands, &news.0);
+}
+
+// system to swap in newer news arriving while the panel is up
+fn refresh_news(
+    mut commands: Commands,
+    news: Res<News>,
+    panel_query: Query<Entity, With<NewsPanel>>,
+) {
+    if !news.is_changed() || news.is_added() || panel_query.is_empty() {
+        return;
+    }
+    for panel in &panel_query {
+        commands.entity(panel).despawn_recursive();
+    }
+    spawn_panel(&mut commands, &news.0);
+}
+
+pub struct NewsPlugin;
+
+impl Plugin for NewsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<News>()
+            .add_systems(OnEnter(MenuState::Main), show_news)
+            .add_systems(OnExit(MenuState::Main), despawn_panel)
+            .add_systems(Update, refresh_news);
+        #[cfg(not(target_arch = "wasm32"))]
+        app.add_systems(Startup, start_fetch).add_systems(
+            Update,
+            finish_fetch
+                .run_if(resource_exists::<NewsFetch>)
+                .before(refresh_news),
+        );
+    }
+}
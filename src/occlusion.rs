@@ -0,0 +1,194 @@
+use crate::audio::AudioZone;
+use crate::collision::Collider;
+use crate::game_time::GameTime;
+use crate::mode::RunRng;
+use crate::startup::{DeferredLoad, GameplayAssets};
+use crate::{GameState, GameplaySet, Player, PlayerOne, RunEntity, GROUND_Y};
+use bevy::prelude::*;
+use bevy_parallax::ParallaxCameraComponent;
+use rand::Rng;
+
+const BUSH_SPRITE: &str = "background-sunset/bush.png";
+const BUSH_SCALE: f32 = 2.0;
+// just in front of the foreground parallax layer
+const BUSH_Z: f32 = 2.1;
+// distance between two bushes, picked at random in this range
+const BUSH_SPACING: (f32, f32) = (900.0, 1800.0);
+// how far past the right edge of the view bushes are placed
+const SPAWN_MARGIN: f32 = 96.0;
+// the player's silhouette, drawn over bushes in front of them
+const SILHOUETTE_COLOR: Color = Color::rgb(0.75, 0.9, 1.0);
+const SILHOUETTE_ALPHA: f32 = 0.55;
+// how fast the silhouette fades in and out, in alpha per second
+const SILHOUETTE_FADE: f32 = 4.0;
+// in front of bushes, relative to the player and scaled with them
+const SILHOUETTE_Z: f32 = 0.25;
+// how much sounds are muffled while running behind a bush
+const BUSH_MUFFLE: f32 = 0.5;
+
+// Bush in front of the player, which they run behind
+#[derive(Component)]
+struct Bush;
+
+// Copy of the player's sprite showing through bushes, so the player stays readable
+#[derive(Component)]
+struct Silhouette;
+
+// silhouettes, kept apart from the player whose sprite they copy
+type SilhouetteOnly = (With<Silhouette>, Without<Player>);
+
+// Where the next bush of the run goes
+#[derive(Resource, Default)]
+struct BushSpawner {
+    next_x: f32,
+}
+
+fn reset_spawner(mut commands: Commands) {
+    commands.insert_resource(BushSpawner {
+        next_x: BUSH_SPACING.0,
+    });
+}
+
+// system to give each new player a silhouette, hidden until they're behind a bush
+fn attach_silhouette(
+    mut commands: Commands,
+    player_query: Query<(Entity, &Handle<Image>, &TextureAtlas), Added<Player>>,
+) {
+    for (player, texture, atlas) in &player_query {
+        commands.entity(player).with_children(|parent| {
+            parent.spawn((
+                SpriteSheetBundle {
+                    texture: texture.clone(),
+                    atlas: atlas.clone(),
+                    sprite: Sprite {
+                        color: SILHOUETTE_COLOR.with_a(0.0),
+                        ..default()
+                    },
+                    transform: Transform::from_xyz(0.0, 0.0, SILHOUETTE_Z),
+                    ..default()
+                },
+                Silhouette,
+            ));
+        });
+    }
+}
+
+// system to place bushes ahead of the camera every so often
+fn spawn_bushes(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut spawner: ResMut<BushSpawner>,
+    mut rng: ResMut<RunRng>,
+    camera_query: Query<(&Transform, &OrthographicProjection), With<ParallaxCameraComponent>>,
+) {
+    let Ok((camera, projection)) = camera_query.get_single() else {
+        return;
+    };
+    if camera.translation.x + projection.area.max.x + SPAWN_MARGIN < spawner.next_x {
+        return;
+    }
+    // bushes are 40x28 pixels, standing on the same ground as the player's feet
+    let y = GROUND_Y - 32.0 + 14.0 * BUSH_SCALE;
+    commands.spawn((
+        SpriteBundle {
+            texture: asset_server.load(BUSH_SPRITE),
+            sprite: Sprite {
+                flip_x: rng.0.gen(),
+                ..default()
+            },
+            transform: Transform {
+                translation: Vec3::new(spawner.next_x, y, BUSH_Z),
+                scale: Vec3::splat(BUSH_SCALE),
+                ..default()
+            },
+            ..default()
+        },
+        Bush,
+        Collider(Rect::new(-18.0, -14.0, 18.0, 10.0)),
+        AudioZone {
+            muffle: BUSH_MUFFLE,
+        },
+        RunEntity,
+    ));
+    spawner.next_x += rng.0.gen_range(BUSH_SPACING.0..=BUSH_SPACING.1);
+}
+
+fn despawn_passed_bushes(
+    mut commands: Commands,
+    bush_query: Query<(Entity, &Transform), With<Bush>>,
+    camera_query: Query<(&Transform, &OrthographicProjection), With<ParallaxCameraComponent>>,
+) {
+    let Ok((camera, projection)) = camera_query.get_single() else {
+        return;
+    };
+    let view_left = camera.translation.x + projection.area.min.x;
+    for (entity, transform) in &bush_query {
+        if transform.translation.x < view_left - SPAWN_MARGIN * BUSH_SCALE {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+// system to fade the silhouette in while the player is behind a bush, and keep it
+// showing the same frame as the player
+fn reveal_player(
+    time: Res<GameTime>,
+    player_query: Query<(&GlobalTransform, &Collider, &TextureAtlas, &Sprite), PlayerOne>,
+    bush_query: Query<(&GlobalTransform, &Collider), With<Bush>>,
+    mut silhouette_query: Query<(&mut TextureAtlas, &mut Sprite), SilhouetteOnly>,
+) {
+    let Ok((player_transform, player_collider, player_atlas, player_sprite)) =
+        player_query.get_single()
+    else {
+        return;
+    };
+    let player_rect = player_collider.world_rect(player_transform);
+    let hidden = bush_query.iter().any(|(transform, collider)| {
+        !collider
+            .world_rect(transform)
+            .intersect(player_rect)
+            .is_empty()
+    });
+    let target = if hidden { SILHOUETTE_ALPHA } else { 0.0 };
+    let step = SILHOUETTE_FADE * time.delta_seconds();
+    for (mut atlas, mut sprite) in &mut silhouette_query {
+        if atlas.index != player_atlas.index {
+            atlas.index = player_atlas.index;
+        }
+        sprite.flip_x = player_sprite.flip_x;
+        let alpha = sprite.color.a();
+        if alpha != target {
+            let alpha = if alpha < target {
+                (alpha + step).min(target)
+            } else {
+                (alpha - step).max(target)
+            };
+            sprite.color.set_a(alpha);
+        }
+    }
+}
+
+// system to start loading the bush before the first one is met
+fn preload_bush(asset_server: Res<AssetServer>, mut assets: ResMut<GameplayAssets>) {
+    let bush: Handle<Image> = asset_server.load(BUSH_SPRITE);
+    assets.0.push(bush.untyped());
+}
+
+pub struct OcclusionPlugin;
+
+impl Plugin for OcclusionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BushSpawner>()
+            .add_systems(OnEnter(GameState::Playing), reset_spawner)
+            .add_systems(
+                Update,
+                (attach_silhouette, preload_bush.in_set(DeferredLoad)),
+            )
+            .add_systems(
+                Update,
+                (spawn_bushes, despawn_passed_bushes, reveal_player)
+                    .chain()
+                    .in_set(GameplaySet),
+            );
+    }
+}
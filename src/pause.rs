@@ -0,0 +1,43 @@
+use crate::input::{Action, ActionState};
+use crate::menu::MenuState;
+use crate::GameState;
+use bevy::prelude::*;
+
+// Whether the current run is paused. Gameplay systems only run while Unpaused.
+#[derive(States, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum PauseState {
+    #[default]
+    Unpaused,
+    Paused,
+}
+
+// system to open the pause screen; resuming is handled by the pause menu
+fn pause_game(
+    actions: Res<ActionState>,
+    mut pause_state: ResMut<NextState<PauseState>>,
+    mut menu_state: ResMut<NextState<MenuState>>,
+) {
+    if actions.just_pressed(Action::Pause) {
+        pause_state.set(PauseState::Paused);
+        menu_state.set(MenuState::Pause);
+    }
+}
+
+fn unpause(mut pause_state: ResMut<NextState<PauseState>>) {
+    pause_state.set(PauseState::Unpaused);
+}
+
+pub struct PausePlugin;
+
+impl Plugin for PausePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_state::<PauseState>()
+            .add_systems(
+                Update,
+                pause_game
+                    .run_if(in_state(GameState::Playing))
+                    .run_if(in_state(PauseState::Unpaused)),
+            )
+            .add_systems(OnExit(GameState::Playing), unpause);
+    }
+}
@@ -0,0 +1,221 @@
+use crate::coin::Coin;
+use crate::collision::Collider;
+use crate::game_time::GameTime;
+use crate::mode::RunRng;
+use crate::mutators::RunConfig;
+use crate::rubber_band::RubberBand;
+use crate::toast::Toast;
+use crate::{GameState, GameplaySet, Player, PlayerOne, RunEntity, GROUND_Y};
+use bevy::prelude::*;
+use bevy_parallax::ParallaxCameraComponent;
+use rand::Rng;
+
+// distance between two power-ups, picked at random in this range
+const POWER_UP_SPACING: (f32, f32) = (1500.0, 3000.0);
+// how high above the ground power-ups float, picked at random in this range
+const POWER_UP_HEIGHT: (f32, f32) = (16.0, 90.0);
+const POWER_UP_SIZE: f32 = 14.0;
+// how fast a magnet pulls coins in, in pixels per second
+const MAGNET_PULL: f32 = 420.0;
+// every this many power-ups of a run, one is a shield
+const SHIELD_EVERY: u32 = 3;
+
+// Temporary boost picked up along the course
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerUp {
+    // pulls nearby coins in
+    Magnet,
+    // takes the next hit instead of the player
+    Shield,
+}
+
+impl PowerUp {
+    pub fn name(&self) -> &'static str {
+        match self {
+            PowerUp::Magnet => "Magnet",
+            PowerUp::Shield => "Shield",
+        }
+    }
+
+    // seconds it lasts before upgrades
+    fn duration(&self) -> f32 {
+        match self {
+            PowerUp::Magnet => 8.0,
+            PowerUp::Shield => 12.0,
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            PowerUp::Magnet => Color::rgb(0.9, 0.25, 0.35),
+            PowerUp::Shield => Color::rgb(0.3, 0.6, 1.0),
+        }
+    }
+}
+
+// Power-up waiting to be picked up
+#[derive(Component)]
+struct PowerUpPickup(PowerUp);
+
+// Where the next power-up of the run goes
+#[derive(Resource, Default)]
+struct PowerUpSpawner {
+    next_x: f32,
+    // power-ups placed so far
+    placed: u32,
+}
+
+// Power-ups in effect and the time they have left
+#[derive(Resource, Default)]
+pub struct ActivePowerUps(pub Vec<(PowerUp, Timer)>);
+
+impl ActivePowerUps {
+    pub fn is_active(&self, power_up: PowerUp) -> bool {
+        self.0.iter().any(|(active, _)| *active == power_up)
+    }
+
+    // ends `power_up` early, if it's in effect
+    pub fn end(&mut self, power_up: PowerUp) {
+        self.0.retain(|(active, _)| *active != power_up);
+    }
+}
+
+fn reset_power_ups(mut commands: Commands) {
+    commands.insert_resource(PowerUpSpawner {
+        next_x: POWER_UP_SPACING.0,
+        placed: 0,
+    });
+    commands.insert_resource(ActivePowerUps::default());
+}
+
+// a power-up floating at `position`, waiting to be picked up
+pub fn power_up_bundle(power_up: PowerUp, position: Vec2) -> impl Bundle {
+    let half = POWER_UP_SIZE / 2.0;
+    (
+        SpriteBundle {
+            sprite: Sprite {
+                color: power_up.color(),
+                custom_size: Some(Vec2::splat(POWER_UP_SIZE)),
+                ..default()
+            },
+            transform: Transform::from_translation(position.extend(1.4)),
+            ..default()
+        },
+        PowerUpPickup(power_up),
+        Collider(Rect::new(-half, -half, half, half)),
+        RunEntity,
+    )
+}
+
+// system to place power-ups ahead of the camera every now and then
+fn spawn_power_ups(
+    mut commands: Commands,
+    mut spawner: ResMut<PowerUpSpawner>,
+    mut rng: ResMut<RunRng>,
+    camera_query: Query<(&Transform, &OrthographicProjection), With<ParallaxCameraComponent>>,
+) {
+    let Ok((camera, projection)) = camera_query.get_single() else {
+        return;
+    };
+    if camera.translation.x + projection.area.max.x < spawner.next_x {
+        return;
+    }
+    spawner.placed += 1;
+    let power_up = if spawner.placed.is_multiple_of(SHIELD_EVERY) {
+        PowerUp::Shield
+    } else {
+        PowerUp::Magnet
+    };
+    let y = GROUND_Y + rng.0.gen_range(POWER_UP_HEIGHT.0..=POWER_UP_HEIGHT.1);
+    commands.spawn(power_up_bundle(power_up, Vec2::new(spawner.next_x, y)));
+    spawner.next_x += rng.0.gen_range(POWER_UP_SPACING.0..=POWER_UP_SPACING.1);
+}
+
+// system to start the power-ups the player touches, picking one up again restarts it
+fn pick_up_power_ups(
+    mut commands: Commands,
+    pickup_query: Query<(Entity, &PowerUpPickup, &GlobalTransform, &Collider)>,
+    player_query: Query<(&GlobalTransform, &Collider, Option<&RubberBand>), PlayerOne>,
+    config: Res<RunConfig>,
+    mut active: ResMut<ActivePowerUps>,
+    mut toasts: EventWriter<Toast>,
+) {
+    for (entity, pickup, transform, collider) in &pickup_query {
+        let rect = collider.world_rect(transform);
+        // a player trailing a race gets more out of it
+        let Some(band) = player_query
+            .iter()
+            .find_map(|(player, player_collider, band)| {
+                let touching = !rect
+                    .intersect(player_collider.world_rect(player))
+                    .is_empty();
+                touching.then(|| band.map_or(1.0, |band| band.power_up))
+            })
+        else {
+            continue;
+        };
+        commands.entity(entity).despawn_recursive();
+        let power_up = pickup.0;
+        let duration = power_up.duration() * config.power_up_duration * band;
+        active.end(power_up);
+        active
+            .0
+            .push((power_up, Timer::from_seconds(duration, TimerMode::Once)));
+        toasts.send(Toast(format!("{}!", power_up.name())));
+    }
+}
+
+fn tick_power_ups(time: Res<GameTime>, mut active: ResMut<ActivePowerUps>) {
+    for (_, timer) in &mut active.0 {
+        timer.tick(time.delta());
+    }
+    active.0.retain(|(_, timer)| !timer.finished());
+}
+
+// system to pull the coins within the magnet's reach towards the player
+fn attract_coins(
+    time: Res<GameTime>,
+    config: Res<RunConfig>,
+    active: Res<ActivePowerUps>,
+    player_query: Query<&Transform, PlayerOne>,
+    mut coin_query: Query<&mut Transform, (With<Coin>, Without<Player>)>,
+) {
+    if !active.is_active(PowerUp::Magnet) {
+        return;
+    }
+    let Ok(player) = player_query.get_single() else {
+        return;
+    };
+    let target = player.translation.truncate();
+    let step = MAGNET_PULL * time.delta_seconds();
+    for mut transform in &mut coin_query {
+        let offset = target - transform.translation.truncate();
+        let distance = offset.length();
+        if distance > config.magnet_radius || distance == 0.0 {
+            continue;
+        }
+        let moved = offset / distance * step.min(distance);
+        transform.translation += moved.extend(0.0);
+    }
+}
+
+pub struct PowerUpPlugin;
+
+impl Plugin for PowerUpPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PowerUpSpawner>()
+            .init_resource::<ActivePowerUps>()
+            .add_systems(OnEnter(GameState::Playing), reset_power_ups)
+            .add_systems(
+                Update,
+                (
+                    spawn_power_ups,
+                    pick_up_power_ups,
+                    tick_power_ups,
+                    attract_coins,
+                )
+                    .chain()
+                    .in_set(GameplaySet),
+            );
+    }
+}
@@ -0,0 +1,160 @@
+use crate::grade::Grade;
+use crate::hud::HudLayout;
+use crate::input::{GamepadTuning, InputMap};
+use crate::settings::{AccessibilitySettings, Settings};
+use crate::stats_panel::StatsPanelSettings;
+use crate::toast::Toast;
+use crate::upgrades::Upgrades;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+const DEFAULT_PROFILE: &str = "Player 1";
+
+// Settings that go with whoever is playing rather than the machine: their controls,
+// accessibility needs and HUD layout
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct ProfileSettings {
+    pub controls: InputMap,
+    pub gamepad: GamepadTuning,
+    pub accessibility: AccessibilitySettings,
+    pub hud: StatsPanelSettings,
+    pub hud_layout: HudLayout,
+}
+
+impl Default for ProfileSettings {
+    fn default() -> Self {
+        ProfileSettings::from_settings(&Settings::default())
+    }
+}
+
+impl ProfileSettings {
+    fn from_settings(settings: &Settings) -> Self {
+        ProfileSettings {
+            controls: settings.controls.clone(),
+            gamepad: settings.gamepad,
+            accessibility: settings.accessibility.clone(),
+            hud: settings.stats_panel.clone(),
+            hud_layout: settings.hud_layout.clone(),
+        }
+    }
+
+    fn apply(&self, settings: &mut Settings) {
+        settings.controls = self.controls.clone();
+        settings.controls.bind_missing();
+        settings.gamepad = self.gamepad;
+        settings.accessibility = self.accessibility.clone();
+        settings.stats_panel = self.hud.clone();
+        settings.hud_layout = self.hud_layout.clone();
+    }
+}
+
+// Progress of one of the people playing on this machine
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct Profile {
+    pub name: String,
+    // coins banked from finished runs, spent on upgrades
+    pub coins: u32,
+    pub upgrades: Upgrades,
+    // best grade reached in each mode, by mode name
+    pub best_grades: BTreeMap<String, Grade>,
+    // modes whose author ghost they beat, paid out for once
+    pub beaten_authors: BTreeSet<String>,
+    // their own settings, None until they change one and for saves from before there
+    // were any, keeping whatever was set when switching to them
+    pub settings: Option<ProfileSettings>,
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Profile {
+            name: DEFAULT_PROFILE.to_string(),
+            coins: 0,
+            upgrades: Upgrades::default(),
+            best_grades: BTreeMap::new(),
+            beaten_authors: BTreeSet::new(),
+            settings: None,
+        }
+    }
+}
+
+// Every profile and the one being played, saved along with the run history
+#[derive(Resource, Serialize, Deserialize, Debug, Clone)]
+pub struct Profiles {
+    pub list: Vec<Profile>,
+    pub active: usize,
+}
+
+impl Default for Profiles {
+    fn default() -> Self {
+        Profiles {
+            list: vec![Profile::default()],
+            active: 0,
+        }
+    }
+}
+
+impl Profiles {
+    pub fn active(&self) -> &Profile {
+        &self.list[self.active]
+    }
+
+    pub fn active_mut(&mut self) -> &mut Profile {
+        &mut self.list[self.active]
+    }
+
+    // keeps `active` pointing at a profile after loading a save edited by hand
+    pub fn validated(mut self) -> Self {
+        if self.list.is_empty() {
+            self.list.push(Profile::default());
+        }
+        self.active = self.active.min(self.list.len() - 1);
+        self
+    }
+}
+
+// Event to play as the profile at this index, or as a new one past the last
+#[derive(Event, Debug, Clone, Copy)]
+pub struct SwitchProfile(pub usize);
+
+// system to keep the active profile's copy of its settings up to date as they're changed
+fn remember_settings(settings: Res<Settings>, mut profiles: ResMut<Profiles>) {
+    if !settings.is_changed() || settings.is_added() {
+        return;
+    }
+    let own = Some(ProfileSettings::from_settings(&settings));
+    if profiles.active().settings != own {
+        profiles.active_mut().settings = own;
+    }
+}
+
+// system to switch profiles, bringing in the settings of the one switched to
+fn switch_profile(
+    mut events: EventReader<SwitchProfile>,
+    mut profiles: ResMut<Profiles>,
+    mut settings: ResMut<Settings>,
+    mut toasts: EventWriter<Toast>,
+) {
+    for SwitchProfile(index) in events.read() {
+        if *index >= profiles.list.len() {
+            let name = format!("Player {}", profiles.list.len() + 1);
+            profiles.list.push(Profile { name, ..default() });
+        }
+        profiles.active = (*index).min(profiles.list.len() - 1);
+        if let Some(own) = &profiles.active().settings {
+            own.apply(&mut settings);
+        }
+        toasts.send(Toast(format!("Playing as {}", profiles.active().name)));
+    }
+}
+
+pub struct ProfilePlugin;
+
+impl Plugin for ProfilePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<SwitchProfile>()
+            .add_systems(Update, (switch_profile, remember_settings).chain());
+    }
+}
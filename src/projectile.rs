@@ -0,0 +1,303 @@
+use crate::audio::{PlaySfx, Sfx};
+use crate::collision::Collider;
+use crate::drop_in::SecondPlayer;
+use crate::enemy::Enemy;
+use crate::feedback::Feedback;
+use crate::game_time::GameTime;
+use crate::health::{Boss, Health, HitSet, Invincible};
+use crate::heatmap::{Hazard, LastHit};
+use crate::input::{Action, ActionState};
+use crate::score::{AddPoints, PointSource};
+use crate::timing::timed;
+use crate::{GameplaySet, Player, PlayerOne, RunEntity, GROUND_Y};
+use bevy::prelude::*;
+
+// downward acceleration of projectiles, in world units per second squared
+const PROJECTILE_GRAVITY: f32 = 600.0;
+// enemies only shoot at players this far ahead of them
+const SHOOT_RANGE: (f32, f32) = (120.0, 520.0);
+// how far the player moves while a projectile is in the air, per second of flight
+const PLAYER_LEAD: f32 = 60.0;
+// a dash started at most this long before impact sends the projectile back
+const DEFLECT_WINDOW: f32 = 0.2;
+// projectiles that fly this long without hitting anything are removed
+const PROJECTILE_LIFETIME: f32 = 5.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProjectileKind {
+    // quick, flat spit from regular enemies
+    Glob,
+    // slow, high lobbed rock from bosses
+    Rock,
+}
+
+impl ProjectileKind {
+    // seconds from being thrown to reaching the target
+    fn flight_time(&self) -> f32 {
+        match self {
+            ProjectileKind::Glob => 0.9,
+            ProjectileKind::Rock => 1.6,
+        }
+    }
+
+    fn hazard(&self) -> Hazard {
+        match self {
+            ProjectileKind::Glob => Hazard::Glob,
+            ProjectileKind::Rock => Hazard::Rock,
+        }
+    }
+
+    fn damage(&self) -> f32 {
+        match self {
+            ProjectileKind::Glob => 1.0,
+            ProjectileKind::Rock => 3.0,
+        }
+    }
+
+    fn sprite(&self) -> Sprite {
+        let (color, size) = match self {
+            ProjectileKind::Glob => (Color::rgb(0.5, 0.85, 0.3), 8.0),
+            ProjectileKind::Rock => (Color::rgb(0.45, 0.4, 0.35), 20.0),
+        };
+        Sprite {
+            color,
+            custom_size: Some(Vec2::splat(size)),
+            ..default()
+        }
+    }
+}
+
+// Lets an enemy throw projectiles of `kind` at the player every so often
+#[derive(Component)]
+pub struct Shooter {
+    pub kind: ProjectileKind,
+    pub timer: Timer,
+}
+
+#[derive(Component)]
+pub struct Projectile {
+    kind: ProjectileKind,
+    velocity: Vec2,
+    // the enemy that threw it, where deflected projectiles go back to
+    shooter: Entity,
+    deflected: bool,
+    age: f32,
+}
+
+impl Projectile {
+    // whether a dash sent it back at its shooter
+    pub fn deflected(&self) -> bool {
+        self.deflected
+    }
+}
+
+// Seconds left in which a projectile touching the player gets deflected
+#[derive(Resource, Default)]
+struct DeflectWindow(f32);
+
+// launch velocity that takes a projectile from `from` to `to` in `time` seconds
+fn arc_velocity(from: Vec2, to: Vec2, time: f32) -> Vec2 {
+    let delta = to - from;
+    Vec2::new(
+        delta.x / time,
+        (delta.y + 0.5 * PROJECTILE_GRAVITY * time * time) / time,
+    )
+}
+
+// system to open the deflection window whenever the player dashes
+fn record_dash(time: Res<GameTime>, actions: Res<ActionState>, mut window: ResMut<DeflectWindow>) {
+    if actions.just_pressed(Action::Run) {
+        window.0 = DEFLECT_WINDOW;
+    } else {
+        window.0 = (window.0 - time.delta_seconds()).max(0.0);
+    }
+}
+
+// system to let enemies in range lob their projectiles at where the player will be
+fn shoot(
+    mut commands: Commands,
+    time: Res<GameTime>,
+    mut shooter_query: Query<(Entity, &Transform, &mut Shooter)>,
+    player_query: Query<&Transform, PlayerOne>,
+    mut sfx: EventWriter<PlaySfx>,
+    mut feedback: EventWriter<Feedback>,
+) {
+    let Ok(player) = player_query.get_single() else {
+        return;
+    };
+    for (shooter, transform, mut shooting) in &mut shooter_query {
+        let distance = transform.translation.x - player.translation.x;
+        if distance < SHOOT_RANGE.0 || distance > SHOOT_RANGE.1 {
+            continue;
+        }
+        if !shooting.timer.tick(time.delta()).just_finished() {
+            continue;
+        }
+        let kind = shooting.kind;
+        let from = transform.translation.truncate();
+        let target =
+            player.translation.truncate() + Vec2::new(PLAYER_LEAD * kind.flight_time(), 0.0);
+        commands.spawn((
+            SpriteBundle {
+                sprite: kind.sprite(),
+                transform: Transform::from_translation(from.extend(1.6)),
+                ..default()
+            },
+            Projectile {
+                kind,
+                velocity: arc_velocity(from, target, kind.flight_time()),
+                shooter,
+                deflected: false,
+                age: 0.0,
+            },
+            Collider(Rect::new(-4.0, -4.0, 4.0, 4.0)),
+            RunEntity,
+        ));
+        // spitters screech as they spit, bosses' rocks rumble
+        match kind {
+            ProjectileKind::Glob => {
+                sfx.send(PlaySfx(Sfx::BirdScreech));
+            }
+            ProjectileKind::Rock => {
+                sfx.send(PlaySfx(Sfx::BoulderRumble));
+                feedback.send(Feedback::Warning("Incoming rock!".to_string()));
+            }
+        }
+    }
+}
+
+// system to move projectiles along their arcs and clear out the ones that missed
+fn move_projectiles(
+    mut commands: Commands,
+    time: Res<GameTime>,
+    mut query: Query<(Entity, &mut Transform, &mut Projectile)>,
+) {
+    let delta = time.delta_seconds();
+    for (entity, mut transform, mut projectile) in &mut query {
+        projectile.velocity.y -= PROJECTILE_GRAVITY * delta;
+        transform.translation += (projectile.velocity * delta).extend(0.0);
+        projectile.age += delta;
+        let landed = transform.translation.y < GROUND_Y - 32.0;
+        if landed || projectile.age > PROJECTILE_LIFETIME {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+// A player projectiles can hit
+type Target<'a> = (
+    &'a GlobalTransform,
+    &'a Collider,
+    &'a mut Health,
+    Has<Invincible>,
+    Has<SecondPlayer>,
+);
+
+// system to bounce projectiles back off a dashing player, or let them hit whichever
+// player they reach
+#[allow(clippy::too_many_arguments)]
+fn hit_player(
+    mut commands: Commands,
+    window: Res<DeflectWindow>,
+    mut projectile_query: Query<(Entity, &Transform, &Collider, &mut Projectile)>,
+    mut player_query: Query<Target, With<Player>>,
+    shooter_query: Query<&Transform, With<Enemy>>,
+    mut last_hit: ResMut<LastHit>,
+    mut points: EventWriter<AddPoints>,
+    mut feedback: EventWriter<Feedback>,
+) {
+    for (entity, transform, collider, mut projectile) in &mut projectile_query {
+        if projectile.deflected {
+            continue;
+        }
+        let rect = collider.world_rect(&GlobalTransform::from(*transform));
+        let Some((_, _, mut player_health, invincible, second)) =
+            player_query
+                .iter_mut()
+                .find(|(player_transform, player_collider, ..)| {
+                    !rect
+                        .intersect(player_collider.world_rect(player_transform))
+                        .is_empty()
+                })
+        else {
+            continue;
+        };
+        // the boost, and the deflect window it opens, are the first player's
+        let dashing = window.0 > 0.0 && !second;
+        let shooter = shooter_query.get(projectile.shooter);
+        match (dashing, shooter) {
+            (true, Ok(shooter)) => {
+                let from = transform.translation.truncate();
+                let to = shooter.translation.truncate();
+                projectile.velocity = arc_velocity(from, to, projectile.kind.flight_time() * 0.5);
+                projectile.deflected = true;
+                projectile.age = 0.0;
+                points.send(AddPoints(PointSource::Deflect));
+                feedback.send(Feedback::Flash(Color::rgba(1.0, 1.0, 1.0, 0.4)));
+            }
+            // nobody left to send it back to, the dash still swats it away
+            (true, Err(_)) => commands.entity(entity).despawn_recursive(),
+            (false, _) if invincible => commands.entity(entity).despawn_recursive(),
+            (false, _) => {
+                commands.entity(entity).despawn_recursive();
+                player_health.current -= projectile.kind.damage();
+                if !second {
+                    last_hit.0 = Some(projectile.kind.hazard());
+                    feedback.send(Feedback::Flash(Color::rgba(1.0, 0.2, 0.2, 0.5)));
+                }
+            }
+        }
+    }
+}
+
+// system to damage enemies struck by deflected projectiles, defeating them at zero health
+fn hit_enemies(
+    mut commands: Commands,
+    projectile_query: Query<(Entity, &Transform, &Collider, &Projectile)>,
+    // the players have health too, and a projectile just sent back still overlaps them
+    mut enemy_query: Query<(Entity, &GlobalTransform, &Collider, &mut Health), With<Enemy>>,
+    boss_query: Query<(), With<Boss>>,
+    mut points: EventWriter<AddPoints>,
+) {
+    for (entity, transform, collider, projectile) in &projectile_query {
+        if !projectile.deflected {
+            continue;
+        }
+        let rect = collider.world_rect(&GlobalTransform::from(*transform));
+        for (enemy, enemy_transform, enemy_collider, mut health) in &mut enemy_query {
+            if rect
+                .intersect(enemy_collider.world_rect(enemy_transform))
+                .is_empty()
+            {
+                continue;
+            }
+            commands.entity(entity).despawn_recursive();
+            health.current -= projectile.kind.damage();
+            if health.current <= 0.0 {
+                commands.entity(enemy).despawn_recursive();
+                let boss = boss_query.contains(enemy);
+                points.send(AddPoints(PointSource::defeated(boss)));
+            }
+            break;
+        }
+    }
+}
+
+pub struct ProjectilePlugin;
+
+impl Plugin for ProjectilePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DeflectWindow>().add_systems(
+            Update,
+            (
+                timed(record_dash),
+                timed(shoot),
+                timed(move_projectiles),
+                timed(hit_player).in_set(HitSet),
+                timed(hit_enemies),
+            )
+                .chain()
+                .in_set(GameplaySet),
+        );
+    }
+}
@@ -0,0 +1,114 @@
+use crate::input::{Action, GamepadKind, InputDevice, InputMap, LastInputDevice};
+use crate::settings::Settings;
+use bevy::prelude::*;
+use bevy::ui::UiSystem;
+
+// Buttons that prompts can refer to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PromptKey {
+    Action(Action),
+    // accept the focused menu item
+    Confirm,
+    // leave the current menu screen
+    Back,
+}
+
+#[derive(Debug, Clone)]
+pub enum PromptPart {
+    Text(&'static str),
+    Glyph(PromptKey),
+}
+
+// Text made of plain parts and button glyphs, re-rendered whenever the player
+// switches device or changes their bindings
+#[derive(Component, Debug, Clone)]
+pub struct Prompt(pub Vec<PromptPart>);
+
+impl Prompt {
+    pub fn render(&self, device: InputDevice, controls: &InputMap) -> String {
+        self.0
+            .iter()
+            .map(|part| match part {
+                PromptPart::Text(text) => text.to_string(),
+                PromptPart::Glyph(key) => glyph(*key, device, controls),
+            })
+            .collect()
+    }
+}
+
+// label of the button that triggers `key` on `device`
+pub fn glyph(key: PromptKey, device: InputDevice, controls: &InputMap) -> String {
+    match device {
+        InputDevice::Keyboard => keyboard_glyph(key, controls),
+        InputDevice::Mouse => match key {
+            PromptKey::Action(Action::Jump) | PromptKey::Confirm => "[Left click]".to_string(),
+            PromptKey::Action(Action::Run) => "[Right click]".to_string(),
+            PromptKey::Action(Action::Duck) => "[Scroll down]".to_string(),
+            PromptKey::Action(Action::Boost) => "[Middle click]".to_string(),
+            _ => keyboard_glyph(key, controls),
+        },
+        InputDevice::Gamepad(kind) => {
+            let (south, east, west, north, start, select, bumper) = match kind {
+                GamepadKind::Xbox => ("A", "B", "X", "Y", "Menu", "View", "RB"),
+                GamepadKind::PlayStation => (
+                    "Cross", "Circle", "Square", "Triangle", "Options", "Share", "R1",
+                ),
+            };
+
+            let button = match key {
+                PromptKey::Action(Action::Jump) | PromptKey::Confirm => south,
+                PromptKey::Back => east,
+                PromptKey::Action(Action::Run) => west,
+                PromptKey::Action(Action::Duck) => "D-pad down",
+                PromptKey::Action(Action::MoveLeft) => "D-pad left",
+                PromptKey::Action(Action::MoveRight) => "D-pad right",
+                PromptKey::Action(Action::Boost) => north,
+                PromptKey::Action(Action::Pause) => start,
+                PromptKey::Action(Action::Restart) => select,
+                PromptKey::Action(Action::SuperJump) => {
+                    return format!("({}+{})", bumper, south);
+                }
+                // no button for the stats panel, it's only on the keyboard
+                PromptKey::Action(Action::StatsPanel | Action::StatsCorner) => {
+                    return keyboard_glyph(key, controls);
+                }
+            };
+            format!("({})", button)
+        }
+    }
+}
+
+fn keyboard_glyph(key: PromptKey, controls: &InputMap) -> String {
+    let name = match key {
+        PromptKey::Action(action) => controls
+            .binding_names(action)
+            .into_iter()
+            .next()
+            .unwrap_or_else(|| "Unbound".to_string()),
+        PromptKey::Confirm => "Enter".to_string(),
+        PromptKey::Back => "Esc".to_string(),
+    };
+    format!("[{}]", name)
+}
+
+// system to redraw prompts for the device the player is currently using
+fn update_prompts(
+    last_device: Res<LastInputDevice>,
+    settings: Res<Settings>,
+    mut query: Query<(Ref<Prompt>, &mut Text)>,
+) {
+    let refresh_all = last_device.is_changed() || settings.is_changed();
+    for (prompt, mut text) in &mut query {
+        if refresh_all || prompt.is_changed() {
+            text.sections[0].value = prompt.render(last_device.0, &settings.controls);
+        }
+    }
+}
+
+pub struct PromptPlugin;
+
+impl Plugin for PromptPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PostUpdate, update_prompts.before(UiSystem::Layout));
+    }
+}
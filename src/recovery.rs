@@ -0,0 +1,50 @@
+use crate::storage;
+use crate::toast::Toast;
+use crate::web::unix_time;
+use bevy::prelude::*;
+use std::sync::Mutex;
+
+// Notices about files that had to be reset, gathered while loading them, which may
+// happen before the app exists, and shown once the game is up
+static NOTICES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+// Move a file that failed to load out of the way, so it can be looked at or fixed by hand
+// while the game starts over from defaults, and let the player know once the game is up
+pub fn back_up_corrupt(path: &str, what: &str) {
+    let seconds = unix_time().as_secs();
+    let backup = format!("{}.corrupt-{}", path, seconds);
+    let notice = match storage::rename(path, &backup) {
+        Ok(()) => {
+            warn!("Moved unreadable {} to {}", path, backup);
+            format!(
+                "{} couldn't be read and were reset, the old file is {}",
+                what, backup
+            )
+        }
+        Err(err) => {
+            error!("Could not back up {}: {}", path, err);
+            format!("{} couldn't be read and were reset", what)
+        }
+    };
+    if let Ok(mut notices) = NOTICES.lock() {
+        notices.push(notice);
+    }
+}
+
+// system to show the notices of files reset while loading
+fn show_notices(mut toasts: EventWriter<Toast>) {
+    let Ok(mut notices) = NOTICES.lock() else {
+        return;
+    };
+    for notice in notices.drain(..) {
+        toasts.send(Toast(notice));
+    }
+}
+
+pub struct RecoveryPlugin;
+
+impl Plugin for RecoveryPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, show_notices);
+    }
+}
@@ -0,0 +1,66 @@
+use crate::input::{Action, ActionState};
+use crate::menu::MenuState;
+use crate::mode::RunSeed;
+use crate::GameState;
+use bevy::prelude::*;
+
+// Seed a restarted run replays the last course with, None for a fresh course
+#[derive(Resource, Default)]
+pub struct RestartSeed(pub Option<u64>);
+
+// Whether the menu is still on the screen a run ended on, where restarting picks the
+// same mode up again
+#[derive(Resource, Default)]
+struct RunJustEnded(bool);
+
+fn mark_run_ended(mut ended: ResMut<RunJustEnded>) {
+    ended.0 = true;
+}
+
+fn clear_run_ended(mut ended: ResMut<RunJustEnded>) {
+    ended.0 = false;
+}
+
+// system to tear the run down and start a new one straight away, from a run, paused or
+// not, or from the screen it ended on. Holding shift replays the same course.
+#[allow(clippy::too_many_arguments)]
+fn quick_restart(
+    actions: Res<ActionState>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    game_state: Res<State<GameState>>,
+    ended: Res<RunJustEnded>,
+    seed: Res<RunSeed>,
+    mut restart_seed: ResMut<RestartSeed>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+    mut menu_state: ResMut<NextState<MenuState>>,
+) {
+    if !actions.just_pressed(Action::Restart) {
+        return;
+    }
+    if *game_state.get() == GameState::Menu && !ended.0 {
+        return;
+    }
+    let same_course = keyboard_input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    restart_seed.0 = same_course.then_some(seed.0);
+    menu_state.set(MenuState::Disabled);
+    next_game_state.set(GameState::Restarting);
+}
+
+// system to go on into the new run, once leaving the old one has torn it down
+fn restart(mut game_state: ResMut<NextState<GameState>>) {
+    game_state.set(GameState::Playing);
+}
+
+pub struct RestartPlugin;
+
+impl Plugin for RestartPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RestartSeed>()
+            .init_resource::<RunJustEnded>()
+            .add_systems(OnExit(GameState::Playing), mark_run_ended)
+            .add_systems(OnExit(MenuState::Main), clear_run_ended)
+            .add_systems(OnExit(MenuState::Duel), clear_run_ended)
+            .add_systems(OnEnter(GameState::Restarting), restart)
+            .add_systems(Update, quick_restart);
+    }
+}
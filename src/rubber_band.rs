@@ -0,0 +1,69 @@
+use crate::settings::Settings;
+use crate::{GameplaySet, Player, PlayerOne};
+use bevy::prelude::*;
+
+// a player this far behind the leader, or further, gets the full catch-up
+const CATCH_UP_DISTANCE: f32 = 600.0;
+// share of extra speed, and of extra power-up time, at the full catch-up
+const MAX_SPEED_BOOST: f32 = 0.15;
+const MAX_POWER_UP_BOOST: f32 = 0.5;
+
+// How much a trailing player is helped along to keep a race with several players close.
+// Always neutral for the leader and in runs with a single player.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct RubberBand {
+    // factor on the player's forward speed
+    pub speed: f32,
+    // factor on the time power-ups picked up by the player last
+    pub power_up: f32,
+}
+
+impl Default for RubberBand {
+    fn default() -> Self {
+        RubberBand {
+            speed: 1.0,
+            power_up: 1.0,
+        }
+    }
+}
+
+fn add_rubber_bands(mut commands: Commands, player_query: Query<Entity, Added<Player>>) {
+    for player in &player_query {
+        commands.entity(player).insert(RubberBand::default());
+    }
+}
+
+// system to help players along by how far they trail the leader, if rubber-banding is on
+fn update_rubber_bands(
+    settings: Res<Settings>,
+    mut player_query: Query<(&Transform, &mut RubberBand), PlayerOne>,
+) {
+    let leader = player_query
+        .iter()
+        .map(|(transform, _)| transform.translation.x)
+        .fold(f32::MIN, f32::max);
+    for (transform, mut band) in &mut player_query {
+        let behind = if settings.rubber_banding {
+            ((leader - transform.translation.x) / CATCH_UP_DISTANCE).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        *band = RubberBand {
+            speed: 1.0 + MAX_SPEED_BOOST * behind,
+            power_up: 1.0 + MAX_POWER_UP_BOOST * behind,
+        };
+    }
+}
+
+pub struct RubberBandPlugin;
+
+impl Plugin for RubberBandPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (add_rubber_bands, update_rubber_bands)
+                .chain()
+                .in_set(GameplaySet),
+        );
+    }
+}
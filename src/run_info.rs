@@ -0,0 +1,75 @@
+use crate::mode::{start_run, ActiveMode, RunSeed};
+use crate::mutators::{configure_run, Mutator, RunConfig};
+use crate::{GameState, RunEntity};
+use bevy::prelude::*;
+
+// What's needed to play a run's course again: its mode, seed and mutators
+pub struct RunInfo {
+    pub mode: String,
+    pub seed: u64,
+    pub mutators: Vec<Mutator>,
+}
+
+impl RunInfo {
+    pub fn current(mode: &ActiveMode, seed: &RunSeed, config: &RunConfig) -> Self {
+        RunInfo {
+            mode: mode.name().to_string(),
+            seed: seed.0,
+            mutators: config.mutators.clone(),
+        }
+    }
+
+    pub fn mutator_names(&self) -> String {
+        if self.mutators.is_empty() {
+            return "none".to_string();
+        }
+        let names: Vec<&str> = self.mutators.iter().map(Mutator::name).collect();
+        names.join(", ")
+    }
+}
+
+// Corner widget naming the run's mode, seed and mutators
+#[derive(Component)]
+struct RunInfoText;
+
+fn spawn_run_info(
+    mut commands: Commands,
+    mode: Res<ActiveMode>,
+    seed: Res<RunSeed>,
+    config: Res<RunConfig>,
+) {
+    let info = RunInfo::current(&mode, &seed, &config);
+    let mut line = format!("{}  seed {}", info.mode, info.seed);
+    if !info.mutators.is_empty() {
+        line = format!("{}  {}", line, info.mutator_names());
+    }
+    commands.spawn((
+        TextBundle::from_section(
+            line,
+            TextStyle {
+                font_size: 11.0,
+                color: Color::rgba(1.0, 1.0, 1.0, 0.6),
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(4.0),
+            left: Val::Px(6.0),
+            ..default()
+        }),
+        RunInfoText,
+        RunEntity,
+    ));
+}
+
+pub struct RunInfoPlugin;
+
+impl Plugin for RunInfoPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(GameState::Playing),
+            spawn_run_info.after(start_run).after(configure_run),
+        );
+    }
+}
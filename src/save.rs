@@ -0,0 +1,146 @@
+use crate::error::GameError;
+use crate::game_over::{RunHistory, RunRecord};
+use crate::profile::Profiles;
+use crate::recovery::back_up_corrupt;
+use crate::storage;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+const SAVE_PATH: &str = "save.ron";
+// Bump whenever SaveData changes shape: keep the old shape as its own struct, and add
+// a migration from it to `load_version`, so older saves are carried over instead of lost
+const SAVE_VERSION: u32 = 2;
+
+// Progress kept between sessions, as of SAVE_VERSION
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct SaveData {
+    pub runs: Vec<RunRecord>,
+    pub profiles: Profiles,
+}
+
+// Version 1, from before profiles
+#[derive(Deserialize)]
+struct SaveV1 {
+    runs: Vec<RunRecord>,
+}
+
+// the runs carry over, played by the one profile there was
+fn migrate_v1(save: SaveV1) -> SaveData {
+    SaveData {
+        runs: save.runs,
+        profiles: Profiles::default(),
+    }
+}
+
+// What's on disk: the data tagged with the version of its shape
+#[derive(Serialize, Deserialize)]
+struct SaveFile<T> {
+    version: u32,
+    data: T,
+}
+
+// Just the version, read first to know which shape the rest has
+#[derive(Deserialize)]
+struct SaveHeader {
+    version: u32,
+}
+
+#[derive(Debug)]
+pub enum SaveError {
+    Ron(ron::error::SpannedError),
+    // written by a newer build of the game, which may have data this one would drop
+    NewerVersion(u32),
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SaveError::Ron(error) => write!(f, "invalid save: {}", error),
+            SaveError::NewerVersion(version) => write!(
+                f,
+                "save is version {}, newer than the supported {}",
+                version, SAVE_VERSION
+            ),
+        }
+    }
+}
+
+impl From<ron::error::SpannedError> for SaveError {
+    fn from(error: ron::error::SpannedError) -> Self {
+        SaveError::Ron(error)
+    }
+}
+
+// the data of a save of any known version, migrated up to SAVE_VERSION
+fn load_version(contents: &str) -> Result<SaveData, SaveError> {
+    let SaveHeader { version } = ron::from_str(contents)?;
+    match version {
+        SAVE_VERSION => Ok(ron::from_str::<SaveFile<SaveData>>(contents)?.data),
+        // older versions go here, each migrating to the next one
+        1 => Ok(migrate_v1(
+            ron::from_str::<SaveFile<SaveV1>>(contents)?.data,
+        )),
+        _ => Err(SaveError::NewerVersion(version)),
+    }
+}
+
+impl SaveData {
+    // the save on disk, a fresh one if there is none or it was unreadable
+    pub fn load() -> Result<Self, GameError> {
+        let Ok(contents) = storage::read_to_string(SAVE_PATH) else {
+            return Ok(SaveData::default());
+        };
+        match load_version(&contents) {
+            Ok(data) => Ok(data),
+            // what a newer build wrote is kept for when it's played again
+            Err(err @ SaveError::NewerVersion(_)) => Err(err.into()),
+            Err(err) => {
+                error!("Could not load {}: {}", SAVE_PATH, err);
+                back_up_corrupt(SAVE_PATH, "Saved runs");
+                let data = SaveData::default();
+                data.save();
+                Ok(data)
+            }
+        }
+    }
+
+    fn save(&self) {
+        let file = SaveFile {
+            version: SAVE_VERSION,
+            data: self,
+        };
+        let contents = match ron::ser::to_string_pretty(&file, ron::ser::PrettyConfig::default()) {
+            Ok(contents) => contents,
+            Err(err) => {
+                error!("Could not serialize the save: {}", err);
+                return;
+            }
+        };
+        if let Err(err) = storage::write(SAVE_PATH, contents) {
+            error!("Could not write {}: {}", SAVE_PATH, err);
+        }
+    }
+}
+
+// system to write progress back to disk after it was modified
+fn save_progress(history: Res<RunHistory>, profiles: Res<Profiles>) {
+    let history_modified = history.is_changed() && !history.is_added();
+    let profiles_modified = profiles.is_changed() && !profiles.is_added();
+    if !(history_modified || profiles_modified) {
+        return;
+    }
+    SaveData {
+        runs: history.0.clone(),
+        profiles: profiles.clone(),
+    }
+    .save();
+}
+
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Last, save_progress);
+    }
+}
@@ -0,0 +1,431 @@
+use crate::balance::Balance;
+use crate::game_time::GameTime;
+use crate::hud::{HudAnchor, HudWidget};
+use crate::mutators::RunConfig;
+use crate::stream::HudText;
+use crate::{GameState, GameplaySet, PlayerOne, RunEntity};
+use bevy::prelude::*;
+
+// how long a combo survives without scoring again
+const COMBO_TIME: f32 = 3.0;
+// scoring this many times in a row raises the multiplier by one
+const COMBO_STEP: u32 = 3;
+const MAX_MULTIPLIER: u32 = 5;
+// the ring around the multiplier that drains as the combo timer runs out: its size, and
+// the segments it's made of, going out one by one clockwise from the top
+const COMBO_RING_SIZE: f32 = 44.0;
+const COMBO_RING_SEGMENTS: usize = 24;
+const COMBO_SEGMENT_SIZE: f32 = 4.0;
+const COMBO_SEGMENT_LIT: Color = Color::rgb(1.0, 0.8, 0.2);
+const COMBO_SEGMENT_OUT: Color = Color::rgba(0.1, 0.1, 0.1, 0.8);
+// points scored within this long of the last ones add to the same popup
+const POPUP_WINDOW: f32 = 0.6;
+// how long a finished popup floats up before it's gone, and how fast
+const POPUP_FADE: f32 = 0.8;
+const POPUP_RISE: f32 = 24.0;
+// where popups show over the player, and how big in world pixels
+const POPUP_OFFSET: Vec3 = Vec3::new(0.0, 28.0, 5.0);
+const POPUP_FONT_SIZE: f32 = 10.0;
+// a popup grows up to half again as big, and turns gold, by this many points
+const POPUP_BIG: u32 = 300;
+
+// Points scored in the current run
+#[derive(Resource, Default)]
+pub struct Score(pub u32);
+
+// What points were scored for, each worth what the balance says
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PointSource {
+    Coin,
+    Spitter,
+    Boss,
+    Deflect,
+    Smash,
+    Swing,
+}
+
+impl PointSource {
+    // the source for defeating an enemy
+    pub fn defeated(boss: bool) -> Self {
+        if boss {
+            PointSource::Boss
+        } else {
+            PointSource::Spitter
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PointSource::Coin => "Coin",
+            PointSource::Spitter => "Spitter",
+            PointSource::Boss => "Boss",
+            PointSource::Deflect => "Deflect",
+            PointSource::Smash => "Smash",
+            PointSource::Swing => "Swing cleared",
+        }
+    }
+}
+
+// Event sent by gameplay systems to award the points `source` is worth, before the combo
+// multiplier
+#[derive(Event, Debug, Clone, Copy)]
+pub struct AddPoints(pub PointSource);
+
+// Event sent once points were added to the score, with the combo multiplier applied
+#[derive(Event, Debug, Clone, Copy)]
+pub struct Scored {
+    pub source: PointSource,
+    pub points: u32,
+}
+
+// Points scored in quick succession, each one keeping the combo alive a little longer
+#[derive(Resource)]
+pub struct Combo {
+    hits: u32,
+    timer: Timer,
+    // highest multiplier reached this run
+    best: u32,
+}
+
+impl Default for Combo {
+    fn default() -> Self {
+        Combo {
+            hits: 0,
+            timer: Timer::from_seconds(COMBO_TIME, TimerMode::Once),
+            best: 1,
+        }
+    }
+}
+
+impl Combo {
+    pub fn multiplier(&self) -> u32 {
+        (1 + self.hits / COMBO_STEP).min(MAX_MULTIPLIER)
+    }
+
+    pub fn best_multiplier(&self) -> u32 {
+        self.best
+    }
+
+    // fraction of the combo time left before the multiplier drops back to 1
+    pub fn remaining(&self) -> f32 {
+        if self.hits == 0 {
+            0.0
+        } else {
+            self.timer.fraction_remaining()
+        }
+    }
+
+    // ends the combo at once, as when the player is hit
+    pub fn break_off(&mut self) {
+        self.hits = 0;
+    }
+}
+
+// Points scored gathered into one popup over the player while they keep coming, rather
+// than one popup for each
+#[derive(Resource)]
+struct PopupBatch {
+    popup: Option<Entity>,
+    total: u32,
+    // scored since the popup was last updated
+    pending: u32,
+    window: Timer,
+}
+
+impl Default for PopupBatch {
+    fn default() -> Self {
+        PopupBatch {
+            popup: None,
+            total: 0,
+            pending: 0,
+            window: Timer::from_seconds(POPUP_WINDOW, TimerMode::Once),
+        }
+    }
+}
+
+// Floating text showing points scored, fading out once its batch is done
+#[derive(Component)]
+struct ScorePopup {
+    fade: Timer,
+}
+
+#[derive(Component)]
+struct ScoreText;
+
+// Multiplier under the score with its ring, hidden while there is no combo
+#[derive(Component)]
+struct ComboDisplay;
+
+#[derive(Component)]
+struct ComboText;
+
+// One segment of the ring around the multiplier, by its place clockwise from the top
+#[derive(Component)]
+struct ComboSegment(usize);
+
+fn reset_score(mut commands: Commands) {
+    commands.insert_resource(Score::default());
+    commands.insert_resource(Combo::default());
+    commands.insert_resource(PopupBatch::default());
+    let text_style = TextStyle {
+        font_size: 16.0,
+        color: Color::WHITE,
+        ..default()
+    };
+    commands.spawn((
+        TextBundle::from_section("Score: 0", text_style.clone()).with_style(Style {
+            position_type: PositionType::Absolute,
+            ..default()
+        }),
+        ScoreText,
+        HudText(16.0),
+        HudAnchor(HudWidget::Score),
+        RunEntity,
+    ));
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Px(COMBO_RING_SIZE),
+                    height: Val::Px(COMBO_RING_SIZE),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            ComboDisplay,
+            HudAnchor(HudWidget::Combo),
+            RunEntity,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section("x1", text_style),
+                ComboText,
+                HudText(16.0),
+            ));
+            let radius = (COMBO_RING_SIZE - COMBO_SEGMENT_SIZE) / 2.0;
+            for index in 0..COMBO_RING_SEGMENTS {
+                let angle = std::f32::consts::TAU * index as f32 / COMBO_RING_SEGMENTS as f32;
+                parent.spawn((
+                    NodeBundle {
+                        style: Style {
+                            position_type: PositionType::Absolute,
+                            left: Val::Px(radius * (1.0 + angle.sin())),
+                            top: Val::Px(radius * (1.0 - angle.cos())),
+                            width: Val::Px(COMBO_SEGMENT_SIZE),
+                            height: Val::Px(COMBO_SEGMENT_SIZE),
+                            ..default()
+                        },
+                        background_color: COMBO_SEGMENT_LIT.into(),
+                        ..default()
+                    },
+                    ComboSegment(index),
+                ));
+            }
+        });
+}
+
+fn add_points(
+    mut score: ResMut<Score>,
+    mut combo: ResMut<Combo>,
+    mut batch: ResMut<PopupBatch>,
+    config: Res<RunConfig>,
+    balance: Res<Balance>,
+    mut events: EventReader<AddPoints>,
+    mut scored: EventWriter<Scored>,
+) {
+    for AddPoints(source) in events.read() {
+        let points = balance.points.of(*source) * combo.multiplier();
+        let points = (points as f32 * config.score_multiplier).round() as u32;
+        score.0 += points;
+        batch.pending += points;
+        scored.send(Scored {
+            source: *source,
+            points,
+        });
+        combo.hits += 1;
+        combo.timer.reset();
+        combo.best = combo.best.max(combo.multiplier());
+    }
+}
+
+// system to run down the combo timer, ending the combo when it runs out
+fn tick_combo(time: Res<GameTime>, mut combo: ResMut<Combo>) {
+    if combo.hits > 0 && combo.timer.tick(time.delta()).just_finished() {
+        combo.hits = 0;
+    }
+}
+
+// text of a popup for `total` points, bigger and more golden the more there are
+fn popup_text(total: u32, done: bool) -> (String, f32, Color) {
+    let size = (total as f32 / POPUP_BIG as f32).min(1.0);
+    let value = format!("+{}{}", total, if done { "!" } else { "…" });
+    let color = Color::rgb(1.0, 1.0 - 0.2 * size, 1.0 - 0.8 * size);
+    (value, POPUP_FONT_SIZE * (1.0 + size * 0.5), color)
+}
+
+// system to show the points scored in a popup over the player, adding to the open
+// one while they come in quick succession and closing it once they stop
+fn batch_popups(
+    mut commands: Commands,
+    time: Res<GameTime>,
+    mut batch: ResMut<PopupBatch>,
+    player_query: Query<&Transform, (PlayerOne, Without<ScorePopup>)>,
+    mut popup_query: Query<(&mut Text, &mut Transform), With<ScorePopup>>,
+) {
+    let closed = batch.window.tick(time.delta()).just_finished();
+    let open = batch.popup;
+    let player = player_query
+        .get_single()
+        .map_or(Vec3::ZERO, |transform| transform.translation);
+    if batch.pending > 0 {
+        batch.total += batch.pending;
+        batch.pending = 0;
+        batch.window.reset();
+        let (value, font_size, color) = popup_text(batch.total, false);
+        match open.and_then(|popup| popup_query.get_mut(popup).ok()) {
+            Some((mut text, _)) => {
+                text.sections[0].value = value;
+                text.sections[0].style.font_size = font_size;
+                text.sections[0].style.color = color;
+            }
+            None => {
+                let popup = commands
+                    .spawn((
+                        Text2dBundle {
+                            text: Text::from_section(
+                                value,
+                                TextStyle {
+                                    font_size,
+                                    color,
+                                    ..default()
+                                },
+                            ),
+                            transform: Transform::from_translation(player + POPUP_OFFSET),
+                            ..default()
+                        },
+                        ScorePopup {
+                            fade: Timer::from_seconds(POPUP_FADE, TimerMode::Once),
+                        },
+                        RunEntity,
+                    ))
+                    .id();
+                batch.popup = Some(popup);
+            }
+        }
+    } else if closed {
+        if let Some((mut text, _)) = open.and_then(|popup| popup_query.get_mut(popup).ok()) {
+            text.sections[0].value = popup_text(batch.total, true).0;
+        }
+        batch.popup = None;
+        batch.total = 0;
+    }
+    // the open popup keeps up with the player
+    if let Some((_, mut transform)) = batch
+        .popup
+        .and_then(|popup| popup_query.get_mut(popup).ok())
+    {
+        transform.translation = player + POPUP_OFFSET;
+    }
+}
+
+// system to float closed popups up and fade them out
+fn fade_popups(
+    mut commands: Commands,
+    time: Res<GameTime>,
+    batch: Res<PopupBatch>,
+    mut popup_query: Query<(Entity, &mut ScorePopup, &mut Text, &mut Transform)>,
+) {
+    for (entity, mut popup, mut text, mut transform) in &mut popup_query {
+        if batch.popup == Some(entity) {
+            continue;
+        }
+        if popup.fade.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        transform.translation.y += POPUP_RISE * time.delta_seconds();
+        let alpha = popup.fade.fraction_remaining();
+        text.sections[0].style.color.set_a(alpha);
+    }
+}
+
+fn update_score_text(score: Res<Score>, mut query: Query<&mut Text, With<ScoreText>>) {
+    if !score.is_changed() {
+        return;
+    }
+    for mut text in &mut query {
+        text.sections[0].value = format!("Score: {}", score.0);
+    }
+}
+
+// system to show the multiplier and drain its ring in step with the combo timer
+fn update_combo_display(
+    combo: Res<Combo>,
+    mut display_query: Query<&mut Visibility, With<ComboDisplay>>,
+    mut text_query: Query<&mut Text, With<ComboText>>,
+    mut segment_query: Query<(&ComboSegment, &mut BackgroundColor)>,
+) {
+    if !combo.is_changed() {
+        return;
+    }
+    let active = combo.multiplier() > 1;
+    for mut visibility in &mut display_query {
+        let wanted = if active {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        if *visibility != wanted {
+            *visibility = wanted;
+        }
+    }
+    if !active {
+        return;
+    }
+    for mut text in &mut text_query {
+        text.sections[0].value = format!("x{}", combo.multiplier());
+    }
+    // the last segments clockwise go out first, so the ring drains back towards the top
+    let lit = (combo.remaining() * COMBO_RING_SEGMENTS as f32).ceil() as usize;
+    for (segment, mut background) in &mut segment_query {
+        let color = if segment.0 < lit {
+            COMBO_SEGMENT_LIT
+        } else {
+            COMBO_SEGMENT_OUT
+        };
+        if background.0 != color {
+            background.0 = color;
+        }
+    }
+}
+
+pub struct ScorePlugin;
+
+impl Plugin for ScorePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Score>()
+            .init_resource::<Combo>()
+            .init_resource::<PopupBatch>()
+            .add_event::<AddPoints>()
+            .add_event::<Scored>()
+            .add_systems(OnEnter(GameState::Playing), reset_score)
+            .add_systems(Update, tick_combo.in_set(GameplaySet))
+            .add_systems(
+                Update,
+                (
+                    add_points,
+                    update_score_text,
+                    update_combo_display,
+                    batch_popups,
+                    fade_popups,
+                )
+                    .chain()
+                    .after(tick_combo)
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
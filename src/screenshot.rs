@@ -0,0 +1,98 @@
+use crate::mode::{ActiveMode, RunSeed};
+use crate::mutators::RunConfig;
+use crate::run_info::RunInfo;
+use crate::toast::Toast;
+use crate::web::unix_time;
+use crate::GameState;
+use bevy::prelude::*;
+use bevy::render::view::screenshot::ScreenshotManager;
+use bevy::window::PrimaryWindow;
+use flate2::Crc;
+use std::io::Cursor;
+
+// end of the PNG signature and IHDR chunk, where text chunks can go
+const AFTER_HEADER: usize = 8 + 12 + 13;
+
+// a PNG tEXt chunk holding `text` under `keyword`
+fn text_chunk(keyword: &str, text: &str) -> Vec<u8> {
+    let mut body = b"tEXt".to_vec();
+    body.extend(keyword.as_bytes());
+    body.push(0);
+    body.extend(text.as_bytes());
+    let mut crc = Crc::new();
+    crc.update(&body);
+    let mut chunk = ((body.len() - 4) as u32).to_be_bytes().to_vec();
+    chunk.extend(&body);
+    chunk.extend(crc.sum().to_be_bytes());
+    chunk
+}
+
+// the screenshot as a PNG, with what's needed to play the run again in its text chunks
+// for anyone the picture is shared with
+fn encode(image: Image, info: Option<&RunInfo>) -> Result<Vec<u8>, String> {
+    // the alpha channel isn't meant to be looked at
+    let rgb = image
+        .try_into_dynamic()
+        .map_err(|err| err.to_string())?
+        .to_rgb8();
+    let mut png = Cursor::new(Vec::new());
+    rgb.write_to(&mut png, image::ImageFormat::Png)
+        .map_err(|err| err.to_string())?;
+    let mut png = png.into_inner();
+    let mut chunks = text_chunk("Software", "dinorun");
+    if let Some(info) = info {
+        chunks.extend(text_chunk("dinorun:mode", &info.mode));
+        chunks.extend(text_chunk("dinorun:seed", &info.seed.to_string()));
+        chunks.extend(text_chunk("dinorun:mutators", &info.mutator_names()));
+    }
+    png.splice(AFTER_HEADER..AFTER_HEADER, chunks);
+    Ok(png)
+}
+
+// system to save a screenshot next to the game when F12 is pressed, tagged with the run
+// being played if there is one
+#[allow(clippy::too_many_arguments)]
+fn take_screenshot(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    window_query: Query<Entity, With<PrimaryWindow>>,
+    mut screenshots: ResMut<ScreenshotManager>,
+    game_state: Res<State<GameState>>,
+    mode: Res<ActiveMode>,
+    seed: Res<RunSeed>,
+    config: Res<RunConfig>,
+    mut toasts: EventWriter<Toast>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::F12) {
+        return;
+    }
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+    let info =
+        (*game_state.get() == GameState::Playing).then(|| RunInfo::current(&mode, &seed, &config));
+    let stamp = unix_time().as_millis();
+    let path = format!("screenshot-{}.png", stamp);
+    let saved_to = path.clone();
+    let requested = screenshots.take_screenshot(window, move |image| {
+        let result = encode(image, info.as_ref())
+            .and_then(|png| std::fs::write(&path, png).map_err(|err| err.to_string()));
+        match result {
+            Ok(()) => info!("Screenshot saved to {}", path),
+            Err(err) => error!("Could not save screenshot {}: {}", path, err),
+        }
+    });
+    if requested.is_ok() {
+        toasts.send(Toast(format!("Screenshot saved to {}", saved_to)));
+    }
+}
+
+pub struct ScreenshotPlugin;
+
+impl Plugin for ScreenshotPlugin {
+    fn build(&self, app: &mut App) {
+        // there's no folder to save to on the web
+        if cfg!(not(target_arch = "wasm32")) {
+            app.add_systems(Update, take_screenshot);
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use crate::cosmetics::Cosmetics;
+use crate::difficulty::CustomDifficulty;
+use crate::display::{VideoSettings, WindowSettings};
+use crate::hud::HudLayout;
+use crate::input::{GamepadTuning, InputMap};
+use crate::locale::Language;
+use crate::mutators::Mutator;
+use crate::recovery::back_up_corrupt;
+use crate::stats_panel::StatsPanelSettings;
+use crate::storage;
+use crate::stream::StreamSettings;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+const SETTINGS_PATH: &str = "settings.ron";
+
+// Player settings, persisted to SETTINGS_PATH whenever they change
+#[derive(Resource, Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct Settings {
+    pub language: Language,
+    pub accessibility: AccessibilitySettings,
+    pub controls: InputMap,
+    pub gamepad: GamepadTuning,
+    pub window: WindowSettings,
+    pub video: VideoSettings,
+    pub stats_panel: StatsPanelSettings,
+    // where each HUD widget sits and how big
+    pub hud_layout: HudLayout,
+    pub stream: StreamSettings,
+    // mutators picked for the next run
+    pub mutators: Vec<Mutator>,
+    // hazard density and boss frequency for the next run
+    pub difficulty: CustomDifficulty,
+    // skin and hat picked in the shop
+    pub cosmetics: Cosmetics,
+    // help players trailing the leader of a race along a little
+    pub rubber_banding: bool,
+    // show the author ghost of courses that have one, to race against the dev's time
+    pub race_author: bool,
+    // where to download balance tweaks from at startup, plain http only and not on the web
+    pub balance_url: Option<String>,
+    // where to download the news shown on the main menu from, the same way
+    pub news_url: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct AccessibilitySettings {
+    // show text captions for important audio cues
+    pub captions: bool,
+    // read menus and state changes out loud (needs the `tts` feature)
+    pub narration: bool,
+    // replace flashes, strobing warnings, camera shake and the mega-dash afterimages with
+    // gentler indicators
+    pub photosensitivity: bool,
+}
+
+impl Settings {
+    pub fn load() -> Self {
+        match storage::read_to_string(SETTINGS_PATH) {
+            Ok(contents) => ron::from_str(&contents)
+                .map(|mut settings: Settings| {
+                    settings.controls.bind_missing();
+                    settings
+                })
+                .unwrap_or_else(|err| {
+                    warn!("Could not parse {}: {}", SETTINGS_PATH, err);
+                    back_up_corrupt(SETTINGS_PATH, "Settings");
+                    let settings = Settings::default();
+                    settings.save();
+                    settings
+                }),
+            Err(_) => Settings::default(),
+        }
+    }
+
+    pub fn save(&self) {
+        let contents = match ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()) {
+            Ok(contents) => contents,
+            Err(err) => {
+                error!("Could not serialize settings: {}", err);
+                return;
+            }
+        };
+        if let Err(err) = storage::write(SETTINGS_PATH, contents) {
+            error!("Could not write {}: {}", SETTINGS_PATH, err);
+        }
+    }
+}
+
+// system to write the settings back to disk after they were modified
+fn save_settings(settings: Res<Settings>) {
+    if settings.is_changed() && !settings.is_added() {
+        settings.save();
+    }
+}
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.world.contains_resource::<Settings>() {
+            app.insert_resource(Settings::load());
+        }
+        app.add_systems(Last, save_settings);
+    }
+}
@@ -0,0 +1,274 @@
+use crate::balance::Balance;
+use crate::barrel::{barrel_bundle, Barrel, BarrelSpawner};
+use crate::coin::{coin_bundle, Coin, CoinSpawner, Falling};
+use crate::enemy::{spawn_enemy, Enemy, EnemySpawner};
+use crate::health::{Boss, Health};
+use crate::projectile::Projectile;
+use crate::score::Score;
+use crate::settings::Settings;
+use crate::storage;
+use crate::swing::{spawn_swing, Swing, SwingKind, SwingSpawner};
+use crate::toast::Toast;
+use crate::{parallax_layers, GameState, Player, PlayerOne, PlayerState};
+use bevy::prelude::*;
+use bevy_parallax::{CreateParallaxEvent, ParallaxCameraComponent};
+use serde::{Deserialize, Serialize};
+
+const SNAPSHOT_PATH: &str = "snapshot.ron";
+
+// entities replaced by the ones in a snapshot when it's restored
+type Restored = Or<(
+    With<Enemy>,
+    With<Coin>,
+    With<Projectile>,
+    With<Swing>,
+    With<Barrel>,
+)>;
+
+#[derive(Serialize, Deserialize)]
+struct PlayerSnapshot {
+    position: Vec2,
+    on_ground: bool,
+    state: PlayerState,
+    health: f32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct EnemySnapshot {
+    x: f32,
+    boss: bool,
+    health: f32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CoinSnapshot {
+    position: Vec2,
+    falling: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SwingSnapshot {
+    x: f32,
+    kind: SwingKind,
+    phase: f32,
+    struck: bool,
+    cleared: bool,
+}
+
+// The state of a run at one moment, enough to play it on from there. Projectiles in
+// flight aren't kept, they're cleared when the snapshot is restored, and barrels already
+// lit are stood back up unlit.
+#[derive(Serialize, Deserialize)]
+struct WorldSnapshot {
+    camera_x: f32,
+    player: PlayerSnapshot,
+    enemies: Vec<EnemySnapshot>,
+    coins: Vec<CoinSnapshot>,
+    swings: Vec<SwingSnapshot>,
+    barrels: Vec<f32>,
+    // where the spawners place their next enemy, row of coins, swing and barrel
+    next_enemy_x: f32,
+    enemies_spawned: u32,
+    next_coin_x: f32,
+    next_swing_x: f32,
+    next_barrel_x: f32,
+    score: u32,
+}
+
+// system to write the run as it is now to SNAPSHOT_PATH on F5
+#[allow(clippy::too_many_arguments)]
+fn save_snapshot(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    camera_query: Query<&Transform, With<ParallaxCameraComponent>>,
+    player_query: Query<(&Player, &Transform, &Health), PlayerOne>,
+    enemy_query: Query<(&Transform, &Health, Has<Boss>), With<Enemy>>,
+    coin_query: Query<(&Transform, Has<Falling>), With<Coin>>,
+    swing_query: Query<(&Swing, &Transform)>,
+    barrel_query: Query<&Transform, With<Barrel>>,
+    enemy_spawner: Res<EnemySpawner>,
+    coin_spawner: Res<CoinSpawner>,
+    swing_spawner: Res<SwingSpawner>,
+    barrel_spawner: Res<BarrelSpawner>,
+    score: Res<Score>,
+    mut toasts: EventWriter<Toast>,
+) {
+    let shift = keyboard_input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    if shift || !keyboard_input.just_pressed(KeyCode::F5) {
+        return;
+    }
+    let (Ok(camera), Ok((player, transform, health))) =
+        (camera_query.get_single(), player_query.get_single())
+    else {
+        return;
+    };
+    let snapshot = WorldSnapshot {
+        camera_x: camera.translation.x,
+        player: PlayerSnapshot {
+            position: transform.translation.truncate(),
+            on_ground: player.on_ground,
+            state: player.state,
+            health: health.current,
+        },
+        enemies: enemy_query
+            .iter()
+            .map(|(transform, health, boss)| EnemySnapshot {
+                x: transform.translation.x,
+                boss,
+                health: health.current,
+            })
+            .collect(),
+        coins: coin_query
+            .iter()
+            .map(|(transform, falling)| CoinSnapshot {
+                position: transform.translation.truncate(),
+                falling,
+            })
+            .collect(),
+        swings: swing_query
+            .iter()
+            .map(|(swing, transform)| SwingSnapshot {
+                x: transform.translation.x,
+                kind: swing.kind,
+                phase: swing.phase,
+                struck: swing.struck,
+                cleared: swing.cleared,
+            })
+            .collect(),
+        barrels: barrel_query
+            .iter()
+            .map(|transform| transform.translation.x)
+            .collect(),
+        next_enemy_x: enemy_spawner.next_x,
+        enemies_spawned: enemy_spawner.spawned,
+        next_coin_x: coin_spawner.next_x,
+        next_swing_x: swing_spawner.next_x,
+        next_barrel_x: barrel_spawner.next_x,
+        score: score.0,
+    };
+    let contents = match ron::ser::to_string_pretty(&snapshot, ron::ser::PrettyConfig::default()) {
+        Ok(contents) => contents,
+        Err(err) => {
+            error!("Could not serialize the snapshot: {}", err);
+            return;
+        }
+    };
+    match storage::write(SNAPSHOT_PATH, contents) {
+        Ok(()) => toasts.send(Toast(format!("Snapshot saved to {}", SNAPSHOT_PATH))),
+        Err(err) => toasts.send(Toast(format!("Could not write {}: {}", SNAPSHOT_PATH, err))),
+    };
+}
+
+// system to put the run back the way SNAPSHOT_PATH has it on Shift+F5
+#[allow(clippy::too_many_arguments)]
+fn load_snapshot(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    asset_server: Res<AssetServer>,
+    balance: Res<Balance>,
+    clear_query: Query<Entity, Restored>,
+    mut camera_query: Query<(Entity, &mut Transform), With<ParallaxCameraComponent>>,
+    mut player_query: Query<
+        (&mut Player, &mut Transform, &mut Health),
+        (PlayerOne, Without<ParallaxCameraComponent>),
+    >,
+    mut enemy_spawner: ResMut<EnemySpawner>,
+    mut coin_spawner: ResMut<CoinSpawner>,
+    mut swing_spawner: ResMut<SwingSpawner>,
+    mut barrel_spawner: ResMut<BarrelSpawner>,
+    mut score: ResMut<Score>,
+    mut create_parallax: EventWriter<CreateParallaxEvent>,
+    mut toasts: EventWriter<Toast>,
+) {
+    let shift = keyboard_input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    if !shift || !keyboard_input.just_pressed(KeyCode::F5) {
+        return;
+    }
+    let snapshot = match storage::read_to_string(SNAPSHOT_PATH)
+        .map_err(|err| err.to_string())
+        .and_then(|contents| {
+            ron::from_str::<WorldSnapshot>(&contents).map_err(|err| err.to_string())
+        }) {
+        Ok(snapshot) => snapshot,
+        Err(err) => {
+            toasts.send(Toast(format!("Could not load {}: {}", SNAPSHOT_PATH, err)));
+            return;
+        }
+    };
+    let (Ok((camera, mut camera_transform)), Ok((mut player, mut transform, mut health))) =
+        (camera_query.get_single_mut(), player_query.get_single_mut())
+    else {
+        return;
+    };
+    for entity in &clear_query {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    camera_transform.translation.x = snapshot.camera_x;
+    create_parallax.send(CreateParallaxEvent {
+        layers_data: parallax_layers(snapshot.camera_x, settings.video.low_spec),
+        camera,
+    });
+    let PlayerSnapshot {
+        position,
+        on_ground,
+        state,
+        health: player_health,
+    } = snapshot.player;
+    transform.translation = position.extend(transform.translation.z);
+    player.on_ground = on_ground;
+    player.state = state;
+    health.current = player_health;
+
+    for enemy in &snapshot.enemies {
+        let entity = spawn_enemy(&mut commands, &asset_server, &balance, enemy.x, enemy.boss);
+        let max = if enemy.boss {
+            balance.boss_health
+        } else {
+            balance.spitter_health
+        };
+        commands.entity(entity).insert(Health {
+            current: enemy.health,
+            max,
+        });
+    }
+    for coin in &snapshot.coins {
+        let mut entity = commands.spawn(coin_bundle(coin.position));
+        if coin.falling {
+            entity.insert(Falling);
+        }
+    }
+    for swing in &snapshot.swings {
+        let restored = Swing {
+            kind: swing.kind,
+            phase: swing.phase,
+            struck: swing.struck,
+            cleared: swing.cleared,
+        };
+        spawn_swing(&mut commands, restored, swing.x);
+    }
+    for x in &snapshot.barrels {
+        commands.spawn(barrel_bundle(*x));
+    }
+
+    enemy_spawner.next_x = snapshot.next_enemy_x;
+    enemy_spawner.spawned = snapshot.enemies_spawned;
+    coin_spawner.next_x = snapshot.next_coin_x;
+    swing_spawner.next_x = snapshot.next_swing_x;
+    barrel_spawner.next_x = snapshot.next_barrel_x;
+    score.0 = snapshot.score;
+    toasts.send(Toast(format!("Snapshot loaded from {}", SNAPSHOT_PATH)));
+}
+
+pub struct SnapshotPlugin;
+
+impl Plugin for SnapshotPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (save_snapshot, load_snapshot)
+                .chain()
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
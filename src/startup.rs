@@ -0,0 +1,112 @@
+use crate::menu::MenuState;
+use bevy::asset::LoadState;
+use bevy::prelude::*;
+use bevy::utils::Instant;
+use std::time::Duration;
+
+// the menu should be on screen this long after the game starts, at the most
+const MENU_BUDGET: Duration = Duration::from_secs(1);
+
+// Systems loading what only a run needs, run once the menu is on screen so it isn't kept
+// waiting on them. What they load streams in while the menu is up: add the handles to
+// GameplayAssets to have it counted.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DeferredLoad;
+
+// Assets loading in the background for the first run
+#[derive(Resource, Default)]
+pub struct GameplayAssets(pub Vec<UntypedHandle>);
+
+// How long each phase of starting the game took, from when the game's plugins were
+// built, in the order they finished
+#[derive(Resource)]
+pub struct StartupTimings {
+    start: Instant,
+    phases: Vec<(&'static str, Duration)>,
+}
+
+impl StartupTimings {
+    // listed by the timing overlay of dev builds
+    #[cfg(feature = "dev_tools")]
+    pub fn phases(&self) -> &[(&'static str, Duration)] {
+        &self.phases
+    }
+
+    fn done(&self, phase: &str) -> bool {
+        self.phases.iter().any(|(name, _)| *name == phase)
+    }
+
+    fn record(&mut self, phase: &'static str) {
+        let elapsed = self.start.elapsed();
+        info!("Startup: {} after {} ms", phase, elapsed.as_millis());
+        self.phases.push((phase, elapsed));
+    }
+}
+
+fn record_startup(mut timings: ResMut<StartupTimings>) {
+    timings.record("startup systems");
+}
+
+// system to record the first frame, and the menu being on screen once a frame with it
+// has gone to be drawn
+fn record_frames(
+    mut timings: ResMut<StartupTimings>,
+    menu_state: Res<State<MenuState>>,
+    mut menu_drawn: Local<bool>,
+) {
+    if !timings.done("first frame") {
+        timings.record("first frame");
+    }
+    if *menu_drawn && !timings.done("menu shown") {
+        timings.record("menu shown");
+        let elapsed = timings.start.elapsed();
+        if elapsed > MENU_BUDGET {
+            warn!(
+                "The menu took {} ms to show, over the {} ms budget",
+                elapsed.as_millis(),
+                MENU_BUDGET.as_millis()
+            );
+        }
+    }
+    *menu_drawn = *menu_state.get() != MenuState::Disabled;
+}
+
+fn menu_shown(timings: Res<StartupTimings>) -> bool {
+    timings.done("menu shown")
+}
+
+// system to record when everything loading for the first run is in, or failed to be
+fn record_gameplay_assets(
+    asset_server: Res<AssetServer>,
+    assets: Res<GameplayAssets>,
+    mut timings: ResMut<StartupTimings>,
+) {
+    if timings.done("gameplay assets") || assets.0.is_empty() {
+        return;
+    }
+    let settled = assets.0.iter().all(|handle| {
+        matches!(
+            asset_server.get_load_state(handle.id()),
+            Some(LoadState::Loaded | LoadState::Failed) | None
+        )
+    });
+    if settled {
+        timings.record("gameplay assets");
+    }
+}
+
+pub struct StartupPlugin;
+
+impl Plugin for StartupPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(StartupTimings {
+            start: Instant::now(),
+            phases: Vec::new(),
+        })
+        .init_resource::<GameplayAssets>()
+        .configure_sets(Update, DeferredLoad.run_if(menu_shown.and_then(run_once())))
+        .add_systems(PostStartup, record_startup)
+        .add_systems(First, record_frames)
+        .add_systems(Last, record_gameplay_assets);
+    }
+}
@@ -0,0 +1,235 @@
+use crate::game_time::GameTime;
+use crate::health::Health;
+use crate::input::{Action, ActionState};
+use crate::mutators::{Mutator, RunConfig};
+use crate::score::Combo;
+use crate::settings::Settings;
+use crate::stream::HudText;
+use crate::{GameState, GameplaySet, PlayerOne, RunEntity};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+// seconds each stat stays up before the panel moves on to the next
+const STAT_TIME: f32 = 3.0;
+const PANEL_MARGIN: f32 = 8.0;
+// keeps the panel clear of the score in the top left corner
+const SCORE_CLEARANCE: f32 = 48.0;
+
+// Corner of the screen the stats panel sits in
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Corner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    #[default]
+    BottomRight,
+}
+
+impl Corner {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Corner::TopLeft => "Top left",
+            Corner::TopRight => "Top right",
+            Corner::BottomLeft => "Bottom left",
+            Corner::BottomRight => "Bottom right",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            Corner::TopLeft => Corner::TopRight,
+            Corner::TopRight => Corner::BottomRight,
+            Corner::BottomRight => Corner::BottomLeft,
+            Corner::BottomLeft => Corner::TopLeft,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct StatsPanelSettings {
+    pub visible: bool,
+    pub corner: Corner,
+}
+
+// The stats the panel cycles through
+#[derive(Debug, Clone, Copy)]
+enum Stat {
+    Speed,
+    Multiplier,
+    Hearts,
+    Effects,
+}
+
+const STATS: [Stat; 4] = [Stat::Speed, Stat::Multiplier, Stat::Hearts, Stat::Effects];
+
+#[derive(Component)]
+struct StatsPanel;
+
+#[derive(Component)]
+struct StatsText;
+
+// Which stat is up and for how much longer, and where the player was last frame
+#[derive(Resource)]
+struct StatsCycle {
+    index: usize,
+    timer: Timer,
+    last_x: Option<f32>,
+    // world units per second, smoothed
+    speed: f32,
+}
+
+impl Default for StatsCycle {
+    fn default() -> Self {
+        StatsCycle {
+            index: 0,
+            timer: Timer::from_seconds(STAT_TIME, TimerMode::Repeating),
+            last_x: None,
+            speed: 0.0,
+        }
+    }
+}
+
+fn corner_style(corner: Corner, style: &mut Style) {
+    let margin = Val::Px(PANEL_MARGIN);
+    let (top, bottom) = match corner {
+        Corner::TopLeft => (Val::Px(SCORE_CLEARANCE), Val::Auto),
+        Corner::TopRight => (Val::Px(PANEL_MARGIN), Val::Auto),
+        Corner::BottomLeft | Corner::BottomRight => (Val::Auto, margin),
+    };
+    let (left, right) = match corner {
+        Corner::TopLeft | Corner::BottomLeft => (margin, Val::Auto),
+        Corner::TopRight | Corner::BottomRight => (Val::Auto, margin),
+    };
+    style.top = top;
+    style.bottom = bottom;
+    style.left = left;
+    style.right = right;
+}
+
+fn spawn_panel(mut commands: Commands, settings: Res<Settings>) {
+    commands.insert_resource(StatsCycle::default());
+    let mut style = Style {
+        position_type: PositionType::Absolute,
+        padding: UiRect::axes(Val::Px(6.0), Val::Px(3.0)),
+        ..default()
+    };
+    corner_style(settings.stats_panel.corner, &mut style);
+    let visibility = if settings.stats_panel.visible {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    };
+    commands
+        .spawn((
+            NodeBundle {
+                style,
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.5).into(),
+                visibility,
+                z_index: ZIndex::Global(5),
+                ..default()
+            },
+            StatsPanel,
+            RunEntity,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 12.0,
+                        color: Color::WHITE,
+                        ..default()
+                    },
+                ),
+                StatsText,
+                HudText(12.0),
+            ));
+        });
+}
+
+// system to show or hide the panel and move it to the next corner, F6 and F7 by default
+fn control_panel(actions: Res<ActionState>, mut settings: ResMut<Settings>) {
+    if actions.just_pressed(Action::StatsPanel) {
+        settings.stats_panel.visible = !settings.stats_panel.visible;
+    }
+    if actions.just_pressed(Action::StatsCorner) {
+        settings.stats_panel.corner = settings.stats_panel.corner.next();
+    }
+}
+
+// system to follow the panel settings as they change, from the keys or the menu
+fn place_panel(
+    settings: Res<Settings>,
+    mut panel_query: Query<(&mut Style, &mut Visibility), With<StatsPanel>>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+    for (mut style, mut visibility) in &mut panel_query {
+        corner_style(settings.stats_panel.corner, &mut style);
+        *visibility = if settings.stats_panel.visible {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+// system to measure the live stats and show the current one
+fn update_panel(
+    time: Res<GameTime>,
+    mut cycle: ResMut<StatsCycle>,
+    combo: Res<Combo>,
+    config: Res<RunConfig>,
+    player_query: Query<(&Transform, &Health), PlayerOne>,
+    mut text_query: Query<&mut Text, With<StatsText>>,
+) {
+    let Ok((transform, health)) = player_query.get_single() else {
+        return;
+    };
+    let delta = time.delta_seconds();
+    let x = transform.translation.x;
+    if let (Some(last_x), true) = (cycle.last_x, delta > 0.0) {
+        let speed = (x - last_x) / delta;
+        cycle.speed += (speed - cycle.speed) * (delta * 4.0).min(1.0);
+    }
+    cycle.last_x = Some(x);
+    if cycle.timer.tick(time.delta()).just_finished() {
+        cycle.index = (cycle.index + 1) % STATS.len();
+    }
+    let line = match STATS[cycle.index] {
+        Stat::Speed => format!("Speed {:.0}", cycle.speed.abs()),
+        Stat::Multiplier => format!(
+            "Multiplier x{:.1}",
+            combo.multiplier() as f32 * config.score_multiplier
+        ),
+        Stat::Hearts => format!("Hearts {:.0}/{:.0}", health.current.max(0.0), health.max),
+        Stat::Effects if config.mutators.is_empty() => "Effects: none".to_string(),
+        Stat::Effects => {
+            let names: Vec<&str> = config.mutators.iter().map(Mutator::name).collect();
+            format!("Effects: {}", names.join(", "))
+        }
+    };
+    for mut text in &mut text_query {
+        if text.sections[0].value != line {
+            text.sections[0].value = line.clone();
+        }
+    }
+}
+
+pub struct StatsPanelPlugin;
+
+impl Plugin for StatsPanelPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<StatsCycle>()
+            .add_systems(OnEnter(GameState::Playing), spawn_panel)
+            .add_systems(
+                Update,
+                (control_panel, place_panel)
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(Update, update_panel.in_set(GameplaySet));
+    }
+}
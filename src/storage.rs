@@ -0,0 +1,67 @@
+// Where settings, saves and caches are kept: files next to the game natively, and the
+// browser's local storage on the web, which outlives the page (also inside an itch.io
+// iframe). Local storage rather than IndexedDB since the files are read synchronously
+// while the game starts, before anything async could finish.
+use std::io;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn read_to_string(path: &str) -> io::Result<String> {
+    std::fs::read_to_string(path)
+}
+
+// written to a file beside it first and renamed over it, so a crash or force-quit
+// partway through leaves the old file whole rather than a half-written one
+#[cfg(not(target_arch = "wasm32"))]
+pub fn write(path: &str, contents: impl AsRef<str>) -> io::Result<()> {
+    use std::io::Write;
+    let partial = format!("{}.partial", path);
+    let mut file = std::fs::File::create(&partial)?;
+    file.write_all(contents.as_ref().as_bytes())?;
+    file.sync_all()?;
+    std::fs::rename(&partial, path)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn rename(from: &str, to: &str) -> io::Result<()> {
+    std::fs::rename(from, to)
+}
+
+// files are kept under their name with this prefix, away from other games on the same site
+#[cfg(target_arch = "wasm32")]
+const KEY_PREFIX: &str = "dinorun/";
+
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> io::Result<web_sys::Storage> {
+    web_sys::window()
+        .and_then(|window| window.local_storage().ok().flatten())
+        .ok_or_else(|| io::Error::other("the browser's local storage is unavailable"))
+}
+
+#[cfg(target_arch = "wasm32")]
+fn js_error(err: web_sys::wasm_bindgen::JsValue) -> io::Error {
+    io::Error::other(format!("{:?}", err))
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn read_to_string(path: &str) -> io::Result<String> {
+    local_storage()?
+        .get_item(&format!("{}{}", KEY_PREFIX, path))
+        .map_err(js_error)?
+        .ok_or_else(|| io::ErrorKind::NotFound.into())
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn write(path: &str, contents: impl AsRef<str>) -> io::Result<()> {
+    local_storage()?
+        .set_item(&format!("{}{}", KEY_PREFIX, path), contents.as_ref())
+        .map_err(js_error)
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn rename(from: &str, to: &str) -> io::Result<()> {
+    let contents = read_to_string(from)?;
+    write(to, contents)?;
+    local_storage()?
+        .remove_item(&format!("{}{}", KEY_PREFIX, from))
+        .map_err(js_error)
+}
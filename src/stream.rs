@@ -0,0 +1,224 @@
+use crate::input::{Action, ActionState};
+use crate::prompt::{Prompt, PromptKey, PromptPart};
+use crate::settings::Settings;
+use crate::stats_panel::Corner;
+use crate::GameState;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+// how much bigger the HUD text gets in stream mode
+const STREAM_SCALE: f32 = 2.0;
+// inputs shown at once, and how long each one stays up
+const SHOWN_INPUTS: usize = 6;
+const INPUT_LIFETIME: f32 = 1.5;
+// share of the screen kept clear for a webcam overlay
+const WEBCAM_SIZE: (f32, f32) = (28.0, 34.0);
+const WEBCAM_BORDER: Color = Color::rgba(1.0, 1.0, 1.0, 0.35);
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct StreamSettings {
+    // bigger HUD and the last inputs shown on screen, for viewers
+    pub enabled: bool,
+    // corner marked out for a webcam overlay, if any
+    pub webcam: Option<Corner>,
+}
+
+impl StreamSettings {
+    // the webcam corner after `webcam`, going round the corners and then to none
+    pub fn next_webcam(&self) -> Option<Corner> {
+        match self.webcam {
+            None => Some(Corner::TopLeft),
+            Some(Corner::BottomLeft) => None,
+            Some(corner) => Some(corner.next()),
+        }
+    }
+}
+
+// HUD text that grows in stream mode, at its usual font size
+#[derive(Component)]
+pub struct HudText(pub f32);
+
+// Row along the bottom of the screen listing the last inputs
+#[derive(Component)]
+struct InputRow;
+
+// One input in the row, fading out
+#[derive(Component)]
+struct ShownInput(Timer);
+
+// Outline of the corner kept for the webcam
+#[derive(Component)]
+struct WebcamMargin;
+
+fn spawn_stream_widgets(mut commands: Commands) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(24.0),
+                width: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                column_gap: Val::Px(6.0),
+                ..default()
+            },
+            z_index: ZIndex::Global(6),
+            ..default()
+        },
+        InputRow,
+    ));
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(WEBCAM_SIZE.0),
+                    height: Val::Percent(WEBCAM_SIZE.1),
+                    border: UiRect::all(Val::Px(2.0)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                border_color: WEBCAM_BORDER.into(),
+                visibility: Visibility::Hidden,
+                z_index: ZIndex::Global(4),
+                ..default()
+            },
+            WebcamMargin,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Webcam",
+                TextStyle {
+                    font_size: 14.0,
+                    color: WEBCAM_BORDER,
+                    ..default()
+                },
+            ));
+        });
+}
+
+// system to size the HUD text for stream mode, as it's turned on or off and as the
+// HUD is spawned
+fn scale_hud(settings: Res<Settings>, mut query: Query<(Ref<HudText>, &mut Text)>) {
+    let scale = if settings.stream.enabled {
+        STREAM_SCALE
+    } else {
+        1.0
+    };
+    for (hud, mut text) in &mut query {
+        if !(settings.is_changed() || hud.is_added()) {
+            continue;
+        }
+        for section in &mut text.sections {
+            section.style.font_size = hud.0 * scale;
+        }
+    }
+}
+
+// system to add the actions pressed in stream mode to the input row, as prompts for the
+// device they came from
+fn show_inputs(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    actions: Res<ActionState>,
+    row_query: Query<(Entity, Option<&Children>), With<InputRow>>,
+) {
+    let Ok((row, shown)) = row_query.get_single() else {
+        return;
+    };
+    if !settings.stream.enabled {
+        return;
+    }
+    let mut count = shown.map_or(0, |shown| shown.len());
+    for action in Action::ALL {
+        if !actions.just_pressed(action) {
+            continue;
+        }
+        if count >= SHOWN_INPUTS {
+            if let Some(oldest) = shown.and_then(|shown| shown.first()) {
+                commands.entity(*oldest).despawn_recursive();
+            }
+        } else {
+            count += 1;
+        }
+        let input = commands
+            .spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 20.0,
+                        color: Color::WHITE,
+                        ..default()
+                    },
+                ),
+                Prompt(vec![PromptPart::Glyph(PromptKey::Action(action))]),
+                ShownInput(Timer::from_seconds(INPUT_LIFETIME, TimerMode::Once)),
+            ))
+            .id();
+        commands.entity(row).add_child(input);
+    }
+}
+
+// system to fade the shown inputs out and drop them once they're gone
+fn fade_inputs(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    mut query: Query<(Entity, &mut ShownInput, &mut Text)>,
+) {
+    for (entity, mut input, mut text) in &mut query {
+        if input.0.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        text.sections[0]
+            .style
+            .color
+            .set_a(input.0.fraction_remaining());
+    }
+}
+
+// system to outline the webcam corner picked in the settings
+fn place_webcam_margin(
+    settings: Res<Settings>,
+    mut query: Query<(&mut Style, &mut Visibility), With<WebcamMargin>>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+    for (mut style, mut visibility) in &mut query {
+        let Some(corner) = settings.stream.webcam else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+        *visibility = Visibility::Inherited;
+        let (top, bottom) = match corner {
+            Corner::TopLeft | Corner::TopRight => (Val::Px(0.0), Val::Auto),
+            Corner::BottomLeft | Corner::BottomRight => (Val::Auto, Val::Px(0.0)),
+        };
+        let (left, right) = match corner {
+            Corner::TopLeft | Corner::BottomLeft => (Val::Px(0.0), Val::Auto),
+            Corner::TopRight | Corner::BottomRight => (Val::Auto, Val::Px(0.0)),
+        };
+        style.top = top;
+        style.bottom = bottom;
+        style.left = left;
+        style.right = right;
+    }
+}
+
+pub struct StreamPlugin;
+
+impl Plugin for StreamPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_stream_widgets).add_systems(
+            Update,
+            (
+                scale_hud,
+                show_inputs.run_if(in_state(GameState::Playing)),
+                fade_inputs,
+                place_webcam_margin,
+            ),
+        );
+    }
+}
@@ -0,0 +1,304 @@
+use crate::balance::Balance;
+use crate::collision::{sync_colliders, Collider, SyncedCollider};
+use crate::drop_in::SecondPlayer;
+use crate::feedback::Feedback;
+use crate::game_time::GameTime;
+use crate::gate::Gate;
+use crate::health::{Health, HitSet, Invincible};
+use crate::heatmap::{Hazard, LastHit};
+use crate::mode::{hazards_enabled, RunRng};
+use crate::mutators::RunConfig;
+use crate::score::{AddPoints, PointSource};
+use crate::timing::timed;
+use crate::{GameState, GameplaySet, Player, PlayerOne, RunEntity, GROUND_Y, PLAYER_SCALE};
+use bevy::prelude::*;
+use bevy_parallax::ParallaxCameraComponent;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::f32::consts::TAU;
+
+// how far past the right edge of the view swings are placed
+const SPAWN_MARGIN: f32 = 256.0;
+// a vine hangs from this high over the player's feet, down to a weight at its end that
+// clears a ducking player at the bottom of its swing but not a standing one
+const VINE_PIVOT: f32 = 230.0;
+const VINE_LENGTH: f32 = 166.0;
+// furthest a vine swings out to either side, in radians, and how fast
+const VINE_AMPLITUDE: f32 = 0.9;
+const VINE_SPEED: f32 = 2.4;
+const WEIGHT_SIZE: f32 = 28.0;
+// a spike bar turns around its middle this high over the player's feet, a spiked ball
+// at each end sweeping the ground
+const BAR_PIVOT: f32 = 70.0;
+const BAR_ARM: f32 = 60.0;
+const BAR_SPEED: f32 = 2.2;
+const BALL_SIZE: f32 = 24.0;
+const SWING_DAMAGE: f32 = 1.0;
+// how far past a swing's pivot player one has to be to have cleared it, out of reach of
+// its parts
+const CLEAR_DISTANCE: f32 = BAR_ARM + BALL_SIZE;
+const ROPE_COLOR: Color = Color::rgb(0.35, 0.5, 0.2);
+const WEIGHT_COLOR: Color = Color::rgb(0.25, 0.35, 0.15);
+const BAR_COLOR: Color = Color::rgb(0.45, 0.45, 0.5);
+const BALL_COLOR: Color = Color::rgb(0.8, 0.2, 0.25);
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SwingKind {
+    // swings back and forth like a pendulum, to be ducked under or timed
+    Vine,
+    // turns round and round, to be jumped or timed
+    SpikeBar,
+}
+
+// Hazard moving on its own, the pivot its parts hang from
+#[derive(Component)]
+pub struct Swing {
+    pub kind: SwingKind,
+    // how far through its motion it is, in radians
+    pub phase: f32,
+    // whether it hit player one, who then gets no points for clearing it
+    pub struck: bool,
+    pub cleared: bool,
+}
+
+// Part of a swing that hurts, the players touching it hit once for as long as they do
+#[derive(Component, Default)]
+struct SwingPart {
+    touching: Vec<Entity>,
+}
+
+// A player swings can hit
+type Target<'a> = (
+    Entity,
+    &'a GlobalTransform,
+    &'a Collider,
+    &'a mut Health,
+    Has<Invincible>,
+    Has<SecondPlayer>,
+);
+
+// Where the next swing of the run goes
+#[derive(Resource, Default)]
+pub struct SwingSpawner {
+    pub next_x: f32,
+}
+
+fn reset_spawner(mut commands: Commands, balance: Res<Balance>) {
+    commands.insert_resource(SwingSpawner {
+        next_x: balance.swing_spacing.1,
+    });
+}
+
+fn sprite(color: Color, size: Vec2, translation: Vec3) -> SpriteBundle {
+    SpriteBundle {
+        sprite: Sprite {
+            color,
+            custom_size: Some(size),
+            ..default()
+        },
+        transform: Transform::from_translation(translation),
+        ..default()
+    }
+}
+
+// a part that hurts: a square of `size` at `translation` from the pivot
+fn part(color: Color, size: f32, translation: Vec3) -> impl Bundle {
+    let half = Vec2::splat(size / 2.0);
+    (
+        sprite(color, Vec2::splat(size), translation),
+        Collider(Rect::from_center_half_size(Vec2::ZERO, half)),
+        SyncedCollider,
+        SwingPart::default(),
+    )
+}
+
+// hang a vine or stand a spike bar with its pivot at `x`
+pub fn spawn_swing(commands: &mut Commands, swing: Swing, x: f32) {
+    let feet = GROUND_Y - 8.0 * PLAYER_SCALE;
+    let kind = swing.kind;
+    let pivot = match kind {
+        SwingKind::Vine => feet + VINE_PIVOT,
+        SwingKind::SpikeBar => feet + BAR_PIVOT,
+    };
+    commands
+        .spawn((
+            SpatialBundle::from_transform(Transform::from_xyz(x, pivot, 1.3)),
+            swing,
+            RunEntity,
+        ))
+        .with_children(|parent| match kind {
+            SwingKind::Vine => {
+                parent.spawn(sprite(
+                    ROPE_COLOR,
+                    Vec2::new(3.0, VINE_LENGTH),
+                    Vec3::new(0.0, -VINE_LENGTH / 2.0, 0.0),
+                ));
+                parent.spawn(part(
+                    WEIGHT_COLOR,
+                    WEIGHT_SIZE,
+                    Vec3::new(0.0, -VINE_LENGTH, 0.1),
+                ));
+            }
+            SwingKind::SpikeBar => {
+                parent.spawn(sprite(BAR_COLOR, Vec2::new(BAR_ARM * 2.0, 4.0), Vec3::ZERO));
+                for side in [-1.0, 1.0] {
+                    parent.spawn(part(
+                        BALL_COLOR,
+                        BALL_SIZE,
+                        Vec3::new(side * BAR_ARM, 0.0, 0.1),
+                    ));
+                }
+            }
+        });
+}
+
+// system to hang vines and stand spike bars just out of view ahead of the camera
+fn spawn_swings(
+    mut commands: Commands,
+    mut spawner: ResMut<SwingSpawner>,
+    mut rng: ResMut<RunRng>,
+    balance: Res<Balance>,
+    config: Res<RunConfig>,
+    gate: Res<Gate>,
+    camera_query: Query<(&Transform, &OrthographicProjection), With<ParallaxCameraComponent>>,
+) {
+    let Ok((camera, projection)) = camera_query.get_single() else {
+        return;
+    };
+    if camera.translation.x + projection.area.max.x + SPAWN_MARGIN < spawner.next_x {
+        return;
+    }
+    if gate.covers(spawner.next_x) {
+        spawner.next_x = gate.end;
+        return;
+    }
+    let swing = Swing {
+        kind: if rng.0.gen() {
+            SwingKind::Vine
+        } else {
+            SwingKind::SpikeBar
+        },
+        phase: rng.0.gen_range(0.0..TAU),
+        struck: false,
+        cleared: false,
+    };
+    spawn_swing(&mut commands, swing, spawner.next_x);
+    let (min, max) = balance.swing_spacing;
+    spawner.next_x += rng.0.gen_range(min..=max)
+        * balance.spacing_at(spawner.next_x)
+        * config.difficulty.spacing();
+}
+
+// system to move every swing along, turning its pivot and with it the parts hanging off it
+fn animate_swings(time: Res<GameTime>, mut swing_query: Query<(&mut Swing, &mut Transform)>) {
+    for (mut swing, mut transform) in &mut swing_query {
+        let (speed, angle) = match swing.kind {
+            SwingKind::Vine => (VINE_SPEED, VINE_AMPLITUDE * swing.phase.sin()),
+            SwingKind::SpikeBar => (BAR_SPEED, swing.phase),
+        };
+        swing.phase = (swing.phase + speed * time.delta_seconds()) % TAU;
+        transform.rotation = Quat::from_rotation_z(angle);
+    }
+}
+
+// system to hurt the players a swing's parts run into, once each time they touch
+fn strike_players(
+    mut part_query: Query<(&GlobalTransform, &Collider, &mut SwingPart, &Parent)>,
+    mut swing_query: Query<&mut Swing>,
+    mut player_query: Query<Target, With<Player>>,
+    mut last_hit: ResMut<LastHit>,
+    mut feedback: EventWriter<Feedback>,
+) {
+    for (transform, collider, mut part, parent) in &mut part_query {
+        let rect = collider.world_rect(transform);
+        for (player, player_transform, player_collider, mut health, invincible, second) in
+            &mut player_query
+        {
+            let touching = !rect
+                .intersect(player_collider.world_rect(player_transform))
+                .is_empty();
+            let was_touching = part.touching.contains(&player);
+            if !touching {
+                if was_touching {
+                    part.touching.retain(|entity| *entity != player);
+                }
+                continue;
+            }
+            if was_touching {
+                continue;
+            }
+            part.touching.push(player);
+            if invincible {
+                continue;
+            }
+            health.current -= SWING_DAMAGE;
+            if !second {
+                if let Ok(mut swing) = swing_query.get_mut(parent.get()) {
+                    swing.struck = true;
+                }
+                last_hit.0 = Some(Hazard::Swing);
+                feedback.send(Feedback::Flash(Color::rgba(1.0, 0.2, 0.2, 0.5)));
+            }
+        }
+    }
+}
+
+// system to score the swings player one got past without being hit
+fn clear_swings(
+    mut swing_query: Query<(&mut Swing, &Transform)>,
+    player_query: Query<&Transform, (PlayerOne, Without<Swing>)>,
+    mut points: EventWriter<AddPoints>,
+) {
+    let Ok(player) = player_query.get_single() else {
+        return;
+    };
+    for (mut swing, transform) in &mut swing_query {
+        if swing.cleared || player.translation.x < transform.translation.x + CLEAR_DISTANCE {
+            continue;
+        }
+        swing.cleared = true;
+        if !swing.struck {
+            points.send(AddPoints(PointSource::Swing));
+        }
+    }
+}
+
+fn despawn_passed_swings(
+    mut commands: Commands,
+    swing_query: Query<(Entity, &Transform), With<Swing>>,
+    camera_query: Query<(&Transform, &OrthographicProjection), With<ParallaxCameraComponent>>,
+) {
+    let Ok((camera, projection)) = camera_query.get_single() else {
+        return;
+    };
+    let view_left = camera.translation.x + projection.area.min.x;
+    for (entity, transform) in &swing_query {
+        if transform.translation.x < view_left - SPAWN_MARGIN {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+pub struct SwingPlugin;
+
+impl Plugin for SwingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SwingSpawner>()
+            .add_systems(OnEnter(GameState::Playing), reset_spawner)
+            .add_systems(
+                Update,
+                (
+                    timed(spawn_swings).run_if(hazards_enabled),
+                    timed(animate_swings),
+                    // the parts are hit where the swing has just moved them to, not
+                    // where they were drawn last frame
+                    sync_colliders,
+                    timed(strike_players).in_set(HitSet),
+                    timed(clear_swings),
+                    timed(despawn_passed_swings),
+                )
+                    .chain()
+                    .in_set(GameplaySet),
+            );
+    }
+}
@@ -0,0 +1,18 @@
+// the F4 panel showing the timings, and what measures them for it: dev tools, release
+// builds have nothing to show the times in
+#[cfg(feature = "dev_tools")]
+mod hud;
+#[cfg(feature = "dev_tools")]
+mod measure;
+
+#[cfg(feature = "dev_tools")]
+pub use hud::TimingPlugin;
+#[cfg(feature = "dev_tools")]
+pub use measure::timed;
+
+// wrap a system to have its run time shown in the timing HUD, or without one to run it as
+// it is
+#[cfg(not(feature = "dev_tools"))]
+pub fn timed<M, S: bevy::ecs::system::IntoSystem<(), (), M>>(system: S) -> S {
+    system
+}
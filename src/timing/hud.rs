@@ -0,0 +1,141 @@
+use super::measure::SystemTimings;
+use crate::latency::INPUT_LATENCY;
+use crate::startup::StartupTimings;
+use bevy::diagnostic::{Diagnostic, DiagnosticMeasurement, DiagnosticPath, DiagnosticsStore};
+use bevy::prelude::*;
+use bevy::utils::Instant;
+
+// systems taking longer than this per frame, in milliseconds, are highlighted
+const SYSTEM_BUDGET: f64 = 0.5;
+// how often the timing list is redrawn, in seconds
+const HUD_REFRESH: f32 = 0.25;
+
+fn diagnostic_path(name: &str) -> DiagnosticPath {
+    DiagnosticPath::new(format!("systems/{}", name.replace("::", "/")))
+}
+
+// system to hand the measured times over to bevy's diagnostics, one per timed system
+fn record_timings(timings: Res<SystemTimings>, mut store: ResMut<DiagnosticsStore>) {
+    let Ok(mut timings) = timings.0.lock() else {
+        return;
+    };
+    let now = Instant::now();
+    for (name, duration) in timings.drain() {
+        let path = diagnostic_path(&name);
+        if store.get(&path).is_none() {
+            store.add(Diagnostic::new(path.clone()).with_suffix("ms"));
+        }
+        if let Some(diagnostic) = store.get_mut(&path) {
+            diagnostic.add_measurement(DiagnosticMeasurement {
+                time: now,
+                value: duration.as_secs_f64() * 1000.0,
+            });
+        }
+    }
+}
+
+// Panel listing how long each timed system takes, under the input latency, toggled with F4
+#[derive(Component)]
+struct TimingHud;
+
+fn toggle_timing_hud(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    hud_query: Query<Entity, With<TimingHud>>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::F4) {
+        return;
+    }
+    if let Ok(hud) = hud_query.get_single() {
+        commands.entity(hud).despawn_recursive();
+        return;
+    }
+    commands.spawn((
+        TextBundle::from_section("", TextStyle::default()).with_style(Style {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(8.0),
+            left: Val::Px(8.0),
+            ..default()
+        }),
+        TimingHud,
+    ));
+}
+
+// system to list how long starting the game took, then the smoothed time of every timed
+// system, slowest first, in red when over budget
+fn update_timing_hud(
+    time: Res<Time<Real>>,
+    mut refresh: Local<f32>,
+    store: Res<DiagnosticsStore>,
+    startup: Res<StartupTimings>,
+    mut hud_query: Query<&mut Text, With<TimingHud>>,
+) {
+    let Ok(mut text) = hud_query.get_single_mut() else {
+        return;
+    };
+    *refresh -= time.delta_seconds();
+    if *refresh > 0.0 {
+        return;
+    }
+    *refresh = HUD_REFRESH;
+    let mut rows: Vec<(String, f64)> = store
+        .iter()
+        .filter_map(|diagnostic| {
+            let name = diagnostic.path().as_str().strip_prefix("systems/")?;
+            Some((name.replace('/', "::"), diagnostic.smoothed()?))
+        })
+        .collect();
+    rows.sort_by(|a, b| b.1.total_cmp(&a.1));
+    let latency = store
+        .get(&INPUT_LATENCY)
+        .and_then(|diagnostic| diagnostic.smoothed())
+        .map(|ms| {
+            TextSection::new(
+                format!("{:>7.1} ms  input to present\n", ms),
+                TextStyle {
+                    font_size: 12.0,
+                    color: Color::YELLOW,
+                    ..default()
+                },
+            )
+        });
+    let phases = startup.phases().iter().map(|(phase, elapsed)| {
+        TextSection::new(
+            format!("{:>7} ms  {}\n", elapsed.as_millis(), phase),
+            TextStyle {
+                font_size: 12.0,
+                color: Color::CYAN,
+                ..default()
+            },
+        )
+    });
+    text.sections = phases
+        .chain(latency)
+        .chain(rows.into_iter().map(|(name, ms)| {
+            let color = if ms > SYSTEM_BUDGET {
+                Color::RED
+            } else {
+                Color::WHITE
+            };
+            TextSection::new(
+                format!("{:>7.3} ms  {}\n", ms, name),
+                TextStyle {
+                    font_size: 12.0,
+                    color,
+                    ..default()
+                },
+            )
+        }))
+        .collect();
+}
+
+pub struct TimingPlugin;
+
+impl Plugin for TimingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SystemTimings>()
+            .init_resource::<DiagnosticsStore>()
+            .add_systems(Update, (toggle_timing_hud, update_timing_hud).chain())
+            .add_systems(Last, record_timings);
+    }
+}
@@ -0,0 +1,104 @@
+use bevy::ecs::archetype::ArchetypeComponentId;
+use bevy::ecs::component::{ComponentId, Tick};
+use bevy::ecs::query::Access;
+use bevy::ecs::schedule::InternedSystemSet;
+use bevy::ecs::world::unsafe_world_cell::UnsafeWorldCell;
+use bevy::prelude::*;
+use bevy::utils::{HashMap, Instant};
+use std::borrow::Cow;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+// Time each timed system took the last time it ran, by system name
+#[derive(Resource, Clone, Default)]
+pub(super) struct SystemTimings(pub(super) Arc<Mutex<HashMap<String, Duration>>>);
+
+// A system that measures how long it takes to run, see `timed`
+pub struct TimedSystem<S> {
+    system: S,
+    name: String,
+    timings: SystemTimings,
+}
+
+// wrap a system to have its run time shown in the timing HUD
+pub fn timed<M, S: IntoSystem<(), (), M>>(system: S) -> TimedSystem<S::System> {
+    let system = IntoSystem::into_system(system);
+    // "dinorun_core::enemy::spawn_enemies" shows up as "enemy::spawn_enemies"
+    let full_name = system.name();
+    let name = full_name.split("::").skip(1).collect::<Vec<_>>().join("::");
+    TimedSystem {
+        system,
+        name,
+        timings: SystemTimings::default(),
+    }
+}
+
+impl<S: System<In = (), Out = ()>> System for TimedSystem<S> {
+    type In = ();
+    type Out = ();
+
+    fn name(&self) -> Cow<'static, str> {
+        self.system.name()
+    }
+
+    fn component_access(&self) -> &Access<ComponentId> {
+        self.system.component_access()
+    }
+
+    fn archetype_component_access(&self) -> &Access<ArchetypeComponentId> {
+        self.system.archetype_component_access()
+    }
+
+    fn is_send(&self) -> bool {
+        self.system.is_send()
+    }
+
+    fn is_exclusive(&self) -> bool {
+        self.system.is_exclusive()
+    }
+
+    fn has_deferred(&self) -> bool {
+        self.system.has_deferred()
+    }
+
+    unsafe fn run_unsafe(&mut self, input: (), world: UnsafeWorldCell) {
+        let start = Instant::now();
+        // SAFETY: forwarded as is, with the access the wrapped system reported
+        unsafe { self.system.run_unsafe(input, world) };
+        if let Ok(mut timings) = self.timings.0.lock() {
+            timings.insert(self.name.clone(), start.elapsed());
+        }
+    }
+
+    fn apply_deferred(&mut self, world: &mut World) {
+        self.system.apply_deferred(world);
+    }
+
+    fn initialize(&mut self, world: &mut World) {
+        self.timings = world
+            .get_resource_or_insert_with(SystemTimings::default)
+            .clone();
+        self.system.initialize(world);
+    }
+
+    fn update_archetype_component_access(&mut self, world: UnsafeWorldCell) {
+        self.system.update_archetype_component_access(world);
+    }
+
+    fn check_change_tick(&mut self, change_tick: Tick) {
+        self.system.check_change_tick(change_tick);
+    }
+
+    // keeps the wrapped function usable in .before() and .after()
+    fn default_system_sets(&self) -> Vec<InternedSystemSet> {
+        self.system.default_system_sets()
+    }
+
+    fn get_last_run(&self) -> Tick {
+        self.system.get_last_run()
+    }
+
+    fn set_last_run(&mut self, last_run: Tick) {
+        self.system.set_last_run(last_run);
+    }
+}
@@ -0,0 +1,96 @@
+use bevy::prelude::*;
+
+const TOAST_TIME: f32 = 2.5;
+// fraction of TOAST_TIME spent fading out at the end
+const TOAST_FADE: f32 = 0.3;
+const TOAST_FONT_SIZE: f32 = 14.0;
+
+// Event to show a short notification in the top right corner
+#[derive(Event, Debug, Clone)]
+pub struct Toast(pub String);
+
+// Column in the top right corner holding the visible toasts
+#[derive(Component)]
+struct ToastArea;
+
+#[derive(Component)]
+struct ToastTimer(Timer);
+
+fn setup_toasts(mut commands: Commands) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(8.0),
+                right: Val::Px(8.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::FlexEnd,
+                row_gap: Val::Px(4.0),
+                ..default()
+            },
+            z_index: ZIndex::Global(20),
+            ..default()
+        },
+        ToastArea,
+    ));
+}
+
+fn show_toasts(
+    mut commands: Commands,
+    mut events: EventReader<Toast>,
+    area_query: Query<Entity, With<ToastArea>>,
+) {
+    let Ok(area) = area_query.get_single() else {
+        return;
+    };
+    for Toast(text) in events.read() {
+        info!("Toast: {}", text);
+        let toast = commands
+            .spawn((
+                TextBundle::from_section(
+                    text.clone(),
+                    TextStyle {
+                        font_size: TOAST_FONT_SIZE,
+                        color: Color::WHITE,
+                        ..default()
+                    },
+                )
+                .with_style(Style {
+                    padding: UiRect::axes(Val::Px(6.0), Val::Px(3.0)),
+                    ..default()
+                })
+                .with_background_color(Color::rgba(0.1, 0.1, 0.15, 0.8)),
+                ToastTimer(Timer::from_seconds(TOAST_TIME, TimerMode::Once)),
+            ))
+            .id();
+        commands.entity(area).add_child(toast);
+    }
+}
+
+// system to fade out and remove toasts when their time is up
+fn expire_toasts(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut ToastTimer, &mut Text, &mut BackgroundColor)>,
+) {
+    for (entity, mut timer, mut text, mut background) in &mut query {
+        timer.0.tick(time.delta());
+        if timer.0.finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        let alpha = (timer.0.fraction_remaining() / TOAST_FADE).min(1.0);
+        text.sections[0].style.color.set_a(alpha);
+        background.0.set_a(0.8 * alpha);
+    }
+}
+
+pub struct ToastPlugin;
+
+impl Plugin for ToastPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Toast>()
+            .add_systems(Startup, setup_toasts)
+            .add_systems(Update, (show_toasts, expire_toasts));
+    }
+}
@@ -0,0 +1,64 @@
+use crate::game_time::GameTime;
+use crate::input::Action;
+use crate::prompt::{Prompt, PromptKey, PromptPart};
+use crate::{GameState, GameplaySet, RunEntity};
+use bevy::prelude::*;
+
+// how long the controls hint stays up at the start of a run
+const HINT_TIME: f32 = 6.0;
+
+#[derive(Component)]
+struct TutorialHint(Timer);
+
+fn spawn_hint(mut commands: Commands) {
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 14.0,
+                color: Color::WHITE,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(72.0),
+            align_self: AlignSelf::Center,
+            justify_self: JustifySelf::Center,
+            ..default()
+        }),
+        Prompt(vec![
+            PromptPart::Glyph(PromptKey::Action(Action::Jump)),
+            PromptPart::Text(" jump   "),
+            PromptPart::Glyph(PromptKey::Action(Action::Run)),
+            PromptPart::Text(" run   "),
+            PromptPart::Glyph(PromptKey::Action(Action::Duck)),
+            PromptPart::Text(" duck   "),
+            PromptPart::Glyph(PromptKey::Action(Action::Pause)),
+            PromptPart::Text(" pause"),
+        ]),
+        TutorialHint(Timer::from_seconds(HINT_TIME, TimerMode::Once)),
+        RunEntity,
+    ));
+}
+
+fn expire_hint(
+    mut commands: Commands,
+    time: Res<GameTime>,
+    mut query: Query<(Entity, &mut TutorialHint)>,
+) {
+    for (entity, mut hint) in &mut query {
+        if hint.0.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+pub struct TutorialPlugin;
+
+impl Plugin for TutorialPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Playing), spawn_hint)
+            .add_systems(Update, expire_hint.in_set(GameplaySet));
+    }
+}
@@ -0,0 +1,189 @@
+use crate::coin::RunCoins;
+use crate::mutators::RunConfig;
+use crate::profile::Profiles;
+use crate::{GameState, GameplaySet, Milestone};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+// what each level of an upgrade adds
+const DURATION_PER_LEVEL: f32 = 0.25;
+const HEARTS_PER_LEVEL: f32 = 1.0;
+const MAGNET_RADIUS_PER_LEVEL: f32 = 0.3;
+
+// Permanent improvements bought with banked coins, each in a few levels
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Upgrade {
+    PowerUpDuration,
+    ExtraHeart,
+    MagnetRadius,
+    // unlocks the super jump action
+    SuperJump,
+}
+
+impl Upgrade {
+    pub const ALL: [Upgrade; 4] = [
+        Upgrade::PowerUpDuration,
+        Upgrade::ExtraHeart,
+        Upgrade::MagnetRadius,
+        Upgrade::SuperJump,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Upgrade::PowerUpDuration => "Power-up duration",
+            Upgrade::ExtraHeart => "Extra heart",
+            Upgrade::MagnetRadius => "Magnet radius",
+            Upgrade::SuperJump => "Super jump",
+        }
+    }
+
+    pub fn max_level(&self) -> u32 {
+        match self {
+            Upgrade::PowerUpDuration => 4,
+            Upgrade::ExtraHeart => 2,
+            Upgrade::MagnetRadius => 4,
+            Upgrade::SuperJump => 1,
+        }
+    }
+
+    // coins the level after `level` costs
+    pub fn cost(&self, level: u32) -> u32 {
+        let base = match self {
+            Upgrade::PowerUpDuration => 50,
+            Upgrade::ExtraHeart => 200,
+            Upgrade::MagnetRadius => 80,
+            Upgrade::SuperJump => 300,
+        };
+        base * (level + 1)
+    }
+
+    // the upgrade, and its level, this one branches off from
+    pub fn requires(&self) -> Option<(Upgrade, u32)> {
+        match self {
+            Upgrade::MagnetRadius => Some((Upgrade::PowerUpDuration, 1)),
+            _ => None,
+        }
+    }
+}
+
+// Levels bought of every upgrade
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct Upgrades {
+    pub power_up_duration: u32,
+    pub extra_heart: u32,
+    pub magnet_radius: u32,
+    pub super_jump: u32,
+}
+
+// Why an upgrade can't be bought
+pub enum Locked {
+    MaxedOut,
+    Requires(Upgrade, u32),
+    Costs(u32),
+}
+
+impl Upgrades {
+    pub fn level(&self, upgrade: Upgrade) -> u32 {
+        match upgrade {
+            Upgrade::PowerUpDuration => self.power_up_duration,
+            Upgrade::ExtraHeart => self.extra_heart,
+            Upgrade::MagnetRadius => self.magnet_radius,
+            Upgrade::SuperJump => self.super_jump,
+        }
+    }
+
+    fn level_mut(&mut self, upgrade: Upgrade) -> &mut u32 {
+        match upgrade {
+            Upgrade::PowerUpDuration => &mut self.power_up_duration,
+            Upgrade::ExtraHeart => &mut self.extra_heart,
+            Upgrade::MagnetRadius => &mut self.magnet_radius,
+            Upgrade::SuperJump => &mut self.super_jump,
+        }
+    }
+
+    // what stands between `upgrade` and its next level with `coins` to spend
+    pub fn check(&self, upgrade: Upgrade, coins: u32) -> Result<u32, Locked> {
+        let level = self.level(upgrade);
+        if level >= upgrade.max_level() {
+            return Err(Locked::MaxedOut);
+        }
+        if let Some((required, required_level)) = upgrade.requires() {
+            if self.level(required) < required_level {
+                return Err(Locked::Requires(required, required_level));
+            }
+        }
+        let cost = upgrade.cost(level);
+        if cost > coins {
+            return Err(Locked::Costs(cost));
+        }
+        Ok(cost)
+    }
+
+    // buy the next level of `upgrade` out of `coins`, if it can be
+    pub fn buy(&mut self, upgrade: Upgrade, coins: &mut u32) -> bool {
+        let Ok(cost) = self.check(upgrade, *coins) else {
+            return false;
+        };
+        *coins -= cost;
+        *self.level_mut(upgrade) += 1;
+        true
+    }
+
+    pub fn apply(&self, config: &mut RunConfig) {
+        config.power_up_duration *= 1.0 + DURATION_PER_LEVEL * self.power_up_duration as f32;
+        config.player_health += HEARTS_PER_LEVEL * self.extra_heart as f32;
+        config.magnet_radius *= 1.0 + MAGNET_RADIUS_PER_LEVEL * self.magnet_radius as f32;
+        config.super_jump = self.super_jump > 0;
+    }
+}
+
+// Coins of the current run already banked at a milestone
+#[derive(Resource, Default)]
+struct BankedCoins(u32);
+
+fn reset_banked(mut banked: ResMut<BankedCoins>) {
+    banked.0 = 0;
+}
+
+// banks the run's coins not banked yet, which saves them
+fn bank(coins: &RunCoins, banked: &mut BankedCoins, profiles: &mut Profiles) {
+    if coins.0 > banked.0 {
+        profiles.active_mut().coins += coins.0 - banked.0;
+        banked.0 = coins.0;
+    }
+}
+
+// system to bank the coins picked up so far at every milestone, so a crash or
+// force-quit later in the run doesn't lose them
+fn bank_at_milestone(
+    mut milestones: EventReader<Milestone>,
+    coins: Res<RunCoins>,
+    mut banked: ResMut<BankedCoins>,
+    mut profiles: ResMut<Profiles>,
+) {
+    if milestones.read().count() > 0 {
+        bank(&coins, &mut banked, &mut profiles);
+    }
+}
+
+// system to bank the rest of the coins picked up during the run that just ended
+fn bank_coins(
+    coins: Res<RunCoins>,
+    mut banked: ResMut<BankedCoins>,
+    mut profiles: ResMut<Profiles>,
+) {
+    bank(&coins, &mut banked, &mut profiles);
+}
+
+pub struct UpgradesPlugin;
+
+impl Plugin for UpgradesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Profiles>()
+            .init_resource::<BankedCoins>()
+            .add_systems(OnEnter(GameState::Playing), reset_banked)
+            .add_systems(Update, bank_at_milestone.in_set(GameplaySet))
+            .add_systems(OnExit(GameState::Playing), bank_coins);
+    }
+}
@@ -0,0 +1,158 @@
+use crate::balance::Balance;
+use crate::barrel::BARREL_SIZE;
+use crate::{jump_heights, MOVE_SPEED, PLAYER_SCALE, RUN_SPEED};
+use std::fmt;
+
+// width of the player's hitbox, as sliced in the player spritesheet
+const PLAYER_WIDTH: f32 = 8.0 * PLAYER_SCALE;
+// frames of leeway below which clearing an obstacle counts as frame-perfect
+const FRAME_PERFECT: f32 = 2.0;
+
+// Something the player has to jump over, its footprint in pixels
+struct Obstacle {
+    width: f32,
+    height: f32,
+}
+
+const BARREL: Obstacle = Obstacle {
+    width: BARREL_SIZE.x,
+    height: BARREL_SIZE.y,
+};
+
+// Obstacles placed together by the spawn tables, by their left edge
+struct Pattern {
+    name: String,
+    obstacles: Vec<(f32, Obstacle)>,
+}
+
+// What's wrong with a pattern
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Problem {
+    // no jump gets the player over it
+    Impossible,
+    // it can be cleared, but only by starting the jumps within this many frames
+    FramePerfect(f32),
+}
+
+// A pattern that shouldn't ship as it is
+#[derive(Debug, Clone)]
+pub struct Finding {
+    pub pattern: String,
+    pub problem: Problem,
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.problem {
+            Problem::Impossible => write!(f, "{}: impossible", self.pattern),
+            Problem::FramePerfect(frames) => write!(
+                f,
+                "{}: frame-perfect only, {:.1} frames of leeway",
+                self.pattern, frames
+            ),
+        }
+    }
+}
+
+// The jump the player makes, at `speed` pixels per frame
+struct Jump {
+    heights: Vec<f32>,
+    speed: f32,
+}
+
+impl Jump {
+    // where the player's left edge can be when jumping to clear `obstacle` at `x`,
+    // None when nowhere
+    fn starts(&self, x: f32, obstacle: &Obstacle) -> Option<(f32, f32)> {
+        let first = self.heights.iter().position(|y| *y > obstacle.height)?;
+        let last = self.heights.iter().rposition(|y| *y > obstacle.height)?;
+        // in the air from before the front touches it until after the back has passed it
+        let earliest = x + obstacle.width - self.speed * (last + 1) as f32;
+        let latest = x - PLAYER_WIDTH - self.speed * (first + 1) as f32;
+        (earliest <= latest).then_some((earliest, latest))
+    }
+
+    // distance covered between taking off and landing
+    fn length(&self) -> f32 {
+        self.speed * (self.heights.len() + 1) as f32
+    }
+
+    // frames of leeway in when to start jumping to get past every obstacle of `pattern`,
+    // in as many jumps as it takes. None when it can't be cleared.
+    fn leeway(&self, pattern: &Pattern) -> Option<f32> {
+        // where the jump under way can start, and the least leeway of the ones before it
+        let mut jump: Option<(f32, f32, f32)> = None;
+        for (x, obstacle) in &pattern.obstacles {
+            let (earliest, latest) = self.starts(*x, obstacle)?;
+            jump = Some(match jump {
+                None => (earliest, latest, f32::INFINITY),
+                // one jump clears this obstacle along with the ones before
+                Some((from, to, before)) if from.max(earliest) <= to.min(latest) => {
+                    (from.max(earliest), to.min(latest), before)
+                }
+                // a jump of its own, after landing from the last one
+                Some((from, to, before)) => {
+                    let landed = from + self.length();
+                    if landed > latest {
+                        return None;
+                    }
+                    let before = before.min((to - from) / self.speed);
+                    (landed.max(earliest), latest, before)
+                }
+            });
+        }
+        jump.map(|(from, to, before)| before.min((to - from) / self.speed))
+    }
+}
+
+// the obstacle patterns the spawn tables can lay out: a lone barrel, and two barrels
+// as close together as each step of the difficulty curve packs them
+fn patterns(balance: &Balance) -> Vec<Pattern> {
+    let mut patterns = vec![Pattern {
+        name: "lone barrel".to_string(),
+        obstacles: vec![(0.0, BARREL)],
+    }];
+    // the spacing at the start, unless a step already covers it, then every step's
+    let start = balance
+        .difficulty
+        .first()
+        .is_none_or(|step| step.distance > 0.0);
+    let spacings = start.then_some((0.0, 1.0)).into_iter().chain(
+        balance
+            .difficulty
+            .iter()
+            .map(|step| (step.distance, step.spacing)),
+    );
+    for (distance, spacing) in spacings {
+        let gap = balance.barrel_spacing.0 * spacing;
+        patterns.push(Pattern {
+            name: format!("two barrels {:.0} apart, from {:.0} on", gap, distance),
+            obstacles: vec![(0.0, BARREL), (gap, BARREL)],
+        });
+    }
+    patterns
+}
+
+/// Jumps over every obstacle pattern `balance` can spawn with the player's own physics,
+/// at full speed and normal gravity, and lists the ones that can't be cleared or only
+/// with frame-perfect timing.
+pub fn validate_balance(balance: &Balance) -> Vec<Finding> {
+    let jump = Jump {
+        heights: jump_heights(1.0),
+        speed: RUN_SPEED + MOVE_SPEED,
+    };
+    patterns(balance)
+        .into_iter()
+        .filter_map(|pattern| {
+            let problem = match jump.leeway(&pattern) {
+                None => Problem::Impossible,
+                Some(frames) if frames < FRAME_PERFECT => Problem::FramePerfect(frames),
+                Some(_) => return None,
+            };
+            Some(Finding {
+                pattern: pattern.name,
+                problem,
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,352 @@
+use crate::mode::{start_run, RunRng};
+use crate::settings::Settings;
+use crate::stream::HudText;
+use crate::toast::Toast;
+use crate::{GameState, GameplaySet, Player, PlayerOne, RunEntity, GROUND_Y};
+use bevy::prelude::*;
+use bevy_parallax::ParallaxCameraComponent;
+use rand::Rng;
+
+// length of course each weather holds for, picked at random in this range
+const WEATHER_STRETCH: (f32, f32) = (2000.0, 4000.0);
+// slick patches laid on the ground while it rains: the gap between two and their length
+const SLICK_SPACING: (f32, f32) = (250.0, 600.0);
+const SLICK_LENGTH: (f32, f32) = (120.0, 320.0);
+const SLICK_COLOR: Color = Color::rgba(0.55, 0.8, 1.0, 0.55);
+// share of the usual acceleration and braking left on a slick patch
+const SLICK_GRIP: f32 = 0.15;
+// pixels per frame the wind pushes the player, on top of their own movement
+const WIND_DRIFT: f32 = 0.5;
+// in fog the course is only clear this far ahead of the player, then thickens over
+// FOG_BANDS bands of FOG_BAND_WIDTH pixels
+const FOG_CLEAR: f32 = 180.0;
+const FOG_BANDS: u32 = 5;
+const FOG_BAND_WIDTH: f32 = 36.0;
+const FOG_COLOR: Color = Color::rgb(0.75, 0.78, 0.82);
+const FOG_HEIGHT: f32 = 600.0;
+// how far past the right edge of the view slick patches are laid
+const SPAWN_MARGIN: f32 = 64.0;
+
+// Weather over the stretch of course being run
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq)]
+pub enum Weather {
+    #[default]
+    Clear,
+    // leaves slick patches on the ground
+    Rain,
+    // pushes the player one way, -1 for left and 1 for right
+    Wind(f32),
+    // hides the course past a short distance ahead
+    Fog,
+}
+
+impl Weather {
+    fn name(&self) -> &'static str {
+        match self {
+            Weather::Clear => "Clear skies",
+            Weather::Rain => "Rain",
+            Weather::Wind(_) => "Wind",
+            Weather::Fog => "Fog",
+        }
+    }
+
+    // what the HUD badge says, None while it's clear
+    fn badge(&self) -> Option<String> {
+        match self {
+            Weather::Clear => None,
+            Weather::Rain => Some("RAIN  slippery".to_string()),
+            Weather::Wind(direction) if *direction < 0.0 => Some("WIND  <<".to_string()),
+            Weather::Wind(_) => Some("WIND  >>".to_string()),
+            Weather::Fog => Some("FOG  short sight".to_string()),
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            Weather::Clear => Color::NONE,
+            Weather::Rain => Color::rgba(0.2, 0.45, 0.8, 0.85),
+            Weather::Wind(_) => Color::rgba(0.3, 0.6, 0.4, 0.85),
+            Weather::Fog => Color::rgba(0.45, 0.45, 0.5, 0.85),
+        }
+    }
+}
+
+// How the weather acts on the player this frame, applied by player_movement
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct WeatherEffects {
+    // share of the usual grip on the ground, 1 on a dry surface
+    pub grip: f32,
+    // pixels per frame the player is pushed along
+    pub wind: f32,
+}
+
+impl Default for WeatherEffects {
+    fn default() -> Self {
+        WeatherEffects {
+            grip: 1.0,
+            wind: 0.0,
+        }
+    }
+}
+
+// Where the weather changes next, and where the next slick patch goes
+#[derive(Resource, Default)]
+struct WeatherSpawner {
+    change_x: f32,
+    next_slick_x: f32,
+}
+
+// Patch of ground made slick by the rain, `length` pixels long from its left edge
+#[derive(Component)]
+pub struct Slick {
+    length: f32,
+}
+
+// Band of fog, `index` bands away from the clear stretch in front of the player
+#[derive(Component)]
+struct FogBand {
+    index: u32,
+}
+
+// fog bands, kept apart from the player's and the camera's transforms
+type FogOnly = (Without<Player>, Without<ParallaxCameraComponent>);
+
+// Badge naming the weather in the top right corner
+#[derive(Component)]
+struct WeatherBadge;
+
+fn reset_weather(mut commands: Commands, mut rng: ResMut<RunRng>) {
+    commands.insert_resource(Weather::Clear);
+    commands.insert_resource(WeatherEffects::default());
+    commands.insert_resource(WeatherSpawner {
+        change_x: rng.0.gen_range(WEATHER_STRETCH.0..=WEATHER_STRETCH.1),
+        next_slick_x: 0.0,
+    });
+}
+
+fn spawn_weather_badge(mut commands: Commands) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(8.0),
+                    right: Val::Px(8.0),
+                    padding: UiRect::axes(Val::Px(6.0), Val::Px(2.0)),
+                    ..default()
+                },
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            WeatherBadge,
+            RunEntity,
+        ))
+        .with_children(|badge| {
+            badge.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 14.0,
+                        color: Color::WHITE,
+                        ..default()
+                    },
+                ),
+                HudText(14.0),
+            ));
+        });
+}
+
+// system to move on to another weather at the end of each stretch. Low-spec mode keeps
+// the skies clear, rolling the weather all the same so the course it lays out is too.
+fn change_weather(
+    settings: Res<Settings>,
+    mut weather: ResMut<Weather>,
+    mut spawner: ResMut<WeatherSpawner>,
+    mut rng: ResMut<RunRng>,
+    player_query: Query<&Transform, PlayerOne>,
+    mut toasts: EventWriter<Toast>,
+) {
+    let Ok(player) = player_query.get_single() else {
+        return;
+    };
+    if player.translation.x < spawner.change_x {
+        return;
+    }
+    spawner.change_x += rng.0.gen_range(WEATHER_STRETCH.0..=WEATHER_STRETCH.1);
+    let direction = if rng.0.gen_bool(0.5) { 1.0 } else { -1.0 };
+    let choices = [
+        Weather::Clear,
+        Weather::Rain,
+        Weather::Wind(direction),
+        Weather::Fog,
+    ];
+    let mut next = choices[rng.0.gen_range(0..choices.len())];
+    if settings.video.low_spec {
+        next = Weather::Clear;
+    }
+    if next == *weather {
+        return;
+    }
+    *weather = next;
+    toasts.send(Toast(next.name().to_string()));
+}
+
+// system to lay slick patches ahead of the camera while it rains
+fn spawn_slicks(
+    mut commands: Commands,
+    weather: Res<Weather>,
+    mut spawner: ResMut<WeatherSpawner>,
+    mut rng: ResMut<RunRng>,
+    camera_query: Query<(&Transform, &OrthographicProjection), With<ParallaxCameraComponent>>,
+) {
+    let Ok((camera, projection)) = camera_query.get_single() else {
+        return;
+    };
+    let view_right = camera.translation.x + projection.area.max.x + SPAWN_MARGIN;
+    // patches only start once the rain does, wherever the view has got to by then
+    if *weather != Weather::Rain {
+        spawner.next_slick_x = spawner.next_slick_x.max(view_right);
+        return;
+    }
+    if view_right < spawner.next_slick_x {
+        return;
+    }
+    let length = rng.0.gen_range(SLICK_LENGTH.0..=SLICK_LENGTH.1);
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: SLICK_COLOR,
+                custom_size: Some(Vec2::new(length, 6.0)),
+                anchor: bevy::sprite::Anchor::CenterLeft,
+                ..default()
+            },
+            transform: Transform::from_xyz(spawner.next_slick_x, GROUND_Y - 30.0, 1.45),
+            ..default()
+        },
+        Slick { length },
+        RunEntity,
+    ));
+    spawner.next_slick_x += length + rng.0.gen_range(SLICK_SPACING.0..=SLICK_SPACING.1);
+}
+
+// system to work out the grip under the player's feet and the wind pushing them
+pub fn update_effects(
+    weather: Res<Weather>,
+    mut effects: ResMut<WeatherEffects>,
+    player_query: Query<(&Player, &Transform), PlayerOne>,
+    slick_query: Query<(&Slick, &Transform)>,
+) {
+    let Ok((player, transform)) = player_query.get_single() else {
+        return;
+    };
+    let x = transform.translation.x;
+    let on_slick = player.on_ground
+        && slick_query.iter().any(|(slick, slick_transform)| {
+            let left = slick_transform.translation.x;
+            (left..=left + slick.length).contains(&x)
+        });
+    effects.grip = if on_slick { SLICK_GRIP } else { 1.0 };
+    effects.wind = match *weather {
+        Weather::Wind(direction) => WIND_DRIFT * direction,
+        _ => 0.0,
+    };
+}
+
+// system to bank the fog up past a short distance ahead of the player while it's foggy
+fn update_fog(
+    mut commands: Commands,
+    weather: Res<Weather>,
+    player_query: Query<&Transform, PlayerOne>,
+    camera_query: Query<&Transform, (With<ParallaxCameraComponent>, Without<Player>)>,
+    mut fog_query: Query<(Entity, &FogBand, &mut Transform), FogOnly>,
+) {
+    if *weather != Weather::Fog {
+        for (entity, ..) in &fog_query {
+            commands.entity(entity).despawn_recursive();
+        }
+        return;
+    }
+    let (Ok(player), Ok(camera)) = (player_query.get_single(), camera_query.get_single()) else {
+        return;
+    };
+    if fog_query.is_empty() {
+        for index in 0..FOG_BANDS {
+            // the last band stretches on well past the edge of the view
+            let width = if index + 1 == FOG_BANDS {
+                FOG_BAND_WIDTH * 40.0
+            } else {
+                FOG_BAND_WIDTH
+            };
+            let alpha = (index + 1) as f32 / FOG_BANDS as f32;
+            commands.spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: FOG_COLOR.with_a(alpha),
+                        custom_size: Some(Vec2::new(width, FOG_HEIGHT)),
+                        anchor: bevy::sprite::Anchor::CenterLeft,
+                        ..default()
+                    },
+                    ..default()
+                },
+                FogBand { index },
+                RunEntity,
+            ));
+        }
+        return;
+    }
+    for (_, band, mut transform) in &mut fog_query {
+        let x = player.translation.x + FOG_CLEAR + band.index as f32 * FOG_BAND_WIDTH;
+        // in front of the course and everything on it, behind the HUD
+        transform.translation = Vec3::new(x, camera.translation.y, 3.0);
+    }
+}
+
+// system to show the weather's badge while it isn't clear
+fn update_badge(
+    weather: Res<Weather>,
+    mut badge_query: Query<(&mut Visibility, &mut BackgroundColor, &Children), With<WeatherBadge>>,
+    mut text_query: Query<&mut Text>,
+) {
+    if !weather.is_changed() {
+        return;
+    }
+    for (mut visibility, mut background, children) in &mut badge_query {
+        let Some(badge) = weather.badge() else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+        *visibility = Visibility::Inherited;
+        *background = weather.color().into();
+        for &child in children {
+            if let Ok(mut text) = text_query.get_mut(child) {
+                text.sections[0].value = badge.clone();
+            }
+        }
+    }
+}
+
+pub struct WeatherPlugin;
+
+impl Plugin for WeatherPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Weather>()
+            .init_resource::<WeatherEffects>()
+            .init_resource::<WeatherSpawner>()
+            .add_systems(
+                OnEnter(GameState::Playing),
+                (reset_weather.after(start_run), spawn_weather_badge),
+            )
+            .add_systems(
+                Update,
+                (
+                    change_weather,
+                    spawn_slicks,
+                    update_effects,
+                    update_fog,
+                    update_badge,
+                )
+                    .chain()
+                    .in_set(GameplaySet),
+            );
+    }
+}
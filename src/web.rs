@@ -0,0 +1,79 @@
+use bevy::prelude::*;
+use std::time::Duration;
+
+// Browsers keep audio suspended until the page gets some input, and bevy creates its
+// audio context while starting up, before there can be any. This keeps track of the
+// contexts the page creates so they can be resumed once the player presses something.
+#[cfg(target_arch = "wasm32")]
+const TRACK_AUDIO_CONTEXTS: &str = "
+    const Base = window.AudioContext || window.webkitAudioContext;
+    if (!Base || window.dinorunAudioContexts) return;
+    const contexts = [];
+    window.dinorunAudioContexts = contexts;
+    window.AudioContext = class extends Base {
+        constructor(...args) { super(...args); contexts.push(this); }
+    };
+";
+
+// Stretches the canvas over the whole page, which bevy then renders at the size of,
+// so the game fills whatever the page gives it, like an itch.io iframe
+#[cfg(target_arch = "wasm32")]
+const FILL_PAGE: &str = "
+    const style = document.createElement('style');
+    style.textContent = `
+        html, body { margin: 0; width: 100%; height: 100%; overflow: hidden; }
+        canvas { display: block; width: 100% !important; height: 100% !important; outline: none; }
+    `;
+    document.head.appendChild(style);
+";
+
+#[cfg(target_arch = "wasm32")]
+const RESUME_AUDIO_CONTEXTS: &str =
+    "(window.dinorunAudioContexts || []).forEach((context) => context.resume());";
+
+#[cfg(target_arch = "wasm32")]
+fn run_js(code: &str) {
+    use web_sys::{js_sys::Function, wasm_bindgen::JsValue};
+    if let Err(err) = Function::new_no_args(code).call0(&JsValue::NULL) {
+        warn!("Could not run page script: {:?}", err);
+    }
+}
+
+// resume the audio the browser held back until the first input
+#[cfg(target_arch = "wasm32")]
+pub fn resume_audio() {
+    run_js(RESUME_AUDIO_CONTEXTS);
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn resume_audio() {}
+
+// time since the unix epoch, read from the page's clock: the standard library has none on
+// the web and panics when asked
+#[cfg(target_arch = "wasm32")]
+pub fn unix_time() -> Duration {
+    Duration::from_secs_f64(web_sys::js_sys::Date::now() / 1000.0)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn unix_time() -> Duration {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+}
+
+// Makes the web build work inside a page it doesn't control, like an itch.io iframe:
+// the canvas fills the page and audio starts on the first input (settings and saves go
+// to the browser's storage, see storage.rs). Has to be added before DefaultPlugins, to
+// be in place before bevy creates its audio context.
+pub struct WebPlugin;
+
+impl Plugin for WebPlugin {
+    fn build(&self, _app: &mut App) {
+        #[cfg(target_arch = "wasm32")]
+        {
+            run_js(FILL_PAGE);
+            run_js(TRACK_AUDIO_CONTEXTS);
+        }
+    }
+}